@@ -0,0 +1,451 @@
+//! Arrow Flight SQL server mode (`eql flight`).
+//!
+//! BI tools and ADBC drivers talk to this over gRPC: `GetFlightInfo` runs the
+//! statement through the same frontend and execution engine as `eql run`, and
+//! `DoGet` streams the typed Arrow batches the Parquet exporter would write.
+//! Catalog requests (`GetCatalogs`/`GetDbSchemas`/`GetTables`) are answered
+//! from the entity schema in `eql_core`, so tools can browse the tables and
+//! their columns without running a query.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+use arrow_flight::sql::metadata::{SqlInfoData, SqlInfoDataBuilder};
+use arrow_flight::sql::server::FlightSqlService;
+use arrow_flight::sql::{
+    CommandGetCatalogs, CommandGetDbSchemas, CommandGetSqlInfo, CommandGetTables,
+    CommandStatementQuery, ProstMessageExt, SqlInfo, TicketStatementQuery,
+};
+use arrow_flight::{FlightDescriptor, FlightEndpoint, FlightInfo, Ticket};
+use eql_core::common::serializer::to_record_batch;
+use eql_core::interpreter::frontend::sql::schema::EntityKind;
+use eql_core::interpreter::Interpreter;
+use futures::TryStreamExt;
+use prost::Message;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+
+/// Catalog and schema every EQL table is listed under.
+pub const CATALOG: &str = "eql";
+pub const DB_SCHEMA: &str = "main";
+
+/// Rows per streamed batch, so large results don't go out as one message.
+const BATCH_ROWS: usize = 8192;
+
+/// How long a result computed by `GetFlightInfo` waits for its `DoGet`.
+const PENDING_TTL: Duration = Duration::from_secs(300);
+
+/// Most results parked at once; past it, the oldest is dropped.
+const MAX_PENDING: usize = 64;
+
+struct Pending {
+    batch: RecordBatch,
+    created: Instant,
+}
+
+#[derive(Default)]
+pub struct EqlFlightSqlService {
+    next_handle: AtomicU64,
+    /// Results computed by `GetFlightInfo`, waiting for their `DoGet`. A
+    /// client that never fetches (or re-plans instead of retrying `DoGet`)
+    /// leaves its result here until it expires or is pushed out by newer ones.
+    pending: Mutex<HashMap<u64, Pending>>,
+}
+
+// The helpers return tonic's `Status` as the `FlightSqlService` methods they serve must.
+#[allow(clippy::result_large_err)]
+impl EqlFlightSqlService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parks `batch` for a later `DoGet` and returns its handle.
+    fn park(&self, batch: RecordBatch) -> u64 {
+        let handle = self.next_handle.fetch_add(1, Ordering::Relaxed);
+        let now = Instant::now();
+        let mut pending = self.pending.lock().unwrap();
+        pending.insert(
+            handle,
+            Pending {
+                batch,
+                created: now,
+            },
+        );
+        evict(&mut pending, now);
+        handle
+    }
+
+    /// Takes the result parked under `handle`, unless it has expired.
+    fn take(&self, handle: u64) -> Option<RecordBatch> {
+        let mut pending = self.pending.lock().unwrap();
+        evict(&mut pending, Instant::now());
+        pending.remove(&handle).map(|pending| pending.batch)
+    }
+
+    fn sql_info() -> Result<SqlInfoData, Status> {
+        let mut builder = SqlInfoDataBuilder::new();
+        builder.append(SqlInfo::FlightSqlServerName, "EQL");
+        builder.append(SqlInfo::FlightSqlServerVersion, env!("CARGO_PKG_VERSION"));
+        builder.append(SqlInfo::FlightSqlServerReadOnly, true);
+        Ok(builder.build()?)
+    }
+
+    /// Runs `sql` and returns its single result set as one Arrow batch. A
+    /// program made only of `SET` statements yields an empty batch.
+    async fn execute(sql: String) -> Result<RecordBatch, Status> {
+        let results = Interpreter::run_program(&sql)
+            .await
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let batches = results
+            .iter()
+            .map(|r| to_record_batch(&r.result).map_err(|e| Status::internal(e.to_string())))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match batches.len() {
+            0 => Ok(RecordBatch::new_empty(Arc::new(
+                arrow::datatypes::Schema::empty(),
+            ))),
+            1 => Ok(batches.into_iter().next().unwrap()),
+            n => Err(Status::invalid_argument(format!(
+                "a Flight SQL statement must produce a single result set, got {n}"
+            ))),
+        }
+    }
+
+    fn info_for(
+        schema: &arrow::datatypes::Schema,
+        ticket: Ticket,
+        descriptor: FlightDescriptor,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let info = FlightInfo::new()
+            .try_with_schema(schema)
+            .map_err(arrow_status)?
+            .with_endpoint(FlightEndpoint::new().with_ticket(ticket))
+            .with_descriptor(descriptor);
+        Ok(Response::new(info))
+    }
+
+    fn stream(
+        batches: Vec<RecordBatch>,
+        schema: arrow::datatypes::SchemaRef,
+    ) -> Response<<Self as FlightService>::DoGetStream> {
+        let batches = batches.into_iter().map(Ok);
+        let stream = FlightDataEncoderBuilder::new()
+            .with_schema(schema)
+            .build(futures::stream::iter(batches))
+            .map_err(Status::from);
+        Response::new(Box::pin(stream))
+    }
+}
+
+fn arrow_status(err: ArrowError) -> Status {
+    Status::internal(err.to_string())
+}
+
+/// Drops results older than `PENDING_TTL`, then the oldest ones past
+/// `MAX_PENDING`.
+fn evict(pending: &mut HashMap<u64, Pending>, now: Instant) {
+    pending.retain(|_, p| now.duration_since(p.created) < PENDING_TTL);
+    while pending.len() > MAX_PENDING {
+        let oldest = pending
+            .iter()
+            .min_by_key(|(handle, p)| (p.created, **handle))
+            .map(|(handle, _)| *handle);
+        match oldest {
+            Some(handle) => pending.remove(&handle),
+            None => break,
+        };
+    }
+}
+
+/// Splits `batch` into slices of at most `BATCH_ROWS` rows.
+fn chunk(batch: &RecordBatch) -> Vec<RecordBatch> {
+    (0..batch.num_rows())
+        .step_by(BATCH_ROWS)
+        .map(|offset| batch.slice(offset, BATCH_ROWS.min(batch.num_rows() - offset)))
+        .collect()
+}
+
+#[tonic::async_trait]
+impl FlightSqlService for EqlFlightSqlService {
+    type FlightService = EqlFlightSqlService;
+
+    async fn get_flight_info_statement(
+        &self,
+        query: CommandStatementQuery,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let batch = Self::execute(query.query).await?;
+        let schema = batch.schema();
+        let rows = batch.num_rows();
+
+        let handle = self.park(batch);
+
+        let ticket = TicketStatementQuery {
+            statement_handle: handle.to_be_bytes().to_vec().into(),
+        };
+        let ticket = Ticket::new(ticket.as_any().encode_to_vec());
+        let info = Self::info_for(&schema, ticket, request.into_inner())?;
+        Ok(info.map(|info| info.with_total_records(rows as i64)))
+    }
+
+    async fn do_get_statement(
+        &self,
+        ticket: TicketStatementQuery,
+        _request: Request<Ticket>,
+    ) -> Result<Response<<Self as FlightService>::DoGetStream>, Status> {
+        let handle: [u8; 8] = ticket
+            .statement_handle
+            .as_ref()
+            .try_into()
+            .map_err(|_| Status::invalid_argument("malformed statement handle"))?;
+        let batch = self
+            .take(u64::from_be_bytes(handle))
+            .ok_or_else(|| Status::not_found("unknown, expired or already fetched statement"))?;
+        Ok(Self::stream(chunk(&batch), batch.schema()))
+    }
+
+    async fn get_flight_info_catalogs(
+        &self,
+        query: CommandGetCatalogs,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let ticket = Ticket::new(query.as_any().encode_to_vec());
+        Self::info_for(&query.into_builder().schema(), ticket, request.into_inner())
+    }
+
+    async fn do_get_catalogs(
+        &self,
+        query: CommandGetCatalogs,
+        _request: Request<Ticket>,
+    ) -> Result<Response<<Self as FlightService>::DoGetStream>, Status> {
+        let mut builder = query.into_builder();
+        builder.append(CATALOG);
+        let schema = builder.schema();
+        Ok(Self::stream(vec![builder.build()?], schema))
+    }
+
+    async fn get_flight_info_schemas(
+        &self,
+        query: CommandGetDbSchemas,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let ticket = Ticket::new(query.as_any().encode_to_vec());
+        Self::info_for(&query.into_builder().schema(), ticket, request.into_inner())
+    }
+
+    async fn do_get_schemas(
+        &self,
+        query: CommandGetDbSchemas,
+        _request: Request<Ticket>,
+    ) -> Result<Response<<Self as FlightService>::DoGetStream>, Status> {
+        let mut builder = query.into_builder();
+        builder.append(CATALOG, DB_SCHEMA);
+        let schema = builder.schema();
+        Ok(Self::stream(vec![builder.build()?], schema))
+    }
+
+    async fn get_flight_info_tables(
+        &self,
+        query: CommandGetTables,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let ticket = Ticket::new(query.as_any().encode_to_vec());
+        Self::info_for(&query.into_builder().schema(), ticket, request.into_inner())
+    }
+
+    async fn do_get_tables(
+        &self,
+        query: CommandGetTables,
+        _request: Request<Ticket>,
+    ) -> Result<Response<<Self as FlightService>::DoGetStream>, Status> {
+        let mut builder = query.into_builder();
+        for kind in EntityKind::ALL {
+            builder.append(
+                CATALOG,
+                DB_SCHEMA,
+                kind.table_name(),
+                "TABLE",
+                &kind.arrow_schema(),
+            )?;
+        }
+        let schema = builder.schema();
+        Ok(Self::stream(vec![builder.build()?], schema))
+    }
+
+    async fn get_flight_info_sql_info(
+        &self,
+        query: CommandGetSqlInfo,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let ticket = Ticket::new(query.as_any().encode_to_vec());
+        let schema = query.into_builder(&Self::sql_info()?).schema();
+        Self::info_for(&schema, ticket, request.into_inner())
+    }
+
+    async fn do_get_sql_info(
+        &self,
+        query: CommandGetSqlInfo,
+        _request: Request<Ticket>,
+    ) -> Result<Response<<Self as FlightService>::DoGetStream>, Status> {
+        let info = Self::sql_info()?;
+        let builder = query.into_builder(&info);
+        let schema = builder.schema();
+        Ok(Self::stream(vec![builder.build()?], schema))
+    }
+
+    async fn register_sql_info(&self, _id: i32, _result: &SqlInfo) {}
+}
+
+pub async fn serve(addr: SocketAddr) -> Result<(), Box<dyn std::error::Error>> {
+    println!("EQL Flight SQL server listening on {addr}");
+    Server::builder()
+        .add_service(FlightServiceServer::new(EqlFlightSqlService::new()))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_flight::sql::client::FlightSqlServiceClient;
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Channel;
+
+    async fn client() -> FlightSqlServiceClient<Channel> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(FlightServiceServer::new(EqlFlightSqlService::new()))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        let channel = Channel::from_shared(format!("http://{addr}"))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        FlightSqlServiceClient::new(channel)
+    }
+
+    async fn fetch(
+        client: &mut FlightSqlServiceClient<Channel>,
+        info: FlightInfo,
+    ) -> Vec<RecordBatch> {
+        let ticket = info.endpoint[0].ticket.clone().unwrap();
+        client
+            .do_get(ticket)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn get_tables_lists_every_entity_with_its_schema() {
+        let mut client = client().await;
+        let info = client
+            .get_tables(CommandGetTables {
+                include_schema: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let batches = fetch(&mut client, info).await;
+        let names = batches[0]
+            .column_by_name("table_name")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<arrow::array::StringArray>()
+            .unwrap()
+            .iter()
+            .flatten()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "accounts",
+                "authorizations",
+                "blocks",
+                "call",
+                "contracts",
+                "ens",
+                "logs",
+                "state_diffs",
+                "storage",
+                "token_balances",
+                "transactions",
+                "withdrawals"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn get_schemas_reports_the_eql_catalog() {
+        let mut client = client().await;
+        let info = client
+            .get_db_schemas(CommandGetDbSchemas::default())
+            .await
+            .unwrap();
+        let batches = fetch(&mut client, info).await;
+        assert_eq!(batches[0].num_rows(), 1);
+    }
+
+    #[tokio::test]
+    async fn statement_errors_surface_as_invalid_argument() {
+        let mut client = client().await;
+        let err = client
+            .execute("SELECT * FROM account".to_string(), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("did you mean"), "{err}");
+    }
+
+    #[test]
+    fn chunk_splits_large_batches() {
+        let rows = (0..BATCH_ROWS as u64 + 1).collect::<Vec<_>>();
+        let schema = Arc::new(arrow::datatypes::Schema::new(vec![
+            arrow::datatypes::Field::new("n", arrow::datatypes::DataType::UInt64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(arrow::array::UInt64Array::from(rows))],
+        )
+        .unwrap();
+        let chunks = chunk(&batch);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1].num_rows(), 1);
+    }
+
+    #[test]
+    fn unfetched_results_expire_and_are_capped() {
+        let service = EqlFlightSqlService::new();
+        let empty = || RecordBatch::new_empty(Arc::new(arrow::datatypes::Schema::empty()));
+
+        service.park(empty());
+        evict(
+            &mut service.pending.lock().unwrap(),
+            Instant::now() + PENDING_TTL,
+        );
+        assert!(service.pending.lock().unwrap().is_empty());
+
+        let first = service.park(empty());
+        for _ in 0..MAX_PENDING {
+            service.park(empty());
+        }
+        assert_eq!(service.pending.lock().unwrap().len(), MAX_PENDING);
+        assert!(service.take(first).is_none());
+        assert!(service.take(first + 1).is_some());
+    }
+}
@@ -1,11 +1,17 @@
+mod flight;
+mod pgwire;
 mod repl;
 
 use crate::repl::Repl;
 use clap::{Parser, Subcommand};
 use csv::ReaderBuilder;
 use eql_core::{
-    common::query_result::{ExpressionResult, QueryResult},
-    interpreter::Interpreter,
+    common::query_result::{ExpressionResult, QueryResult, TransactionCsvRow},
+    interpreter::{
+        backend::chain_cache::ChainCache,
+        frontend::sql::params::{Param, Params},
+        Interpreter,
+    },
 };
 use serde::Serialize;
 use std::error::Error;
@@ -18,6 +24,10 @@ use tabled::{builder::Builder, settings::Style, Table};
     author = "Ian K. Guimaraes <ianguimaraes31@gmail.com>"
 )]
 struct Arguments {
+    /// Fetch everything, without reading or writing the local chain cache.
+    #[clap(long, global = true)]
+    no_cache: bool,
+
     #[clap(subcommand)]
     subcmd: SubCommand,
 }
@@ -29,11 +39,87 @@ enum SubCommand {
 
     #[clap(name = "repl", about = "Start an interactive REPL")]
     Repl,
+
+    #[clap(name = "flight", about = "Serve queries over Arrow Flight SQL")]
+    Flight(FlightArguments),
+
+    #[clap(
+        name = "pgwire",
+        about = "Serve queries over the PostgreSQL wire protocol"
+    )]
+    Pgwire(PgwireArguments),
+
+    #[clap(name = "cache", about = "Inspect or clear the local chain data cache")]
+    Cache(CacheArguments),
 }
 
 #[derive(Debug, Parser)]
 struct RunArguments {
     file: String,
+
+    /// Binds a query parameter, e.g. `--param 1=vitalik.eth` for `$1` or
+    /// `--param addr=0xd8dA...` for `:addr`. Repeatable.
+    #[clap(long = "param", short = 'p', value_parser = parse_param)]
+    params: Vec<(String, String)>,
+}
+
+fn parse_param(arg: &str) -> Result<(String, String), String> {
+    arg.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected <name>=<value>, got '{arg}'"))
+}
+
+#[derive(Debug, Parser)]
+struct FlightArguments {
+    #[clap(long, default_value = "127.0.0.1:50051")]
+    addr: std::net::SocketAddr,
+}
+
+#[derive(Debug, Parser)]
+struct PgwireArguments {
+    #[clap(long, default_value = "127.0.0.1:5432")]
+    addr: std::net::SocketAddr,
+}
+
+#[derive(Debug, Parser)]
+struct CacheArguments {
+    #[clap(subcommand)]
+    action: CacheAction,
+}
+
+#[derive(Debug, Subcommand)]
+enum CacheAction {
+    #[clap(
+        name = "stats",
+        about = "List the cached segments per chain and entity"
+    )]
+    Stats,
+
+    #[clap(name = "clear", about = "Delete every cached segment")]
+    Clear,
+}
+
+fn run_cache_command(action: CacheAction) -> Result<(), Box<dyn Error>> {
+    let Some(cache) = ChainCache::from_settings() else {
+        println!("The chain cache is disabled");
+        return Ok(());
+    };
+    match action {
+        CacheAction::Stats => {
+            let stats = cache.stats()?;
+            println!("{}", cache.dir().display());
+            if stats.is_empty() {
+                println!("No cached segments");
+            } else {
+                println!("{}", to_table(stats)?);
+            }
+        }
+        CacheAction::Clear => {
+            let removed = cache.clear()?;
+            println!("Removed {removed} segments from {}", cache.dir().display());
+        }
+    }
+    Ok(())
 }
 
 struct ResultHandler;
@@ -45,6 +131,9 @@ impl ResultHandler {
 
     pub fn handle_result(&self, query_results: Vec<QueryResult>) -> Result<(), Box<dyn Error>> {
         for query_result in query_results {
+            for (name, address) in &query_result.resolved_names {
+                println!("{name} -> {address}");
+            }
             match query_result.result {
                 ExpressionResult::Account(query_res) => {
                     println!("{}", to_table(query_res)?);
@@ -53,11 +142,45 @@ impl ResultHandler {
                     println!("{}", to_table(query_res)?);
                 }
                 ExpressionResult::Transaction(query_res) => {
-                    println!("{}", to_table(query_res)?);
+                    let rows = query_res.iter().map(TransactionCsvRow).collect();
+                    println!("{}", to_table(rows)?);
                 }
                 ExpressionResult::Log(query_res) => {
                     println!("{}", to_table(query_res)?);
                 }
+                ExpressionResult::Storage(query_res) => {
+                    println!("{}", to_table(query_res)?);
+                }
+                ExpressionResult::TokenBalance(query_res) => {
+                    println!("{}", to_table(query_res)?);
+                }
+                ExpressionResult::Call(query_res) => {
+                    println!("{}", to_table(query_res)?);
+                }
+                ExpressionResult::Withdrawal(query_res) => {
+                    println!("{}", to_table(query_res)?);
+                }
+                ExpressionResult::Authorization(query_res) => {
+                    println!("{}", to_table(query_res)?);
+                }
+                ExpressionResult::Contract(query_res) => {
+                    println!("{}", to_table(query_res)?);
+                }
+                ExpressionResult::StateDiff(query_res) => {
+                    println!("{}", to_table(query_res)?);
+                }
+                ExpressionResult::Ens(query_res) => {
+                    println!("{}", to_table(query_res)?);
+                }
+                ExpressionResult::Table(query_res) => {
+                    println!("{}", to_table(query_res)?);
+                }
+                ExpressionResult::Column(query_res) => {
+                    println!("{}", to_table(query_res)?);
+                }
+                ExpressionResult::Plan(query_res) => {
+                    println!("{}", to_table(query_res)?);
+                }
             }
         }
 
@@ -96,12 +219,19 @@ pub fn to_table<S: Serialize + core::fmt::Debug>(data: Vec<S>) -> Result<Table,
 #[tokio::main]
 pub async fn main() -> Result<(), Box<dyn Error>> {
     let args = Arguments::parse();
+    if args.no_cache {
+        ChainCache::disable();
+    }
 
     match args.subcmd {
         SubCommand::Run(run_args) => {
             let source = std::fs::read_to_string(run_args.file)?;
             let result_handler = ResultHandler::new();
-            let result = Interpreter::run_program(&source).await;
+            let mut params = Params::new();
+            for (key, value) in &run_args.params {
+                params.set(key.as_str(), Param::from_text(value));
+            }
+            let result = Interpreter::run_program_with_params(&source, &params).await;
             match result {
                 Ok(query_results) => {
                     result_handler.handle_result(query_results)?;
@@ -114,6 +244,15 @@ pub async fn main() -> Result<(), Box<dyn Error>> {
         SubCommand::Repl => {
             Repl::new().run().await?;
         }
+        SubCommand::Flight(flight_args) => {
+            flight::serve(flight_args.addr).await?;
+        }
+        SubCommand::Pgwire(pgwire_args) => {
+            pgwire::serve(pgwire_args.addr).await?;
+        }
+        SubCommand::Cache(cache_args) => {
+            run_cache_command(cache_args.action)?;
+        }
     }
 
     Ok(())
@@ -0,0 +1,1109 @@
+//! PostgreSQL wire protocol front end (`eql pgwire`).
+//!
+//! Speaks protocol 3.0 — the simple query flow (`Q`) and the extended flow
+//! (`Parse`/`Bind`/`Describe`/`Execute`/`Sync`) — so psql, Metabase and
+//! Grafana's Postgres source can point at EQL directly. Statements go through
+//! `parse_program` and the execution engine like `eql run`; rows are encoded
+//! from the same typed Arrow columns the Parquet exporter writes, each column
+//! carrying its Postgres type OID.
+//!
+//! Client tools also send catalog queries at startup (`version()`,
+//! `information_schema.tables`, `pg_catalog.pg_namespace`, session `SET`s).
+//! Those never reach EQL: `catalog` answers them from the entity schema.
+
+mod catalog;
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+use arrow::array::Array;
+use arrow::datatypes::DataType;
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::array_value_to_string;
+use eql_core::common::query_result::QueryResult;
+use eql_core::common::serializer::to_record_batch;
+use eql_core::interpreter::frontend::sql::params::{Param, Placeholder};
+use eql_core::interpreter::frontend::sql::EqlSqlError;
+use eql_core::interpreter::{Interpreter, PreparedStatement};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const PROTOCOL_VERSION: i32 = 196608;
+const SSL_REQUEST: i32 = 80877103;
+const GSSENC_REQUEST: i32 = 80877104;
+const CANCEL_REQUEST: i32 = 80877102;
+
+/// The longest startup packet accepted, its length word included. Real ones
+/// are a few hundred bytes of parameters.
+const MAX_STARTUP_LEN: i32 = 10_000;
+/// The longest message accepted after startup, its length word included.
+const MAX_MESSAGE_LEN: i32 = 8 << 20;
+
+/// The subset of Postgres types EQL columns map onto.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgType {
+    Bool,
+    Int2,
+    Int8,
+    Numeric,
+    Text,
+}
+
+impl PgType {
+    pub fn from_arrow(data_type: &DataType) -> PgType {
+        match data_type {
+            DataType::Boolean => PgType::Bool,
+            DataType::UInt8 => PgType::Int2,
+            DataType::Int64 => PgType::Int8,
+            // `bigint` is signed, so u64 values past i64::MAX only fit `numeric`.
+            DataType::UInt64 => PgType::Numeric,
+            DataType::Decimal128(_, _) => PgType::Numeric,
+            _ => PgType::Text,
+        }
+    }
+
+    pub fn oid(&self) -> i32 {
+        match self {
+            PgType::Bool => 16,
+            PgType::Int2 => 21,
+            PgType::Int8 => 20,
+            PgType::Numeric => 1700,
+            PgType::Text => 25,
+        }
+    }
+
+    /// The name `information_schema.columns.data_type` reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PgType::Bool => "boolean",
+            PgType::Int2 => "smallint",
+            PgType::Int8 => "bigint",
+            PgType::Numeric => "numeric",
+            PgType::Text => "text",
+        }
+    }
+
+    fn len(&self) -> i16 {
+        match self {
+            PgType::Bool => 1,
+            PgType::Int2 => 2,
+            PgType::Int8 => 8,
+            PgType::Numeric | PgType::Text => -1,
+        }
+    }
+
+    /// Encodes a value given in its text form (as Postgres prints it) in the
+    /// requested format: text (`0`) or binary (`1`).
+    fn encode(&self, value: &str, binary: bool) -> Result<Vec<u8>, PgError> {
+        if !binary {
+            return Ok(value.as_bytes().to_vec());
+        }
+        let invalid = || PgError::internal(format!("invalid {} value '{value}'", self.name()));
+        Ok(match self {
+            PgType::Bool => vec![(value == "t") as u8],
+            PgType::Int2 => value
+                .parse::<i16>()
+                .map_err(|_| invalid())?
+                .to_be_bytes()
+                .to_vec(),
+            PgType::Int8 => value
+                .parse::<i64>()
+                .map_err(|_| invalid())?
+                .to_be_bytes()
+                .to_vec(),
+            PgType::Numeric => encode_numeric(value).ok_or_else(invalid)?,
+            PgType::Text => value.as_bytes().to_vec(),
+        })
+    }
+}
+
+/// Binary `numeric` for an integer in decimal text: base-10000 digit groups,
+/// most significant first, with trailing zero groups dropped as Postgres does.
+fn encode_numeric(value: &str) -> Option<Vec<u8>> {
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let digits = digits.trim_start_matches('0');
+    let pad = (4 - digits.len() % 4) % 4;
+    let padded = format!("{}{digits}", "0".repeat(pad));
+    let mut groups = padded
+        .as_bytes()
+        .chunks(4)
+        .map(|c| std::str::from_utf8(c).unwrap().parse::<i16>().unwrap())
+        .collect::<Vec<_>>();
+    let weight = groups.len() as i16 - 1;
+    while groups.last() == Some(&0) {
+        groups.pop();
+    }
+
+    let mut out = Vec::with_capacity(8 + groups.len() * 2);
+    out.extend((groups.len() as i16).to_be_bytes());
+    out.extend((if groups.is_empty() { 0 } else { weight }).to_be_bytes());
+    out.extend((if negative { 0x4000u16 } else { 0 }).to_be_bytes());
+    out.extend(0i16.to_be_bytes());
+    for group in groups {
+        out.extend(group.to_be_bytes());
+    }
+    Some(out)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgError {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl PgError {
+    pub fn new(code: &'static str, message: impl Into<String>) -> Self {
+        PgError {
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        PgError::new("XX000", message)
+    }
+
+    fn protocol(message: impl Into<String>) -> Self {
+        PgError::new("08P01", message)
+    }
+
+    /// Maps an engine error onto the closest SQLSTATE class.
+    fn from_engine(err: anyhow::Error) -> Self {
+        let code = match err.downcast_ref::<EqlSqlError>() {
+            Some(EqlSqlError::Parse(_)) | Some(EqlSqlError::LegacySyntax { .. }) => "42601",
+            Some(EqlSqlError::NotSupported(_)) => "0A000",
+            Some(EqlSqlError::Validation(_)) => "42000",
+            None => "XX000",
+        };
+        PgError::new(code, err.to_string())
+    }
+}
+
+/// One statement's output: its columns, its rows in Postgres text form, and
+/// the `CommandComplete` tag. Commands without rows have no columns.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResultSet {
+    pub columns: Vec<(String, PgType)>,
+    pub rows: Vec<Vec<Option<String>>>,
+    pub tag: String,
+}
+
+impl ResultSet {
+    pub fn command(tag: &str) -> Self {
+        ResultSet {
+            tag: tag.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn from_batch(batch: &RecordBatch) -> Result<Self, PgError> {
+        let columns = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| (f.name().clone(), PgType::from_arrow(f.data_type())))
+            .collect::<Vec<_>>();
+        let rows = (0..batch.num_rows())
+            .map(|row| {
+                batch
+                    .columns()
+                    .iter()
+                    .map(|array| {
+                        if array.is_null(row) {
+                            return Ok(None);
+                        }
+                        let value = array_value_to_string(array, row)
+                            .map_err(|e| PgError::internal(e.to_string()))?;
+                        Ok(Some(match array.data_type() {
+                            DataType::Boolean => (if value == "true" { "t" } else { "f" }).into(),
+                            _ => value,
+                        }))
+                    })
+                    .collect::<Result<Vec<_>, PgError>>()
+            })
+            .collect::<Result<Vec<_>, PgError>>()?;
+        Ok(ResultSet {
+            tag: format!("SELECT {}", rows.len()),
+            columns,
+            rows,
+        })
+    }
+}
+
+fn is_empty_query(sql: &str) -> bool {
+    sql.trim().trim_end_matches(';').trim().is_empty()
+}
+
+/// Runs one query string. Catalog and session statements are answered
+/// locally; everything else is an EQL program.
+async fn run(sql: &str) -> Result<Vec<ResultSet>, PgError> {
+    if is_empty_query(sql) {
+        return Ok(vec![]);
+    }
+    if let Some(answer) = catalog::answer(sql) {
+        return answer;
+    }
+    let results = Interpreter::run_program(sql)
+        .await
+        .map_err(PgError::from_engine)?;
+    result_sets(&results)
+}
+
+/// Runs a statement from the extended flow: its prepared EQL program when it
+/// has one, otherwise its text, which is a catalog or empty statement.
+async fn run_statement(
+    sql: &str,
+    prepared: Option<&PreparedStatement>,
+) -> Result<Vec<ResultSet>, PgError> {
+    match prepared {
+        Some(prepared) => result_sets(&prepared.run().await.map_err(PgError::from_engine)?),
+        None => run(sql).await,
+    }
+}
+
+fn result_sets(results: &[QueryResult]) -> Result<Vec<ResultSet>, PgError> {
+    if results.is_empty() {
+        // Only `SET rpc_<chain>` statements produce no rows.
+        return Ok(vec![ResultSet::command("SET")]);
+    }
+    results
+        .iter()
+        .map(|r| {
+            let batch = to_record_batch(&r.result).map_err(|e| PgError::internal(e.to_string()))?;
+            ResultSet::from_batch(&batch)
+        })
+        .collect()
+}
+
+// --- Message framing ---------------------------------------------------------
+
+fn message(tag: u8, body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len() + 5);
+    out.push(tag);
+    out.extend((body.len() as i32 + 4).to_be_bytes());
+    out.extend(body);
+    out
+}
+
+fn cstr(out: &mut Vec<u8>, s: &str) {
+    out.extend(s.as_bytes());
+    out.push(0);
+}
+
+fn ready_for_query() -> Vec<u8> {
+    message(b'Z', b"I")
+}
+
+fn parameter_status(name: &str, value: &str) -> Vec<u8> {
+    let mut body = Vec::new();
+    cstr(&mut body, name);
+    cstr(&mut body, value);
+    message(b'S', &body)
+}
+
+fn error_response(err: &PgError) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (field, value) in [
+        (b'S', "ERROR"),
+        (b'V', "ERROR"),
+        (b'C', err.code),
+        (b'M', err.message.as_str()),
+    ] {
+        body.push(field);
+        cstr(&mut body, value);
+    }
+    body.push(0);
+    message(b'E', &body)
+}
+
+fn row_description(columns: &[(String, PgType)], formats: &[i16]) -> Vec<u8> {
+    if columns.is_empty() {
+        return message(b'n', &[]);
+    }
+    let mut body = Vec::new();
+    body.extend((columns.len() as i16).to_be_bytes());
+    for (i, (name, ty)) in columns.iter().enumerate() {
+        cstr(&mut body, name);
+        body.extend(0i32.to_be_bytes()); // table oid
+        body.extend(0i16.to_be_bytes()); // column attribute number
+        body.extend(ty.oid().to_be_bytes());
+        body.extend(ty.len().to_be_bytes());
+        body.extend((-1i32).to_be_bytes()); // type modifier
+        body.extend(format_for(formats, i).to_be_bytes());
+    }
+    message(b'T', &body)
+}
+
+/// The result format of column `i` under a `Bind`'s format codes: none means
+/// all text, one applies to every column, otherwise one per column.
+fn format_for(formats: &[i16], i: usize) -> i16 {
+    match formats.len() {
+        0 => 0,
+        1 => formats[0],
+        _ => formats.get(i).copied().unwrap_or(0),
+    }
+}
+
+fn data_row(
+    columns: &[(String, PgType)],
+    row: &[Option<String>],
+    formats: &[i16],
+) -> Result<Vec<u8>, PgError> {
+    let mut body = Vec::new();
+    body.extend((row.len() as i16).to_be_bytes());
+    for (i, value) in row.iter().enumerate() {
+        match value {
+            None => body.extend((-1i32).to_be_bytes()),
+            Some(value) => {
+                let bytes = columns[i].1.encode(value, format_for(formats, i) == 1)?;
+                body.extend((bytes.len() as i32).to_be_bytes());
+                body.extend(bytes);
+            }
+        }
+    }
+    Ok(message(b'D', &body))
+}
+
+fn command_complete(tag: &str) -> Vec<u8> {
+    let mut body = Vec::new();
+    cstr(&mut body, tag);
+    message(b'C', &body)
+}
+
+/// A cursor over a message body.
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Reader { buf }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], PgError> {
+        if self.buf.len() < n {
+            return Err(PgError::protocol("message too short"));
+        }
+        let (head, tail) = self.buf.split_at(n);
+        self.buf = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, PgError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn i16(&mut self) -> Result<i16, PgError> {
+        Ok(i16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn i32(&mut self) -> Result<i32, PgError> {
+        Ok(i32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn cstr(&mut self) -> Result<String, PgError> {
+        let end = self
+            .buf
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| PgError::protocol("unterminated string"))?;
+        let s = String::from_utf8_lossy(&self.buf[..end]).into_owned();
+        self.buf = &self.buf[end + 1..];
+        Ok(s)
+    }
+
+    fn i16_list(&mut self) -> Result<Vec<i16>, PgError> {
+        let n = self.i16()?;
+        (0..n).map(|_| self.i16()).collect()
+    }
+
+    fn i32_list(&mut self) -> Result<Vec<i32>, PgError> {
+        let n = self.i16()?;
+        (0..n).map(|_| self.i32()).collect()
+    }
+
+    /// A length-prefixed value; a length of -1 is NULL.
+    fn value(&mut self) -> Result<Option<&'a [u8]>, PgError> {
+        match self.i32()? {
+            len if len < 0 => Ok(None),
+            len => self.take(len as usize).map(Some),
+        }
+    }
+}
+
+fn parameter_description(types: &[i32]) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend((types.len() as i16).to_be_bytes());
+    for oid in types {
+        body.extend(oid.to_be_bytes());
+    }
+    message(b't', &body)
+}
+
+/// A `Bind` value as an EQL parameter. Text values are typed by shape, as
+/// `--param` values are; binary ones are read as their declared type.
+fn decode_param(oid: i32, binary: bool, bytes: &[u8]) -> Result<Param, PgError> {
+    let utf8 = |bytes: &[u8]| {
+        std::str::from_utf8(bytes)
+            .map(Param::from_text)
+            .map_err(|_| PgError::new("22021", "parameter value is not valid UTF-8"))
+    };
+    if !binary {
+        return utf8(bytes);
+    }
+    let invalid = || PgError::new("22P03", format!("invalid binary value for type {oid}"));
+    let integer = |value: i64| {
+        u64::try_from(value)
+            .map(Param::from)
+            .map_err(|_| PgError::new("22003", format!("parameter {value} is negative")))
+    };
+    match oid {
+        // bool
+        16 => match bytes {
+            [b] => Ok(Param::Bool(*b != 0)),
+            _ => Err(invalid()),
+        },
+        // int2, int4, int8
+        21 => integer(i16::from_be_bytes(bytes.try_into().map_err(|_| invalid())?).into()),
+        23 => integer(i32::from_be_bytes(bytes.try_into().map_err(|_| invalid())?).into()),
+        20 => integer(i64::from_be_bytes(bytes.try_into().map_err(|_| invalid())?)),
+        // bytea, sent as the `0x` hex an address or hash is written in
+        17 => Ok(Param::from_text(&format!(
+            "0x{}",
+            bytes.iter().map(|b| format!("{b:02x}")).collect::<String>()
+        ))),
+        // text, varchar, name, unknown
+        25 | 1043 | 19 | 705 => utf8(bytes),
+        _ => Err(PgError::new(
+            "0A000",
+            format!("binary parameters of type {oid} are not supported; send them as text"),
+        )),
+    }
+}
+
+// --- Session -----------------------------------------------------------------
+
+struct Statement {
+    sql: String,
+    /// The EQL program, lexed once at `Parse`. `None` for catalog, session
+    /// and empty statements, which are answered from `sql`.
+    prepared: Option<PreparedStatement>,
+    /// One type OID per parameter: the type `Parse` declared, or text.
+    param_types: Vec<i32>,
+    /// Results of a `Describe` that had to run the query to learn its
+    /// columns, reused by the first `Execute` instead of running it again.
+    described: Option<Vec<ResultSet>>,
+}
+
+struct Portal {
+    sql: String,
+    /// The statement's program with the `Bind` values bound.
+    prepared: Option<PreparedStatement>,
+    formats: Vec<i16>,
+    result: Option<ResultSet>,
+    /// Rows already sent by `Execute` calls with a row limit.
+    sent: usize,
+}
+
+#[derive(Default)]
+struct Session {
+    statements: HashMap<String, Statement>,
+    portals: HashMap<String, Portal>,
+    /// Set after an error in the extended flow: messages are discarded
+    /// until the next `Sync`.
+    failed: bool,
+}
+
+impl Session {
+    async fn simple_query(&mut self, body: &[u8]) -> Result<Vec<u8>, PgError> {
+        let sql = Reader::new(body).cstr()?;
+        let mut out = Vec::new();
+        match run(&sql).await {
+            Ok(results) if results.is_empty() => out.extend(message(b'I', &[])),
+            Ok(results) => {
+                for result in results {
+                    if !result.columns.is_empty() {
+                        out.extend(row_description(&result.columns, &[]));
+                    }
+                    for row in &result.rows {
+                        out.extend(data_row(&result.columns, row, &[])?);
+                    }
+                    out.extend(command_complete(&result.tag));
+                }
+            }
+            Err(err) => out.extend(error_response(&err)),
+        }
+        out.extend(ready_for_query());
+        Ok(out)
+    }
+
+    fn parse(&mut self, body: &[u8]) -> Result<Vec<u8>, PgError> {
+        let mut r = Reader::new(body);
+        let name = r.cstr()?;
+        let sql = r.cstr()?;
+        let declared = r.i32_list()?;
+        let prepared = if is_empty_query(&sql) || catalog::answer(&sql).is_some() {
+            None
+        } else {
+            Some(Interpreter::prepare(&sql).map_err(PgError::from_engine)?)
+        };
+        let mut count = declared.len();
+        for placeholder in prepared.iter().flat_map(|p| p.placeholders()) {
+            match placeholder {
+                Placeholder::Position(n) => count = count.max(*n),
+                Placeholder::Name(_) => {
+                    let message =
+                        format!("{placeholder} can't be bound over pgwire; use $1, $2, ...");
+                    return Err(PgError::new("0A000", message));
+                }
+            }
+        }
+        let param_types = (0..count)
+            .map(|i| match declared.get(i) {
+                Some(oid) if *oid != 0 => *oid,
+                _ => PgType::Text.oid(),
+            })
+            .collect();
+        self.statements.insert(
+            name,
+            Statement {
+                sql,
+                prepared,
+                param_types,
+                described: None,
+            },
+        );
+        Ok(message(b'1', &[]))
+    }
+
+    fn bind(&mut self, body: &[u8]) -> Result<Vec<u8>, PgError> {
+        let mut r = Reader::new(body);
+        let portal = r.cstr()?;
+        let statement = r.cstr()?;
+        let param_formats = r.i16_list()?;
+        let values = (0..r.i16()?)
+            .map(|_| r.value())
+            .collect::<Result<Vec<_>, _>>()?;
+        let formats = r.i16_list()?;
+        let statement = self
+            .statements
+            .get_mut(&statement)
+            .ok_or_else(|| PgError::new("26000", format!("unknown statement '{statement}'")))?;
+        if values.len() != statement.param_types.len() {
+            return Err(PgError::protocol(format!(
+                "bind message supplies {} parameters, but prepared statement requires {}",
+                values.len(),
+                statement.param_types.len()
+            )));
+        }
+        let mut prepared = statement.prepared.clone();
+        if let Some(program) = prepared.as_mut() {
+            for (i, value) in values.into_iter().enumerate() {
+                let position = Placeholder::Position(i + 1);
+                // A type declared past the last placeholder binds nothing.
+                if !program.placeholders().contains(&position) {
+                    continue;
+                }
+                let value = value.ok_or_else(|| {
+                    PgError::new(
+                        "22004",
+                        format!("{position} is NULL; EQL has no NULL values"),
+                    )
+                })?;
+                let param = decode_param(
+                    statement.param_types[i],
+                    format_for(&param_formats, i) == 1,
+                    value,
+                )?;
+                *program = program.clone().bind(position, param);
+            }
+        }
+        let result = statement
+            .described
+            .take()
+            .and_then(|results| results.into_iter().next());
+        self.portals.insert(
+            portal,
+            Portal {
+                sql: statement.sql.clone(),
+                prepared,
+                formats,
+                result,
+                sent: 0,
+            },
+        );
+        Ok(message(b'2', &[]))
+    }
+
+    async fn describe(&mut self, body: &[u8]) -> Result<Vec<u8>, PgError> {
+        let mut r = Reader::new(body);
+        let kind = r.u8()?;
+        let name = r.cstr()?;
+        match kind {
+            b'S' => {
+                let statement = self
+                    .statements
+                    .get_mut(&name)
+                    .ok_or_else(|| PgError::new("26000", format!("unknown statement '{name}'")))?;
+                let mut out = parameter_description(&statement.param_types);
+                if !statement.param_types.is_empty() {
+                    // The columns depend on the bound values, so clients
+                    // learn them by describing the portal instead.
+                    out.extend(message(b'n', &[]));
+                    return Ok(out);
+                }
+                // EQL's columns depend on the result, so describing means
+                // running the query.
+                let results = run_statement(&statement.sql, statement.prepared.as_ref()).await?;
+                let columns = results
+                    .first()
+                    .map(|r| r.columns.clone())
+                    .unwrap_or_default();
+                statement.described = Some(results);
+                out.extend(row_description(&columns, &[]));
+                Ok(out)
+            }
+            b'P' => {
+                let portal = self
+                    .portals
+                    .get_mut(&name)
+                    .ok_or_else(|| PgError::new("34000", format!("unknown portal '{name}'")))?;
+                if portal.result.is_none() {
+                    let results = run_statement(&portal.sql, portal.prepared.as_ref()).await?;
+                    portal.result = Some(first_result(results));
+                }
+                let result = portal.result.as_ref().unwrap();
+                Ok(row_description(&result.columns, &portal.formats))
+            }
+            _ => Err(PgError::protocol("invalid Describe target")),
+        }
+    }
+
+    async fn execute(&mut self, body: &[u8]) -> Result<Vec<u8>, PgError> {
+        let mut r = Reader::new(body);
+        let name = r.cstr()?;
+        let max_rows = r.i32()?;
+        let portal = self
+            .portals
+            .get_mut(&name)
+            .ok_or_else(|| PgError::new("34000", format!("unknown portal '{name}'")))?;
+        if portal.result.is_none() {
+            let results = run_statement(&portal.sql, portal.prepared.as_ref()).await?;
+            portal.result = Some(first_result(results));
+        }
+        let result = portal.result.as_ref().unwrap();
+        if result.columns.is_empty() && result.tag.is_empty() {
+            return Ok(message(b'I', &[]));
+        }
+
+        let remaining = &result.rows[portal.sent..];
+        let take = match max_rows {
+            n if n > 0 => remaining.len().min(n as usize),
+            _ => remaining.len(),
+        };
+        let mut out = Vec::new();
+        for row in &remaining[..take] {
+            out.extend(data_row(&result.columns, row, &portal.formats)?);
+        }
+        portal.sent += take;
+        if portal.sent < result.rows.len() {
+            out.extend(message(b's', &[]));
+        } else {
+            out.extend(command_complete(&result.tag));
+        }
+        Ok(out)
+    }
+
+    fn close(&mut self, body: &[u8]) -> Result<Vec<u8>, PgError> {
+        let mut r = Reader::new(body);
+        let kind = r.u8()?;
+        let name = r.cstr()?;
+        match kind {
+            b'S' => self.statements.remove(&name).map(|_| ()),
+            _ => self.portals.remove(&name).map(|_| ()),
+        };
+        Ok(message(b'3', &[]))
+    }
+}
+
+/// The extended protocol serves one result per portal; an empty query has
+/// none, which is marked by an empty tag.
+fn first_result(results: Vec<ResultSet>) -> ResultSet {
+    results.into_iter().next().unwrap_or_default()
+}
+
+/// Reads a message's tag and body, the body a protocol error when its
+/// length is out of bounds.
+async fn read_message<S: AsyncRead + Unpin>(
+    stream: &mut S,
+) -> std::io::Result<Option<(u8, Result<Vec<u8>, PgError>)>> {
+    let tag = match stream.read_u8().await {
+        Ok(tag) => tag,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let body = read_body(stream, 4, MAX_MESSAGE_LEN).await?;
+    Ok(Some((tag, body)))
+}
+
+/// Reads a length word and the body it measures. The length comes from a
+/// client that hasn't authenticated, so one below `min` or above `max` is a
+/// protocol error, and nothing is allocated for it.
+async fn read_body<S: AsyncRead + Unpin>(
+    stream: &mut S,
+    min: i32,
+    max: i32,
+) -> std::io::Result<Result<Vec<u8>, PgError>> {
+    let len = stream.read_i32().await?;
+    if !(min..=max).contains(&len) {
+        return Ok(Err(PgError::protocol(format!(
+            "invalid message length {len}"
+        ))));
+    }
+    let mut body = vec![0; (len - 4) as usize];
+    stream.read_exact(&mut body).await?;
+    Ok(Ok(body))
+}
+
+/// Reads the startup packet, declining SSL/GSS encryption requests. Returns
+/// `false` when the connection should be dropped (e.g. a cancel request).
+async fn startup<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut S) -> std::io::Result<bool> {
+    loop {
+        // The length word and the protocol code, at least.
+        let body = match read_body(stream, 8, MAX_STARTUP_LEN).await? {
+            Ok(body) => body,
+            Err(err) => {
+                stream.write_all(&error_response(&err)).await?;
+                return Ok(false);
+            }
+        };
+        let code = i32::from_be_bytes(body[..4].try_into().unwrap());
+        match code {
+            SSL_REQUEST | GSSENC_REQUEST => stream.write_all(b"N").await?,
+            CANCEL_REQUEST => return Ok(false),
+            PROTOCOL_VERSION => break,
+            other => {
+                let err = PgError::protocol(format!("unsupported protocol version {other}"));
+                stream.write_all(&error_response(&err)).await?;
+                return Ok(false);
+            }
+        }
+    }
+
+    let mut out = message(b'R', &0i32.to_be_bytes()); // AuthenticationOk
+    for (name, value) in [
+        ("server_version", "14.0 (EQL)"),
+        ("server_encoding", "UTF8"),
+        ("client_encoding", "UTF8"),
+        ("DateStyle", "ISO, MDY"),
+        ("integer_datetimes", "on"),
+        ("standard_conforming_strings", "on"),
+        ("TimeZone", "UTC"),
+    ] {
+        out.extend(parameter_status(name, value));
+    }
+    let mut key = 0i32.to_be_bytes().to_vec(); // process id
+    key.extend(0i32.to_be_bytes()); // secret key
+    out.extend(message(b'K', &key));
+    out.extend(ready_for_query());
+    stream.write_all(&out).await?;
+    Ok(true)
+}
+
+pub async fn handle_connection<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+) -> std::io::Result<()> {
+    if !startup(&mut stream).await? {
+        return Ok(());
+    }
+
+    let mut session = Session::default();
+    while let Some((tag, body)) = read_message(&mut stream).await? {
+        let body = match body {
+            Ok(body) => body,
+            Err(err) => {
+                stream.write_all(&error_response(&err)).await?;
+                return Ok(());
+            }
+        };
+        let extended = matches!(tag, b'P' | b'B' | b'D' | b'E' | b'C' | b'H');
+        if extended && session.failed {
+            continue;
+        }
+        let response = match tag {
+            b'Q' => session.simple_query(&body).await,
+            b'P' => session.parse(&body),
+            b'B' => session.bind(&body),
+            b'D' => session.describe(&body).await,
+            b'E' => session.execute(&body).await,
+            b'C' => session.close(&body),
+            b'H' => Ok(vec![]),
+            b'S' => {
+                session.failed = false;
+                Ok(ready_for_query())
+            }
+            b'X' => return Ok(()),
+            other => Err(PgError::protocol(format!(
+                "unsupported message type '{}'",
+                other as char
+            ))),
+        };
+        let out = match response {
+            Ok(out) => out,
+            Err(err) => {
+                session.failed = extended;
+                error_response(&err)
+            }
+        };
+        stream.write_all(&out).await?;
+    }
+    Ok(())
+}
+
+pub async fn serve(addr: SocketAddr) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(addr).await?;
+    println!("EQL pgwire server listening on {addr}");
+    loop {
+        let (socket, _) = listener.accept().await?;
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(socket).await {
+                eprintln!("pgwire connection error: {e}");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_postgres::{NoTls, SimpleQueryMessage};
+
+    async fn client() -> tokio_postgres::Client {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (socket, _) = listener.accept().await.unwrap();
+                tokio::spawn(handle_connection(socket));
+            }
+        });
+        let (client, connection) = tokio_postgres::connect(
+            &format!("host=127.0.0.1 port={} user=eql", addr.port()),
+            NoTls,
+        )
+        .await
+        .unwrap();
+        tokio::spawn(connection);
+        client
+    }
+
+    /// What the server answers `packets`, up to closing the connection.
+    async fn raw_reply(packets: &[u8]) -> Vec<u8> {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let handler = tokio::spawn(handle_connection(server));
+        client.write_all(packets).await.unwrap();
+        let mut reply = Vec::new();
+        client.read_to_end(&mut reply).await.unwrap();
+        handler.await.unwrap().unwrap();
+        reply
+    }
+
+    fn is_protocol_error(reply: &[u8]) -> bool {
+        reply.first() == Some(&b'E') && reply.windows(5).any(|code| code == b"08P01")
+    }
+
+    #[tokio::test]
+    async fn startup_lengths_out_of_bounds_close_the_connection() {
+        // Too short to hold a protocol code.
+        assert!(is_protocol_error(&raw_reply(&4i32.to_be_bytes()).await));
+        assert!(is_protocol_error(&raw_reply(&i32::MIN.to_be_bytes()).await));
+        // Nothing is allocated for a length no startup packet has.
+        assert!(is_protocol_error(&raw_reply(&i32::MAX.to_be_bytes()).await));
+    }
+
+    #[tokio::test]
+    async fn message_lengths_out_of_bounds_close_the_connection() {
+        let mut startup = PROTOCOL_VERSION.to_be_bytes().to_vec();
+        startup.extend(b"user\0eql\0\0");
+        let mut packets = (startup.len() as i32 + 4).to_be_bytes().to_vec();
+        packets.extend(startup);
+        for len in [3, i32::MAX] {
+            let mut packets = packets.clone();
+            packets.push(b'Q');
+            packets.extend(len.to_be_bytes());
+            let reply = raw_reply(&packets).await;
+            // AuthenticationOk and the rest of startup, then the error.
+            let error = reply.iter().rposition(|&tag| tag == b'E').unwrap();
+            assert!(reply.starts_with(b"R"));
+            assert!(is_protocol_error(&reply[error..]), "{reply:?}");
+        }
+    }
+
+    #[test]
+    fn encodes_binary_numeric() {
+        // 1_0000_0000 -> digits [1] with weight 2 (trailing zero groups dropped).
+        assert_eq!(
+            encode_numeric("100000000").unwrap(),
+            [0, 1, 0, 2, 0, 0, 0, 0, 0, 1]
+        );
+        assert_eq!(
+            encode_numeric("12345").unwrap(),
+            [0, 2, 0, 1, 0, 0, 0, 0, 0, 1, 9, 41]
+        );
+        assert_eq!(encode_numeric("0").unwrap(), [0, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(encode_numeric("1.5").is_none());
+    }
+
+    #[test]
+    fn u64_columns_encode_past_i64_max_in_binary() {
+        let pg_type = PgType::from_arrow(&DataType::UInt64);
+        let max = u64::MAX.to_string();
+        assert_eq!(
+            pg_type.encode(&max, true).unwrap(),
+            encode_numeric(&max).unwrap()
+        );
+    }
+
+    #[test]
+    fn maps_arrow_types_to_postgres_oids() {
+        assert_eq!(PgType::from_arrow(&DataType::UInt64).oid(), 1700);
+        assert_eq!(PgType::from_arrow(&DataType::Int64).oid(), 20);
+        assert_eq!(PgType::from_arrow(&DataType::Decimal128(38, 0)).oid(), 1700);
+        assert_eq!(PgType::from_arrow(&DataType::Utf8).oid(), 25);
+        assert_eq!(PgType::from_arrow(&DataType::Boolean).oid(), 16);
+    }
+
+    #[tokio::test]
+    async fn simple_query_answers_catalog_tables() {
+        let client = client().await;
+        let rows = client
+            .simple_query(
+                "SELECT table_name FROM information_schema.tables WHERE table_schema = 'public'",
+            )
+            .await
+            .unwrap();
+        let names = rows
+            .iter()
+            .filter_map(|m| match m {
+                SimpleQueryMessage::Row(row) => row.get(0).map(str::to_string),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "accounts",
+                "blocks",
+                "transactions",
+                "logs",
+                "storage",
+                "token_balances",
+                "call",
+                "withdrawals",
+                "authorizations",
+                "contracts",
+                "state_diffs",
+                "ens"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn extended_query_returns_typed_columns() {
+        let client = client().await;
+        let rows = client
+            .query(
+                "SELECT column_name, data_type FROM information_schema.columns \
+                 WHERE table_name = 'logs' AND column_name = 'block_number'",
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].get::<_, &str>("data_type"), "numeric");
+    }
+
+    #[tokio::test]
+    async fn startup_probes_are_answered() {
+        let client = client().await;
+        client
+            .simple_query("SET extra_float_digits = 3")
+            .await
+            .unwrap();
+        let row = client.query_one("SELECT version()", &[]).await.unwrap();
+        assert!(row.get::<_, &str>(0).contains("EQL"));
+    }
+
+    #[tokio::test]
+    async fn eql_errors_carry_a_sqlstate() {
+        let client = client().await;
+        let err = client
+            .query("SELECT * FROM account WHERE chain = eth", &[])
+            .await
+            .unwrap_err();
+        let db = err.as_db_error().unwrap();
+        assert_eq!(db.code().code(), "42000");
+        assert!(db.message().contains("did you mean"), "{}", db.message());
+    }
+
+    #[tokio::test]
+    async fn prepared_statements_report_their_parameters() {
+        let client = client().await;
+        let statement = client
+            .prepare("SELECT * FROM blocks WHERE number BETWEEN $1 AND $2 AND chain = eth")
+            .await
+            .unwrap();
+        assert_eq!(
+            statement.params(),
+            [
+                tokio_postgres::types::Type::TEXT,
+                tokio_postgres::types::Type::TEXT
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn bound_parameters_reach_eql() {
+        let client = client().await;
+        // Bound, `1 ether` stays a string rather than folding into wei, so
+        // the block number is rejected with the value as it was sent.
+        let err = client
+            .query(
+                "SELECT * FROM blocks WHERE number = $1 AND chain = eth",
+                &[&"1 ether"],
+            )
+            .await
+            .unwrap_err();
+        let db = err.as_db_error().unwrap();
+        assert!(db.message().contains("'1 ether'"), "{}", db.message());
+    }
+
+    #[test]
+    fn decodes_binary_parameters_by_type() {
+        assert_eq!(
+            decode_param(20, true, &7i64.to_be_bytes()).unwrap(),
+            Param::from(7u64)
+        );
+        assert_eq!(decode_param(16, true, &[1]).unwrap(), Param::Bool(true));
+        assert!(matches!(
+            decode_param(17, true, &[0xab; 20]).unwrap(),
+            Param::Address(_)
+        ));
+        assert_eq!(
+            decode_param(23, true, &(-1i32).to_be_bytes())
+                .unwrap_err()
+                .code,
+            "22003"
+        );
+        assert_eq!(
+            decode_param(0, false, b"vitalik.eth").unwrap(),
+            Param::Text("vitalik.eth".into())
+        );
+    }
+}
@@ -0,0 +1,458 @@
+//! Catalog and session statements that Postgres clients send on their own —
+//! `version()`, `SET extra_float_digits`, `information_schema.tables`,
+//! `pg_catalog.pg_namespace` — answered from the entity schema rather than
+//! handed to EQL, which has no such tables.
+//!
+//! Catalog queries are served from in-memory tables with a deliberately small
+//! evaluator: a single catalog relation, a projection of plain columns, and
+//! `=`/`IN` conjuncts against literals in WHERE. Other predicates are
+//! ignored, so a query may see extra rows but never misses one.
+
+use super::{PgError, PgType, ResultSet};
+use eql_core::interpreter::frontend::sql::schema::EntityKind;
+use sqlparser::ast::{
+    BinaryOperator, Expr, Query, SelectItem, SetExpr, Statement, TableFactor, Value,
+};
+use sqlparser::dialect::PostgreSqlDialect;
+use sqlparser::parser::Parser;
+
+pub const DATABASE: &str = "eql";
+pub const SCHEMA: &str = "public";
+
+/// Answers `sql` if every statement in it is a catalog or session statement;
+/// `None` means it's an EQL program.
+pub fn answer(sql: &str) -> Option<Result<Vec<ResultSet>, PgError>> {
+    let statements = Parser::parse_sql(&PostgreSqlDialect {}, sql).ok()?;
+    if statements.is_empty() {
+        return None;
+    }
+    statements.iter().map(statement).collect()
+}
+
+fn statement(stmt: &Statement) -> Option<Result<ResultSet, PgError>> {
+    match stmt {
+        // `SET rpc_<chain>` is EQL's own session statement.
+        Statement::SetVariable { variables, .. } => {
+            if variables
+                .to_string()
+                .to_ascii_lowercase()
+                .starts_with("rpc_")
+            {
+                None
+            } else {
+                Some(Ok(ResultSet::command("SET")))
+            }
+        }
+        Statement::SetNames { .. } | Statement::SetTimeZone { .. } => {
+            Some(Ok(ResultSet::command("SET")))
+        }
+        Statement::StartTransaction { .. } => Some(Ok(ResultSet::command("BEGIN"))),
+        Statement::Commit { .. } => Some(Ok(ResultSet::command("COMMIT"))),
+        Statement::Rollback { .. } => Some(Ok(ResultSet::command("ROLLBACK"))),
+        Statement::Discard { .. } => Some(Ok(ResultSet::command("DISCARD ALL"))),
+        Statement::ShowVariable { variable } => {
+            let name = variable
+                .iter()
+                .map(|i| i.value.to_ascii_lowercase())
+                .collect::<Vec<_>>()
+                .join(" ");
+            Some(Ok(single_row(&name, PgType::Text, show(&name))))
+        }
+        Statement::Query(query) => select(query),
+        _ => None,
+    }
+}
+
+/// Values for `SHOW <name>`, matching the parameters sent at startup.
+fn show(name: &str) -> String {
+    match name {
+        "server_version" => "14.0",
+        "server_encoding" | "client_encoding" => "UTF8",
+        "datestyle" => "ISO, MDY",
+        "timezone" => "UTC",
+        "standard_conforming_strings" | "integer_datetimes" => "on",
+        "transaction isolation level" | "transaction_isolation" => "read committed",
+        "search_path" => SCHEMA,
+        _ => "",
+    }
+    .to_string()
+}
+
+fn single_row(column: &str, ty: PgType, value: String) -> ResultSet {
+    ResultSet {
+        columns: vec![(column.to_string(), ty)],
+        rows: vec![vec![Some(value)]],
+        tag: "SELECT 1".to_string(),
+    }
+}
+
+fn select(query: &Query) -> Option<Result<ResultSet, PgError>> {
+    let SetExpr::Select(select) = query.body.as_ref() else {
+        return None;
+    };
+    if select.from.is_empty() {
+        return Some(constant_row(&select.projection));
+    }
+    let [from] = select.from.as_slice() else {
+        return None;
+    };
+    let TableFactor::Table { name, .. } = &from.relation else {
+        return None;
+    };
+    let parts = name
+        .0
+        .iter()
+        .map(|i| i.value.to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    let relation = match parts.as_slice() {
+        [schema, table] if schema == "information_schema" || schema == "pg_catalog" => {
+            format!("{schema}.{table}")
+        }
+        [table] if table.starts_with("pg_") => format!("pg_catalog.{table}"),
+        _ => return None,
+    };
+    if !from.joins.is_empty() {
+        return Some(Err(PgError::new(
+            "0A000",
+            "joins over catalog tables are not supported by EQL",
+        )));
+    }
+    let Some(table) = catalog_table(&relation) else {
+        return Some(Err(PgError::new(
+            "42P01",
+            format!("relation \"{relation}\" does not exist"),
+        )));
+    };
+    Some(table.query(&select.projection, select.selection.as_ref(), &query.limit))
+}
+
+/// `SELECT version()`, `SELECT current_schema()`, `SELECT 1` and friends.
+fn constant_row(projection: &[SelectItem]) -> Result<ResultSet, PgError> {
+    let mut columns = Vec::new();
+    let mut row = Vec::new();
+    for item in projection {
+        let (expr, alias) = match item {
+            SelectItem::UnnamedExpr(expr) => (expr, None),
+            SelectItem::ExprWithAlias { expr, alias } => (expr, Some(alias.value.clone())),
+            _ => return Err(unsupported(item)),
+        };
+        let (name, ty, value) = match expr {
+            Expr::Function(function) => {
+                let name = function.name.to_string().to_ascii_lowercase();
+                let value = match name.trim_start_matches("pg_catalog.") {
+                    "version" => format!("PostgreSQL 14.0 (EQL {})", env!("CARGO_PKG_VERSION")),
+                    "current_schema" => SCHEMA.to_string(),
+                    "current_database" => DATABASE.to_string(),
+                    "current_user" | "session_user" | "user" => DATABASE.to_string(),
+                    _ => return Err(unsupported(item)),
+                };
+                (name, PgType::Text, Some(value))
+            }
+            Expr::Value(Value::Number(n, _)) => {
+                ("?column?".to_string(), PgType::Int8, Some(n.clone()))
+            }
+            Expr::Value(Value::SingleQuotedString(s)) => {
+                ("?column?".to_string(), PgType::Text, Some(s.clone()))
+            }
+            Expr::Value(Value::Null) => ("?column?".to_string(), PgType::Text, None),
+            _ => return Err(unsupported(item)),
+        };
+        columns.push((alias.unwrap_or(name), ty));
+        row.push(value);
+    }
+    Ok(ResultSet {
+        columns,
+        rows: vec![row],
+        tag: "SELECT 1".to_string(),
+    })
+}
+
+fn unsupported(item: &impl std::fmt::Display) -> PgError {
+    PgError::new(
+        "0A000",
+        format!("catalog expression '{item}' is not supported by EQL"),
+    )
+}
+
+struct CatalogTable {
+    columns: Vec<(&'static str, PgType)>,
+    rows: Vec<Vec<Option<String>>>,
+}
+
+impl CatalogTable {
+    fn new(columns: Vec<(&'static str, PgType)>, rows: Vec<Vec<String>>) -> Self {
+        CatalogTable {
+            columns,
+            rows: rows
+                .into_iter()
+                .map(|row| row.into_iter().map(Some).collect())
+                .collect(),
+        }
+    }
+
+    fn index(&self, column: &str) -> Result<usize, PgError> {
+        self.columns
+            .iter()
+            .position(|(name, _)| *name == column)
+            .ok_or_else(|| PgError::new("42703", format!("column \"{column}\" does not exist")))
+    }
+
+    fn query(
+        &self,
+        projection: &[SelectItem],
+        selection: Option<&Expr>,
+        limit: &Option<Expr>,
+    ) -> Result<ResultSet, PgError> {
+        let mut filters = Vec::new();
+        if let Some(selection) = selection {
+            self.filters(selection, &mut filters)?;
+        }
+        let mut rows = self
+            .rows
+            .iter()
+            .filter(|row| {
+                filters
+                    .iter()
+                    .all(|(i, values)| values.iter().any(|v| row[*i].as_deref() == Some(v)))
+            })
+            .collect::<Vec<_>>();
+        if let Some(Expr::Value(Value::Number(n, _))) = limit {
+            rows.truncate(n.parse().unwrap_or(usize::MAX));
+        }
+
+        let mut picked = Vec::new();
+        for item in projection {
+            match item {
+                SelectItem::Wildcard(_) | SelectItem::QualifiedWildcard(..) => {
+                    picked.extend(
+                        self.columns
+                            .iter()
+                            .enumerate()
+                            .map(|(i, c)| (i, c.0.to_string())),
+                    );
+                }
+                SelectItem::UnnamedExpr(expr) => {
+                    let column = column_name(expr).ok_or_else(|| unsupported(item))?;
+                    picked.push((self.index(&column)?, column));
+                }
+                SelectItem::ExprWithAlias { expr, alias } => {
+                    let column = column_name(expr).ok_or_else(|| unsupported(item))?;
+                    picked.push((self.index(&column)?, alias.value.clone()));
+                }
+            }
+        }
+
+        Ok(ResultSet {
+            columns: picked
+                .iter()
+                .map(|(i, name)| (name.clone(), self.columns[*i].1))
+                .collect(),
+            tag: format!("SELECT {}", rows.len()),
+            rows: rows
+                .into_iter()
+                .map(|row| picked.iter().map(|(i, _)| row[*i].clone()).collect())
+                .collect(),
+        })
+    }
+
+    /// Collects `column = 'x'` and `column IN ('x', …)` conjuncts as
+    /// (column index, accepted values). Anything else is skipped.
+    fn filters(&self, expr: &Expr, out: &mut Vec<(usize, Vec<String>)>) -> Result<(), PgError> {
+        match expr {
+            Expr::BinaryOp {
+                left,
+                op: BinaryOperator::And,
+                right,
+            } => {
+                self.filters(left, out)?;
+                self.filters(right, out)?;
+            }
+            Expr::Nested(inner) => self.filters(inner, out)?,
+            Expr::BinaryOp {
+                left,
+                op: BinaryOperator::Eq,
+                right,
+            } => {
+                if let (Some(column), Some(value)) = (column_name(left), literal(right)) {
+                    out.push((self.index(&column)?, vec![value]));
+                }
+            }
+            Expr::InList {
+                expr,
+                list,
+                negated: false,
+            } => {
+                if let (Some(column), Some(values)) = (
+                    column_name(expr),
+                    list.iter().map(literal).collect::<Option<Vec<_>>>(),
+                ) {
+                    out.push((self.index(&column)?, values));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn column_name(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Identifier(ident) => Some(ident.value.to_ascii_lowercase()),
+        Expr::CompoundIdentifier(idents) => idents.last().map(|i| i.value.to_ascii_lowercase()),
+        _ => None,
+    }
+}
+
+fn literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Value(Value::SingleQuotedString(s)) | Expr::Value(Value::Number(s, _)) => {
+            Some(s.clone())
+        }
+        Expr::Cast { expr, .. } => literal(expr),
+        _ => None,
+    }
+}
+
+fn catalog_table(relation: &str) -> Option<CatalogTable> {
+    let text = PgType::Text;
+    let tables = || {
+        EntityKind::ALL
+            .iter()
+            .map(|kind| kind.table_name().to_string())
+    };
+    Some(match relation {
+        "information_schema.schemata" => CatalogTable::new(
+            vec![
+                ("catalog_name", text),
+                ("schema_name", text),
+                ("schema_owner", text),
+            ],
+            [SCHEMA, "information_schema", "pg_catalog"]
+                .iter()
+                .map(|s| vec![DATABASE.into(), s.to_string(), DATABASE.into()])
+                .collect(),
+        ),
+        "information_schema.tables" => CatalogTable::new(
+            vec![
+                ("table_catalog", text),
+                ("table_schema", text),
+                ("table_name", text),
+                ("table_type", text),
+            ],
+            tables()
+                .map(|t| vec![DATABASE.into(), SCHEMA.into(), t, "BASE TABLE".into()])
+                .collect(),
+        ),
+        "information_schema.columns" => CatalogTable::new(
+            vec![
+                ("table_catalog", text),
+                ("table_schema", text),
+                ("table_name", text),
+                ("column_name", text),
+                ("ordinal_position", PgType::Int8),
+                ("data_type", text),
+                ("udt_name", text),
+                ("is_nullable", text),
+            ],
+            EntityKind::ALL
+                .iter()
+                .flat_map(|kind| {
+                    let schema = kind.arrow_schema();
+                    schema
+                        .fields()
+                        .iter()
+                        .enumerate()
+                        .map(|(i, field)| {
+                            let ty = PgType::from_arrow(field.data_type());
+                            vec![
+                                DATABASE.into(),
+                                SCHEMA.into(),
+                                kind.table_name().into(),
+                                field.name().clone(),
+                                (i + 1).to_string(),
+                                ty.name().into(),
+                                udt_name(ty).into(),
+                                "YES".into(),
+                            ]
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect(),
+        ),
+        "pg_catalog.pg_namespace" => CatalogTable::new(
+            vec![("oid", PgType::Int8), ("nspname", text)],
+            [
+                ("2200", SCHEMA),
+                ("11", "pg_catalog"),
+                ("13000", "information_schema"),
+            ]
+            .iter()
+            .map(|(oid, name)| vec![oid.to_string(), name.to_string()])
+            .collect(),
+        ),
+        "pg_catalog.pg_tables" => CatalogTable::new(
+            vec![
+                ("schemaname", text),
+                ("tablename", text),
+                ("tableowner", text),
+            ],
+            tables()
+                .map(|t| vec![SCHEMA.into(), t, DATABASE.into()])
+                .collect(),
+        ),
+        "pg_catalog.pg_database" => {
+            CatalogTable::new(vec![("datname", text)], vec![vec![DATABASE.into()]])
+        }
+        _ => return None,
+    })
+}
+
+fn udt_name(ty: PgType) -> &'static str {
+    match ty {
+        PgType::Bool => "bool",
+        PgType::Int2 => "int2",
+        PgType::Int8 => "int8",
+        PgType::Numeric => "numeric",
+        PgType::Text => "text",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn one(sql: &str) -> ResultSet {
+        answer(sql).unwrap().unwrap().remove(0)
+    }
+
+    #[test]
+    fn eql_programs_are_not_catalog_queries() {
+        assert!(answer("SELECT * FROM blocks WHERE number = 1 AND chain = eth").is_none());
+        assert!(answer("SET rpc_eth = 'http://localhost:8545'").is_none());
+    }
+
+    #[test]
+    fn filters_and_projects_catalog_columns() {
+        let result = one(
+            "SELECT column_name AS name FROM information_schema.columns \
+             WHERE table_schema = 'public' AND table_name IN ('accounts')",
+        );
+        assert_eq!(result.columns, [("name".to_string(), PgType::Text)]);
+        assert!(result.rows.contains(&vec![Some("balance".to_string())]));
+        assert!(!result.rows.contains(&vec![Some("topic0".to_string())]));
+    }
+
+    #[test]
+    fn unknown_catalog_relations_are_errors() {
+        let err = answer("SELECT * FROM pg_catalog.pg_proc")
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(err.code, "42P01");
+    }
+
+    #[test]
+    fn show_reports_session_parameters() {
+        let result = one("SHOW search_path");
+        assert_eq!(result.rows, [[Some(SCHEMA.to_string())]]);
+    }
+}
@@ -7,7 +7,7 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType},
 };
 use eql_core::{
-    common::query_result::{ExpressionResult, QueryResult},
+    common::query_result::{ExpressionResult, QueryResult, TransactionCsvRow},
     interpreter::Interpreter,
 };
 use std::io::{stdout, Stdout, Write};
@@ -216,6 +216,10 @@ impl Repl {
         query_results: Vec<QueryResult>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         for query_result in query_results {
+            for (name, address) in &query_result.resolved_names {
+                let line = format!("{name} -> {address}");
+                queue!(stdout(), MoveToNextLine(1), Print(line.dark_grey())).unwrap();
+            }
             match query_result.result {
                 ExpressionResult::Account(query_res) => {
                     let table = to_table(query_res)?;
@@ -230,7 +234,8 @@ impl Repl {
                     });
                 }
                 ExpressionResult::Transaction(query_res) => {
-                    let table = to_table(query_res)?;
+                    let rows = query_res.iter().map(TransactionCsvRow).collect();
+                    let table = to_table(rows)?;
                     table.to_string().split("\n").for_each(|line| {
                         queue!(stdout(), MoveToNextLine(1), Print(line.yellow())).unwrap();
                     });
@@ -241,6 +246,72 @@ impl Repl {
                         queue!(stdout(), MoveToNextLine(1), Print(line.cyan())).unwrap();
                     });
                 }
+                ExpressionResult::Storage(query_res) => {
+                    let table = to_table(query_res)?;
+                    table.to_string().split("\n").for_each(|line| {
+                        queue!(stdout(), MoveToNextLine(1), Print(line.blue())).unwrap();
+                    });
+                }
+                ExpressionResult::TokenBalance(query_res) => {
+                    let table = to_table(query_res)?;
+                    table.to_string().split("\n").for_each(|line| {
+                        queue!(stdout(), MoveToNextLine(1), Print(line.green())).unwrap();
+                    });
+                }
+                ExpressionResult::Call(query_res) => {
+                    let table = to_table(query_res)?;
+                    table.to_string().split("\n").for_each(|line| {
+                        queue!(stdout(), MoveToNextLine(1), Print(line.yellow())).unwrap();
+                    });
+                }
+                ExpressionResult::Withdrawal(query_res) => {
+                    let table = to_table(query_res)?;
+                    table.to_string().split("\n").for_each(|line| {
+                        queue!(stdout(), MoveToNextLine(1), Print(line.green())).unwrap();
+                    });
+                }
+                ExpressionResult::Authorization(query_res) => {
+                    let table = to_table(query_res)?;
+                    table.to_string().split("\n").for_each(|line| {
+                        queue!(stdout(), MoveToNextLine(1), Print(line.yellow())).unwrap();
+                    });
+                }
+                ExpressionResult::Contract(query_res) => {
+                    let table = to_table(query_res)?;
+                    table.to_string().split("\n").for_each(|line| {
+                        queue!(stdout(), MoveToNextLine(1), Print(line.blue())).unwrap();
+                    });
+                }
+                ExpressionResult::StateDiff(query_res) => {
+                    let table = to_table(query_res)?;
+                    table.to_string().split("\n").for_each(|line| {
+                        queue!(stdout(), MoveToNextLine(1), Print(line.cyan())).unwrap();
+                    });
+                }
+                ExpressionResult::Ens(query_res) => {
+                    let table = to_table(query_res)?;
+                    table.to_string().split("\n").for_each(|line| {
+                        queue!(stdout(), MoveToNextLine(1), Print(line.green())).unwrap();
+                    });
+                }
+                ExpressionResult::Table(query_res) => {
+                    let table = to_table(query_res)?;
+                    table.to_string().split("\n").for_each(|line| {
+                        queue!(stdout(), MoveToNextLine(1), Print(line.magenta())).unwrap();
+                    });
+                }
+                ExpressionResult::Column(query_res) => {
+                    let table = to_table(query_res)?;
+                    table.to_string().split("\n").for_each(|line| {
+                        queue!(stdout(), MoveToNextLine(1), Print(line.magenta())).unwrap();
+                    });
+                }
+                ExpressionResult::Plan(query_res) => {
+                    let table = to_table(query_res)?;
+                    table.to_string().split("\n").for_each(|line| {
+                        queue!(stdout(), MoveToNextLine(1), Print(line.dark_grey())).unwrap();
+                    });
+                }
             }
         }
 
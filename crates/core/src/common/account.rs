@@ -1,4 +1,7 @@
-use super::ens::NameOrAddress;
+use super::{
+    block::BlockId,
+    ens::{NameOrAddress, NamesAddresses},
+};
 use crate::interpreter::frontend::parser::Rule;
 use alloy::hex::FromHexError;
 use eql_macros::EnumVariants;
@@ -48,6 +51,36 @@ impl Account {
     pub fn fields(&self) -> Vec<AccountField> {
         self.fields.clone()
     }
+
+    /// The blocks to read state at. Empty means the latest state.
+    pub fn block_ids(&self) -> Vec<&BlockId> {
+        self.filter
+            .iter()
+            .flatten()
+            .filter_map(|filter| match filter {
+                AccountFilter::BlockNumber(id) => Some(id),
+                AccountFilter::Address(_) => None,
+            })
+            .collect()
+    }
+}
+
+impl NamesAddresses for Account {
+    fn name_or_addresses_mut(&mut self) -> Vec<&mut NameOrAddress> {
+        self.id
+            .iter_mut()
+            .flatten()
+            .chain(
+                self.filter
+                    .iter_mut()
+                    .flatten()
+                    .filter_map(|filter| match filter {
+                        AccountFilter::Address(address) => Some(address),
+                        AccountFilter::BlockNumber(_) => None,
+                    }),
+            )
+            .collect()
+    }
 }
 
 impl TryFrom<Pairs<'_, Rule>> for Account {
@@ -65,7 +98,7 @@ impl TryFrom<Pairs<'_, Rule>> for Account {
 
                     if let Some(pair) = inner_pairs.peek() {
                         if pair.as_rule() == Rule::wildcard {
-                            fields = AccountField::all_variants().to_vec();
+                            fields = AccountField::wildcard();
                             continue;
                         }
                     }
@@ -110,6 +143,8 @@ pub enum AccountFilterError {
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum AccountFilter {
     Address(NameOrAddress),
+    /// Reads balance, nonce and code as of this block instead of `latest`.
+    BlockNumber(BlockId),
 }
 
 impl TryFrom<Pair<'_, Rule>> for AccountFilter {
@@ -137,6 +172,22 @@ pub enum AccountField {
     Balance,
     Code,
     Chain,
+    BlockNumber,
+    /// The address's verified primary ENS name. Only read when selected by
+    /// name: `*` leaves it out.
+    AddressEns,
+}
+
+impl AccountField {
+    /// The fields `*` selects: every one but the ENS name, which costs
+    /// Ethereum reads of its own.
+    pub fn wildcard() -> Vec<AccountField> {
+        Self::all_variants()
+            .iter()
+            .filter(|field| **field != AccountField::AddressEns)
+            .copied()
+            .collect()
+    }
 }
 
 impl Display for AccountField {
@@ -147,6 +198,8 @@ impl Display for AccountField {
             AccountField::Balance => write!(f, "balance"),
             AccountField::Code => write!(f, "code"),
             AccountField::Chain => write!(f, "chain"),
+            AccountField::BlockNumber => write!(f, "block_number"),
+            AccountField::AddressEns => write!(f, "address_ens"),
         }
     }
 }
@@ -178,6 +231,8 @@ impl TryFrom<&str> for AccountField {
             "balance" => Ok(AccountField::Balance),
             "code" => Ok(AccountField::Code),
             "chain" => Ok(AccountField::Chain),
+            "block_number" => Ok(AccountField::BlockNumber),
+            "address_ens" => Ok(AccountField::AddressEns),
             invalid_field => Err(AccountFieldError::InvalidField(invalid_field.to_string())),
         }
     }
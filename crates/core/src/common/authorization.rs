@@ -0,0 +1,119 @@
+use super::{
+    block::BlockId,
+    ens::{NameOrAddress, NamesAddresses},
+};
+use eql_macros::EnumVariants;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// EIP-7702 authorizations carried by the transactions of every block, one
+/// per entry of their authorization lists, optionally narrowed to some
+/// authorities and delegates.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Authorizations {
+    blocks: Vec<BlockId>,
+    authorities: Vec<NameOrAddress>,
+    delegates: Vec<NameOrAddress>,
+    fields: Vec<AuthorizationField>,
+}
+
+impl Authorizations {
+    pub fn new(
+        blocks: Vec<BlockId>,
+        authorities: Vec<NameOrAddress>,
+        delegates: Vec<NameOrAddress>,
+        fields: Vec<AuthorizationField>,
+    ) -> Self {
+        Self {
+            blocks,
+            authorities,
+            delegates,
+            fields,
+        }
+    }
+
+    pub fn block_ids(&self) -> Vec<&BlockId> {
+        self.blocks.iter().collect()
+    }
+
+    /// The signing accounts to keep. Empty keeps every authorization.
+    pub fn authorities(&self) -> &[NameOrAddress] {
+        &self.authorities
+    }
+
+    /// The delegated-to addresses to keep. Empty keeps every authorization.
+    pub fn delegates(&self) -> &[NameOrAddress] {
+        &self.delegates
+    }
+
+    pub fn fields(&self) -> Vec<AuthorizationField> {
+        self.fields.clone()
+    }
+}
+
+impl NamesAddresses for Authorizations {
+    fn name_or_addresses_mut(&mut self) -> Vec<&mut NameOrAddress> {
+        self.authorities
+            .iter_mut()
+            .chain(&mut self.delegates)
+            .collect()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, EnumVariants)]
+pub enum AuthorizationField {
+    BlockNumber,
+    TransactionHash,
+    /// The position of the authorization in its transaction's list.
+    Index,
+    /// The account that signed the authorization, recovered from its
+    /// signature.
+    Authority,
+    /// The address whose code the authority delegates to.
+    Delegate,
+    Nonce,
+    /// The chain id the authorization is valid on; 0 means any chain.
+    ChainId,
+    Chain,
+}
+
+impl Display for AuthorizationField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthorizationField::BlockNumber => write!(f, "block_number"),
+            AuthorizationField::TransactionHash => write!(f, "transaction_hash"),
+            AuthorizationField::Index => write!(f, "index"),
+            AuthorizationField::Authority => write!(f, "authority"),
+            AuthorizationField::Delegate => write!(f, "delegate"),
+            AuthorizationField::Nonce => write!(f, "nonce"),
+            AuthorizationField::ChainId => write!(f, "chain_id"),
+            AuthorizationField::Chain => write!(f, "chain"),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum AuthorizationFieldError {
+    #[error("Invalid field for entity Authorizations: {0}")]
+    InvalidField(String),
+}
+
+impl TryFrom<&str> for AuthorizationField {
+    type Error = AuthorizationFieldError;
+
+    fn try_from(value: &str) -> Result<Self, AuthorizationFieldError> {
+        match value {
+            "block_number" => Ok(AuthorizationField::BlockNumber),
+            "transaction_hash" => Ok(AuthorizationField::TransactionHash),
+            "index" => Ok(AuthorizationField::Index),
+            "authority" => Ok(AuthorizationField::Authority),
+            "delegate" => Ok(AuthorizationField::Delegate),
+            "nonce" => Ok(AuthorizationField::Nonce),
+            "chain_id" => Ok(AuthorizationField::ChainId),
+            "chain" => Ok(AuthorizationField::Chain),
+            invalid_field => Err(AuthorizationFieldError::InvalidField(
+                invalid_field.to_string(),
+            )),
+        }
+    }
+}
@@ -1,4 +1,9 @@
-use super::entity_id::{parse_block_number_or_tag, EntityIdError};
+use super::{
+    ens::{NameOrAddress, NamesAddresses},
+    entity_id::{parse_block_number_or_tag, EntityIdError},
+    filters::{EqualityFilter, Filter, FilterType},
+    query_result::BlockQueryRes,
+};
 use crate::interpreter::frontend::parser::Rule;
 use alloy::{
     eips::BlockNumberOrTag,
@@ -68,6 +73,29 @@ impl Block {
     pub fn filters(&self) -> Option<&Vec<BlockFilter>> {
         self.filter.as_ref()
     }
+
+    /// Whether a fetched block passes every column filter. `Range` filters
+    /// pick the blocks to fetch, so they pass here.
+    pub fn filter(&self, block: &BlockQueryRes) -> bool {
+        self.filter.iter().flatten().all(|filter| match filter {
+            BlockFilter::Range(_) => true,
+            BlockFilter::Miner(m) => block.miner.is_some_and(|v| m.compare(&v.into())),
+            BlockFilter::Timestamp(t) => block.timestamp.as_ref().is_some_and(|v| t.compare(v)),
+        })
+    }
+}
+
+impl NamesAddresses for Block {
+    fn name_or_addresses_mut(&mut self) -> Vec<&mut NameOrAddress> {
+        self.filter
+            .iter_mut()
+            .flatten()
+            .filter_map(|filter| match filter {
+                BlockFilter::Miner(miner) => Some(miner.value_mut()),
+                BlockFilter::Range(_) | BlockFilter::Timestamp(_) => None,
+            })
+            .collect()
+    }
 }
 
 impl TryFrom<Pairs<'_, Rule>> for Block {
@@ -157,6 +185,20 @@ pub enum BlockFilterError {
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum BlockFilter {
     Range(BlockRange),
+    Miner(EqualityFilter<NameOrAddress>),
+    Timestamp(FilterType<u64>),
+}
+
+impl BlockFilter {
+    /// The column a filter is evaluated on once blocks are fetched; `None`
+    /// for `Range`, which chooses the blocks instead.
+    pub fn field(&self) -> Option<BlockField> {
+        match self {
+            BlockFilter::Range(_) => None,
+            BlockFilter::Miner(_) => Some(BlockField::Miner),
+            BlockFilter::Timestamp(_) => Some(BlockField::Timestamp),
+        }
+    }
 }
 
 impl TryFrom<Pair<'_, Rule>> for BlockFilter {
@@ -190,7 +232,7 @@ pub enum BlockFieldError {
     InvalidBlockField(String),
 }
 
-// TODO: should include nonce, transactions and withdrawals
+// TODO: should include transactions and withdrawals
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, EnumVariants)]
 pub enum BlockField {
     Number,
@@ -198,6 +240,12 @@ pub enum BlockField {
     Size,
     Hash,
     ParentHash,
+    Miner,
+    GasUsed,
+    GasLimit,
+    Nonce,
+    Difficulty,
+    TransactionCount,
     StateRoot,
     TransactionsRoot,
     ReceiptsRoot,
@@ -221,6 +269,12 @@ impl Display for BlockField {
             BlockField::Size => write!(f, "size"),
             BlockField::Hash => write!(f, "hash"),
             BlockField::ParentHash => write!(f, "parent_hash"),
+            BlockField::Miner => write!(f, "miner"),
+            BlockField::GasUsed => write!(f, "gas_used"),
+            BlockField::GasLimit => write!(f, "gas_limit"),
+            BlockField::Nonce => write!(f, "nonce"),
+            BlockField::Difficulty => write!(f, "difficulty"),
+            BlockField::TransactionCount => write!(f, "transaction_count"),
             BlockField::StateRoot => write!(f, "state_root"),
             BlockField::TransactionsRoot => write!(f, "transactions_root"),
             BlockField::ReceiptsRoot => write!(f, "receipts_root"),
@@ -248,6 +302,12 @@ impl TryFrom<&str> for BlockField {
             "size" => Ok(BlockField::Size),
             "hash" => Ok(BlockField::Hash),
             "parent_hash" => Ok(BlockField::ParentHash),
+            "miner" => Ok(BlockField::Miner),
+            "gas_used" => Ok(BlockField::GasUsed),
+            "gas_limit" => Ok(BlockField::GasLimit),
+            "nonce" => Ok(BlockField::Nonce),
+            "difficulty" => Ok(BlockField::Difficulty),
+            "transaction_count" => Ok(BlockField::TransactionCount),
             "state_root" => Ok(BlockField::StateRoot),
             "transactions_root" => Ok(BlockField::TransactionsRoot),
             "receipts_root" => Ok(BlockField::ReceiptsRoot),
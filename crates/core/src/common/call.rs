@@ -0,0 +1,176 @@
+use super::{
+    block::BlockId,
+    ens::{NameOrAddress, NamesAddresses},
+};
+use alloy::{dyn_abi::DynSolValue, json_abi::Function};
+
+/// The columns every `call` has, whatever the function, in the order the
+/// catalog lists them.
+pub const CALL_COLUMNS: [&str; 3] = ["address", "block_number", "chain"];
+
+/// A read-only contract call: `function` on every address with every
+/// combination of argument values, once per block.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Call {
+    addresses: Vec<NameOrAddress>,
+    function: Function,
+    arguments: Vec<Vec<DynSolValue>>,
+    blocks: Vec<BlockId>,
+    fields: Vec<CallField>,
+}
+
+impl Call {
+    pub fn new(
+        addresses: Vec<NameOrAddress>,
+        function: Function,
+        arguments: Vec<Vec<DynSolValue>>,
+        blocks: Vec<BlockId>,
+        fields: Vec<CallField>,
+    ) -> Self {
+        Self {
+            addresses,
+            function,
+            arguments,
+            blocks,
+            fields,
+        }
+    }
+
+    pub fn addresses(&self) -> &[NameOrAddress] {
+        &self.addresses
+    }
+
+    pub fn function(&self) -> &Function {
+        &self.function
+    }
+
+    /// The values given for each input, in input order.
+    pub fn arguments(&self) -> &[Vec<DynSolValue>] {
+        &self.arguments
+    }
+
+    /// Every combination of argument values, varying the last input fastest.
+    /// A function without inputs has one, empty, combination.
+    pub fn argument_sets(&self) -> Vec<Vec<DynSolValue>> {
+        self.arguments.iter().fold(vec![vec![]], |sets, values| {
+            sets.iter()
+                .flat_map(|set| {
+                    values.iter().map(move |value| {
+                        let mut set = set.clone();
+                        set.push(value.clone());
+                        set
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// The blocks to call at. Empty means the latest state.
+    pub fn block_ids(&self) -> Vec<&BlockId> {
+        self.blocks.iter().collect()
+    }
+
+    pub fn fields(&self) -> Vec<CallField> {
+        self.fields.clone()
+    }
+}
+
+impl NamesAddresses for Call {
+    fn name_or_addresses_mut(&mut self) -> Vec<&mut NameOrAddress> {
+        self.addresses.iter_mut().collect()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CallField {
+    Address,
+    /// The value passed for the function's input at this index.
+    Argument(usize),
+    /// The function's output at this index.
+    Output(usize),
+    BlockNumber,
+    Chain,
+}
+
+/// Every column of a call to `function`: the fixed ones around its inputs
+/// and outputs, as `SELECT *` returns them.
+pub fn call_fields(function: &Function) -> Vec<CallField> {
+    let mut fields = vec![CallField::Address];
+    fields.extend((0..function.inputs.len()).map(CallField::Argument));
+    fields.extend((0..function.outputs.len()).map(CallField::Output));
+    fields.extend([CallField::BlockNumber, CallField::Chain]);
+    fields
+}
+
+/// The column names of `function`'s inputs. An input is named after its
+/// parameter when the signature names it, and `arg<index>` otherwise (or
+/// when the name is taken); `arg<index>` works as a filter either way.
+pub fn argument_columns(function: &Function) -> Vec<String> {
+    let names: Vec<_> = function.inputs.iter().map(|p| p.name.as_str()).collect();
+    column_names(&names, "arg", &[])
+}
+
+/// The column names of `function`'s outputs, named like its inputs but with
+/// `output<index>` for unnamed ones.
+pub fn output_columns(function: &Function) -> Vec<String> {
+    let names: Vec<_> = function.outputs.iter().map(|p| p.name.as_str()).collect();
+    column_names(&names, "output", &argument_columns(function))
+}
+
+fn column_names(names: &[&str], prefix: &str, taken: &[String]) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::with_capacity(names.len());
+    for (index, name) in names.iter().enumerate() {
+        let name = name.to_ascii_lowercase();
+        let is_free = |name: &str| {
+            !name.is_empty()
+                && !CALL_COLUMNS.contains(&name)
+                && name != "signature"
+                && !taken.iter().chain(&columns).any(|c| c == name)
+        };
+        columns.push(if is_free(&name) {
+            name
+        } else {
+            format!("{prefix}{index}")
+        });
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::U256;
+
+    #[test]
+    fn columns_are_named_after_the_signature() {
+        let function = Function::parse("getReserves()(uint112,uint112,uint32)").unwrap();
+        assert!(argument_columns(&function).is_empty());
+        assert_eq!(output_columns(&function), ["output0", "output1", "output2"]);
+
+        // Named parameters keep their names unless they clash.
+        let function =
+            Function::parse("f(address owner, uint256 chain)(uint256 owner, bool ok)").unwrap();
+        assert_eq!(argument_columns(&function), ["owner", "arg1"]);
+        assert_eq!(output_columns(&function), ["output0", "ok"]);
+    }
+
+    #[test]
+    fn argument_sets_are_the_cartesian_product() {
+        let function = Function::parse("f(uint8,bool)").unwrap();
+        let uint = |n: u8| DynSolValue::Uint(U256::from(n), 8);
+        let call = Call::new(
+            vec![],
+            function,
+            vec![
+                vec![uint(1), uint(2)],
+                vec![DynSolValue::Bool(true), DynSolValue::Bool(false)],
+            ],
+            vec![],
+            vec![],
+        );
+        let sets = call.argument_sets();
+        assert_eq!(sets.len(), 4);
+        assert_eq!(sets[1], [uint(1), DynSolValue::Bool(false)]);
+        assert_eq!(sets[2], [uint(2), DynSolValue::Bool(true)]);
+    }
+}
@@ -66,6 +66,18 @@ pub enum Chain {
     Mekong,
 }
 
+/// The rollup stack a chain runs, which decides the L2-specific transaction
+/// columns it fills in.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ChainFamily {
+    /// Optimism's OP Stack: L1 data fees and deposit transactions.
+    OpStack,
+    /// Arbitrum Nitro: L1 gas reported on receipts.
+    Arbitrum,
+    /// Everything else, with no L2-specific transaction columns.
+    Other,
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum ChainError {
     #[error("Invalid chain {0}")]
@@ -115,6 +127,40 @@ impl Chain {
         }
     }
 
+    /// Whether this chain's Portal dataset serves the `traces` and
+    /// `stateDiffs` tables. The 2026-07-20 audit found Celo, Mantle and
+    /// Taiko index blocks, transactions and logs only.
+    pub fn portal_has_traces(&self) -> bool {
+        match self {
+            Chain::Celo | Chain::Mantle | Chain::Taiko => false,
+            _ => self.portal_dataset().is_some(),
+        }
+    }
+
+    pub fn family(&self) -> ChainFamily {
+        match self {
+            Chain::Base | Chain::Blast | Chain::Optimism | Chain::Zora => ChainFamily::OpStack,
+            Chain::Arbitrum => ChainFamily::Arbitrum,
+            Chain::Ethereum
+            | Chain::Sepolia
+            | Chain::Polygon
+            | Chain::Mantle
+            | Chain::Zksync
+            | Chain::Taiko
+            | Chain::Celo
+            | Chain::Avalanche
+            | Chain::Scroll
+            | Chain::Bnb
+            | Chain::Linea
+            | Chain::Moonbeam
+            | Chain::Moonriver
+            | Chain::Ronin
+            | Chain::Kava
+            | Chain::Gnosis
+            | Chain::Mekong => ChainFamily::Other,
+        }
+    }
+
     pub fn from_selector(selector: &str) -> Result<Vec<ChainOrRpc>, ChainError> {
         if selector == "*" {
             let chains = Chain::all_variants();
@@ -344,4 +390,21 @@ mod tests {
     fn test_supported_chain_still_has_dataset() {
         assert_eq!(Chain::Ethereum.portal_dataset(), Some("ethereum-mainnet"));
     }
+
+    #[test]
+    fn test_rollup_families() {
+        for chain in [Chain::Base, Chain::Optimism, Chain::Zora, Chain::Blast] {
+            assert_eq!(chain.family(), ChainFamily::OpStack);
+        }
+        assert_eq!(Chain::Arbitrum.family(), ChainFamily::Arbitrum);
+        assert_eq!(Chain::Ethereum.family(), ChainFamily::Other);
+    }
+
+    #[test]
+    fn test_portal_traces_coverage() {
+        assert!(Chain::Ethereum.portal_has_traces());
+        assert!(!Chain::Celo.portal_has_traces());
+        // No dataset at all.
+        assert!(!Chain::Ronin.portal_has_traces());
+    }
 }
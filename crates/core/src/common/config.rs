@@ -12,7 +12,12 @@ const CONFIG_FILE: &str = "eql-config.json";
 
 #[derive(Serialize, Deserialize, Debug)]
 struct ConfigFile {
+    #[serde(default)]
     chains: HashMap<String, ChainConfig>,
+    #[serde(default)]
+    cache: Option<CacheConfig>,
+    #[serde(default)]
+    lake: Option<LakeConfig>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -21,6 +26,34 @@ struct ChainConfig {
     rpcs: Vec<String>,
 }
 
+/// The `cache` section of the config file, for the on-disk chain data cache.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// `false` turns the cache off.
+    #[serde(default = "cache_enabled_default")]
+    pub enabled: bool,
+    /// Where the cache lives, instead of `$HOME/.eql/cache`.
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+}
+
+fn cache_enabled_default() -> bool {
+    true
+}
+
+/// The `lake` section of the config file, for chains queried from Parquet
+/// files on disk instead of Portal or the RPC.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct LakeConfig {
+    /// The directory of each chain's files, by chain name.
+    #[serde(default)]
+    pub chains: HashMap<String, PathBuf>,
+    /// `true` fetches a query the files can't answer from Portal or the RPC,
+    /// instead of failing it.
+    #[serde(default)]
+    pub fallback: bool,
+}
+
 pub struct Config {
     file_path: Option<PathBuf>,
 }
@@ -227,6 +260,30 @@ impl Config {
         }
     }
 
+    /// The config file's `cache` section, if it has one.
+    pub fn cache_config(&self) -> Result<Option<CacheConfig>> {
+        match &self.file_path {
+            Some(file_path) => {
+                let file = fs::read_to_string(file_path)?;
+                let config_file: ConfigFile = serde_json::from_str(&file)?;
+                Ok(config_file.cache)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// The config file's `lake` section, if it has one.
+    pub fn lake_config(&self) -> Result<Option<LakeConfig>> {
+        match &self.file_path {
+            Some(file_path) => {
+                let file = fs::read_to_string(file_path)?;
+                let config_file: ConfigFile = serde_json::from_str(&file)?;
+                Ok(config_file.lake)
+            }
+            None => Ok(None),
+        }
+    }
+
     /// Records a session-scoped RPC override for `chain`, set by `SET
     /// rpc_<chain> = '<url>'`. See `SESSION_RPCS`'s doc comment for the
     /// process-wide blast radius this carries.
@@ -299,6 +356,42 @@ mod tests {
         assert_eq!(Config::session_rpc(&chain), Some(second));
     }
 
+    #[test]
+    fn cache_section_is_optional_and_enabled_by_default() {
+        let config: ConfigFile = serde_json::from_str(r#"{ "chains": {} }"#).unwrap();
+        assert_eq!(config.cache, None);
+
+        let config: ConfigFile =
+            serde_json::from_str(r#"{ "cache": { "path": "/tmp/eql-cache" } }"#).unwrap();
+        assert_eq!(
+            config.cache,
+            Some(CacheConfig {
+                enabled: true,
+                path: Some(PathBuf::from("/tmp/eql-cache")),
+            })
+        );
+
+        let config: ConfigFile =
+            serde_json::from_str(r#"{ "cache": { "enabled": false } }"#).unwrap();
+        assert!(!config.cache.unwrap().enabled);
+    }
+
+    #[test]
+    fn lake_section_maps_chains_to_directories_without_fallback_by_default() {
+        let config: ConfigFile = serde_json::from_str(r#"{ "chains": {} }"#).unwrap();
+        assert_eq!(config.lake, None);
+
+        let config: ConfigFile =
+            serde_json::from_str(r#"{ "lake": { "chains": { "eth": "/data/eth" } } }"#).unwrap();
+        assert_eq!(
+            config.lake,
+            Some(LakeConfig {
+                chains: HashMap::from([("eth".to_string(), PathBuf::from("/data/eth"))]),
+                fallback: false,
+            })
+        );
+    }
+
     #[test]
     #[should_panic(expected = "is not in SESSION_RPC_TEST_CHAINS")]
     fn guard_panics_loudly_on_an_unreserved_chain() {
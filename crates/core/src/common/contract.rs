@@ -0,0 +1,113 @@
+use super::{
+    block::BlockId,
+    ens::{NameOrAddress, NamesAddresses},
+};
+use eql_macros::EnumVariants;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Contract deployments, either every one in some blocks or the creation of
+/// some addresses, optionally narrowed to some deployers.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Contracts {
+    addresses: Vec<NameOrAddress>,
+    deployers: Vec<NameOrAddress>,
+    blocks: Vec<BlockId>,
+    fields: Vec<ContractField>,
+}
+
+impl Contracts {
+    pub fn new(
+        addresses: Vec<NameOrAddress>,
+        deployers: Vec<NameOrAddress>,
+        blocks: Vec<BlockId>,
+        fields: Vec<ContractField>,
+    ) -> Self {
+        Self {
+            addresses,
+            deployers,
+            blocks,
+            fields,
+        }
+    }
+
+    /// The contracts to keep. Without blocks to scan, the block each one was
+    /// created in is searched for instead.
+    pub fn addresses(&self) -> &[NameOrAddress] {
+        &self.addresses
+    }
+
+    /// The deploying accounts to keep. Empty keeps every deployment.
+    pub fn deployers(&self) -> &[NameOrAddress] {
+        &self.deployers
+    }
+
+    pub fn block_ids(&self) -> Vec<&BlockId> {
+        self.blocks.iter().collect()
+    }
+
+    pub fn fields(&self) -> Vec<ContractField> {
+        self.fields.clone()
+    }
+}
+
+impl NamesAddresses for Contracts {
+    fn name_or_addresses_mut(&mut self) -> Vec<&mut NameOrAddress> {
+        self.addresses
+            .iter_mut()
+            .chain(&mut self.deployers)
+            .collect()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, EnumVariants)]
+pub enum ContractField {
+    Address,
+    /// The account that sent the creation: the transaction's sender for a
+    /// top-level deployment, the factory contract for a nested one.
+    Deployer,
+    TransactionHash,
+    BlockNumber,
+    /// The keccak256 hash of the creation (init) code.
+    InitCodeHash,
+    /// The keccak256 hash of the deployed (runtime) code.
+    RuntimeCodeHash,
+    Chain,
+}
+
+impl Display for ContractField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContractField::Address => write!(f, "address"),
+            ContractField::Deployer => write!(f, "deployer"),
+            ContractField::TransactionHash => write!(f, "transaction_hash"),
+            ContractField::BlockNumber => write!(f, "block_number"),
+            ContractField::InitCodeHash => write!(f, "init_code_hash"),
+            ContractField::RuntimeCodeHash => write!(f, "runtime_code_hash"),
+            ContractField::Chain => write!(f, "chain"),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ContractFieldError {
+    #[error("Invalid field for entity Contracts: {0}")]
+    InvalidField(String),
+}
+
+impl TryFrom<&str> for ContractField {
+    type Error = ContractFieldError;
+
+    fn try_from(value: &str) -> Result<Self, ContractFieldError> {
+        match value {
+            "address" => Ok(ContractField::Address),
+            "deployer" => Ok(ContractField::Deployer),
+            "transaction_hash" => Ok(ContractField::TransactionHash),
+            "block_number" => Ok(ContractField::BlockNumber),
+            "init_code_hash" => Ok(ContractField::InitCodeHash),
+            "runtime_code_hash" => Ok(ContractField::RuntimeCodeHash),
+            "chain" => Ok(ContractField::Chain),
+            invalid_field => Err(ContractFieldError::InvalidField(invalid_field.to_string())),
+        }
+    }
+}
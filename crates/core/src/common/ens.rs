@@ -1,10 +1,13 @@
 /// Based on foundry-common implementation
 /// https://github.com/foundry-rs/foundry/blob/master/crates/common/src/ens.rs
 use self::EnsResolver::EnsResolverInstance;
-use alloy::primitives::{address, Address, Keccak256, B256};
+use super::block::BlockId;
+use alloy::primitives::{address, keccak256, Address, Bytes, Keccak256, B256};
 use alloy::providers::RootProvider;
 use alloy::sol;
 use alloy::transports::http::{Client, Http};
+use eql_macros::EnumVariants;
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 use std::{borrow::Cow, str::FromStr};
 
@@ -15,6 +18,9 @@ sol! {
     contract EnsRegistry {
         /// Returns the resolver for the specified node.
         function resolver(bytes32 node) view returns (address);
+
+        /// Returns the account that controls the specified node.
+        function owner(bytes32 node) view returns (address);
     }
 
     /// ENS Resolver interface.
@@ -25,14 +31,64 @@ sol! {
 
         /// Returns the name associated with an ENS node, for reverse records.
         function name(bytes32 node) view returns (string);
+
+        /// Returns the EIP-1577 content hash of the specified node.
+        function contenthash(bytes32 node) view returns (bytes);
+
+        /// Returns a text record (ENSIP-5) of the specified node.
+        function text(bytes32 node, string key) view returns (string);
+    }
+
+    /// The `.eth` registrar, an ERC-721 whose token ids are the labelhashes
+    /// of second-level names.
+    contract EthRegistrar {
+        /// Returns the expiry timestamp of the specified label.
+        function nameExpires(uint256 id) view returns (uint256);
+    }
+
+    /// The NameWrapper, an ERC-1155 whose token ids are the nodes of the
+    /// names wrapped in it.
+    contract NameWrapper {
+        /// Returns the owner of the specified wrapped node.
+        function ownerOf(uint256 id) view returns (address);
+    }
+
+    /// An ENSIP-10 wildcard resolver, which answers for the subnames of the
+    /// name it's set on.
+    contract ExtendedResolver {
+        /// Returns whether the contract implements the interface (ERC-165).
+        function supportsInterface(bytes4 interfaceID) view returns (bool);
+
+        /// Runs `data`, a resolver call such as `addr(node)`, for the
+        /// DNS-encoded `name`, returning its ABI-encoded output.
+        function resolve(bytes name, bytes data) view returns (bytes);
     }
+
+    /// The revert an EIP-3668 (CCIP-read) contract asks for offchain data
+    /// with: fetch it from one of `urls`, then call `callbackFunction` on
+    /// `sender` with it and `extraData`.
+    error OffchainLookup(address sender, string[] urls, bytes callData, bytes4 callbackFunction, bytes extraData);
 }
 
+/// The ERC-165 id of ENSIP-10's `resolve(bytes,bytes)`.
+pub const EXTENDED_RESOLVER_INTERFACE_ID: [u8; 4] = [0x90, 0x61, 0xb9, 0x23];
+
 /// ENS registry address (`0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e`)
 pub const ENS_ADDRESS: Address = address!("00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
 
 pub const ENS_REVERSE_REGISTRAR_DOMAIN: &str = "addr.reverse";
 
+/// `.eth` registrar address on mainnet and Sepolia
+/// (`0x57f1887a8BF19b14fC0dF6Fd9B2acc9Af147eA85`)
+pub const ETH_REGISTRAR_ADDRESS: Address = address!("57f1887a8BF19b14fC0dF6Fd9B2acc9Af147eA85");
+
+/// NameWrapper address on mainnet (`0xD4416b13d2b3a9aBae7AcD5D6C2BbDBE25686401`)
+pub const NAME_WRAPPER_ADDRESS: Address = address!("D4416b13d2b3a9aBae7AcD5D6C2BbDBE25686401");
+
+/// NameWrapper address on Sepolia (`0x0635513f179D50A207757E05759CbD106d7dFcE8`)
+pub const SEPOLIA_NAME_WRAPPER_ADDRESS: Address =
+    address!("0635513f179D50A207757E05759CbD106d7dFcE8");
+
 /// Error type for ENS resolution.
 #[derive(Debug, thiserror::Error)]
 pub enum EnsError {
@@ -48,6 +104,12 @@ pub enum EnsError {
     /// Failed to resolve ENS name to an address.
     #[error("Failed to resolve ENS name to an address: {0}")]
     Resolve(alloy::contract::Error),
+    /// The name has no resolver, or its resolver has no address for it.
+    #[error("ENS name {0} doesn't resolve to an address")]
+    Unresolvable(String),
+    /// The name isn't valid under ENSIP-15.
+    #[error("Invalid ENS name {0:?}: {1}")]
+    InvalidName(String, &'static str),
 }
 
 /// ENS name or Ethereum Address.
@@ -59,7 +121,25 @@ pub enum NameOrAddress {
     Address(Address),
 }
 
+/// A query whose address-typed values may be written as ENS names.
+pub(crate) trait NamesAddresses {
+    /// Every address-typed value in the query: its ids and the values of its
+    /// address filters, `IN` lists included. ENS names among them are
+    /// resolved in place, in one batch, before the query is dispatched.
+    fn name_or_addresses_mut(&mut self) -> Vec<&mut NameOrAddress>;
+}
+
 impl NameOrAddress {
+    /// The address of a value the query's names were already resolved for
+    /// (see `ExecutionEngine::run`), for the resolvers of filters that can
+    /// only be evaluated against an address.
+    pub fn resolved(&self) -> Address {
+        match self {
+            Self::Address(addr) => *addr,
+            Self::Name(name) => unreachable!("ENS name {name} reached a resolver unresolved"),
+        }
+    }
+
     /// Resolves the name to an Ethereum Address.
     pub async fn resolve(
         &self,
@@ -158,22 +238,94 @@ pub fn namehash(name: &str) -> B256 {
     };
 
     // Generate the node starting from the right.
-    // This buffer is `[node @ [u8; 32], label_hash @ [u8; 32]]`.
-    let mut buffer = [0u8; 64];
+    let mut node = B256::ZERO;
     for label in name.rsplit('.') {
         // node = keccak256([node, keccak256(label)])
+        node = keccak_pair(&node, &keccak256(label.as_bytes()));
+    }
+    node
+}
 
-        // Hash the label.
-        let mut label_hasher = Keccak256::new();
-        label_hasher.update(label.as_bytes());
-        label_hasher.finalize_into(&mut buffer[32..]);
+/// Characters ENSIP-15 only allows between two other characters.
+const FENCED: [char; 3] = ['\u{2019}', '\u{2044}', '\u{30fb}'];
 
-        // Hash both the node and the label hash, writing into the node.
-        let mut buffer_hasher = Keccak256::new();
-        buffer_hasher.update(buffer.as_slice());
-        buffer_hasher.finalize_into(&mut buffer[..32]);
+/// Normalizes `name` as ENSIP-15 asks before it's hashed: case folded,
+/// compatibility forms mapped (full-width letters, ligatures), NFC composed
+/// and emoji presentation selectors dropped, following UTS-46 without the
+/// transitional mappings, so `ß` stays `ß`. Names ENSIP-15 rejects are an
+/// error: empty labels, `_` past the start of a label, `--` as a label's
+/// third and fourth characters (punycode among them: ENS names are never
+/// punycode) and misplaced apostrophes and other fenced characters. Unlike
+/// a full ENSIP-15 implementation it doesn't check for confusable
+/// characters or mixed scripts.
+pub fn normalize(name: &str) -> Result<String, EnsError> {
+    let invalid = |reason| EnsError::InvalidName(name.to_string(), reason);
+    if name.split('.').any(has_label_extension) {
+        return Err(invalid("a label has -- as its 3rd and 4th characters"));
     }
-    buffer[..32].try_into().unwrap()
+    let (normalized, result) = idna::Config::default()
+        .use_std3_ascii_rules(false)
+        .transitional_processing(false)
+        .check_hyphens(false)
+        .verify_dns_length(false)
+        .to_unicode(&name.replace('\'', "\u{2019}"));
+    result.map_err(|_| invalid("it has a character ENS doesn't allow"))?;
+
+    for label in normalized.split('.') {
+        if label.is_empty() {
+            return Err(invalid("it has an empty label"));
+        }
+        // UTS-46 lets ASCII punctuation through that ENSIP-15 doesn't.
+        let disallowed_ascii = |c: char| {
+            c.is_ascii() && !(c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        };
+        if label.chars().any(disallowed_ascii) {
+            return Err(invalid("it has a character ENS doesn't allow"));
+        }
+        if label.trim_start_matches('_').contains('_') {
+            return Err(invalid("_ is only allowed at the start of a label"));
+        }
+        if has_label_extension(label) {
+            return Err(invalid("a label has -- as its 3rd and 4th characters"));
+        }
+        let chars: Vec<char> = label.chars().collect();
+        let fenced = |c: &char| FENCED.contains(c);
+        if chars.first().is_some_and(fenced)
+            || chars.last().is_some_and(fenced)
+            || chars.windows(2).any(|pair| pair.iter().all(fenced))
+        {
+            return Err(invalid("an apostrophe or fenced character is misplaced"));
+        }
+    }
+    Ok(normalized)
+}
+
+fn has_label_extension(label: &str) -> bool {
+    label.get(2..4) == Some("--")
+}
+
+/// `name` in DNS wire format, as ENSIP-10's `resolve` takes it: each label
+/// prefixed by its length, then a zero byte.
+pub fn dns_encode(name: &str) -> Result<Bytes, EnsError> {
+    let mut encoded = Vec::with_capacity(name.len() + 2);
+    for label in name.split('.').filter(|label| !label.is_empty()) {
+        let len = u8::try_from(label.len())
+            .map_err(|_| EnsError::InvalidName(name.to_string(), "a label is over 255 bytes"))?;
+        encoded.push(len);
+        encoded.extend_from_slice(label.as_bytes());
+    }
+    encoded.push(0);
+    Ok(encoded.into())
+}
+
+/// keccak256 of two 32-byte words laid end to end. This is the step the
+/// namehash is folded from, and also how Solidity places a mapping entry
+/// (`keccak256(key . slot)`), see [`crate::common::storage::mapping_slot`].
+pub fn keccak_pair(left: &B256, right: &B256) -> B256 {
+    let mut hasher = Keccak256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize()
 }
 
 /// Returns the reverse-registrar name of an address.
@@ -181,6 +333,150 @@ pub fn reverse_address(addr: &Address) -> String {
     format!("{addr:x}.{ENS_REVERSE_REGISTRAR_DOMAIN}")
 }
 
+/// Returns the `.eth` registrar token id of a second-level `.eth` name (the
+/// labelhash of its first label), or `None` for any other name.
+pub fn eth_label_id(name: &str) -> Option<B256> {
+    match name.split('.').collect::<Vec<_>>()[..] {
+        [label, "eth"] if !label.is_empty() => Some(keccak256(label.as_bytes())),
+        _ => None,
+    }
+}
+
+/// ENS records of some names, and the primary names of some addresses, read
+/// from the registry on Ethereum.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Ens {
+    names: Vec<String>,
+    addresses: Vec<Address>,
+    blocks: Vec<BlockId>,
+    fields: Vec<EnsField>,
+}
+
+impl Ens {
+    pub fn new(
+        names: Vec<String>,
+        addresses: Vec<Address>,
+        blocks: Vec<BlockId>,
+        fields: Vec<EnsField>,
+    ) -> Self {
+        Self {
+            names,
+            addresses,
+            blocks,
+            fields,
+        }
+    }
+
+    /// The names to read the records of.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The addresses to look up the primary name of, by reverse resolution.
+    pub fn addresses(&self) -> &[Address] {
+        &self.addresses
+    }
+
+    /// The blocks to read at. Empty means the latest state.
+    pub fn block_ids(&self) -> Vec<&BlockId> {
+        self.blocks.iter().collect()
+    }
+
+    pub fn fields(&self) -> Vec<EnsField> {
+        self.fields.clone()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, EnumVariants)]
+pub enum EnsField {
+    Name,
+    Node,
+    /// The address the name resolves to.
+    Address,
+    Resolver,
+    /// The registry owner, or the NameWrapper token owner for a wrapped name.
+    Owner,
+    /// When a second-level `.eth` name's registration expires.
+    Expiry,
+    Contenthash,
+    /// The `avatar` text record.
+    Avatar,
+    /// The `url` text record.
+    Url,
+    /// The `com.twitter` text record.
+    Twitter,
+    BlockNumber,
+    Chain,
+}
+
+impl EnsField {
+    /// The text record key read for this field, if it is one.
+    pub fn text_key(&self) -> Option<&'static str> {
+        match self {
+            EnsField::Avatar => Some("avatar"),
+            EnsField::Url => Some("url"),
+            EnsField::Twitter => Some("com.twitter"),
+            _ => None,
+        }
+    }
+
+    /// The other names `TryFrom<&str>` accepts for this field, besides its
+    /// `Display` name.
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            EnsField::Twitter => &["com.twitter"],
+            _ => &[],
+        }
+    }
+}
+
+impl Display for EnsField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnsField::Name => write!(f, "name"),
+            EnsField::Node => write!(f, "node"),
+            EnsField::Address => write!(f, "address"),
+            EnsField::Resolver => write!(f, "resolver"),
+            EnsField::Owner => write!(f, "owner"),
+            EnsField::Expiry => write!(f, "expiry"),
+            EnsField::Contenthash => write!(f, "contenthash"),
+            EnsField::Avatar => write!(f, "avatar"),
+            EnsField::Url => write!(f, "url"),
+            EnsField::Twitter => write!(f, "twitter"),
+            EnsField::BlockNumber => write!(f, "block_number"),
+            EnsField::Chain => write!(f, "chain"),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum EnsFieldError {
+    #[error("Invalid field for entity Ens: {0}")]
+    InvalidField(String),
+}
+
+impl TryFrom<&str> for EnsField {
+    type Error = EnsFieldError;
+
+    fn try_from(value: &str) -> Result<Self, EnsFieldError> {
+        match value {
+            "name" => Ok(EnsField::Name),
+            "node" => Ok(EnsField::Node),
+            "address" => Ok(EnsField::Address),
+            "resolver" => Ok(EnsField::Resolver),
+            "owner" => Ok(EnsField::Owner),
+            "expiry" => Ok(EnsField::Expiry),
+            "contenthash" => Ok(EnsField::Contenthash),
+            "avatar" => Ok(EnsField::Avatar),
+            "url" => Ok(EnsField::Url),
+            "twitter" | "com.twitter" => Ok(EnsField::Twitter),
+            "block_number" => Ok(EnsField::BlockNumber),
+            "chain" => Ok(EnsField::Chain),
+            invalid_field => Err(EnsFieldError::InvalidField(invalid_field.to_string())),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -233,4 +529,63 @@ mod test {
             assert_eq!(reverse_address(&addr.parse().unwrap()), expected, "{addr}");
         }
     }
+
+    #[test]
+    fn test_eth_label_id() {
+        assert_eq!(
+            eth_label_id("vitalik.eth"),
+            Some(keccak256("vitalik".as_bytes()))
+        );
+        assert_eq!(eth_label_id("pay.vitalik.eth"), None);
+        assert_eq!(eth_label_id("eth"), None);
+        assert_eq!(eth_label_id("vitalik.xyz"), None);
+    }
+
+    #[test]
+    fn test_normalize_maps_names_to_their_canonical_form() {
+        for (name, expected) in [
+            ("vitalik.eth", "vitalik.eth"),
+            ("Vitalik.ETH", "vitalik.eth"),
+            ("ｖｉｔａｌｉｋ.eth", "vitalik.eth"),
+            ("my-name.eth", "my-name.eth"),
+            ("_underscore.eth", "_underscore.eth"),
+            ("🚀🚀🚀.eth", "🚀🚀🚀.eth"),
+            ("❤\u{fe0f}.eth", "❤.eth"),
+            ("straße.eth", "straße.eth"),
+            ("nick's.eth", "nick\u{2019}s.eth"),
+            ("Alice.CB.ID", "alice.cb.id"),
+            ("brantly.xyz", "brantly.xyz"),
+        ] {
+            assert_eq!(normalize(name).unwrap(), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_normalize_rejects_what_ensip_15_does() {
+        for name in [
+            "a..eth",
+            "a_b.eth",
+            "ab--cd.eth",
+            "xn--ls8h.eth",
+            "'quoted.eth",
+            "nick''s.eth",
+            "a b.eth",
+            "a!.eth",
+        ] {
+            assert!(
+                matches!(normalize(name), Err(EnsError::InvalidName(..))),
+                "{name} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_dns_encode() {
+        assert_eq!(
+            dns_encode("alice.cb.id").unwrap(),
+            Bytes::from(b"\x05alice\x02cb\x02id\x00".to_vec())
+        );
+        assert_eq!(dns_encode("").unwrap(), Bytes::from(vec![0]));
+        assert!(dns_encode(&format!("{}.eth", "a".repeat(256))).is_err());
+    }
 }
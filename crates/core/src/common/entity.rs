@@ -2,7 +2,19 @@ use super::account::AccountError;
 use super::logs::LogsError;
 use super::transaction::TransactionError;
 use crate::common::{
-    account::Account, block::Block, block::BlockError, logs::Logs, transaction::Transaction,
+    account::Account,
+    authorization::Authorizations,
+    block::Block,
+    block::BlockError,
+    call::Call,
+    contract::Contracts,
+    ens::{Ens, NameOrAddress, NamesAddresses},
+    logs::Logs,
+    state_diff::StateDiffs,
+    storage::Storage,
+    token_balance::TokenBalances,
+    transaction::Transaction,
+    withdrawal::Withdrawals,
 };
 use crate::interpreter::frontend::parser::Rule;
 use pest::iterators::Pairs;
@@ -34,6 +46,47 @@ pub enum Entity {
     Block(Block),
     Transaction(Transaction),
     Logs(Logs),
+    /// Raw `eth_getStorageAt` reads. Only the SQL frontend produces it.
+    Storage(Storage),
+    /// ERC-20/721 reads through Multicall3. Only the SQL frontend produces it.
+    TokenBalances(TokenBalances),
+    /// Arbitrary view-function `eth_call`s. Only the SQL frontend produces it.
+    Call(Call),
+    /// Beacon-chain withdrawals read from block bodies. Only the SQL frontend
+    /// produces it.
+    Withdrawals(Withdrawals),
+    /// EIP-7702 authorizations read from block bodies. Only the SQL frontend
+    /// produces it.
+    Authorizations(Authorizations),
+    /// Contract deployments read from Portal traces or receipts. Only the
+    /// SQL frontend produces it.
+    Contracts(Contracts),
+    /// Per-transaction state changes read from Portal state diffs or a
+    /// node's prestate tracer. Only the SQL frontend produces it.
+    StateDiffs(StateDiffs),
+    /// ENS records and primary names read from the registry through
+    /// Multicall3. Only the SQL frontend produces it.
+    Ens(Ens),
+}
+
+impl NamesAddresses for Entity {
+    fn name_or_addresses_mut(&mut self) -> Vec<&mut NameOrAddress> {
+        match self {
+            Entity::Account(account) => account.name_or_addresses_mut(),
+            Entity::Block(block) => block.name_or_addresses_mut(),
+            Entity::Transaction(transaction) => transaction.name_or_addresses_mut(),
+            Entity::Logs(logs) => logs.name_or_addresses_mut(),
+            Entity::Storage(storage) => storage.name_or_addresses_mut(),
+            Entity::TokenBalances(balances) => balances.name_or_addresses_mut(),
+            Entity::Call(call) => call.name_or_addresses_mut(),
+            Entity::Withdrawals(withdrawals) => withdrawals.name_or_addresses_mut(),
+            Entity::Authorizations(authorizations) => authorizations.name_or_addresses_mut(),
+            Entity::Contracts(contracts) => contracts.name_or_addresses_mut(),
+            Entity::StateDiffs(state_diffs) => state_diffs.name_or_addresses_mut(),
+            // `ens.name` is what the entity reads, not a filter to resolve.
+            Entity::Ens(_) => vec![],
+        }
+    }
 }
 
 impl TryFrom<Pairs<'_, Rule>> for Entity {
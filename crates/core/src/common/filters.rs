@@ -5,7 +5,7 @@ pub trait Filter<T> {
     fn compare(&self, a: &T) -> bool;
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum FilterType<T> {
     Equality(EqualityFilter<T>),
     Comparison(ComparisonFilter<T>),
@@ -86,6 +86,14 @@ impl<T> TryFrom<(Pair<'_, Rule>, T)> for EqualityFilter<T> {
     }
 }
 
+impl<T> EqualityFilter<T> {
+    pub fn value_mut(&mut self) -> &mut T {
+        match self {
+            EqualityFilter::Eq(value) | EqualityFilter::Neq(value) => value,
+        }
+    }
+}
+
 impl<T> Filter<T> for EqualityFilter<T>
 where
     T: PartialEq,
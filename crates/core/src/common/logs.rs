@@ -2,6 +2,7 @@ use std::str::FromStr;
 
 use super::{
     block::BlockRange,
+    ens::{NameOrAddress, NamesAddresses},
     entity_id::{parse_block_number_or_tag, EntityIdError},
 };
 use crate::interpreter::frontend::parser::{ParserError, Rule};
@@ -50,6 +51,18 @@ impl Logs {
     }
 }
 
+impl NamesAddresses for Logs {
+    fn name_or_addresses_mut(&mut self) -> Vec<&mut NameOrAddress> {
+        self.filter
+            .iter_mut()
+            .filter_map(|filter| match filter {
+                LogFilter::EmitterAddress(address) => Some(address),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum LogsError {
     #[error("Invalid log filter {0}")]
@@ -92,7 +105,7 @@ impl TryFrom<Pairs<'_, Rule>> for Logs {
 
                     if let Some(pair) = inner_pairs.peek() {
                         if pair.as_rule() == Rule::wildcard {
-                            fields = LogField::all_variants().to_vec();
+                            fields = LogField::wildcard();
                             continue;
                         }
                     }
@@ -115,7 +128,7 @@ impl TryFrom<Pairs<'_, Rule>> for Logs {
 pub enum LogFilter {
     BlockRange(BlockRange),
     BlockHash(B256),
-    EmitterAddress(Address),
+    EmitterAddress(NameOrAddress),
     EventSignature(String),
     Topic0(B256),
     Topic1(B256),
@@ -141,9 +154,13 @@ impl TryFrom<Pair<'_, Rule>> for LogFilter {
 
         match pair.as_rule() {
             Rule::address_filter_type => extract_value(pair, |s| {
-                Ok(LogFilter::EmitterAddress(Address::parse_checksummed(
-                    Address::to_checksum(&Address::from_str(s)?, None), None,
-                )?))
+                Ok(LogFilter::EmitterAddress(
+                    Address::parse_checksummed(
+                        Address::to_checksum(&Address::from_str(s)?, None),
+                        None,
+                    )?
+                    .into(),
+                ))
             }),
             Rule::blockrange_filter => parse_block_range(pair),
             Rule::blockhash_filter_type => {
@@ -207,7 +224,7 @@ impl LogFilter {
                     .to_block(range.end().unwrap_or(range.start()))
             }
             LogFilter::BlockHash(hash) => filter.at_block_hash(*hash),
-            LogFilter::EmitterAddress(address) => filter.address(*address),
+            LogFilter::EmitterAddress(address) => filter.address(address.resolved()),
             LogFilter::EventSignature(signature) => filter.event(signature),
             LogFilter::Topic0(topic_hash) => filter.event_signature(*topic_hash),
             LogFilter::Topic1(topic_hash) => filter.topic1(*topic_hash),
@@ -241,6 +258,21 @@ pub enum LogField {
     LogIndex,
     Removed,
     Chain,
+    /// The emitting contract's verified primary ENS name. Only read when
+    /// selected by name: `*` leaves it out.
+    AddressEns,
+}
+
+impl LogField {
+    /// The fields `*` selects: every one but the ENS name, which costs
+    /// Ethereum reads of its own.
+    pub fn wildcard() -> Vec<LogField> {
+        Self::all_variants()
+            .iter()
+            .filter(|field| **field != LogField::AddressEns)
+            .copied()
+            .collect()
+    }
 }
 
 impl std::fmt::Display for LogField {
@@ -260,6 +292,7 @@ impl std::fmt::Display for LogField {
             LogField::LogIndex => write!(f, "log_index"),
             LogField::Removed => write!(f, "removed"),
             LogField::Chain => write!(f, "chain"),
+            LogField::AddressEns => write!(f, "address_ens"),
         }
     }
 }
@@ -289,6 +322,7 @@ impl TryFrom<&str> for LogField {
             "log_index" => Ok(LogField::LogIndex),
             "removed" => Ok(LogField::Removed),
             "chain" => Ok(LogField::Chain),
+            "address_ens" => Ok(LogField::AddressEns),
             invalid_field => Err(LogFieldError::InvalidLogField(invalid_field.to_string())),
         }
     }
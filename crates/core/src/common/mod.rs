@@ -1,7 +1,10 @@
 pub mod account;
+pub mod authorization;
 pub mod block;
+pub mod call;
 pub mod chain;
 pub mod config;
+pub mod contract;
 pub mod dump;
 pub mod ens;
 pub mod entity;
@@ -10,5 +13,9 @@ pub mod filters;
 pub mod logs;
 pub mod query_result;
 pub mod serializer;
+pub mod state_diff;
+pub mod storage;
+pub mod token_balance;
 pub mod transaction;
 pub mod types;
+pub mod withdrawal;
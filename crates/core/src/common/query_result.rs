@@ -1,17 +1,34 @@
-use crate::common::chain::Chain;
-use alloy::primitives::{Address, Bloom, Bytes, FixedBytes, B256, U256};
+use crate::common::{chain::Chain, state_diff::StateDiffKind};
+use alloy::{
+    eips::eip2930::AccessList,
+    primitives::{Address, Bloom, Bytes, FixedBytes, B256, U256},
+};
 use alloy_eip7702::SignedAuthorization;
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Mutex, PoisonError};
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub struct QueryResult {
     pub result: ExpressionResult,
+    /// The address each ENS name in the query's address filters resolved
+    /// to, so what a name pointed to when the query ran can be checked.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub resolved_names: BTreeMap<String, Address>,
 }
 
 impl QueryResult {
     pub fn new(result: ExpressionResult) -> QueryResult {
-        QueryResult { result }
+        QueryResult {
+            result,
+            resolved_names: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_resolved_names(mut self, resolved_names: BTreeMap<String, Address>) -> Self {
+        self.resolved_names = resolved_names;
+        self
     }
 }
 
@@ -25,6 +42,28 @@ pub enum ExpressionResult {
     Transaction(Vec<TransactionQueryRes>),
     #[serde(rename = "log")]
     Log(Vec<LogQueryRes>),
+    #[serde(rename = "storage")]
+    Storage(Vec<StorageQueryRes>),
+    #[serde(rename = "token_balance")]
+    TokenBalance(Vec<TokenBalanceQueryRes>),
+    #[serde(rename = "call")]
+    Call(Vec<CallQueryRes>),
+    #[serde(rename = "withdrawal")]
+    Withdrawal(Vec<WithdrawalQueryRes>),
+    #[serde(rename = "authorization")]
+    Authorization(Vec<AuthorizationQueryRes>),
+    #[serde(rename = "contract")]
+    Contract(Vec<ContractQueryRes>),
+    #[serde(rename = "state_diff")]
+    StateDiff(Vec<StateDiffQueryRes>),
+    #[serde(rename = "ens")]
+    Ens(Vec<EnsQueryRes>),
+    #[serde(rename = "table")]
+    Table(Vec<TableInfoRes>),
+    #[serde(rename = "column")]
+    Column(Vec<ColumnInfoRes>),
+    #[serde(rename = "plan")]
+    Plan(Vec<PlanRes>),
 }
 
 impl ExpressionResult {
@@ -39,6 +78,66 @@ impl ExpressionResult {
             ExpressionResult::Block(v) => v.truncate(n),
             ExpressionResult::Transaction(v) => v.truncate(n),
             ExpressionResult::Log(v) => v.truncate(n),
+            ExpressionResult::Storage(v) => v.truncate(n),
+            ExpressionResult::TokenBalance(v) => v.truncate(n),
+            ExpressionResult::Call(v) => v.truncate(n),
+            ExpressionResult::Withdrawal(v) => v.truncate(n),
+            ExpressionResult::Authorization(v) => v.truncate(n),
+            ExpressionResult::Contract(v) => v.truncate(n),
+            ExpressionResult::StateDiff(v) => v.truncate(n),
+            ExpressionResult::Ens(v) => v.truncate(n),
+            ExpressionResult::Table(v) => v.truncate(n),
+            ExpressionResult::Column(v) => v.truncate(n),
+            ExpressionResult::Plan(v) => v.truncate(n),
+        }
+    }
+
+    /// The row count, exhaustive over every variant for the same reason as
+    /// `truncate`.
+    pub fn len(&self) -> usize {
+        match self {
+            ExpressionResult::Account(v) => v.len(),
+            ExpressionResult::Block(v) => v.len(),
+            ExpressionResult::Transaction(v) => v.len(),
+            ExpressionResult::Log(v) => v.len(),
+            ExpressionResult::Storage(v) => v.len(),
+            ExpressionResult::TokenBalance(v) => v.len(),
+            ExpressionResult::Call(v) => v.len(),
+            ExpressionResult::Withdrawal(v) => v.len(),
+            ExpressionResult::Authorization(v) => v.len(),
+            ExpressionResult::Contract(v) => v.len(),
+            ExpressionResult::StateDiff(v) => v.len(),
+            ExpressionResult::Ens(v) => v.len(),
+            ExpressionResult::Table(v) => v.len(),
+            ExpressionResult::Column(v) => v.len(),
+            ExpressionResult::Plan(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends `other`'s rows. Both must be rows of the same entity, as the
+    /// per-chain results of one query are.
+    pub(crate) fn append(&mut self, other: ExpressionResult) {
+        match (self, other) {
+            (ExpressionResult::Account(v), ExpressionResult::Account(o)) => v.extend(o),
+            (ExpressionResult::Block(v), ExpressionResult::Block(o)) => v.extend(o),
+            (ExpressionResult::Transaction(v), ExpressionResult::Transaction(o)) => v.extend(o),
+            (ExpressionResult::Log(v), ExpressionResult::Log(o)) => v.extend(o),
+            (ExpressionResult::Storage(v), ExpressionResult::Storage(o)) => v.extend(o),
+            (ExpressionResult::TokenBalance(v), ExpressionResult::TokenBalance(o)) => v.extend(o),
+            (ExpressionResult::Call(v), ExpressionResult::Call(o)) => v.extend(o),
+            (ExpressionResult::Withdrawal(v), ExpressionResult::Withdrawal(o)) => v.extend(o),
+            (ExpressionResult::Authorization(v), ExpressionResult::Authorization(o)) => v.extend(o),
+            (ExpressionResult::Contract(v), ExpressionResult::Contract(o)) => v.extend(o),
+            (ExpressionResult::StateDiff(v), ExpressionResult::StateDiff(o)) => v.extend(o),
+            (ExpressionResult::Ens(v), ExpressionResult::Ens(o)) => v.extend(o),
+            (ExpressionResult::Table(v), ExpressionResult::Table(o)) => v.extend(o),
+            (ExpressionResult::Column(v), ExpressionResult::Column(o)) => v.extend(o),
+            (ExpressionResult::Plan(v), ExpressionResult::Plan(o)) => v.extend(o),
+            _ => unreachable!("appended the rows of one entity to another's"),
         }
     }
 }
@@ -54,6 +153,13 @@ pub struct BlockQueryRes {
     #[serde(serialize_with = "serialize_option_u256")]
     pub size: Option<U256>,
     pub parent_hash: Option<B256>,
+    pub miner: Option<Address>,
+    pub gas_used: Option<u64>,
+    pub gas_limit: Option<u64>,
+    pub nonce: Option<u64>,
+    #[serde(serialize_with = "serialize_option_u256")]
+    pub difficulty: Option<U256>,
+    pub transaction_count: Option<u64>,
     pub state_root: Option<B256>,
     pub transactions_root: Option<B256>,
     pub receipts_root: Option<B256>,
@@ -77,6 +183,12 @@ impl Default for BlockQueryRes {
             hash: None,
             size: None,
             parent_hash: None,
+            miner: None,
+            gas_used: None,
+            gas_limit: None,
+            nonce: None,
+            difficulty: None,
+            transaction_count: None,
             state_root: None,
             transactions_root: None,
             receipts_root: None,
@@ -102,6 +214,8 @@ pub struct AccountQueryRes {
     pub balance: Option<U256>,
     pub address: Option<Address>,
     pub code: Option<Bytes>,
+    pub block_number: Option<u64>,
+    pub address_ens: Option<String>,
 }
 
 impl Default for AccountQueryRes {
@@ -112,6 +226,8 @@ impl Default for AccountQueryRes {
             balance: None,
             address: None,
             code: None,
+            block_number: None,
+            address_ens: None,
         }
     }
 }
@@ -132,15 +248,42 @@ pub struct TransactionQueryRes {
     pub gas_limit: Option<u64>,
     pub effective_gas_price: Option<u128>,
     pub status: Option<bool>,
+    pub gas_used: Option<u64>,
+    pub cumulative_gas_used: Option<u64>,
+    pub contract_address: Option<Address>,
+    pub logs_count: Option<u64>,
+    #[serde(serialize_with = "serialize_option_u256")]
+    pub fee: Option<U256>,
     pub chain_id: Option<u64>,
     pub v: Option<bool>,
     pub r: Option<U256>,
     pub s: Option<U256>,
     pub max_fee_per_blob_gas: Option<u128>,
+    pub blob_versioned_hashes: Option<Vec<B256>>,
+    pub blob_count: Option<u64>,
+    pub blob_gas_used: Option<u64>,
+    pub blob_gas_price: Option<u128>,
     pub max_fee_per_gas: Option<u128>,
     pub max_priority_fee_per_gas: Option<u128>,
+    pub access_list: Option<AccessList>,
     pub y_parity: Option<bool>,
     pub authorization_list: Option<Vec<SignedAuthorization>>,
+    // OP Stack only: the L1 data fee and its inputs, from the receipt.
+    #[serde(serialize_with = "serialize_option_u256")]
+    pub l1_fee: Option<U256>,
+    pub l1_gas_used: Option<u64>,
+    pub l1_gas_price: Option<u128>,
+    /// Decimal text: pre-Ecotone receipts report a fractional scalar.
+    pub l1_fee_scalar: Option<String>,
+    // OP Stack deposit transactions (type 0x7e) only.
+    pub source_hash: Option<B256>,
+    #[serde(serialize_with = "serialize_option_u256")]
+    pub mint: Option<U256>,
+    pub is_system_tx: Option<bool>,
+    // Arbitrum only, from the receipt.
+    pub gas_used_for_l1: Option<u64>,
+    pub from_ens: Option<String>,
+    pub to_ens: Option<String>,
 }
 
 impl Default for TransactionQueryRes {
@@ -157,16 +300,36 @@ impl Default for TransactionQueryRes {
             gas_price: None,
             gas_limit: None,
             status: None,
+            gas_used: None,
+            cumulative_gas_used: None,
+            contract_address: None,
+            logs_count: None,
+            fee: None,
             chain_id: None,
             v: None,
             r: None,
             s: None,
             effective_gas_price: None,
             max_fee_per_blob_gas: None,
+            blob_versioned_hashes: None,
+            blob_count: None,
+            blob_gas_used: None,
+            blob_gas_price: None,
             max_fee_per_gas: None,
             max_priority_fee_per_gas: None,
+            access_list: None,
             y_parity: None,
             authorization_list: None,
+            l1_fee: None,
+            l1_gas_used: None,
+            l1_gas_price: None,
+            l1_fee_scalar: None,
+            source_hash: None,
+            mint: None,
+            is_system_tx: None,
+            gas_used_for_l1: None,
+            from_ens: None,
+            to_ens: None,
         }
     }
 }
@@ -185,15 +348,35 @@ impl TransactionQueryRes {
             || self.gas_limit.is_some()
             || self.effective_gas_price.is_some()
             || self.status.is_some()
+            || self.gas_used.is_some()
+            || self.cumulative_gas_used.is_some()
+            || self.contract_address.is_some()
+            || self.logs_count.is_some()
+            || self.fee.is_some()
             || self.chain_id.is_some()
             || self.v.is_some()
             || self.r.is_some()
             || self.s.is_some()
             || self.max_fee_per_blob_gas.is_some()
+            || self.blob_versioned_hashes.is_some()
+            || self.blob_count.is_some()
+            || self.blob_gas_used.is_some()
+            || self.blob_gas_price.is_some()
             || self.max_fee_per_gas.is_some()
             || self.max_priority_fee_per_gas.is_some()
+            || self.access_list.is_some()
             || self.y_parity.is_some()
             || self.authorization_list.is_some()
+            || self.l1_fee.is_some()
+            || self.l1_gas_used.is_some()
+            || self.l1_gas_price.is_some()
+            || self.l1_fee_scalar.is_some()
+            || self.source_hash.is_some()
+            || self.mint.is_some()
+            || self.is_system_tx.is_some()
+            || self.gas_used_for_l1.is_some()
+            || self.from_ens.is_some()
+            || self.to_ens.is_some()
     }
 
     fn get_field_values(&self) -> Vec<(&'static str, String)> {
@@ -234,6 +417,21 @@ impl TransactionQueryRes {
         if let Some(status) = self.status {
             fields.push(("status", Some(status.to_string())));
         }
+        if let Some(gas_used) = self.gas_used {
+            fields.push(("gas_used", Some(gas_used.to_string())));
+        }
+        if let Some(cumulative_gas_used) = self.cumulative_gas_used {
+            fields.push(("cumulative_gas_used", Some(cumulative_gas_used.to_string())));
+        }
+        if let Some(contract_address) = &self.contract_address {
+            fields.push(("contract_address", Some(contract_address.to_string())));
+        }
+        if let Some(logs_count) = self.logs_count {
+            fields.push(("logs_count", Some(logs_count.to_string())));
+        }
+        if let Some(fee) = &self.fee {
+            fields.push(("fee", Some(fee.to_string())));
+        }
         if let Some(chain_id) = self.chain_id {
             fields.push(("chain_id", Some(chain_id.to_string())));
         }
@@ -252,6 +450,19 @@ impl TransactionQueryRes {
                 Some(max_fee_per_blob_gas.to_string()),
             ));
         }
+        if let Some(hashes) = &self.blob_versioned_hashes {
+            let hashes: Vec<_> = hashes.iter().map(|h| format!("{h:?}")).collect();
+            fields.push(("blob_versioned_hashes", Some(hashes.join(","))));
+        }
+        if let Some(blob_count) = self.blob_count {
+            fields.push(("blob_count", Some(blob_count.to_string())));
+        }
+        if let Some(blob_gas_used) = self.blob_gas_used {
+            fields.push(("blob_gas_used", Some(blob_gas_used.to_string())));
+        }
+        if let Some(blob_gas_price) = self.blob_gas_price {
+            fields.push(("blob_gas_price", Some(blob_gas_price.to_string())));
+        }
         if let Some(max_fee_per_gas) = self.max_fee_per_gas {
             fields.push(("max_fee_per_gas", Some(max_fee_per_gas.to_string())));
         }
@@ -264,34 +475,35 @@ impl TransactionQueryRes {
         if let Some(y_parity) = self.y_parity {
             fields.push(("y_parity", Some(y_parity.to_string())));
         }
-
-        if let Some(auths) = &self.authorization_list {
-            for (i, auth) in auths.iter().enumerate() {
-                fields.push((
-                    Box::leak(format!("authorization_list_{i}_chain_id").into_boxed_str()),
-                    Some(auth.chain_id.to_string()),
-                ));
-                fields.push((
-                    Box::leak(format!("authorization_list_{i}_address").into_boxed_str()),
-                    Some(auth.address.to_string()),
-                ));
-                fields.push((
-                    Box::leak(format!("authorization_list_{i}_r").into_boxed_str()),
-                    Some(format!("{:?}", auth.r())),
-                ));
-                fields.push((
-                    Box::leak(format!("authorization_list_{i}_s").into_boxed_str()),
-                    Some(format!("{:?}", auth.s())),
-                ));
-                fields.push((
-                    Box::leak(format!("authorization_list_{i}_y_parity").into_boxed_str()),
-                    Some(format!("{:?}", auth.y_parity())),
-                ));
-                fields.push((
-                    Box::leak(format!("authorization_list_{i}_nonce").into_boxed_str()),
-                    Some(auth.nonce.to_string()),
-                ));
-            }
+        if let Some(l1_fee) = &self.l1_fee {
+            fields.push(("l1_fee", Some(l1_fee.to_string())));
+        }
+        if let Some(l1_gas_used) = self.l1_gas_used {
+            fields.push(("l1_gas_used", Some(l1_gas_used.to_string())));
+        }
+        if let Some(l1_gas_price) = self.l1_gas_price {
+            fields.push(("l1_gas_price", Some(l1_gas_price.to_string())));
+        }
+        if let Some(l1_fee_scalar) = &self.l1_fee_scalar {
+            fields.push(("l1_fee_scalar", Some(l1_fee_scalar.clone())));
+        }
+        if let Some(source_hash) = &self.source_hash {
+            fields.push(("source_hash", Some(format!("{source_hash:?}"))));
+        }
+        if let Some(mint) = &self.mint {
+            fields.push(("mint", Some(mint.to_string())));
+        }
+        if let Some(is_system_tx) = self.is_system_tx {
+            fields.push(("is_system_tx", Some(is_system_tx.to_string())));
+        }
+        if let Some(gas_used_for_l1) = self.gas_used_for_l1 {
+            fields.push(("gas_used_for_l1", Some(gas_used_for_l1.to_string())));
+        }
+        if let Some(from_ens) = &self.from_ens {
+            fields.push(("from_ens", Some(from_ens.clone())));
+        }
+        if let Some(to_ens) = &self.to_ens {
+            fields.push(("to_ens", Some(to_ens.clone())));
         }
 
         fields
@@ -299,6 +511,42 @@ impl TransactionQueryRes {
             .map(|(name, value)| (name, value.unwrap_or_default()))
             .collect()
     }
+
+    /// The list-valued fields, as nested JSON: an array of objects with one
+    /// key per struct member, the same shape as their Arrow columns.
+    fn get_nested_values(&self) -> Vec<(&'static str, serde_json::Value)> {
+        let mut fields = Vec::new();
+        if let Some(access_list) = &self.access_list {
+            let items: Vec<_> = access_list
+                .iter()
+                .map(|item| {
+                    let keys: Vec<_> = item.storage_keys.iter().map(|k| format!("{k:?}")).collect();
+                    serde_json::json!({
+                        "address": item.address.to_string(),
+                        "storage_keys": keys,
+                    })
+                })
+                .collect();
+            fields.push(("access_list", serde_json::Value::Array(items)));
+        }
+        if let Some(auths) = &self.authorization_list {
+            let items: Vec<_> = auths
+                .iter()
+                .map(|auth| {
+                    serde_json::json!({
+                        "chain_id": auth.chain_id.to_string(),
+                        "address": auth.address.to_string(),
+                        "nonce": auth.nonce.to_string(),
+                        "y_parity": auth.y_parity().to_string(),
+                        "r": auth.r().to_string(),
+                        "s": auth.s().to_string(),
+                    })
+                })
+                .collect();
+            fields.push(("authorization_list", serde_json::Value::Array(items)));
+        }
+        fields
+    }
 }
 
 impl Serialize for TransactionQueryRes {
@@ -307,14 +555,96 @@ impl Serialize for TransactionQueryRes {
         S: Serializer,
     {
         let fields = self.get_field_values();
-        let mut state = serializer.serialize_struct("TransactionQueryRes", fields.len())?;
+        let nested = self.get_nested_values();
+        let mut state =
+            serializer.serialize_struct("TransactionQueryRes", fields.len() + nested.len())?;
+        for (field_name, value) in fields {
+            state.serialize_field(field_name, &value)?;
+        }
+        for (field_name, value) in nested {
+            state.serialize_field(field_name, &value)?;
+        }
+        state.end()
+    }
+}
+
+/// A transaction row for CSV and the CLI's tables, which can't hold nested
+/// values: the list-valued fields are written as JSON text in one cell.
+#[derive(Debug)]
+pub struct TransactionCsvRow<'a>(pub &'a TransactionQueryRes);
+
+impl Serialize for TransactionCsvRow<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let fields = self.0.get_field_values();
+        let nested = self.0.get_nested_values();
+        let mut state =
+            serializer.serialize_struct("TransactionQueryRes", fields.len() + nested.len())?;
         for (field_name, value) in fields {
             state.serialize_field(field_name, &value)?;
         }
+        for (field_name, value) in nested {
+            state.serialize_field(field_name, &value.to_string())?;
+        }
         state.end()
     }
 }
 
+/// A row of `SHOW TABLES` / `information_schema.tables`.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct TableInfoRes {
+    pub table_name: String,
+}
+
+/// A row of `DESCRIBE <entity>` / `information_schema.columns`. `aliases`
+/// lists the other names the column resolves from (e.g. `"from"` for
+/// `from_address`), comma-separated; `portal_pushdown` says whether a
+/// predicate on it is sent to Portal rather than evaluated client-side.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct ColumnInfoRes {
+    pub table_name: String,
+    pub column_name: String,
+    pub data_type: String,
+    pub aliases: String,
+    pub portal_pushdown: bool,
+}
+
+/// One row of `EXPLAIN` output: how one chain's share of the query is
+/// fetched. The last four columns are only filled in by `EXPLAIN ANALYZE`.
+///
+/// Not `skip_serializing_none`: a Portal row and an RPC row of the same plan
+/// leave different columns empty, and every row must keep the same columns.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct PlanRes {
+    /// The chain name, or the URL of an ad-hoc RPC endpoint.
+    pub chain: String,
+    pub entity: String,
+    /// `portal`, `rpc`, `cache`, `lake`, or the name of a registered data
+    /// source.
+    pub source: String,
+    pub block_range: Option<String>,
+    /// The exact Portal stream query, as JSON — an array when the query is
+    /// split into several requests (one per block id).
+    pub portal_query: Option<String>,
+    /// Columns whose predicates are evaluated over the fetched rows rather
+    /// than by the data source.
+    pub client_filters: Option<String>,
+    /// The data lake files read, each with the row groups their statistics
+    /// don't rule out.
+    pub lake_files: Option<String>,
+    /// RPC calls made up front; `None` when it depends on a block tag that
+    /// isn't resolved until execution.
+    pub estimated_rpc_calls: Option<u64>,
+    /// Further RPC calls for every row fetched (receipts, chain id lookups).
+    pub rpc_calls_per_row: u64,
+    pub elapsed_ms: Option<u64>,
+    pub rows: Option<u64>,
+    pub portal_pages: Option<u64>,
+    pub portal_bytes: Option<u64>,
+}
+
 #[serde_with::skip_serializing_none]
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub struct LogQueryRes {
@@ -332,6 +662,7 @@ pub struct LogQueryRes {
     pub transaction_index: Option<u64>,
     pub log_index: Option<u64>,
     pub removed: Option<bool>,
+    pub address_ens: Option<String>,
 }
 
 impl Default for LogQueryRes {
@@ -351,10 +682,171 @@ impl Default for LogQueryRes {
             transaction_index: None,
             log_index: None,
             removed: None,
+            address_ens: None,
+        }
+    }
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct StorageQueryRes {
+    pub chain: Option<Chain>,
+    pub address: Option<Address>,
+    pub slot: Option<B256>,
+    /// The raw 32-byte word, as `eth_getStorageAt` returns it.
+    pub value: Option<B256>,
+    pub block_number: Option<u64>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct TokenBalanceQueryRes {
+    pub chain: Option<Chain>,
+    pub holder: Option<Address>,
+    pub token: Option<Address>,
+    #[serde(serialize_with = "serialize_option_u256")]
+    pub balance: Option<U256>,
+    pub decimals: Option<u8>,
+    /// `balance` divided by `10^decimals`, as an exact decimal string.
+    pub scaled_balance: Option<String>,
+    pub spender: Option<Address>,
+    #[serde(serialize_with = "serialize_option_u256")]
+    pub allowance: Option<U256>,
+    pub scaled_allowance: Option<String>,
+    #[serde(serialize_with = "serialize_option_u256")]
+    pub token_id: Option<U256>,
+    pub owner: Option<Address>,
+    pub block_number: Option<u64>,
+}
+
+/// A row of `call`. Which columns it has besides the fixed ones depends on
+/// the function called, so its arguments and outputs travel as named
+/// `values`, in signature order, and serialize as columns of their own.
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Clone)]
+pub struct CallQueryRes {
+    pub chain: Option<Chain>,
+    pub address: Option<Address>,
+    pub values: Vec<CallValue>,
+    pub block_number: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct CallValue {
+    pub column: String,
+    /// The Solidity type, e.g. `uint112`, which picks the column's type.
+    pub ty: String,
+    /// `None` when the call reverted or its output didn't decode. Integers
+    /// wider than 64 bits are decimal strings, and arrays and tuples are
+    /// JSON text, so every value fits a flat CSV cell.
+    pub value: Option<serde_json::Value>,
+}
+
+impl Serialize for CallQueryRes {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("CallQueryRes", self.values.len() + 3)?;
+        if let Some(chain) = &self.chain {
+            state.serialize_field("chain", chain)?;
         }
+        if let Some(address) = &self.address {
+            state.serialize_field("address", address)?;
+        }
+        for value in &self.values {
+            state.serialize_field(static_column_name(&value.column), &value.value)?;
+        }
+        if let Some(block_number) = &self.block_number {
+            state.serialize_field("block_number", block_number)?;
+        }
+        state.end()
     }
 }
 
+/// `serialize_struct` takes `'static` field names, but a `call`'s columns
+/// come from its signature. Each distinct name is leaked once and reused, so
+/// a long-running server doesn't leak per row.
+fn static_column_name(name: &str) -> &'static str {
+    static NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+    let mut names = NAMES.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(name) = names.get(name) {
+        return name;
+    }
+    let name: &'static str = Box::leak(name.to_owned().into_boxed_str());
+    names.insert(name);
+    name
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct WithdrawalQueryRes {
+    pub chain: Option<Chain>,
+    pub block_number: Option<u64>,
+    pub index: Option<u64>,
+    pub validator_index: Option<u64>,
+    pub address: Option<Address>,
+    pub amount_gwei: Option<u64>,
+    #[serde(serialize_with = "serialize_option_u256")]
+    pub amount: Option<U256>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct AuthorizationQueryRes {
+    pub chain: Option<Chain>,
+    pub block_number: Option<u64>,
+    pub transaction_hash: Option<B256>,
+    pub index: Option<u64>,
+    pub authority: Option<Address>,
+    pub delegate: Option<Address>,
+    pub nonce: Option<u64>,
+    pub chain_id: Option<u64>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct ContractQueryRes {
+    pub chain: Option<Chain>,
+    pub address: Option<Address>,
+    pub deployer: Option<Address>,
+    pub transaction_hash: Option<B256>,
+    pub block_number: Option<u64>,
+    pub init_code_hash: Option<B256>,
+    pub runtime_code_hash: Option<B256>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct StateDiffQueryRes {
+    pub chain: Option<Chain>,
+    pub address: Option<Address>,
+    pub key: Option<B256>,
+    pub kind: Option<StateDiffKind>,
+    /// Balances and nonces in decimal, code and storage words in hex.
+    pub prev: Option<String>,
+    pub next: Option<String>,
+    pub transaction_hash: Option<B256>,
+    pub block_number: Option<u64>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct EnsQueryRes {
+    pub chain: Option<Chain>,
+    pub name: Option<String>,
+    pub node: Option<B256>,
+    pub address: Option<Address>,
+    pub resolver: Option<Address>,
+    pub owner: Option<Address>,
+    /// Unix timestamp.
+    pub expiry: Option<u64>,
+    pub contenthash: Option<Bytes>,
+    pub avatar: Option<String>,
+    pub url: Option<String>,
+    pub twitter: Option<String>,
+    pub block_number: Option<u64>,
+}
+
 fn serialize_option_u256<S>(option: &Option<U256>, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -372,7 +864,7 @@ mod test {
     use super::serialize_option_u256;
     use super::TransactionQueryRes;
     use super::{AccountQueryRes, BlockQueryRes, ExpressionResult, LogQueryRes};
-    use alloy::primitives::{Address, U256};
+    use alloy::primitives::{Address, B256, U256};
     use serde::Serialize;
     use serde_json::json;
 
@@ -404,6 +896,30 @@ mod test {
         assert!(json.get("to").is_none());
     }
 
+    #[test]
+    fn transaction_lists_nest_in_json_and_flatten_in_csv() {
+        use alloy::eips::eip2930::{AccessList, AccessListItem};
+
+        let res = TransactionQueryRes {
+            access_list: Some(AccessList(vec![AccessListItem {
+                address: Address::ZERO,
+                storage_keys: vec![B256::ZERO],
+            }])),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&res).unwrap();
+        assert_eq!(
+            json["access_list"][0]["storage_keys"][0],
+            format!("{:?}", B256::ZERO)
+        );
+
+        let mut writer = csv::Writer::from_writer(vec![]);
+        writer.serialize(super::TransactionCsvRow(&res)).unwrap();
+        let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert!(csv.starts_with("access_list\n"), "{csv}");
+        assert!(csv.contains("storage_keys"), "{csv}");
+    }
+
     #[test]
     fn truncate_caps_each_variant() {
         let mut res = ExpressionResult::Block(vec![BlockQueryRes::default(); 5]);
@@ -468,4 +984,37 @@ mod test {
         };
         assert_eq!(rows.len(), 3);
     }
+
+    #[test]
+    fn call_values_serialize_as_columns_in_order() {
+        let row = super::CallQueryRes {
+            address: Some(Address::ZERO),
+            values: vec![
+                super::CallValue {
+                    column: "reserve0".into(),
+                    ty: "uint112".into(),
+                    value: Some(json!("100")),
+                },
+                super::CallValue {
+                    column: "output1".into(),
+                    ty: "bool".into(),
+                    value: None,
+                },
+            ],
+            block_number: Some(7),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&row).unwrap(),
+            r#"{"address":"0x0000000000000000000000000000000000000000","reserve0":"100","output1":null,"block_number":7}"#
+        );
+
+        let mut writer = csv::Writer::from_writer(vec![]);
+        writer.serialize(&row).unwrap();
+        let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            csv,
+            "address,reserve0,output1,block_number\n0x0000000000000000000000000000000000000000,100,,7\n"
+        );
+    }
 }
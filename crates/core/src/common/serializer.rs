@@ -5,12 +5,21 @@ use std::sync::Arc;
 use super::{
     dump::{Dump, DumpFormat},
     query_result::{
-        AccountQueryRes, BlockQueryRes, ExpressionResult, LogQueryRes, TransactionQueryRes,
+        AccountQueryRes, AuthorizationQueryRes, BlockQueryRes, CallQueryRes, ColumnInfoRes,
+        ContractQueryRes, EnsQueryRes, ExpressionResult, LogQueryRes, PlanRes, StateDiffQueryRes,
+        StorageQueryRes, TableInfoRes, TokenBalanceQueryRes, TransactionCsvRow,
+        TransactionQueryRes, WithdrawalQueryRes,
     },
 };
 use alloy::primitives::U256;
-use arrow::array::{ArrayRef, BooleanArray, Decimal128Array, StringArray, UInt64Array, UInt8Array};
-use arrow::datatypes::{DataType, Field, Schema};
+use arrow::array::{
+    builder::{
+        ArrayBuilder, ListBuilder, StringBuilder, StructBuilder, UInt64Builder, UInt8Builder,
+    },
+    Array, ArrayRef, BooleanArray, Decimal128Array, Int64Array, StringArray, UInt64Array,
+    UInt8Array,
+};
+use arrow::datatypes::{DataType, Field, Fields, Schema};
 use arrow::record_batch::RecordBatch;
 use parquet::arrow::ArrowWriter;
 use serde::Serialize;
@@ -27,8 +36,21 @@ pub(crate) fn dump_results(result: &ExpressionResult, dump: &Dump) -> Result<(),
             let content = match result {
                 ExpressionResult::Account(accounts) => serialize_csv(accounts)?,
                 ExpressionResult::Block(blocks) => serialize_csv(blocks)?,
-                ExpressionResult::Transaction(txs) => serialize_csv(txs)?,
+                ExpressionResult::Transaction(txs) => {
+                    serialize_csv(&txs.iter().map(TransactionCsvRow).collect())?
+                }
                 ExpressionResult::Log(logs) => serialize_csv(logs)?,
+                ExpressionResult::Storage(slots) => serialize_csv(slots)?,
+                ExpressionResult::TokenBalance(balances) => serialize_csv(balances)?,
+                ExpressionResult::Call(calls) => serialize_csv(calls)?,
+                ExpressionResult::Withdrawal(withdrawals) => serialize_csv(withdrawals)?,
+                ExpressionResult::Authorization(authorizations) => serialize_csv(authorizations)?,
+                ExpressionResult::Contract(contracts) => serialize_csv(contracts)?,
+                ExpressionResult::StateDiff(diffs) => serialize_csv(diffs)?,
+                ExpressionResult::Ens(records) => serialize_csv(records)?,
+                ExpressionResult::Table(tables) => serialize_csv(tables)?,
+                ExpressionResult::Column(columns) => serialize_csv(columns)?,
+                ExpressionResult::Plan(plans) => serialize_csv(plans)?,
             };
 
             std::fs::write(dump.path(), content)?;
@@ -113,6 +135,20 @@ fn serialize_csv<T: Serialize>(results: &Vec<T>) -> Result<String, Box<dyn Error
 }
 
 fn serialize_parquet(result: &ExpressionResult) -> Result<Vec<u8>, Box<dyn Error>> {
+    let batch = to_record_batch(result)?;
+    let mut buf = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut buf, batch.schema(), None)?;
+
+    writer.write(&batch)?;
+    writer.close()?;
+
+    Ok(buf)
+}
+
+/// The typed Arrow batch for `result`: the same columns a Parquet export
+/// writes, for consumers that speak Arrow directly (e.g. the Flight SQL
+/// server in the CLI).
+pub fn to_record_batch(result: &ExpressionResult) -> Result<RecordBatch, Box<dyn Error>> {
     let mut columns = entity_columns(result, false)?;
 
     // No columns means an empty result, or a query whose selected fields were
@@ -125,15 +161,7 @@ fn serialize_parquet(result: &ExpressionResult) -> Result<Vec<u8>, Box<dyn Error
 
     let (fields, arrays): (Vec<Field>, Vec<ArrayRef>) = columns.into_iter().unzip();
     let schema = Arc::new(Schema::new(fields));
-    let batch = RecordBatch::try_new(schema.clone(), arrays)?;
-
-    let mut buf = Vec::new();
-    let mut writer = ArrowWriter::try_new(&mut buf, schema, None)?;
-
-    writer.write(&batch)?;
-    writer.close()?;
-
-    Ok(buf)
+    Ok(RecordBatch::try_new(schema, arrays)?)
 }
 
 /// Typed columns for `result`. With `schema_only`, the builders run over no
@@ -150,6 +178,25 @@ fn entity_columns(
             transaction_columns(if schema_only { &[] } else { rows })
         }
         ExpressionResult::Log(rows) => log_columns(if schema_only { &[] } else { rows }),
+        ExpressionResult::Storage(rows) => storage_columns(if schema_only { &[] } else { rows }),
+        ExpressionResult::TokenBalance(rows) => {
+            token_balance_columns(if schema_only { &[] } else { rows })
+        }
+        ExpressionResult::Call(rows) => call_columns(if schema_only { &[] } else { rows }),
+        ExpressionResult::Withdrawal(rows) => {
+            withdrawal_columns(if schema_only { &[] } else { rows })
+        }
+        ExpressionResult::Authorization(rows) => {
+            authorization_columns(if schema_only { &[] } else { rows })
+        }
+        ExpressionResult::Contract(rows) => contract_columns(if schema_only { &[] } else { rows }),
+        ExpressionResult::StateDiff(rows) => {
+            state_diff_columns(if schema_only { &[] } else { rows })
+        }
+        ExpressionResult::Ens(rows) => ens_columns(if schema_only { &[] } else { rows }),
+        ExpressionResult::Table(rows) => table_columns(if schema_only { &[] } else { rows }),
+        ExpressionResult::Column(rows) => column_columns(if schema_only { &[] } else { rows }),
+        ExpressionResult::Plan(rows) => plan_columns(if schema_only { &[] } else { rows }),
     }
 }
 
@@ -205,6 +252,16 @@ fn u8_col(name: &str, vals: Vec<Option<u8>>) -> Option<Column> {
     ))
 }
 
+fn i64_col(name: &str, vals: Vec<Option<i64>>) -> Option<Column> {
+    if skip(&vals) {
+        return None;
+    }
+    Some((
+        Field::new(name, DataType::Int64, true),
+        Arc::new(Int64Array::from(vals)) as ArrayRef,
+    ))
+}
+
 fn bool_col(name: &str, vals: Vec<Option<bool>>) -> Option<Column> {
     if skip(&vals) {
         return None;
@@ -225,6 +282,64 @@ fn str_col(name: &str, vals: Vec<Option<String>>) -> Option<Column> {
     ))
 }
 
+/// Multi-valued fields as a list of strings (`VARCHAR[]` once loaded), so
+/// each element stays addressable instead of being packed into one cell.
+fn str_list_col(name: &str, vals: Vec<Option<Vec<String>>>) -> Option<Column> {
+    if skip(&vals) {
+        return None;
+    }
+    let mut builder = ListBuilder::new(StringBuilder::new());
+    for val in vals {
+        match val {
+            Some(items) => {
+                for item in items {
+                    builder.values().append_value(item);
+                }
+                builder.append(true);
+            }
+            None => builder.append(false),
+        }
+    }
+    let array = builder.finish();
+    Some((
+        Field::new(name, array.data_type().clone(), true),
+        Arc::new(array) as ArrayRef,
+    ))
+}
+
+/// List-of-struct fields (access lists, authorization lists), so each entry
+/// keeps its members as typed, addressable values once loaded. `append`
+/// fills one entry's members through `builders`, which match `fields`.
+fn struct_list_col<T>(
+    name: &str,
+    fields: Vec<Field>,
+    builders: Vec<Box<dyn ArrayBuilder>>,
+    vals: Vec<Option<&[T]>>,
+    append: impl Fn(&mut StructBuilder, &T),
+) -> Option<Column> {
+    if skip(&vals) {
+        return None;
+    }
+    let mut builder = ListBuilder::new(StructBuilder::new(Fields::from(fields), builders));
+    for val in vals {
+        match val {
+            Some(entries) => {
+                for entry in entries {
+                    append(builder.values(), entry);
+                    builder.values().append(true);
+                }
+                builder.append(true);
+            }
+            None => builder.append(false),
+        }
+    }
+    let array = builder.finish();
+    Some((
+        Field::new(name, array.data_type().clone(), true),
+        Arc::new(array) as ArrayRef,
+    ))
+}
+
 /// Quantity `U256` fields (balances, values, sizes, difficulty) as
 /// `Decimal128(38, 0)`. 38 decimal digits hold every real chain quantity (the
 /// largest conceivable native balance is far below 10^38 wei), and DuckDB and
@@ -314,6 +429,14 @@ fn account_columns(rows: &[AccountQueryRes]) -> Result<Vec<Column>, Box<dyn Erro
             col(rows, |r| r.code.as_ref().map(|c| format!("{c:?}"))),
         ),
     );
+    push(
+        &mut cols,
+        u64_col("block_number", col(rows, |r| r.block_number)),
+    );
+    push(
+        &mut cols,
+        str_col("address_ens", col(rows, |r| r.address_ens.clone())),
+    );
     Ok(cols)
 }
 
@@ -343,6 +466,24 @@ fn block_columns(rows: &[BlockQueryRes]) -> Result<Vec<Column>, Box<dyn Error>>
             col(rows, |r| r.parent_hash.as_ref().map(|h| format!("{h:?}"))),
         ),
     );
+    push(
+        &mut cols,
+        str_col(
+            "miner",
+            col(rows, |r| r.miner.as_ref().map(|a| format!("{a:#x}"))),
+        ),
+    );
+    push(&mut cols, u64_col("gas_used", col(rows, |r| r.gas_used)));
+    push(&mut cols, u64_col("gas_limit", col(rows, |r| r.gas_limit)));
+    push(&mut cols, u64_col("nonce", col(rows, |r| r.nonce)));
+    push(
+        &mut cols,
+        u256_col("difficulty", col(rows, |r| r.difficulty))?,
+    );
+    push(
+        &mut cols,
+        u64_col("transaction_count", col(rows, |r| r.transaction_count)),
+    );
     push(
         &mut cols,
         str_col(
@@ -479,6 +620,25 @@ fn transaction_columns(rows: &[TransactionQueryRes]) -> Result<Vec<Column>, Box<
         u128_col("effective_gas_price", col(rows, |r| r.effective_gas_price))?,
     );
     push(&mut cols, bool_col("status", col(rows, |r| r.status)));
+    push(&mut cols, u64_col("gas_used", col(rows, |r| r.gas_used)));
+    push(
+        &mut cols,
+        u64_col("cumulative_gas_used", col(rows, |r| r.cumulative_gas_used)),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "contract_address",
+            col(rows, |r| {
+                r.contract_address.as_ref().map(|a| format!("{a:#x}"))
+            }),
+        ),
+    );
+    push(
+        &mut cols,
+        u64_col("logs_count", col(rows, |r| r.logs_count)),
+    );
+    push(&mut cols, u256_col("fee", col(rows, |r| r.fee))?);
     push(&mut cols, u64_col("chain_id", col(rows, |r| r.chain_id)));
     push(&mut cols, bool_col("v", col(rows, |r| r.v)));
     push(
@@ -496,6 +656,29 @@ fn transaction_columns(rows: &[TransactionQueryRes]) -> Result<Vec<Column>, Box<
             col(rows, |r| r.max_fee_per_blob_gas),
         )?,
     );
+    push(
+        &mut cols,
+        str_list_col(
+            "blob_versioned_hashes",
+            col(rows, |r| {
+                r.blob_versioned_hashes
+                    .as_ref()
+                    .map(|hashes| hashes.iter().map(|h| format!("{h:?}")).collect())
+            }),
+        ),
+    );
+    push(
+        &mut cols,
+        u64_col("blob_count", col(rows, |r| r.blob_count)),
+    );
+    push(
+        &mut cols,
+        u64_col("blob_gas_used", col(rows, |r| r.blob_gas_used)),
+    );
+    push(
+        &mut cols,
+        u128_col("blob_gas_price", col(rows, |r| r.blob_gas_price))?,
+    );
     push(
         &mut cols,
         u128_col("max_fee_per_gas", col(rows, |r| r.max_fee_per_gas))?,
@@ -507,22 +690,119 @@ fn transaction_columns(rows: &[TransactionQueryRes]) -> Result<Vec<Column>, Box<
             col(rows, |r| r.max_priority_fee_per_gas),
         )?,
     );
+    push(
+        &mut cols,
+        struct_list_col(
+            "access_list",
+            vec![
+                Field::new("address", DataType::Utf8, true),
+                Field::new_list(
+                    "storage_keys",
+                    Field::new_list_field(DataType::Utf8, true),
+                    true,
+                ),
+            ],
+            vec![
+                Box::new(StringBuilder::new()),
+                Box::new(ListBuilder::new(StringBuilder::new())),
+            ],
+            rows.iter()
+                .map(|r| r.access_list.as_ref().map(|list| &list.0[..]))
+                .collect(),
+            |entry, item| {
+                struct_field::<StringBuilder>(entry, 0)
+                    .append_value(format!("{:#x}", item.address));
+                let keys = struct_field::<ListBuilder<StringBuilder>>(entry, 1);
+                for key in &item.storage_keys {
+                    keys.values().append_value(format!("{key:?}"));
+                }
+                keys.append(true);
+            },
+        ),
+    );
     push(&mut cols, bool_col("y_parity", col(rows, |r| r.y_parity)));
-    // Serialize the authorization list to JSON, propagating any error rather
-    // than swallowing it into an empty string.
-    let authorization_list = rows
-        .iter()
-        .map(|r| {
-            r.authorization_list
-                .as_ref()
-                .map(serde_json::to_string)
-                .transpose()
-        })
-        .collect::<Result<Vec<Option<String>>, _>>()?;
-    push(&mut cols, str_col("authorization_list", authorization_list));
+    push(
+        &mut cols,
+        struct_list_col(
+            "authorization_list",
+            vec![
+                Field::new("chain_id", DataType::UInt64, true),
+                Field::new("address", DataType::Utf8, true),
+                Field::new("nonce", DataType::UInt64, true),
+                Field::new("y_parity", DataType::UInt8, true),
+                Field::new("r", DataType::Utf8, true),
+                Field::new("s", DataType::Utf8, true),
+            ],
+            vec![
+                Box::new(UInt64Builder::new()),
+                Box::new(StringBuilder::new()),
+                Box::new(UInt64Builder::new()),
+                Box::new(UInt8Builder::new()),
+                Box::new(StringBuilder::new()),
+                Box::new(StringBuilder::new()),
+            ],
+            rows.iter()
+                .map(|r| r.authorization_list.as_deref())
+                .collect(),
+            |entry, auth| {
+                struct_field::<UInt64Builder>(entry, 0).append_value(auth.chain_id);
+                struct_field::<StringBuilder>(entry, 1)
+                    .append_value(format!("{:#x}", auth.address));
+                struct_field::<UInt64Builder>(entry, 2).append_value(auth.nonce);
+                struct_field::<UInt8Builder>(entry, 3).append_value(auth.y_parity());
+                struct_field::<StringBuilder>(entry, 4).append_value(auth.r().to_string());
+                struct_field::<StringBuilder>(entry, 5).append_value(auth.s().to_string());
+            },
+        ),
+    );
+    push(&mut cols, u256_col("l1_fee", col(rows, |r| r.l1_fee))?);
+    push(
+        &mut cols,
+        u64_col("l1_gas_used", col(rows, |r| r.l1_gas_used)),
+    );
+    push(
+        &mut cols,
+        u128_col("l1_gas_price", col(rows, |r| r.l1_gas_price))?,
+    );
+    push(
+        &mut cols,
+        str_col("l1_fee_scalar", col(rows, |r| r.l1_fee_scalar.clone())),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "source_hash",
+            col(rows, |r| r.source_hash.as_ref().map(|h| format!("{h:?}"))),
+        ),
+    );
+    push(&mut cols, u256_col("mint", col(rows, |r| r.mint))?);
+    push(
+        &mut cols,
+        bool_col("is_system_tx", col(rows, |r| r.is_system_tx)),
+    );
+    push(
+        &mut cols,
+        u64_col("gas_used_for_l1", col(rows, |r| r.gas_used_for_l1)),
+    );
+    push(
+        &mut cols,
+        str_col("from_ens", col(rows, |r| r.from_ens.clone())),
+    );
+    push(
+        &mut cols,
+        str_col("to_ens", col(rows, |r| r.to_ens.clone())),
+    );
     Ok(cols)
 }
 
+/// The builder of member `index` of a struct column, as built by
+/// `struct_list_col`, which always passes matching builder types.
+fn struct_field<B: ArrayBuilder>(entry: &mut StructBuilder, index: usize) -> &mut B {
+    entry
+        .field_builder::<B>(index)
+        .expect("struct member builders match their fields")
+}
+
 fn log_columns(rows: &[LogQueryRes]) -> Result<Vec<Column>, Box<dyn Error>> {
     let mut cols = Vec::new();
     push(
@@ -604,41 +884,534 @@ fn log_columns(rows: &[LogQueryRes]) -> Result<Vec<Column>, Box<dyn Error>> {
     );
     push(&mut cols, u64_col("log_index", col(rows, |r| r.log_index)));
     push(&mut cols, bool_col("removed", col(rows, |r| r.removed)));
+    push(
+        &mut cols,
+        str_col("address_ens", col(rows, |r| r.address_ens.clone())),
+    );
     Ok(cols)
 }
 
-#[cfg(test)]
-mod test {
-    use super::{
-        account_columns, apply_aliases, block_columns, log_columns, serialize_csv, serialize_json,
-        serialize_parquet, transaction_columns, Column,
-    };
-    use crate::common::query_result::{
-        AccountQueryRes, BlockQueryRes, ExpressionResult, LogQueryRes, TransactionQueryRes,
-    };
-    use alloy::primitives::{B256, U256};
-    use arrow::array::{StringArray, UInt64Array};
-    use arrow::datatypes::DataType;
-    use std::collections::HashMap;
-    use std::str::FromStr;
+fn storage_columns(rows: &[StorageQueryRes]) -> Result<Vec<Column>, Box<dyn Error>> {
+    let mut cols = Vec::new();
+    push(
+        &mut cols,
+        str_col(
+            "chain",
+            col(rows, |r| r.chain.as_ref().map(|c| c.to_string())),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "address",
+            col(rows, |r| r.address.as_ref().map(|a| format!("{a:#x}"))),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "slot",
+            col(rows, |r| r.slot.as_ref().map(|s| format!("{s:?}"))),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "value",
+            col(rows, |r| r.value.as_ref().map(|v| format!("{v:?}"))),
+        ),
+    );
+    push(
+        &mut cols,
+        u64_col("block_number", col(rows, |r| r.block_number)),
+    );
+    Ok(cols)
+}
 
-    fn column_types(cols: &[Column]) -> HashMap<String, DataType> {
-        cols.iter()
-            .map(|(f, _)| (f.name().clone(), f.data_type().clone()))
-            .collect()
-    }
+fn token_balance_columns(rows: &[TokenBalanceQueryRes]) -> Result<Vec<Column>, Box<dyn Error>> {
+    let address = |a: &Option<alloy::primitives::Address>| a.as_ref().map(|a| format!("{a:#x}"));
+    let mut cols = Vec::new();
+    push(
+        &mut cols,
+        str_col(
+            "chain",
+            col(rows, |r| r.chain.as_ref().map(|c| c.to_string())),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col("holder", col(rows, |r| address(&r.holder))),
+    );
+    push(
+        &mut cols,
+        str_col("token", col(rows, |r| address(&r.token))),
+    );
+    push(&mut cols, u256_col("balance", col(rows, |r| r.balance))?);
+    push(&mut cols, u8_col("decimals", col(rows, |r| r.decimals)));
+    push(
+        &mut cols,
+        str_col("scaled_balance", col(rows, |r| r.scaled_balance.clone())),
+    );
+    push(
+        &mut cols,
+        str_col("spender", col(rows, |r| address(&r.spender))),
+    );
+    push(
+        &mut cols,
+        u256_col("allowance", col(rows, |r| r.allowance))?,
+    );
+    push(
+        &mut cols,
+        str_col(
+            "scaled_allowance",
+            col(rows, |r| r.scaled_allowance.clone()),
+        ),
+    );
+    // Token ids are opaque identifiers that often use the full 256 bits
+    // (hashes, packed values), so they are always decimal strings.
+    push(
+        &mut cols,
+        str_col("token_id", decimal_strings(col(rows, |r| r.token_id))),
+    );
+    push(
+        &mut cols,
+        str_col("owner", col(rows, |r| address(&r.owner))),
+    );
+    push(
+        &mut cols,
+        u64_col("block_number", col(rows, |r| r.block_number)),
+    );
+    Ok(cols)
+}
 
-    #[test]
-    fn test_serialize_json() {
-        let res = AccountQueryRes {
-            address: None,
-            balance: Some(U256::from_str("100").unwrap()),
-            nonce: Some(0),
-            code: None,
-            chain: None,
+/// The fixed columns, then one per argument and output, typed by its Solidity
+/// type: `bool` as a boolean, integers up to 64 bits as 64-bit integers,
+/// wider unsigned ones like other quantities, and everything else as text.
+/// With no rows, only the fixed columns are known.
+fn call_columns(rows: &[CallQueryRes]) -> Result<Vec<Column>, Box<dyn Error>> {
+    let mut cols = Vec::new();
+    push(
+        &mut cols,
+        str_col(
+            "chain",
+            col(rows, |r| r.chain.as_ref().map(|c| c.to_string())),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "address",
+            col(rows, |r| r.address.as_ref().map(|a| format!("{a:#x}"))),
+        ),
+    );
+    let columns = rows.first().map_or(&[][..], |r| &r.values[..]);
+    for (i, column) in columns.iter().enumerate() {
+        let name = column.column.as_str();
+        let vals = col(rows, |r| r.values.get(i).and_then(|v| v.value.clone()));
+        let bits = |prefix| {
+            column
+                .ty
+                .strip_prefix(prefix)
+                .and_then(|bits: &str| bits.parse::<u16>().ok())
         };
-        let result = ExpressionResult::Account(vec![res]);
-        let content = serialize_json(&result).unwrap();
+        let column = match (column.ty.as_str(), bits("uint"), bits("int")) {
+            ("bool", _, _) => bool_col(name, vals.iter().map(|v| v.as_ref()?.as_bool()).collect()),
+            (_, Some(bits), _) if bits <= 64 => {
+                u64_col(name, vals.iter().map(|v| v.as_ref()?.as_u64()).collect())
+            }
+            (_, Some(_), _) => u256_col(
+                name,
+                vals.iter()
+                    .map(|v| v.as_ref()?.as_str()?.parse().ok())
+                    .collect(),
+            )?,
+            (_, _, Some(bits)) if bits <= 64 => {
+                i64_col(name, vals.iter().map(|v| v.as_ref()?.as_i64()).collect())
+            }
+            _ => str_col(
+                name,
+                vals.into_iter()
+                    .map(|v| match v? {
+                        serde_json::Value::String(s) => Some(s),
+                        other => Some(other.to_string()),
+                    })
+                    .collect(),
+            ),
+        };
+        push(&mut cols, column);
+    }
+    push(
+        &mut cols,
+        u64_col("block_number", col(rows, |r| r.block_number)),
+    );
+    Ok(cols)
+}
+
+fn withdrawal_columns(rows: &[WithdrawalQueryRes]) -> Result<Vec<Column>, Box<dyn Error>> {
+    let mut cols = Vec::new();
+    push(
+        &mut cols,
+        str_col(
+            "chain",
+            col(rows, |r| r.chain.as_ref().map(|c| c.to_string())),
+        ),
+    );
+    push(
+        &mut cols,
+        u64_col("block_number", col(rows, |r| r.block_number)),
+    );
+    push(&mut cols, u64_col("index", col(rows, |r| r.index)));
+    push(
+        &mut cols,
+        u64_col("validator_index", col(rows, |r| r.validator_index)),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "address",
+            col(rows, |r| r.address.as_ref().map(|a| format!("{a:#x}"))),
+        ),
+    );
+    push(
+        &mut cols,
+        u64_col("amount_gwei", col(rows, |r| r.amount_gwei)),
+    );
+    push(&mut cols, u256_col("amount", col(rows, |r| r.amount))?);
+    Ok(cols)
+}
+
+fn authorization_columns(rows: &[AuthorizationQueryRes]) -> Result<Vec<Column>, Box<dyn Error>> {
+    let mut cols = Vec::new();
+    push(
+        &mut cols,
+        str_col(
+            "chain",
+            col(rows, |r| r.chain.as_ref().map(|c| c.to_string())),
+        ),
+    );
+    push(
+        &mut cols,
+        u64_col("block_number", col(rows, |r| r.block_number)),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "transaction_hash",
+            col(rows, |r| {
+                r.transaction_hash.as_ref().map(|h| format!("{h:?}"))
+            }),
+        ),
+    );
+    push(&mut cols, u64_col("index", col(rows, |r| r.index)));
+    push(
+        &mut cols,
+        str_col(
+            "authority",
+            col(rows, |r| r.authority.as_ref().map(|a| format!("{a:#x}"))),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "delegate",
+            col(rows, |r| r.delegate.as_ref().map(|a| format!("{a:#x}"))),
+        ),
+    );
+    push(&mut cols, u64_col("nonce", col(rows, |r| r.nonce)));
+    push(&mut cols, u64_col("chain_id", col(rows, |r| r.chain_id)));
+    Ok(cols)
+}
+
+fn contract_columns(rows: &[ContractQueryRes]) -> Result<Vec<Column>, Box<dyn Error>> {
+    let mut cols = Vec::new();
+    push(
+        &mut cols,
+        str_col(
+            "chain",
+            col(rows, |r| r.chain.as_ref().map(|c| c.to_string())),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "address",
+            col(rows, |r| r.address.as_ref().map(|a| format!("{a:#x}"))),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "deployer",
+            col(rows, |r| r.deployer.as_ref().map(|a| format!("{a:#x}"))),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "transaction_hash",
+            col(rows, |r| {
+                r.transaction_hash.as_ref().map(|h| format!("{h:?}"))
+            }),
+        ),
+    );
+    push(
+        &mut cols,
+        u64_col("block_number", col(rows, |r| r.block_number)),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "init_code_hash",
+            col(rows, |r| {
+                r.init_code_hash.as_ref().map(|h| format!("{h:?}"))
+            }),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "runtime_code_hash",
+            col(rows, |r| {
+                r.runtime_code_hash.as_ref().map(|h| format!("{h:?}"))
+            }),
+        ),
+    );
+    Ok(cols)
+}
+
+fn state_diff_columns(rows: &[StateDiffQueryRes]) -> Result<Vec<Column>, Box<dyn Error>> {
+    let mut cols = Vec::new();
+    push(
+        &mut cols,
+        str_col(
+            "chain",
+            col(rows, |r| r.chain.as_ref().map(|c| c.to_string())),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "address",
+            col(rows, |r| r.address.as_ref().map(|a| format!("{a:#x}"))),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "key",
+            col(rows, |r| r.key.as_ref().map(|k| format!("{k:?}"))),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col("kind", col(rows, |r| r.kind.map(|k| k.to_string()))),
+    );
+    push(&mut cols, str_col("prev", col(rows, |r| r.prev.clone())));
+    push(&mut cols, str_col("next", col(rows, |r| r.next.clone())));
+    push(
+        &mut cols,
+        str_col(
+            "transaction_hash",
+            col(rows, |r| {
+                r.transaction_hash.as_ref().map(|h| format!("{h:?}"))
+            }),
+        ),
+    );
+    push(
+        &mut cols,
+        u64_col("block_number", col(rows, |r| r.block_number)),
+    );
+    Ok(cols)
+}
+
+fn ens_columns(rows: &[EnsQueryRes]) -> Result<Vec<Column>, Box<dyn Error>> {
+    let mut cols = Vec::new();
+    push(
+        &mut cols,
+        str_col(
+            "chain",
+            col(rows, |r| r.chain.as_ref().map(|c| c.to_string())),
+        ),
+    );
+    push(&mut cols, str_col("name", col(rows, |r| r.name.clone())));
+    push(
+        &mut cols,
+        str_col(
+            "node",
+            col(rows, |r| r.node.as_ref().map(|n| format!("{n:?}"))),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "address",
+            col(rows, |r| r.address.as_ref().map(|a| format!("{a:#x}"))),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "resolver",
+            col(rows, |r| r.resolver.as_ref().map(|a| format!("{a:#x}"))),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col(
+            "owner",
+            col(rows, |r| r.owner.as_ref().map(|a| format!("{a:#x}"))),
+        ),
+    );
+    push(&mut cols, u64_col("expiry", col(rows, |r| r.expiry)));
+    push(
+        &mut cols,
+        str_col(
+            "contenthash",
+            col(rows, |r| r.contenthash.as_ref().map(|c| format!("{c:?}"))),
+        ),
+    );
+    push(
+        &mut cols,
+        str_col("avatar", col(rows, |r| r.avatar.clone())),
+    );
+    push(&mut cols, str_col("url", col(rows, |r| r.url.clone())));
+    push(
+        &mut cols,
+        str_col("twitter", col(rows, |r| r.twitter.clone())),
+    );
+    push(
+        &mut cols,
+        u64_col("block_number", col(rows, |r| r.block_number)),
+    );
+    Ok(cols)
+}
+
+fn table_columns(rows: &[TableInfoRes]) -> Result<Vec<Column>, Box<dyn Error>> {
+    let mut cols = Vec::new();
+    push(
+        &mut cols,
+        str_col("table_name", col(rows, |r| Some(r.table_name.clone()))),
+    );
+    Ok(cols)
+}
+
+fn column_columns(rows: &[ColumnInfoRes]) -> Result<Vec<Column>, Box<dyn Error>> {
+    let mut cols = Vec::new();
+    push(
+        &mut cols,
+        str_col("table_name", col(rows, |r| Some(r.table_name.clone()))),
+    );
+    push(
+        &mut cols,
+        str_col("column_name", col(rows, |r| Some(r.column_name.clone()))),
+    );
+    push(
+        &mut cols,
+        str_col("data_type", col(rows, |r| Some(r.data_type.clone()))),
+    );
+    push(
+        &mut cols,
+        str_col("aliases", col(rows, |r| Some(r.aliases.clone()))),
+    );
+    push(
+        &mut cols,
+        bool_col("portal_pushdown", col(rows, |r| Some(r.portal_pushdown))),
+    );
+    Ok(cols)
+}
+
+fn plan_columns(rows: &[PlanRes]) -> Result<Vec<Column>, Box<dyn Error>> {
+    let mut cols = Vec::new();
+    push(
+        &mut cols,
+        str_col("chain", col(rows, |r| Some(r.chain.clone()))),
+    );
+    push(
+        &mut cols,
+        str_col("entity", col(rows, |r| Some(r.entity.clone()))),
+    );
+    push(
+        &mut cols,
+        str_col("source", col(rows, |r| Some(r.source.clone()))),
+    );
+    push(
+        &mut cols,
+        str_col("block_range", col(rows, |r| r.block_range.clone())),
+    );
+    push(
+        &mut cols,
+        str_col("portal_query", col(rows, |r| r.portal_query.clone())),
+    );
+    push(
+        &mut cols,
+        str_col("client_filters", col(rows, |r| r.client_filters.clone())),
+    );
+    push(
+        &mut cols,
+        str_col("lake_files", col(rows, |r| r.lake_files.clone())),
+    );
+    push(
+        &mut cols,
+        u64_col("estimated_rpc_calls", col(rows, |r| r.estimated_rpc_calls)),
+    );
+    push(
+        &mut cols,
+        u64_col(
+            "rpc_calls_per_row",
+            col(rows, |r| Some(r.rpc_calls_per_row)),
+        ),
+    );
+    push(
+        &mut cols,
+        u64_col("elapsed_ms", col(rows, |r| r.elapsed_ms)),
+    );
+    push(&mut cols, u64_col("rows", col(rows, |r| r.rows)));
+    push(
+        &mut cols,
+        u64_col("portal_pages", col(rows, |r| r.portal_pages)),
+    );
+    push(
+        &mut cols,
+        u64_col("portal_bytes", col(rows, |r| r.portal_bytes)),
+    );
+    Ok(cols)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        account_columns, apply_aliases, block_columns, call_columns, log_columns, serialize_csv,
+        serialize_json, serialize_parquet, transaction_columns, Column,
+    };
+    use crate::common::query_result::{
+        AccountQueryRes, BlockQueryRes, CallQueryRes, CallValue, ExpressionResult, LogQueryRes,
+        TransactionQueryRes,
+    };
+    use alloy::primitives::{B256, U256};
+    use arrow::array::{StringArray, UInt64Array};
+    use arrow::datatypes::DataType;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    fn column_types(cols: &[Column]) -> HashMap<String, DataType> {
+        cols.iter()
+            .map(|(f, _)| (f.name().clone(), f.data_type().clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_serialize_json() {
+        let res = AccountQueryRes {
+            address: None,
+            balance: Some(U256::from_str("100").unwrap()),
+            nonce: Some(0),
+            code: None,
+            chain: None,
+            block_number: None,
+            address_ens: None,
+        };
+        let result = ExpressionResult::Account(vec![res]);
+        let content = serialize_json(&result).unwrap();
 
         assert_eq!(content, "{\n  \"account\": [\n    {\n      \"nonce\": 0,\n      \"balance\": \"100\"\n    }\n  ]\n}");
     }
@@ -652,6 +1425,8 @@ mod test {
                 nonce: Some(0),
                 code: None,
                 chain: None,
+                block_number: None,
+                address_ens: None,
             },
             AccountQueryRes {
                 address: None,
@@ -659,6 +1434,8 @@ mod test {
                 nonce: Some(1),
                 code: None,
                 chain: None,
+                block_number: None,
+                address_ens: None,
             },
         ];
         let content = serialize_csv(&res).unwrap();
@@ -674,6 +1451,8 @@ mod test {
             nonce: Some(0),
             code: None,
             chain: None,
+            block_number: None,
+            address_ens: None,
         };
         let result = ExpressionResult::Account(vec![res]);
         let content = serialize_parquet(&result).unwrap();
@@ -710,11 +1489,18 @@ mod test {
             size: Some(U256::from(1234u64)),
             hash: Some(B256::ZERO),
             base_fee_per_gas: Some(7),
+            miner: Some(alloy::primitives::Address::ZERO),
+            gas_used: Some(15_000_000),
+            difficulty: Some(U256::ZERO),
             ..Default::default()
         }];
         let cols = block_columns(&rows).unwrap();
         let types = column_types(&cols);
 
+        assert_eq!(types["miner"], DataType::Utf8);
+        assert_eq!(types["gas_used"], DataType::UInt64);
+        assert_eq!(types["difficulty"], DataType::Decimal128(38, 0));
+
         assert_eq!(types["number"], DataType::UInt64);
         assert_eq!(types["timestamp"], DataType::UInt64);
         assert_eq!(types["size"], DataType::Decimal128(38, 0));
@@ -740,6 +1526,9 @@ mod test {
             gas_price: Some(1_000_000_000),
             value: Some(U256::from(42u64)),
             r: Some(U256::from(7u64)),
+            gas_used: Some(21_000),
+            contract_address: Some(alloy::primitives::Address::ZERO),
+            fee: Some(U256::from(21_000_000_000_000u64)),
             authorization_list: Some(vec![]),
             ..Default::default()
         }];
@@ -749,9 +1538,114 @@ mod test {
         assert_eq!(types["status"], DataType::Boolean);
         assert_eq!(types["gas_price"], DataType::Decimal128(38, 0));
         assert_eq!(types["value"], DataType::Decimal128(38, 0));
+        assert_eq!(types["gas_used"], DataType::UInt64);
+        assert_eq!(types["contract_address"], DataType::Utf8);
+        assert_eq!(types["fee"], DataType::Decimal128(38, 0));
         // Full-range signature components stay as decimal strings.
         assert_eq!(types["r"], DataType::Utf8);
-        assert_eq!(types["authorization_list"], DataType::Utf8);
+        let DataType::List(item) = &types["authorization_list"] else {
+            panic!("not a list: {:?}", types["authorization_list"]);
+        };
+        assert!(matches!(item.data_type(), DataType::Struct(_)));
+    }
+
+    #[test]
+    fn parquet_access_and_authorization_lists_are_lists_of_structs() {
+        use alloy::eips::eip2930::{AccessList, AccessListItem};
+        use alloy_eip7702::{Authorization, SignedAuthorization};
+        use arrow::array::{Array, ListArray, StructArray, UInt8Array};
+
+        let auth = SignedAuthorization::new_unchecked(
+            Authorization {
+                chain_id: 1,
+                address: alloy::primitives::Address::with_last_byte(9),
+                nonce: 4,
+            },
+            1,
+            U256::from(2u64),
+            U256::from(3u64),
+        );
+        let rows = vec![
+            TransactionQueryRes {
+                access_list: Some(AccessList(vec![AccessListItem {
+                    address: alloy::primitives::Address::with_last_byte(1),
+                    storage_keys: vec![B256::ZERO, B256::with_last_byte(1)],
+                }])),
+                authorization_list: Some(vec![auth]),
+                ..Default::default()
+            },
+            TransactionQueryRes::default(),
+        ];
+        let cols = transaction_columns(&rows).unwrap();
+        let list = |name: &str| {
+            let (_, array) = cols.iter().find(|(f, _)| f.name() == name).unwrap();
+            array.as_any().downcast_ref::<ListArray>().unwrap().clone()
+        };
+
+        let access_list = list("access_list");
+        assert!(access_list.is_null(1));
+        let entry = access_list.value(0);
+        let entry = entry.as_any().downcast_ref::<StructArray>().unwrap();
+        let address = entry.column_by_name("address").unwrap();
+        let address = address.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(
+            address.value(0),
+            "0x0000000000000000000000000000000000000001"
+        );
+        let keys = entry.column_by_name("storage_keys").unwrap();
+        let keys = keys.as_any().downcast_ref::<ListArray>().unwrap();
+        assert_eq!(keys.value_length(0), 2);
+
+        let authorizations = list("authorization_list");
+        let entry = authorizations.value(0);
+        let entry = entry.as_any().downcast_ref::<StructArray>().unwrap();
+        let nonce = entry.column_by_name("nonce").unwrap();
+        let nonce = nonce.as_any().downcast_ref::<UInt64Array>().unwrap();
+        assert_eq!(nonce.value(0), 4);
+        let y_parity = entry.column_by_name("y_parity").unwrap();
+        assert_eq!(
+            y_parity
+                .as_any()
+                .downcast_ref::<UInt8Array>()
+                .unwrap()
+                .value(0),
+            1
+        );
+    }
+
+    #[test]
+    fn parquet_blob_hashes_are_a_list_column() {
+        let rows = vec![
+            TransactionQueryRes {
+                blob_versioned_hashes: Some(vec![B256::ZERO, B256::with_last_byte(1)]),
+                blob_count: Some(2),
+                blob_gas_price: Some(1),
+                ..Default::default()
+            },
+            TransactionQueryRes {
+                blob_count: Some(0),
+                ..Default::default()
+            },
+        ];
+        let cols = transaction_columns(&rows).unwrap();
+        let types = column_types(&cols);
+
+        let DataType::List(item) = &types["blob_versioned_hashes"] else {
+            panic!("not a list: {:?}", types["blob_versioned_hashes"]);
+        };
+        assert_eq!(item.data_type(), &DataType::Utf8);
+        assert_eq!(types["blob_count"], DataType::UInt64);
+        assert_eq!(types["blob_gas_price"], DataType::Decimal128(38, 0));
+
+        let (_, hashes) = cols
+            .iter()
+            .find(|(field, _)| field.name() == "blob_versioned_hashes")
+            .unwrap();
+        let hashes = arrow::array::Array::as_any(hashes.as_ref())
+            .downcast_ref::<arrow::array::ListArray>()
+            .unwrap();
+        assert_eq!(hashes.value_length(0), 2);
+        assert!(arrow::array::Array::is_null(hashes, 1));
     }
 
     #[test]
@@ -772,6 +1666,48 @@ mod test {
         assert_eq!(types["log_index"], DataType::UInt64);
     }
 
+    #[test]
+    fn parquet_call_columns_are_typed_by_solidity_type() {
+        let value = |column: &str, ty: &str, value| CallValue {
+            column: column.into(),
+            ty: ty.into(),
+            value: Some(value),
+        };
+        let rows = vec![CallQueryRes {
+            values: vec![
+                value("reserve0", "uint112", serde_json::json!("1000")),
+                value("timestamp", "uint32", serde_json::json!(1700000000)),
+                value("tick", "int24", serde_json::json!(-5)),
+                value("paused", "bool", serde_json::json!(false)),
+                value("owner", "address", serde_json::json!("0x00")),
+                value("path", "address[]", serde_json::json!("[\"0x00\"]")),
+            ],
+            block_number: Some(1),
+            ..Default::default()
+        }];
+        let cols = call_columns(&rows).unwrap();
+        let names: Vec<_> = cols.iter().map(|(f, _)| f.name().as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "reserve0",
+                "timestamp",
+                "tick",
+                "paused",
+                "owner",
+                "path",
+                "block_number"
+            ]
+        );
+        let types = column_types(&cols);
+        assert_eq!(types["reserve0"], DataType::Decimal128(38, 0));
+        assert_eq!(types["timestamp"], DataType::UInt64);
+        assert_eq!(types["tick"], DataType::Int64);
+        assert_eq!(types["paused"], DataType::Boolean);
+        assert_eq!(types["owner"], DataType::Utf8);
+        assert_eq!(types["path"], DataType::Utf8);
+    }
+
     #[test]
     fn parquet_u256_beyond_decimal128_falls_back_to_string() {
         // A value that overflows Decimal128(38, 0) — here U256::MAX, the kind
@@ -0,0 +1,170 @@
+use super::{
+    block::BlockId,
+    ens::{NameOrAddress, NamesAddresses},
+};
+use alloy::primitives::B256;
+use eql_macros::EnumVariants;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// The state each transaction in some blocks changed: balances, nonces,
+/// code and storage slots, optionally narrowed to some accounts, kinds and
+/// slots.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StateDiffs {
+    blocks: Vec<BlockId>,
+    addresses: Vec<NameOrAddress>,
+    kinds: Vec<StateDiffKind>,
+    keys: Vec<B256>,
+    fields: Vec<StateDiffField>,
+}
+
+impl StateDiffs {
+    pub fn new(
+        blocks: Vec<BlockId>,
+        addresses: Vec<NameOrAddress>,
+        kinds: Vec<StateDiffKind>,
+        keys: Vec<B256>,
+        fields: Vec<StateDiffField>,
+    ) -> Self {
+        Self {
+            blocks,
+            addresses,
+            kinds,
+            keys,
+            fields,
+        }
+    }
+
+    pub fn block_ids(&self) -> Vec<&BlockId> {
+        self.blocks.iter().collect()
+    }
+
+    /// The accounts to keep. Empty keeps every change.
+    pub fn addresses(&self) -> &[NameOrAddress] {
+        &self.addresses
+    }
+
+    /// The kinds of state to keep. Empty keeps every change.
+    pub fn kinds(&self) -> &[StateDiffKind] {
+        &self.kinds
+    }
+
+    /// The storage slots to keep. Empty keeps every change.
+    pub fn keys(&self) -> &[B256] {
+        &self.keys
+    }
+
+    pub fn fields(&self) -> Vec<StateDiffField> {
+        self.fields.clone()
+    }
+}
+
+impl NamesAddresses for StateDiffs {
+    fn name_or_addresses_mut(&mut self) -> Vec<&mut NameOrAddress> {
+        self.addresses.iter_mut().collect()
+    }
+}
+
+/// The piece of an account's state a diff changes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, EnumVariants)]
+#[serde(rename_all = "lowercase")]
+pub enum StateDiffKind {
+    Balance,
+    Nonce,
+    Code,
+    Storage,
+}
+
+impl Display for StateDiffKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateDiffKind::Balance => write!(f, "balance"),
+            StateDiffKind::Nonce => write!(f, "nonce"),
+            StateDiffKind::Code => write!(f, "code"),
+            StateDiffKind::Storage => write!(f, "storage"),
+        }
+    }
+}
+
+impl TryFrom<&str> for StateDiffKind {
+    type Error = StateDiffFieldError;
+
+    fn try_from(value: &str) -> Result<Self, StateDiffFieldError> {
+        match value {
+            "balance" => Ok(StateDiffKind::Balance),
+            "nonce" => Ok(StateDiffKind::Nonce),
+            "code" => Ok(StateDiffKind::Code),
+            "storage" => Ok(StateDiffKind::Storage),
+            invalid_kind => Err(StateDiffFieldError::InvalidKind(invalid_kind.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, EnumVariants)]
+pub enum StateDiffField {
+    Address,
+    /// The storage slot a `storage` diff changes; null for other kinds.
+    Key,
+    Kind,
+    /// The value before the transaction; null when it created the state.
+    Prev,
+    /// The value after the transaction; null when it deleted the state.
+    Next,
+    TransactionHash,
+    BlockNumber,
+    Chain,
+}
+
+impl Display for StateDiffField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateDiffField::Address => write!(f, "address"),
+            StateDiffField::Key => write!(f, "key"),
+            StateDiffField::Kind => write!(f, "kind"),
+            StateDiffField::Prev => write!(f, "prev"),
+            StateDiffField::Next => write!(f, "next"),
+            StateDiffField::TransactionHash => write!(f, "transaction_hash"),
+            StateDiffField::BlockNumber => write!(f, "block_number"),
+            StateDiffField::Chain => write!(f, "chain"),
+        }
+    }
+}
+
+impl StateDiffField {
+    /// The other names `TryFrom<&str>` accepts for this field, besides its
+    /// `Display` name.
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            StateDiffField::Key => &["slot"],
+            _ => &[],
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum StateDiffFieldError {
+    #[error("Invalid field for entity StateDiffs: {0}")]
+    InvalidField(String),
+
+    #[error("Invalid state diff kind {0}; expected balance, nonce, code or storage")]
+    InvalidKind(String),
+}
+
+impl TryFrom<&str> for StateDiffField {
+    type Error = StateDiffFieldError;
+
+    fn try_from(value: &str) -> Result<Self, StateDiffFieldError> {
+        match value {
+            "address" => Ok(StateDiffField::Address),
+            "key" | "slot" => Ok(StateDiffField::Key),
+            "kind" => Ok(StateDiffField::Kind),
+            "prev" => Ok(StateDiffField::Prev),
+            "next" => Ok(StateDiffField::Next),
+            "transaction_hash" => Ok(StateDiffField::TransactionHash),
+            "block_number" => Ok(StateDiffField::BlockNumber),
+            "chain" => Ok(StateDiffField::Chain),
+            invalid_field => Err(StateDiffFieldError::InvalidField(invalid_field.to_string())),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use super::{
+    block::BlockId,
+    ens::{keccak_pair, NameOrAddress, NamesAddresses},
+};
+use alloy::primitives::{b256, B256};
+use eql_macros::EnumVariants;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// `keccak256("eip1967.proxy.implementation") - 1`, where EIP-1967 proxies
+/// keep the address of their logic contract.
+pub const EIP1967_IMPLEMENTATION_SLOT: B256 =
+    b256!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+
+/// `keccak256("eip1967.proxy.admin") - 1`, the proxy's admin address.
+pub const EIP1967_ADMIN_SLOT: B256 =
+    b256!("b53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103");
+
+/// `keccak256("eip1967.proxy.beacon") - 1`, the beacon a beacon proxy reads
+/// its implementation from.
+pub const EIP1967_BEACON_SLOT: B256 =
+    b256!("a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50");
+
+/// Well-known slots that can be written by name in a `slot` filter.
+pub const WELL_KNOWN_SLOTS: [(&str, B256); 3] = [
+    ("eip1967_implementation", EIP1967_IMPLEMENTATION_SLOT),
+    ("eip1967_admin", EIP1967_ADMIN_SLOT),
+    ("eip1967_beacon", EIP1967_BEACON_SLOT),
+];
+
+pub fn well_known_slot(name: &str) -> Option<B256> {
+    WELL_KNOWN_SLOTS
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(name))
+        .map(|(_, slot)| *slot)
+}
+
+/// The slot of `mapping[key]` for a Solidity mapping declared at `base`:
+/// `keccak256(key . base)`, both left-padded to 32 bytes. Nested mappings
+/// fold this, e.g. `allowance[owner][spender]` is
+/// `mapping_slot(mapping_slot(base, owner), spender)`.
+pub fn mapping_slot(base: B256, key: B256) -> B256 {
+    keccak_pair(&key, &base)
+}
+
+/// Raw storage reads: every slot of every address, once per block.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Storage {
+    addresses: Vec<NameOrAddress>,
+    slots: Vec<B256>,
+    blocks: Vec<BlockId>,
+    fields: Vec<StorageField>,
+}
+
+impl Storage {
+    pub fn new(
+        addresses: Vec<NameOrAddress>,
+        slots: Vec<B256>,
+        blocks: Vec<BlockId>,
+        fields: Vec<StorageField>,
+    ) -> Self {
+        Self {
+            addresses,
+            slots,
+            blocks,
+            fields,
+        }
+    }
+
+    pub fn addresses(&self) -> &[NameOrAddress] {
+        &self.addresses
+    }
+
+    pub fn slots(&self) -> &[B256] {
+        &self.slots
+    }
+
+    /// The blocks to read at. Empty means the latest state.
+    pub fn block_ids(&self) -> Vec<&BlockId> {
+        self.blocks.iter().collect()
+    }
+
+    pub fn fields(&self) -> Vec<StorageField> {
+        self.fields.clone()
+    }
+}
+
+impl NamesAddresses for Storage {
+    fn name_or_addresses_mut(&mut self) -> Vec<&mut NameOrAddress> {
+        self.addresses.iter_mut().collect()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, EnumVariants)]
+pub enum StorageField {
+    Address,
+    Slot,
+    Value,
+    BlockNumber,
+    Chain,
+}
+
+impl Display for StorageField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageField::Address => write!(f, "address"),
+            StorageField::Slot => write!(f, "slot"),
+            StorageField::Value => write!(f, "value"),
+            StorageField::BlockNumber => write!(f, "block_number"),
+            StorageField::Chain => write!(f, "chain"),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum StorageFieldError {
+    #[error("Invalid field for entity Storage: {0}")]
+    InvalidField(String),
+}
+
+impl TryFrom<&str> for StorageField {
+    type Error = StorageFieldError;
+
+    fn try_from(value: &str) -> Result<Self, StorageFieldError> {
+        match value {
+            "address" => Ok(StorageField::Address),
+            "slot" => Ok(StorageField::Slot),
+            "value" => Ok(StorageField::Value),
+            "block_number" => Ok(StorageField::BlockNumber),
+            "chain" => Ok(StorageField::Chain),
+            invalid_field => Err(StorageFieldError::InvalidField(invalid_field.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{address, keccak256, U256};
+
+    #[test]
+    fn eip1967_slots_are_the_hashed_names_minus_one() {
+        for (name, slot) in [
+            ("eip1967.proxy.implementation", EIP1967_IMPLEMENTATION_SLOT),
+            ("eip1967.proxy.admin", EIP1967_ADMIN_SLOT),
+            ("eip1967.proxy.beacon", EIP1967_BEACON_SLOT),
+        ] {
+            let expected = U256::from_be_bytes(keccak256(name).0) - U256::from(1);
+            assert_eq!(slot, B256::from(expected), "{name}");
+        }
+        assert_eq!(
+            well_known_slot("EIP1967_Implementation"),
+            Some(EIP1967_IMPLEMENTATION_SLOT)
+        );
+        assert_eq!(well_known_slot("eip1967_owner"), None);
+    }
+
+    #[test]
+    fn mapping_slot_matches_solidity() {
+        // `balanceOf[0xd8dA...6045]` for a mapping at slot 0, as
+        // `keccak256(abi.encode(holder, uint256(0)))` computes it.
+        let holder = address!("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+        let key = B256::left_padding_from(holder.as_slice());
+        let mut preimage = [0u8; 64];
+        preimage[..32].copy_from_slice(key.as_slice());
+        assert_eq!(mapping_slot(B256::ZERO, key), keccak256(preimage));
+
+        // Nesting folds left to right.
+        let spender = B256::with_last_byte(1);
+        assert_eq!(
+            mapping_slot(mapping_slot(B256::ZERO, key), spender),
+            keccak_pair(&spender, &keccak_pair(&key, &B256::ZERO))
+        );
+    }
+}
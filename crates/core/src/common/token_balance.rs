@@ -0,0 +1,154 @@
+use super::{
+    block::BlockId,
+    ens::{NameOrAddress, NamesAddresses},
+};
+use alloy::primitives::U256;
+use eql_macros::EnumVariants;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// ERC-20/721 reads for every holder × token (× spender × token id), once
+/// per block, through Multicall3.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TokenBalances {
+    holders: Vec<NameOrAddress>,
+    tokens: Vec<NameOrAddress>,
+    spenders: Vec<NameOrAddress>,
+    token_ids: Vec<U256>,
+    blocks: Vec<BlockId>,
+    fields: Vec<TokenBalanceField>,
+}
+
+impl TokenBalances {
+    pub fn new(
+        holders: Vec<NameOrAddress>,
+        tokens: Vec<NameOrAddress>,
+        spenders: Vec<NameOrAddress>,
+        token_ids: Vec<U256>,
+        blocks: Vec<BlockId>,
+        fields: Vec<TokenBalanceField>,
+    ) -> Self {
+        Self {
+            holders,
+            tokens,
+            spenders,
+            token_ids,
+            blocks,
+            fields,
+        }
+    }
+
+    pub fn holders(&self) -> &[NameOrAddress] {
+        &self.holders
+    }
+
+    pub fn tokens(&self) -> &[NameOrAddress] {
+        &self.tokens
+    }
+
+    /// `allowance(holder, spender)` is read for each of these.
+    pub fn spenders(&self) -> &[NameOrAddress] {
+        &self.spenders
+    }
+
+    /// `ownerOf(token_id)` is read for each of these.
+    pub fn token_ids(&self) -> &[U256] {
+        &self.token_ids
+    }
+
+    /// The blocks to read at. Empty means the latest state.
+    pub fn block_ids(&self) -> Vec<&BlockId> {
+        self.blocks.iter().collect()
+    }
+
+    pub fn fields(&self) -> Vec<TokenBalanceField> {
+        self.fields.clone()
+    }
+}
+
+impl NamesAddresses for TokenBalances {
+    fn name_or_addresses_mut(&mut self) -> Vec<&mut NameOrAddress> {
+        self.holders
+            .iter_mut()
+            .chain(&mut self.tokens)
+            .chain(&mut self.spenders)
+            .collect()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, EnumVariants)]
+pub enum TokenBalanceField {
+    Holder,
+    Token,
+    Balance,
+    Decimals,
+    ScaledBalance,
+    Spender,
+    Allowance,
+    ScaledAllowance,
+    TokenId,
+    Owner,
+    BlockNumber,
+    Chain,
+}
+
+impl TokenBalanceField {
+    /// Whether the field needs the token's `decimals()`.
+    pub fn needs_decimals(&self) -> bool {
+        matches!(
+            self,
+            TokenBalanceField::Decimals
+                | TokenBalanceField::ScaledBalance
+                | TokenBalanceField::ScaledAllowance
+        )
+    }
+}
+
+impl Display for TokenBalanceField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenBalanceField::Holder => write!(f, "holder"),
+            TokenBalanceField::Token => write!(f, "token"),
+            TokenBalanceField::Balance => write!(f, "balance"),
+            TokenBalanceField::Decimals => write!(f, "decimals"),
+            TokenBalanceField::ScaledBalance => write!(f, "scaled_balance"),
+            TokenBalanceField::Spender => write!(f, "spender"),
+            TokenBalanceField::Allowance => write!(f, "allowance"),
+            TokenBalanceField::ScaledAllowance => write!(f, "scaled_allowance"),
+            TokenBalanceField::TokenId => write!(f, "token_id"),
+            TokenBalanceField::Owner => write!(f, "owner"),
+            TokenBalanceField::BlockNumber => write!(f, "block_number"),
+            TokenBalanceField::Chain => write!(f, "chain"),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TokenBalanceFieldError {
+    #[error("Invalid field for entity TokenBalances: {0}")]
+    InvalidField(String),
+}
+
+impl TryFrom<&str> for TokenBalanceField {
+    type Error = TokenBalanceFieldError;
+
+    fn try_from(value: &str) -> Result<Self, TokenBalanceFieldError> {
+        match value {
+            "holder" => Ok(TokenBalanceField::Holder),
+            "token" => Ok(TokenBalanceField::Token),
+            "balance" => Ok(TokenBalanceField::Balance),
+            "decimals" => Ok(TokenBalanceField::Decimals),
+            "scaled_balance" => Ok(TokenBalanceField::ScaledBalance),
+            "spender" => Ok(TokenBalanceField::Spender),
+            "allowance" => Ok(TokenBalanceField::Allowance),
+            "scaled_allowance" => Ok(TokenBalanceField::ScaledAllowance),
+            "token_id" => Ok(TokenBalanceField::TokenId),
+            "owner" => Ok(TokenBalanceField::Owner),
+            "block_number" => Ok(TokenBalanceField::BlockNumber),
+            "chain" => Ok(TokenBalanceField::Chain),
+            invalid_field => Err(TokenBalanceFieldError::InvalidField(
+                invalid_field.to_string(),
+            )),
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use super::{
     block::{BlockId, BlockRange},
+    chain::{Chain, ChainFamily},
+    ens::{NameOrAddress, NamesAddresses},
     entity_id::{parse_block_number_or_tag, EntityIdError},
     filters::{
         ComparisonFilterError, EqualityFilter, EqualityFilterError, Filter, FilterError, FilterType,
@@ -16,7 +18,7 @@ use pest::iterators::{Pair, Pairs};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Transaction {
     ids: Option<Vec<B256>>,
     filters: Option<Vec<TransactionFilter>>,
@@ -65,10 +67,8 @@ impl Transaction {
             filters.iter().all(|filter| match filter {
                 TransactionFilter::Type(t) => tx.r#type.as_ref().is_some_and(|v| t.compare(v)),
                 TransactionFilter::Hash(h) => tx.hash.as_ref().is_some_and(|v| h.compare(v)),
-                TransactionFilter::From(f) => {
-                    tx.from_address.as_ref().is_some_and(|v| f.compare(v))
-                }
-                TransactionFilter::To(t) => tx.to_address.as_ref().is_some_and(|v| t.compare(v)),
+                TransactionFilter::From(f) => tx.from_address.is_some_and(|v| f.compare(&v.into())),
+                TransactionFilter::To(t) => tx.to_address.is_some_and(|v| t.compare(&v.into())),
                 TransactionFilter::Data(d) => tx.data.as_ref().is_some_and(|v| d.compare(v)),
                 TransactionFilter::Value(v) => tx.value.as_ref().is_some_and(|n| v.compare(n)),
                 TransactionFilter::GasPrice(gp) => {
@@ -85,6 +85,18 @@ impl Transaction {
                     tx.chain_id.as_ref().is_some_and(|v| cid.compare(v))
                 }
                 TransactionFilter::Status(s) => tx.status.as_ref().is_some_and(|v| s.compare(v)),
+                TransactionFilter::GasUsed(g) => tx.gas_used.as_ref().is_some_and(|v| g.compare(v)),
+                TransactionFilter::CumulativeGasUsed(g) => tx
+                    .cumulative_gas_used
+                    .as_ref()
+                    .is_some_and(|v| g.compare(v)),
+                TransactionFilter::ContractAddress(a) => {
+                    tx.contract_address.is_some_and(|v| a.compare(&v.into()))
+                }
+                TransactionFilter::LogsCount(l) => {
+                    tx.logs_count.as_ref().is_some_and(|v| l.compare(v))
+                }
+                TransactionFilter::Fee(f) => tx.fee.as_ref().is_some_and(|v| f.compare(v)),
                 TransactionFilter::V(v) => tx.v.as_ref().is_some_and(|n| v.compare(n)),
                 TransactionFilter::R(r) => tx.r.as_ref().is_some_and(|v| r.compare(v)),
                 TransactionFilter::S(s) => tx.s.as_ref().is_some_and(|v| s.compare(v)),
@@ -92,6 +104,15 @@ impl Transaction {
                     .max_fee_per_blob_gas
                     .as_ref()
                     .is_some_and(|v| mfbg.compare(v)),
+                TransactionFilter::BlobCount(b) => {
+                    tx.blob_count.as_ref().is_some_and(|v| b.compare(v))
+                }
+                TransactionFilter::BlobGasUsed(b) => {
+                    tx.blob_gas_used.as_ref().is_some_and(|v| b.compare(v))
+                }
+                TransactionFilter::BlobGasPrice(b) => {
+                    tx.blob_gas_price.as_ref().is_some_and(|v| b.compare(v))
+                }
                 TransactionFilter::MaxFeePerGas(mfg) => {
                     tx.max_fee_per_gas.as_ref().is_some_and(|v| mfg.compare(v))
                 }
@@ -120,6 +141,21 @@ impl Transaction {
     }
 }
 
+impl NamesAddresses for Transaction {
+    fn name_or_addresses_mut(&mut self) -> Vec<&mut NameOrAddress> {
+        self.filters
+            .iter_mut()
+            .flatten()
+            .filter_map(|filter| match filter {
+                TransactionFilter::From(f)
+                | TransactionFilter::To(f)
+                | TransactionFilter::ContractAddress(f) => Some(f.value_mut()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum TransactionError {
     #[error("Unexpected token {0} for transaction")]
@@ -166,7 +202,7 @@ impl TryFrom<Pairs<'_, Rule>> for Transaction {
 
                     if let Some(pair) = inner_pairs.peek() {
                         if pair.as_rule() == Rule::wildcard {
-                            fields = TransactionField::all_variants().to_vec();
+                            fields = TransactionField::wildcard();
                             continue;
                         }
                     }
@@ -201,16 +237,39 @@ pub enum TransactionField {
     GasLimit,
     EffectiveGasPrice,
     Status,
+    GasUsed,
+    CumulativeGasUsed,
+    ContractAddress,
+    LogsCount,
+    Fee,
     ChainId,
     V,
     R,
     S,
     MaxFeePerBlobGas,
+    BlobVersionedHashes,
+    BlobCount,
+    BlobGasUsed,
+    BlobGasPrice,
     MaxFeePerGas,
     MaxPriorityFeePerGas,
+    AccessList,
     YParity,
     Chain,
     AuthorizationList,
+    L1Fee,
+    L1GasUsed,
+    L1GasPrice,
+    L1FeeScalar,
+    SourceHash,
+    Mint,
+    IsSystemTx,
+    GasUsedForL1,
+    /// The sender's verified primary ENS name. Only read when selected by
+    /// name: `*` leaves it out.
+    FromEns,
+    /// The recipient's verified primary ENS name, likewise.
+    ToEns,
 }
 
 impl std::fmt::Display for TransactionField {
@@ -227,18 +286,104 @@ impl std::fmt::Display for TransactionField {
             TransactionField::GasLimit => write!(f, "gas_limit"),
             TransactionField::EffectiveGasPrice => write!(f, "effective_gas_price"),
             TransactionField::Status => write!(f, "status"),
+            TransactionField::GasUsed => write!(f, "gas_used"),
+            TransactionField::CumulativeGasUsed => write!(f, "cumulative_gas_used"),
+            TransactionField::ContractAddress => write!(f, "contract_address"),
+            TransactionField::LogsCount => write!(f, "logs_count"),
+            TransactionField::Fee => write!(f, "fee"),
             TransactionField::ChainId => write!(f, "chain_id"),
             TransactionField::V => write!(f, "v"),
             TransactionField::R => write!(f, "r"),
             TransactionField::S => write!(f, "s"),
             TransactionField::MaxFeePerBlobGas => write!(f, "max_fee_per_blob_gas"),
+            TransactionField::BlobVersionedHashes => write!(f, "blob_versioned_hashes"),
+            TransactionField::BlobCount => write!(f, "blob_count"),
+            TransactionField::BlobGasUsed => write!(f, "blob_gas_used"),
+            TransactionField::BlobGasPrice => write!(f, "blob_gas_price"),
             TransactionField::MaxFeePerGas => write!(f, "max_fee_per_gas"),
             TransactionField::MaxPriorityFeePerGas => write!(f, "max_priority_fee_per_gas"),
+            TransactionField::AccessList => write!(f, "access_list"),
             TransactionField::YParity => write!(f, "y_parity"),
             TransactionField::Chain => write!(f, "chain"),
             TransactionField::AuthorizationList => write!(f, "authorization_list"),
+            TransactionField::L1Fee => write!(f, "l1_fee"),
+            TransactionField::L1GasUsed => write!(f, "l1_gas_used"),
+            TransactionField::L1GasPrice => write!(f, "l1_gas_price"),
+            TransactionField::L1FeeScalar => write!(f, "l1_fee_scalar"),
+            TransactionField::SourceHash => write!(f, "source_hash"),
+            TransactionField::Mint => write!(f, "mint"),
+            TransactionField::IsSystemTx => write!(f, "is_system_tx"),
+            TransactionField::GasUsedForL1 => write!(f, "gas_used_for_l1"),
+            TransactionField::FromEns => write!(f, "from_ens"),
+            TransactionField::ToEns => write!(f, "to_ens"),
+        }
+    }
+}
+
+impl TransactionField {
+    /// The fields `*` selects: every one but the ENS names, which cost
+    /// Ethereum reads of their own.
+    pub fn wildcard() -> Vec<TransactionField> {
+        Self::all_variants()
+            .iter()
+            .filter(|field| !field.is_ens_name())
+            .copied()
+            .collect()
+    }
+
+    /// Whether the field is an address's primary ENS name, looked up once
+    /// the rows are fetched.
+    pub fn is_ens_name(&self) -> bool {
+        matches!(self, TransactionField::FromEns | TransactionField::ToEns)
+    }
+
+    /// The other names `TryFrom<&str>` accepts for this field, besides its
+    /// `Display` name.
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            TransactionField::From => &["from"],
+            TransactionField::To => &["to"],
+            _ => &[],
         }
     }
+
+    /// Whether the field comes from the transaction's receipt rather than
+    /// the transaction itself. `fee` is derived from receipt fields too.
+    pub fn needs_receipt(&self) -> bool {
+        matches!(
+            self,
+            TransactionField::Status
+                | TransactionField::GasUsed
+                | TransactionField::CumulativeGasUsed
+                | TransactionField::ContractAddress
+                | TransactionField::LogsCount
+                | TransactionField::Fee
+                | TransactionField::BlobGasUsed
+                | TransactionField::BlobGasPrice
+                | TransactionField::L1Fee
+                | TransactionField::L1GasUsed
+                | TransactionField::L1GasPrice
+                | TransactionField::L1FeeScalar
+                | TransactionField::GasUsedForL1
+        )
+    }
+
+    /// Whether `chain` fills in this field. The L2 columns only apply to
+    /// their rollup stack's chains, and are always NULL elsewhere.
+    pub fn applies_to(&self, chain: &Chain) -> bool {
+        let family = match self {
+            TransactionField::L1Fee
+            | TransactionField::L1GasUsed
+            | TransactionField::L1GasPrice
+            | TransactionField::L1FeeScalar
+            | TransactionField::SourceHash
+            | TransactionField::Mint
+            | TransactionField::IsSystemTx => ChainFamily::OpStack,
+            TransactionField::GasUsedForL1 => ChainFamily::Arbitrum,
+            _ => return true,
+        };
+        chain.family() == family
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -264,16 +409,36 @@ impl TryFrom<&str> for TransactionField {
             "gas_limit" => Ok(TransactionField::GasLimit),
             "effective_gas_price" => Ok(TransactionField::EffectiveGasPrice),
             "status" => Ok(TransactionField::Status),
+            "gas_used" => Ok(TransactionField::GasUsed),
+            "cumulative_gas_used" => Ok(TransactionField::CumulativeGasUsed),
+            "contract_address" => Ok(TransactionField::ContractAddress),
+            "logs_count" => Ok(TransactionField::LogsCount),
+            "fee" => Ok(TransactionField::Fee),
             "chain_id" => Ok(TransactionField::ChainId),
             "v" => Ok(TransactionField::V),
             "r" => Ok(TransactionField::R),
             "s" => Ok(TransactionField::S),
             "max_fee_per_blob_gas" => Ok(TransactionField::MaxFeePerBlobGas),
+            "blob_versioned_hashes" => Ok(TransactionField::BlobVersionedHashes),
+            "blob_count" => Ok(TransactionField::BlobCount),
+            "blob_gas_used" => Ok(TransactionField::BlobGasUsed),
+            "blob_gas_price" => Ok(TransactionField::BlobGasPrice),
             "max_fee_per_gas" => Ok(TransactionField::MaxFeePerGas),
             "max_priority_fee_per_gas" => Ok(TransactionField::MaxPriorityFeePerGas),
+            "access_list" => Ok(TransactionField::AccessList),
             "y_parity" => Ok(TransactionField::YParity),
             "chain" => Ok(TransactionField::Chain),
             "authorization_list" => Ok(TransactionField::AuthorizationList),
+            "l1_fee" => Ok(TransactionField::L1Fee),
+            "l1_gas_used" => Ok(TransactionField::L1GasUsed),
+            "l1_gas_price" => Ok(TransactionField::L1GasPrice),
+            "l1_fee_scalar" => Ok(TransactionField::L1FeeScalar),
+            "source_hash" => Ok(TransactionField::SourceHash),
+            "mint" => Ok(TransactionField::Mint),
+            "is_system_tx" => Ok(TransactionField::IsSystemTx),
+            "gas_used_for_l1" => Ok(TransactionField::GasUsedForL1),
+            "from_ens" => Ok(TransactionField::FromEns),
+            "to_ens" => Ok(TransactionField::ToEns),
             invalid_field => Err(TransactionFieldError::InvalidTransactionField(
                 invalid_field.to_string(),
             )),
@@ -299,12 +464,12 @@ pub enum TransactionFilterError {
     FilterError(#[from] FilterError),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TransactionFilter {
     Type(EqualityFilter<u8>),
     Hash(EqualityFilter<B256>),
-    From(EqualityFilter<Address>),
-    To(EqualityFilter<Address>),
+    From(EqualityFilter<NameOrAddress>),
+    To(EqualityFilter<NameOrAddress>),
     Data(EqualityFilter<alloy::primitives::Bytes>),
     Value(FilterType<U256>),
     GasPrice(FilterType<u128>),
@@ -313,10 +478,18 @@ pub enum TransactionFilter {
     ChainId(EqualityFilter<u64>),
     BlockId(BlockId),
     Status(EqualityFilter<bool>),
+    GasUsed(FilterType<u64>),
+    CumulativeGasUsed(FilterType<u64>),
+    ContractAddress(EqualityFilter<NameOrAddress>),
+    LogsCount(FilterType<u64>),
+    Fee(FilterType<U256>),
     V(EqualityFilter<bool>),
     R(EqualityFilter<U256>),
     S(EqualityFilter<U256>),
     MaxFeePerBlobGas(FilterType<u128>),
+    BlobCount(FilterType<u64>),
+    BlobGasUsed(FilterType<u64>),
+    BlobGasPrice(FilterType<u128>),
     MaxFeePerGas(FilterType<u128>),
     MaxPriorityFeePerGas(FilterType<u128>),
     YParity(EqualityFilter<bool>),
@@ -446,12 +619,12 @@ impl TransactionFilter {
             }
             Rule::from_filter_type => Self::parse_equality_filter(
                 pair,
-                |s| Address::from_str(s).unwrap(),
+                |s| Address::from_str(s).unwrap().into(),
                 TransactionFilter::From,
             ),
             Rule::to_filter_type => Self::parse_equality_filter(
                 pair,
-                |s| Address::from_str(s).unwrap(),
+                |s| Address::from_str(s).unwrap().into(),
                 TransactionFilter::To,
             ),
             Rule::data_filter_type => {
@@ -536,7 +709,7 @@ mod tests {
         let transaction = Transaction::new(
             None,
             Some(vec![TransactionFilter::To(EqualityFilter::Eq(
-                Address::ZERO,
+                Address::ZERO.into(),
             ))]),
             vec![TransactionField::AuthorizationList],
         );
@@ -575,4 +748,20 @@ mod tests {
             TransactionField::To
         );
     }
+
+    #[test]
+    fn wildcard_leaves_out_the_ens_names() {
+        let fields = TransactionField::wildcard();
+        assert_eq!(fields.len(), TransactionField::all_variants().len() - 2);
+        assert!(!fields.iter().any(TransactionField::is_ens_name));
+    }
+
+    #[test]
+    fn every_alias_resolves_to_its_field() {
+        for field in TransactionField::all_variants() {
+            for alias in field.aliases() {
+                assert_eq!(TransactionField::try_from(*alias).unwrap(), *field);
+            }
+        }
+    }
 }
@@ -3,7 +3,7 @@ use super::{
     dump::{Dump, DumpError},
     entity::{Entity, EntityError},
 };
-use crate::interpreter::frontend::parser::Rule;
+use crate::interpreter::frontend::{parser::Rule, sql::schema::EntityKind};
 use alloy::transports::http::reqwest::Url;
 use pest::iterators::Pairs;
 
@@ -11,6 +11,34 @@ use pest::iterators::Pairs;
 pub enum Expression {
     Get(GetExpression),
     Set(SetRpcExpression),
+    Catalog(CatalogExpression),
+    Explain(ExplainExpression),
+}
+
+/// `EXPLAIN [ANALYZE] <query>`: the per-chain fetch plan for `get`. Plain
+/// `EXPLAIN` never fetches rows; `ANALYZE` also runs the query and reports
+/// what it cost.
+#[derive(Debug, PartialEq)]
+pub struct ExplainExpression {
+    pub get: GetExpression,
+    pub analyze: bool,
+}
+
+/// A metadata statement — `SHOW TABLES`, `DESCRIBE <entity>`, `SHOW COLUMNS`
+/// or a `SELECT * FROM information_schema.<tables|columns>`. It is answered
+/// from the field enums alone: no chain, no fetch.
+#[derive(Debug, PartialEq)]
+pub struct CatalogExpression {
+    pub target: CatalogTarget,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CatalogTarget {
+    Tables,
+    /// The columns of these entities, in `EntityKind::ALL` order when every
+    /// entity is listed.
+    Columns(Vec<EntityKind>),
 }
 
 /// A session-scoped RPC override produced by `SET rpc_<chain> = '<url>'`.
@@ -0,0 +1,108 @@
+use super::{
+    block::BlockId,
+    ens::{NameOrAddress, NamesAddresses},
+};
+use eql_macros::EnumVariants;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Beacon-chain withdrawals (EIP-4895) credited in every block, optionally
+/// narrowed to some recipients and validators.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Withdrawals {
+    blocks: Vec<BlockId>,
+    addresses: Vec<NameOrAddress>,
+    validator_indices: Vec<u64>,
+    fields: Vec<WithdrawalField>,
+}
+
+impl Withdrawals {
+    pub fn new(
+        blocks: Vec<BlockId>,
+        addresses: Vec<NameOrAddress>,
+        validator_indices: Vec<u64>,
+        fields: Vec<WithdrawalField>,
+    ) -> Self {
+        Self {
+            blocks,
+            addresses,
+            validator_indices,
+            fields,
+        }
+    }
+
+    pub fn block_ids(&self) -> Vec<&BlockId> {
+        self.blocks.iter().collect()
+    }
+
+    /// The recipients to keep. Empty keeps every withdrawal.
+    pub fn addresses(&self) -> &[NameOrAddress] {
+        &self.addresses
+    }
+
+    /// The validators to keep. Empty keeps every withdrawal.
+    pub fn validator_indices(&self) -> &[u64] {
+        &self.validator_indices
+    }
+
+    pub fn fields(&self) -> Vec<WithdrawalField> {
+        self.fields.clone()
+    }
+}
+
+impl NamesAddresses for Withdrawals {
+    fn name_or_addresses_mut(&mut self) -> Vec<&mut NameOrAddress> {
+        self.addresses.iter_mut().collect()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, EnumVariants)]
+pub enum WithdrawalField {
+    BlockNumber,
+    Index,
+    ValidatorIndex,
+    Address,
+    AmountGwei,
+    /// The amount in wei.
+    Amount,
+    Chain,
+}
+
+impl Display for WithdrawalField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WithdrawalField::BlockNumber => write!(f, "block_number"),
+            WithdrawalField::Index => write!(f, "index"),
+            WithdrawalField::ValidatorIndex => write!(f, "validator_index"),
+            WithdrawalField::Address => write!(f, "address"),
+            WithdrawalField::AmountGwei => write!(f, "amount_gwei"),
+            WithdrawalField::Amount => write!(f, "amount"),
+            WithdrawalField::Chain => write!(f, "chain"),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum WithdrawalFieldError {
+    #[error("Invalid field for entity Withdrawals: {0}")]
+    InvalidField(String),
+}
+
+impl TryFrom<&str> for WithdrawalField {
+    type Error = WithdrawalFieldError;
+
+    fn try_from(value: &str) -> Result<Self, WithdrawalFieldError> {
+        match value {
+            "block_number" => Ok(WithdrawalField::BlockNumber),
+            "index" => Ok(WithdrawalField::Index),
+            "validator_index" => Ok(WithdrawalField::ValidatorIndex),
+            "address" => Ok(WithdrawalField::Address),
+            "amount_gwei" => Ok(WithdrawalField::AmountGwei),
+            "amount" => Ok(WithdrawalField::Amount),
+            "chain" => Ok(WithdrawalField::Chain),
+            invalid_field => Err(WithdrawalFieldError::InvalidField(
+                invalid_field.to_string(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! EIP-3668 (CCIP-read): a contract that keeps its data offchain reverts
+//! with `OffchainLookup`, naming the gateways to fetch it from and a
+//! callback that checks what was fetched. The ENS resolvers of names like
+//! `*.cb.id` and of DNS names work this way.
+
+use super::{
+    multicall::{aggregate_results, CallResult},
+    resolve_portal::value_to_bytes,
+};
+use crate::common::{chain::ChainOrRpc, ens::OffchainLookup};
+use alloy::{
+    primitives::{Address, Bytes},
+    providers::RootProvider,
+    sol_types::{SolError, SolValue},
+    transports::http::{Client, Http},
+};
+use anyhow::Result;
+use futures::future::join_all;
+use serde_json::{json, Value};
+use std::{sync::OnceLock, time::Duration};
+
+/// How many lookups in a row a call may ask for: a callback may revert with
+/// another `OffchainLookup`.
+const MAX_LOOKUPS: usize = 4;
+
+const GATEWAY_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, thiserror::Error)]
+pub enum CcipError {
+    #[error("CCIP-read gateway {url} answered {status}")]
+    Rejected { url: String, status: u16 },
+    #[error("CCIP-read gateway {0} didn't answer with hex data")]
+    BadResponse(String),
+    #[error("No CCIP-read gateway answered ({0})")]
+    Unreachable(String),
+}
+
+fn gateway_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(GATEWAY_TIMEOUT)
+            .build()
+            .expect("failed to build the CCIP-read HTTP client")
+    })
+}
+
+/// Runs `calls` like `aggregate`, following the offchain lookups of those
+/// that revert with `OffchainLookup`: their gateways are asked for the data,
+/// then the callbacks run with it, in one more `aggregate3` per round of
+/// lookups. A call whose lookup fails, whose lookup names another contract
+/// as its sender, or that still asks for more after `MAX_LOOKUPS` is `None`,
+/// like any other failed call.
+pub(crate) async fn aggregate_offchain(
+    provider: &RootProvider<Http<Client>>,
+    chain: &ChainOrRpc,
+    calls: &[(Address, Bytes)],
+    block: Option<u64>,
+) -> Result<Vec<Option<Bytes>>> {
+    let mut outputs = vec![None; calls.len()];
+    let mut pending: Vec<(usize, (Address, Bytes))> = calls.iter().cloned().enumerate().collect();
+
+    for _ in 0..=MAX_LOOKUPS {
+        if pending.is_empty() {
+            break;
+        }
+        let round: Vec<_> = pending.iter().map(|(_, call)| call.clone()).collect();
+        let results = aggregate_results(provider, chain, &round, block).await?;
+
+        let mut lookups = Vec::new();
+        for ((index, (target, _)), result) in pending.into_iter().zip(results) {
+            match offchain_lookup(result, target) {
+                Ok(output) => outputs[index] = Some(output).filter(|output| !output.is_empty()),
+                Err(Some(lookup)) => lookups.push((index, lookup)),
+                Err(None) => {}
+            }
+        }
+        let responses = join_all(
+            lookups
+                .iter()
+                .map(|(_, lookup)| gateway_fetch(&lookup.urls, lookup.sender, &lookup.callData)),
+        )
+        .await;
+        pending = lookups
+            .into_iter()
+            .zip(responses)
+            .filter_map(|((index, lookup), response)| {
+                Some((index, callback_call(&lookup, response.ok()?)))
+            })
+            .collect();
+    }
+
+    Ok(outputs)
+}
+
+/// A call's output, or the lookup it asked for, if any, when it reverted.
+/// A lookup only counts from the contract called: EIP-3668 has clients
+/// ignore one whose sender is another contract.
+fn offchain_lookup(
+    result: CallResult,
+    target: Address,
+) -> std::result::Result<Bytes, Option<OffchainLookup>> {
+    result.map_err(|revert| {
+        OffchainLookup::abi_decode(&revert, true)
+            .ok()
+            .filter(|lookup| lookup.sender == target)
+    })
+}
+
+/// The callback `lookup` asked for, with the gateway's `response`.
+fn callback_call(lookup: &OffchainLookup, response: Bytes) -> (Address, Bytes) {
+    let mut data = lookup.callbackFunction.to_vec();
+    data.extend((response, lookup.extraData.clone()).abi_encode_params());
+    (lookup.sender, data.into())
+}
+
+/// Asks `urls` in turn for the answer to `call_data`, as EIP-3668 says: a
+/// URL with a `{data}` template is a GET, any other a POST of the sender and
+/// data as JSON. A 4xx answer is final; a 5xx or no answer moves on to the
+/// next URL.
+pub(crate) async fn gateway_fetch(
+    urls: &[String],
+    sender: Address,
+    call_data: &Bytes,
+) -> Result<Bytes> {
+    let sender = format!("{sender:#x}");
+    let data = call_data.to_string();
+    let mut failures = Vec::new();
+
+    for url in urls {
+        let target = url.replace("{sender}", &sender);
+        let request = if url.contains("{data}") {
+            gateway_client().get(target.replace("{data}", &data))
+        } else {
+            gateway_client()
+                .post(target)
+                .json(&json!({ "sender": sender, "data": data }))
+        };
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(err) => {
+                failures.push(format!("{url}: {err}"));
+                continue;
+            }
+        };
+        let status = response.status();
+        if status.is_client_error() {
+            return Err(CcipError::Rejected {
+                url: url.clone(),
+                status: status.as_u16(),
+            }
+            .into());
+        }
+        if !status.is_success() {
+            failures.push(format!("{url}: {status}"));
+            continue;
+        }
+        let body: Value = response.json().await?;
+        return body
+            .get("data")
+            .and_then(value_to_bytes)
+            .ok_or_else(|| CcipError::BadResponse(url.clone()).into());
+    }
+
+    Err(CcipError::Unreachable(failures.join(", ")).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{address, bytes, FixedBytes};
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread::{self, JoinHandle},
+    };
+
+    const RESOLVER: Address = address!("4976fb03c32e5b8cfe2b6ccb31c09ba78ebaba41");
+
+    /// A gateway answering each request with the next of `responses`
+    /// (status, body), recording each request's line and body.
+    fn spawn_gateway(
+        responses: Vec<(u16, &'static str)>,
+    ) -> (String, Arc<Mutex<Vec<(String, String)>>>, JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind gateway stub");
+        let address = listener.local_addr().expect("read gateway stub address");
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let captured = Arc::clone(&requests);
+
+        let handle = thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().expect("accept gateway request");
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut request_body = vec![0; content_length];
+                reader.read_exact(&mut request_body).unwrap();
+                captured.lock().unwrap().push((
+                    request_line.trim_end().to_string(),
+                    String::from_utf8(request_body).unwrap(),
+                ));
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len(),
+                )
+                .unwrap();
+            }
+        });
+
+        (format!("http://{address}"), requests, handle)
+    }
+
+    fn lookup(sender: Address) -> OffchainLookup {
+        OffchainLookup {
+            sender,
+            urls: vec!["https://gateway.example/{sender}/{data}.json".to_string()],
+            callData: bytes!("1234"),
+            callbackFunction: FixedBytes([0xf4, 0xd4, 0xd2, 0xf8]),
+            extraData: bytes!("abcd"),
+        }
+    }
+
+    #[tokio::test]
+    async fn gets_templated_urls() {
+        let (base, requests, handle) = spawn_gateway(vec![(200, r#"{"data":"0xbeef"}"#)]);
+        let urls = vec![format!("{base}/{{sender}}/{{data}}.json")];
+
+        let response = gateway_fetch(&urls, RESOLVER, &bytes!("1234"))
+            .await
+            .unwrap();
+        handle.join().unwrap();
+
+        assert_eq!(response, bytes!("beef"));
+        assert_eq!(
+            requests.lock().unwrap()[0].0,
+            format!("GET /{RESOLVER:#x}/0x1234.json HTTP/1.1")
+        );
+    }
+
+    #[tokio::test]
+    async fn posts_to_urls_without_a_data_template() {
+        let (base, requests, handle) = spawn_gateway(vec![(200, r#"{"data":"0xbeef"}"#)]);
+        let urls = vec![format!("{base}/lookup")];
+
+        gateway_fetch(&urls, RESOLVER, &bytes!("1234"))
+            .await
+            .unwrap();
+        handle.join().unwrap();
+
+        let (line, body) = &requests.lock().unwrap()[0];
+        assert_eq!(line, "POST /lookup HTTP/1.1");
+        let body: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(
+            body,
+            json!({ "sender": format!("{RESOLVER:#x}"), "data": "0x1234" })
+        );
+    }
+
+    #[tokio::test]
+    async fn server_errors_fall_through_to_the_next_gateway() {
+        let (down, _, down_handle) = spawn_gateway(vec![(503, "{}")]);
+        let (up, _, up_handle) = spawn_gateway(vec![(200, r#"{"data":"0xbeef"}"#)]);
+        let urls = vec![format!("{down}/{{data}}"), format!("{up}/{{data}}")];
+
+        let response = gateway_fetch(&urls, RESOLVER, &bytes!("1234"))
+            .await
+            .unwrap();
+        down_handle.join().unwrap();
+        up_handle.join().unwrap();
+
+        assert_eq!(response, bytes!("beef"));
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_final() {
+        let (base, requests, handle) = spawn_gateway(vec![(404, "{}")]);
+        let urls = vec![
+            format!("{base}/{{data}}"),
+            "http://127.0.0.1:9/never-asked".to_string(),
+        ];
+
+        let err = gateway_fetch(&urls, RESOLVER, &bytes!("1234"))
+            .await
+            .unwrap_err();
+        handle.join().unwrap();
+
+        assert!(
+            matches!(
+                err.downcast_ref::<CcipError>(),
+                Some(CcipError::Rejected { status: 404, .. })
+            ),
+            "{err}"
+        );
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn answers_without_hex_data_are_an_error() {
+        let (base, _, handle) = spawn_gateway(vec![(200, r#"{"result":"0xbeef"}"#)]);
+        let urls = vec![format!("{base}/{{data}}")];
+
+        let err = gateway_fetch(&urls, RESOLVER, &bytes!("1234"))
+            .await
+            .unwrap_err();
+        handle.join().unwrap();
+
+        assert!(matches!(
+            err.downcast_ref::<CcipError>(),
+            Some(CcipError::BadResponse(_))
+        ));
+    }
+
+    #[test]
+    fn lookups_only_count_from_the_contract_called() {
+        let revert: Bytes = lookup(RESOLVER).abi_encode().into();
+        assert_eq!(
+            offchain_lookup(Err(revert.clone()), RESOLVER)
+                .unwrap_err()
+                .map(|lookup| lookup.callData),
+            Some(bytes!("1234"))
+        );
+        assert!(offchain_lookup(Err(revert), Address::ZERO)
+            .unwrap_err()
+            .is_none());
+        assert!(offchain_lookup(Err(bytes!("08c379a0")), RESOLVER)
+            .unwrap_err()
+            .is_none());
+        assert_eq!(
+            offchain_lookup(Ok(bytes!("beef")), RESOLVER).ok(),
+            Some(bytes!("beef"))
+        );
+    }
+
+    #[test]
+    fn callbacks_get_the_response_and_extra_data() {
+        let (target, data) = callback_call(&lookup(RESOLVER), bytes!("beef"));
+        assert_eq!(target, RESOLVER);
+        assert_eq!(data[..4], [0xf4, 0xd4, 0xd2, 0xf8]);
+        let (response, extra_data) = <(Bytes, Bytes)>::abi_decode_params(&data[4..], true).unwrap();
+        assert_eq!(response, bytes!("beef"));
+        assert_eq!(extra_data, bytes!("abcd"));
+    }
+}
@@ -0,0 +1,1231 @@
+//! The on-disk cache of finalized chain data. Blocks, transactions, logs
+//! and what's read from them never change once their block is finalized, so
+//! a query pinned to finalized block numbers is fetched once and its rows
+//! kept as a Parquet segment, keyed by chain, entity, block span, fields and
+//! filters. Running the same query again reads the segment instead of Portal
+//! or the RPC. For blocks, transactions and logs the cache is a data source,
+//! offered the query after the lake and ahead of Portal (see `data_source`);
+//! the other entities' segments are read before their resolvers run.
+
+use super::{
+    data_source::{boxed, unsupported, DataSource, SourceFuture, SourceQuery},
+    resolve_explain::{block_id_text, block_range_text, entity_name},
+};
+use crate::common::{
+    block::{get_block_number_from_tag, Block, BlockFilter, BlockId},
+    chain::{Chain, ChainOrRpc},
+    config::Config,
+    entity::Entity,
+    filters::{ComparisonFilter, EqualityFilter, FilterType},
+    logs::{LogField, LogFilter, Logs},
+    query_result::{BlockQueryRes, ExpressionResult, LogQueryRes, TransactionQueryRes},
+    transaction::{Transaction, TransactionFilter},
+};
+use alloy::{
+    eips::{eip2930::AccessList, BlockNumberOrTag},
+    primitives::{keccak256, Address, Bytes, B256, U256},
+    providers::ProviderBuilder,
+};
+use alloy_eip7702::SignedAuthorization;
+use anyhow::Result;
+use arrow::{
+    array::new_null_array,
+    datatypes::{DataType, Field, Schema},
+    json::{reader::infer_json_schema_from_iterator, ArrayWriter, ReaderBuilder},
+    record_batch::RecordBatch,
+};
+use parquet::{
+    arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ArrowWriter},
+    basic::{Compression, ZstdLevel},
+    file::{
+        metadata::KeyValue, properties::WriterProperties, reader::FileReader,
+        serialized_reader::SerializedFileReader,
+    },
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    env,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+    slice,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tokio::task::{self, JoinError};
+
+const SEGMENT_EXTENSION: &str = "parquet";
+
+/// The first line of every key `SegmentKey::query_hash` hashes. Changing
+/// what a segment holds for a query means bumping it, so segments written
+/// before stop matching.
+const KEY_VERSION: &str = "eql-segment-v1";
+
+/// The all-null column of a batch whose rows have no values, or that has
+/// no rows: Parquet needs one column, and JSON leaves nulls out, so it reads
+/// back as nothing.
+const PLACEHOLDER_COLUMN: &str = "_";
+
+/// Set by `ChainCache::disable`, for the rest of the process.
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, thiserror::Error)]
+pub enum ChainCacheError {
+    #[error("Rows of {0} don't survive a round trip through Arrow, so they aren't cached")]
+    Lossy(&'static str),
+    #[error("it holds another query's rows")]
+    OtherQuery,
+}
+
+/// The segments under a cache directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainCache {
+    dir: PathBuf,
+}
+
+/// The segments of one entity on one chain, as `eql cache stats` lists them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    pub chain: String,
+    pub entity: String,
+    pub segments: u64,
+    pub rows: u64,
+    pub bytes: u64,
+}
+
+/// What a segment holds: the rows of one entity, with one set of fields and
+/// filters, over one span of blocks on one chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SegmentKey {
+    chain: Chain,
+    entity: &'static str,
+    first_block: u64,
+    last_block: u64,
+    /// Hashes the whole query, fields and filters included, so two queries
+    /// over the same blocks get segments of their own (see
+    /// `canonical_query`).
+    query_hash: B256,
+}
+
+impl ChainCache {
+    pub const NAME: &'static str = "cache";
+
+    /// The cache the settings point to, or `None` when it's off. It's on
+    /// unless the config file's `cache` section says `"enabled": false` or
+    /// `disable` was called. It lives in the `EQL_CACHE_PATH` environment
+    /// variable's directory, the config file's `cache.path`, or
+    /// `$HOME/.eql/cache`, in that order.
+    pub fn from_settings() -> Option<Self> {
+        if DISABLED.load(Ordering::Relaxed) {
+            return None;
+        }
+        let config = Config::new().cache_config().ok().flatten();
+        if config.as_ref().is_some_and(|config| !config.enabled) {
+            return None;
+        }
+        let dir = env::var("EQL_CACHE_PATH")
+            .ok()
+            .map(PathBuf::from)
+            .or_else(|| config.and_then(|config| config.path))
+            .or_else(|| {
+                env::var("HOME")
+                    .ok()
+                    .map(|home| PathBuf::from(home).join(".eql").join("cache"))
+            })?;
+        Some(Self::new(dir))
+    }
+
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Turns the cache off for the rest of the process, as `--no-cache` does.
+    pub fn disable() {
+        DISABLED.store(true, Ordering::Relaxed);
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The segments per chain and entity, sorted by both.
+    pub fn stats(&self) -> io::Result<Vec<CacheStats>> {
+        let mut stats = Vec::new();
+        for chain_dir in sorted_dirs(&self.dir)? {
+            for entity_dir in sorted_dirs(&chain_dir)? {
+                let mut entry = CacheStats {
+                    chain: file_name(&chain_dir),
+                    entity: file_name(&entity_dir),
+                    segments: 0,
+                    rows: 0,
+                    bytes: 0,
+                };
+                for segment in segments(&entity_dir)? {
+                    entry.segments += 1;
+                    entry.bytes += fs::metadata(&segment)?.len();
+                    entry.rows += segment_rows(&segment).unwrap_or_default();
+                }
+                if entry.segments > 0 {
+                    stats.push(entry);
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Deletes every segment, returning how many there were.
+    pub fn clear(&self) -> io::Result<u64> {
+        let mut removed = 0;
+        for chain_dir in sorted_dirs(&self.dir)? {
+            for entity_dir in sorted_dirs(&chain_dir)? {
+                removed += segments(&entity_dir)?.len() as u64;
+            }
+            fs::remove_dir_all(chain_dir)?;
+        }
+        Ok(removed)
+    }
+
+    fn segment_path(&self, key: &SegmentKey) -> PathBuf {
+        self.dir
+            .join(key.chain.to_string())
+            .join(key.entity)
+            .join(format!(
+                "{}-{}-{}.{SEGMENT_EXTENSION}",
+                key.first_block,
+                key.last_block,
+                hex_hash(&key.query_hash)
+            ))
+    }
+
+    /// The rows `key`'s segment holds, if it's been written. A segment that
+    /// doesn't read, or was written for another query, is a miss, not an
+    /// error: the rows are fetched again, and `keep` writes the segment over.
+    pub(crate) async fn read(&self, key: &SegmentKey, entity: &Entity) -> Option<ExpressionResult> {
+        let path = self.segment_path(key);
+        let query_hash = hex_hash(&key.query_hash);
+        let read = task::spawn_blocking(move || read_segment(&path, &query_hash)).await;
+        joined(read)
+            .and_then(|batches| batches.map(|batches| decode(entity, &batches)).transpose())
+            .unwrap_or(None)
+    }
+
+    /// Whether `key`'s segment has been written, as `EXPLAIN` reports it.
+    pub(crate) async fn contains(&self, key: &SegmentKey) -> bool {
+        let path = self.segment_path(key);
+        let query_hash = hex_hash(&key.query_hash);
+        let read = task::spawn_blocking(move || segment_reader(&path, &query_hash)).await;
+        joined(read).is_ok_and(|reader| reader.is_some())
+    }
+
+    /// Writes `result` as `key`'s segment. Like `read`, a failed write only
+    /// costs the next run a fetch, so it's dropped rather than failing the
+    /// query, as are rows that aren't cached by design.
+    pub(crate) async fn write(&self, key: &SegmentKey, result: &ExpressionResult) {
+        let path = self.segment_path(key);
+        let (key, result) = (key.clone(), result.clone());
+        let written = task::spawn_blocking(move || write_segment(&path, &key, &result)).await;
+        // Nothing to report to: the query's rows are already on their way.
+        let _ = joined(written);
+    }
+
+    /// Keeps `result`, the rows of `entity` fetched from `chain`, for next
+    /// time, if they can be cached and their blocks are finalized.
+    pub(crate) async fn keep(
+        &self,
+        entity: &Entity,
+        chain: &ChainOrRpc,
+        result: &ExpressionResult,
+    ) {
+        let Some(key) = SegmentKey::new(entity, chain) else {
+            return;
+        };
+        if key.is_finalized().await {
+            self.write(&key, result).await;
+        }
+    }
+
+    /// `read`, as a data source: a query without a segment goes to the next
+    /// source.
+    async fn fetch(&self, query: SourceQuery<'_>, chain: &ChainOrRpc) -> Result<ExpressionResult> {
+        let entity = query.entity();
+        let read = match SegmentKey::new(&entity, chain) {
+            Some(key) => self.read(&key, &entity).await,
+            None => None,
+        };
+        match read {
+            Some(result) => Ok(result),
+            None => unsupported(Self::NAME, query, chain).await,
+        }
+    }
+}
+
+/// The cache is offered the block, transaction and log queries it can keep,
+/// and serves the ones it has a segment of.
+impl DataSource for ChainCache {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn can_fetch(&self, query: SourceQuery<'_>, chain: &ChainOrRpc) -> bool {
+        SegmentKey::new(&query.entity(), chain).is_some()
+    }
+
+    fn holds<'a>(
+        &'a self,
+        query: SourceQuery<'a>,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, bool> {
+        boxed(async move {
+            Ok(match SegmentKey::new(&query.entity(), chain) {
+                Some(key) => self.contains(&key).await,
+                None => false,
+            })
+        })
+    }
+
+    fn fetch_blocks<'a>(
+        &'a self,
+        block: &'a Block,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, Vec<BlockQueryRes>> {
+        boxed(async move {
+            let ExpressionResult::Block(rows) =
+                self.fetch(SourceQuery::Blocks(block), chain).await?
+            else {
+                unreachable!("a block query's segment decodes as blocks");
+            };
+            Ok(rows)
+        })
+    }
+
+    fn fetch_transactions<'a>(
+        &'a self,
+        transaction: &'a Transaction,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, Vec<TransactionQueryRes>> {
+        boxed(async move {
+            let query = SourceQuery::Transactions(transaction);
+            let ExpressionResult::Transaction(rows) = self.fetch(query, chain).await? else {
+                unreachable!("a transaction query's segment decodes as transactions");
+            };
+            Ok(rows)
+        })
+    }
+
+    fn fetch_logs<'a>(
+        &'a self,
+        logs: &'a Logs,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, Vec<LogQueryRes>> {
+        boxed(async move {
+            let ExpressionResult::Log(rows) = self.fetch(SourceQuery::Logs(logs), chain).await?
+            else {
+                unreachable!("a log query's segment decodes as logs");
+            };
+            Ok(rows)
+        })
+    }
+}
+
+impl SegmentKey {
+    /// The key of `entity`'s rows on `chain`, when they can be cached: the
+    /// query reads a named chain (a custom RPC might be a fork) at block
+    /// numbers, not tags, and reads what's in those blocks, not state as
+    /// of now or ENS names, which change.
+    pub(crate) fn new(entity: &Entity, chain: &ChainOrRpc) -> Option<Self> {
+        let ChainOrRpc::Chain(chain) = chain else {
+            return None;
+        };
+        let blocks = pinned_blocks(entity)?;
+        let mut first_block = u64::MAX;
+        let mut last_block = 0;
+        for id in blocks {
+            let (start, end) = block_numbers(&id)?;
+            first_block = first_block.min(start);
+            last_block = last_block.max(end);
+        }
+        let query_hash = keccak256(canonical_query(chain, entity));
+        Some(Self {
+            chain: chain.clone(),
+            entity: entity_name(entity),
+            first_block,
+            last_block,
+            query_hash,
+        })
+    }
+
+    /// The first and last block the rows come from.
+    pub(crate) fn blocks(&self) -> (u64, u64) {
+        (self.first_block, self.last_block)
+    }
+
+    /// Whether every block the rows come from is finalized on the chain, so
+    /// they can be kept. A chain whose RPC doesn't know the `finalized` tag
+    /// isn't cached.
+    pub(crate) async fn is_finalized(&self) -> bool {
+        let Ok(url) = self.chain.rpc_url() else {
+            return false;
+        };
+        let provider = Arc::new(ProviderBuilder::new().on_http(url));
+        get_block_number_from_tag(provider, &BlockNumberOrTag::Finalized)
+            .await
+            .is_ok_and(|finalized| self.last_block <= finalized)
+    }
+}
+
+/// The blocks the cacheable `entity` reads. `None` for state reads (accounts, storage, token balances, calls, ENS), which are
+/// cheap point reads, for lookups by hash or creation, whose blocks aren't
+/// known up front, and for rows with ENS name columns.
+fn pinned_blocks(entity: &Entity) -> Option<Vec<BlockId>> {
+    let blocks = match entity {
+        Entity::Block(block) => {
+            let ranges = block
+                .filters()
+                .into_iter()
+                .flatten()
+                .filter_map(|filter| match filter {
+                    BlockFilter::Range(range) => Some(BlockId::Range(range.clone())),
+                    _ => None,
+                });
+            let ids = block.ids().into_iter().flatten().cloned();
+            ids.chain(ranges).collect()
+        }
+        Entity::Transaction(transaction) => {
+            if transaction.fields().iter().any(|field| field.is_ens_name()) {
+                return None;
+            }
+            let block = transaction.get_block_id_filter().ok()?.clone();
+            vec![block]
+        }
+        Entity::Logs(logs) => {
+            if logs.fields().contains(&LogField::AddressEns) {
+                return None;
+            }
+            let ranges = logs.filter().iter().filter_map(|filter| match filter {
+                LogFilter::BlockRange(range) => Some(BlockId::Range(range.clone())),
+                _ => None,
+            });
+            ranges.collect()
+        }
+        Entity::Withdrawals(withdrawals) => cloned(withdrawals.block_ids()),
+        Entity::Authorizations(authorizations) => cloned(authorizations.block_ids()),
+        Entity::Contracts(contracts) => cloned(contracts.block_ids()),
+        Entity::StateDiffs(state_diffs) => cloned(state_diffs.block_ids()),
+        Entity::Account(_)
+        | Entity::Storage(_)
+        | Entity::TokenBalances(_)
+        | Entity::Call(_)
+        | Entity::Ens(_) => return None,
+    };
+    (!blocks.is_empty()).then_some(blocks)
+}
+
+fn cloned(ids: Vec<&BlockId>) -> Vec<BlockId> {
+    ids.into_iter().cloned().collect()
+}
+
+/// The first and last block `id` covers, if it names them by number.
+pub(crate) fn block_numbers(id: &BlockId) -> Option<(u64, u64)> {
+    let number = |tag: BlockNumberOrTag| match tag {
+        BlockNumberOrTag::Number(number) => Some(number),
+        _ => None,
+    };
+    match id {
+        BlockId::Number(tag) => number(*tag).map(|n| (n, n)),
+        BlockId::Range(range) => {
+            let start = number(range.start())?;
+            let end = match range.end() {
+                Some(end) => number(end)?,
+                None => start,
+            };
+            Some((start, end))
+        }
+    }
+}
+
+/// The text `SegmentKey::query_hash` hashes: the key's version, the chain,
+/// the entity, and each field and filter of the query, one per line. Fields
+/// and filters are sorted, since their order doesn't change the rows, and
+/// spelled out column by column, so the key only changes when the rows do.
+fn canonical_query(chain: &Chain, entity: &Entity) -> String {
+    let (fields, filters) = match entity {
+        Entity::Block(block) => {
+            let ids = block.ids().into_iter().flatten().map(block_id_filter);
+            let filters = block
+                .filters()
+                .into_iter()
+                .flatten()
+                .map(|filter| match filter {
+                    BlockFilter::Range(range) => format!("block = {}", block_range_text(range)),
+                    BlockFilter::Miner(filter) => equality("miner", filter),
+                    BlockFilter::Timestamp(filter) => comparison("timestamp", filter),
+                });
+            (texts(block.fields()), ids.chain(filters).collect())
+        }
+        Entity::Transaction(transaction) => {
+            let hashes = transaction.ids().into_iter().flatten();
+            let filters = transaction.filters().into_iter().flatten();
+            let filters = hashes
+                .map(|hash| format!("hash = {hash}"))
+                .chain(filters.map(transaction_filter))
+                .collect();
+            (texts(transaction.fields()), filters)
+        }
+        Entity::Logs(logs) => {
+            let filters = logs.filter().iter().map(|filter| match filter {
+                LogFilter::BlockRange(range) => format!("block = {}", block_range_text(range)),
+                LogFilter::BlockHash(hash) => format!("block_hash = {hash}"),
+                LogFilter::EmitterAddress(address) => format!("address = {address}"),
+                LogFilter::EventSignature(signature) => format!("event = {signature}"),
+                LogFilter::Topic0(topic) => format!("topic0 = {topic}"),
+                LogFilter::Topic1(topic) => format!("topic1 = {topic}"),
+                LogFilter::Topic2(topic) => format!("topic2 = {topic}"),
+                LogFilter::Topic3(topic) => format!("topic3 = {topic}"),
+            });
+            (texts(logs.fields()), filters.collect())
+        }
+        Entity::Withdrawals(withdrawals) => {
+            let blocks = withdrawals.block_ids().into_iter().map(block_id_filter);
+            let addresses = withdrawals.addresses().iter();
+            let validators = withdrawals.validator_indices().iter();
+            let filters = blocks
+                .chain(addresses.map(|address| format!("address = {address}")))
+                .chain(validators.map(|index| format!("validator_index = {index}")))
+                .collect();
+            (texts(&withdrawals.fields()), filters)
+        }
+        Entity::Authorizations(authorizations) => {
+            let blocks = authorizations.block_ids().into_iter().map(block_id_filter);
+            let authorities = authorizations.authorities().iter();
+            let delegates = authorizations.delegates().iter();
+            let filters = blocks
+                .chain(authorities.map(|authority| format!("authority = {authority}")))
+                .chain(delegates.map(|delegate| format!("delegate = {delegate}")))
+                .collect();
+            (texts(&authorizations.fields()), filters)
+        }
+        Entity::Contracts(contracts) => {
+            let blocks = contracts.block_ids().into_iter().map(block_id_filter);
+            let addresses = contracts.addresses().iter();
+            let deployers = contracts.deployers().iter();
+            let filters = blocks
+                .chain(addresses.map(|address| format!("address = {address}")))
+                .chain(deployers.map(|deployer| format!("deployer = {deployer}")))
+                .collect();
+            (texts(&contracts.fields()), filters)
+        }
+        Entity::StateDiffs(state_diffs) => {
+            let blocks = state_diffs.block_ids().into_iter().map(block_id_filter);
+            let addresses = state_diffs.addresses().iter();
+            let kinds = state_diffs.kinds().iter();
+            let keys = state_diffs.keys().iter();
+            let filters = blocks
+                .chain(addresses.map(|address| format!("address = {address}")))
+                .chain(kinds.map(|kind| format!("kind = {kind}")))
+                .chain(keys.map(|key| format!("key = {key}")))
+                .collect();
+            (texts(&state_diffs.fields()), filters)
+        }
+        Entity::Account(_)
+        | Entity::Storage(_)
+        | Entity::TokenBalances(_)
+        | Entity::Call(_)
+        | Entity::Ens(_) => unreachable!("only entities with pinned blocks are cached"),
+    };
+    let mut lines = vec![
+        KEY_VERSION.to_string(),
+        format!("chain {chain}"),
+        format!("entity {}", entity_name(entity)),
+    ];
+    lines.extend(
+        sorted(fields)
+            .into_iter()
+            .map(|field| format!("field {field}")),
+    );
+    lines.extend(
+        sorted(filters)
+            .into_iter()
+            .map(|filter| format!("where {filter}")),
+    );
+    lines.join("\n")
+}
+
+fn transaction_filter(filter: &TransactionFilter) -> String {
+    match filter {
+        TransactionFilter::Type(filter) => equality("type", filter),
+        TransactionFilter::Hash(filter) => equality("hash", filter),
+        TransactionFilter::From(filter) => equality("from_address", filter),
+        TransactionFilter::To(filter) => equality("to_address", filter),
+        TransactionFilter::Data(filter) => equality("data", filter),
+        TransactionFilter::Value(filter) => comparison("value", filter),
+        TransactionFilter::GasPrice(filter) => comparison("gas_price", filter),
+        TransactionFilter::GasLimit(filter) => comparison("gas_limit", filter),
+        TransactionFilter::EffectiveGasPrice(filter) => comparison("effective_gas_price", filter),
+        TransactionFilter::ChainId(filter) => equality("chain_id", filter),
+        TransactionFilter::BlockId(id) => block_id_filter(id),
+        TransactionFilter::Status(filter) => equality("status", filter),
+        TransactionFilter::GasUsed(filter) => comparison("gas_used", filter),
+        TransactionFilter::CumulativeGasUsed(filter) => comparison("cumulative_gas_used", filter),
+        TransactionFilter::ContractAddress(filter) => equality("contract_address", filter),
+        TransactionFilter::LogsCount(filter) => comparison("logs_count", filter),
+        TransactionFilter::Fee(filter) => comparison("fee", filter),
+        TransactionFilter::V(filter) => equality("v", filter),
+        TransactionFilter::R(filter) => equality("r", filter),
+        TransactionFilter::S(filter) => equality("s", filter),
+        TransactionFilter::MaxFeePerBlobGas(filter) => comparison("max_fee_per_blob_gas", filter),
+        TransactionFilter::BlobCount(filter) => comparison("blob_count", filter),
+        TransactionFilter::BlobGasUsed(filter) => comparison("blob_gas_used", filter),
+        TransactionFilter::BlobGasPrice(filter) => comparison("blob_gas_price", filter),
+        TransactionFilter::MaxFeePerGas(filter) => comparison("max_fee_per_gas", filter),
+        TransactionFilter::MaxPriorityFeePerGas(filter) => {
+            comparison("max_priority_fee_per_gas", filter)
+        }
+        TransactionFilter::YParity(filter) => equality("y_parity", filter),
+    }
+}
+
+fn block_id_filter(id: &BlockId) -> String {
+    format!("block = {}", block_id_text(id))
+}
+
+fn equality<T: Display>(column: &str, filter: &EqualityFilter<T>) -> String {
+    match filter {
+        EqualityFilter::Eq(value) => format!("{column} = {value}"),
+        EqualityFilter::Neq(value) => format!("{column} != {value}"),
+    }
+}
+
+fn comparison<T: Display>(column: &str, filter: &FilterType<T>) -> String {
+    match filter {
+        FilterType::Equality(filter) => equality(column, filter),
+        FilterType::Comparison(ComparisonFilter::Gt(value)) => format!("{column} > {value}"),
+        FilterType::Comparison(ComparisonFilter::Gte(value)) => format!("{column} >= {value}"),
+        FilterType::Comparison(ComparisonFilter::Lt(value)) => format!("{column} < {value}"),
+        FilterType::Comparison(ComparisonFilter::Lte(value)) => format!("{column} <= {value}"),
+    }
+}
+
+fn texts<T: Display>(values: &[T]) -> Vec<String> {
+    values.iter().map(ToString::to_string).collect()
+}
+
+fn sorted(mut texts: Vec<String>) -> Vec<String> {
+    texts.sort();
+    texts.dedup();
+    texts
+}
+
+/// The result of segment IO run on the blocking pool, so it doesn't stall
+/// the other chains' fetches, or why the task didn't finish.
+fn joined<T>(task: Result<Result<T>, JoinError>) -> Result<T> {
+    task?
+}
+
+/// A reader of the segment at `path`, `None` if there's none. A segment
+/// whose metadata names another query, as after a hash collision or a
+/// file copied between caches, is an error.
+fn segment_reader(
+    path: &Path,
+    query_hash: &str,
+) -> Result<Option<ParquetRecordBatchReaderBuilder<fs::File>>> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)?;
+    let stored = reader
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .into_iter()
+        .flatten()
+        .find(|entry| entry.key == "eql.query_hash")
+        .and_then(|entry| entry.value.clone());
+    if stored.as_deref() != Some(query_hash) {
+        return Err(ChainCacheError::OtherQuery.into());
+    }
+    Ok(Some(reader))
+}
+
+fn read_segment(path: &Path, query_hash: &str) -> Result<Option<Vec<RecordBatch>>> {
+    let Some(reader) = segment_reader(path, query_hash)? else {
+        return Ok(None);
+    };
+    Ok(Some(reader.build()?.collect::<Result<Vec<_>, _>>()?))
+}
+
+fn write_segment(path: &Path, key: &SegmentKey, result: &ExpressionResult) -> Result<()> {
+    let segment = encode(key, result)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Written aside and renamed, so a reader never sees half a segment.
+    let partial = path.with_extension("partial");
+    fs::write(&partial, segment)?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+/// `result` as a Parquet segment, its key in the file's metadata.
+fn encode(key: &SegmentKey, result: &ExpressionResult) -> Result<Vec<u8>> {
+    let batch = match result {
+        ExpressionResult::Block(rows) => to_batch(key.entity, rows)?,
+        ExpressionResult::Transaction(rows) => {
+            let rows: Vec<TransactionRow> = rows.iter().cloned().map(Into::into).collect();
+            to_batch(key.entity, &rows)?
+        }
+        ExpressionResult::Log(rows) => to_batch(key.entity, rows)?,
+        ExpressionResult::Withdrawal(rows) => to_batch(key.entity, rows)?,
+        ExpressionResult::Authorization(rows) => to_batch(key.entity, rows)?,
+        ExpressionResult::Contract(rows) => to_batch(key.entity, rows)?,
+        ExpressionResult::StateDiff(rows) => to_batch(key.entity, rows)?,
+        ExpressionResult::Account(_)
+        | ExpressionResult::Storage(_)
+        | ExpressionResult::TokenBalance(_)
+        | ExpressionResult::Call(_)
+        | ExpressionResult::Ens(_)
+        | ExpressionResult::Table(_)
+        | ExpressionResult::Column(_)
+        | ExpressionResult::Plan(_) => return Err(ChainCacheError::Lossy(key.entity).into()),
+    };
+    let metadata = [
+        ("eql.chain", key.chain.to_string()),
+        ("eql.entity", key.entity.to_string()),
+        ("eql.first_block", key.first_block.to_string()),
+        ("eql.last_block", key.last_block.to_string()),
+        ("eql.query_hash", hex_hash(&key.query_hash)),
+    ];
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::ZSTD(ZstdLevel::default()))
+        .set_key_value_metadata(Some(
+            metadata
+                .into_iter()
+                .map(|(key, value)| KeyValue::new(key.to_string(), value))
+                .collect(),
+        ))
+        .build();
+    let mut buffer = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut buffer, batch.schema(), Some(properties))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(buffer)
+}
+
+/// The rows of `entity` in `batches`.
+fn decode(entity: &Entity, batches: &[RecordBatch]) -> Result<ExpressionResult> {
+    Ok(match entity {
+        Entity::Block(_) => ExpressionResult::Block(from_batches(batches)?),
+        Entity::Transaction(_) => ExpressionResult::Transaction(
+            from_batches::<TransactionRow>(batches)?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        ),
+        Entity::Logs(_) => ExpressionResult::Log(from_batches(batches)?),
+        Entity::Withdrawals(_) => ExpressionResult::Withdrawal(from_batches(batches)?),
+        Entity::Authorizations(_) => ExpressionResult::Authorization(from_batches(batches)?),
+        Entity::Contracts(_) => ExpressionResult::Contract(from_batches(batches)?),
+        Entity::StateDiffs(_) => ExpressionResult::StateDiff(from_batches(batches)?),
+        Entity::Account(_)
+        | Entity::Storage(_)
+        | Entity::TokenBalances(_)
+        | Entity::Call(_)
+        | Entity::Ens(_) => unreachable!("only entities with pinned blocks are cached"),
+    })
+}
+
+/// `rows` as one record batch, its columns as the rows serialize to JSON.
+/// The batch must read back as the same rows: a value Arrow's JSON reader
+/// can't hold exactly, like an integer over `i64::MAX`, leaves them uncached
+/// rather than cached wrong.
+fn to_batch<T>(entity: &'static str, rows: &[T]) -> Result<RecordBatch>
+where
+    T: Serialize + DeserializeOwned + PartialEq,
+{
+    let values = rows
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<_>, _>>()?;
+    let schema = Arc::new(infer_json_schema_from_iterator(values.iter().map(Ok))?);
+    let mut decoder = ReaderBuilder::new(schema.clone()).build_decoder()?;
+    decoder.serialize(&values)?;
+    let batch = decoder
+        .flush()?
+        .unwrap_or_else(|| RecordBatch::new_empty(schema));
+    if from_batches::<T>(slice::from_ref(&batch))? != rows {
+        return Err(ChainCacheError::Lossy(entity).into());
+    }
+    if batch.num_columns() > 0 {
+        return Ok(batch);
+    }
+    let schema = Schema::new(vec![Field::new(PLACEHOLDER_COLUMN, DataType::Null, true)]);
+    let placeholder = new_null_array(&DataType::Null, batch.num_rows());
+    Ok(RecordBatch::try_new(Arc::new(schema), vec![placeholder])?)
+}
+
+fn from_batches<T: DeserializeOwned>(batches: &[RecordBatch]) -> Result<Vec<T>> {
+    let mut writer = ArrayWriter::new(Vec::new());
+    writer.write_batches(&batches.iter().collect::<Vec<_>>())?;
+    writer.finish()?;
+    let json = writer.into_inner();
+    if json.is_empty() {
+        return Ok(vec![]);
+    }
+    Ok(serde_json::from_slice(&json)?)
+}
+
+/// Declares `TransactionRow` with the fields of `TransactionQueryRes`, and
+/// the conversions both ways. Each conversion destructures every field, so
+/// a field added to one struct and not the other doesn't compile.
+macro_rules! transaction_row {
+    ($($field:ident: $ty:ty,)*) => {
+        /// A transaction as a segment stores it. `TransactionQueryRes`
+        /// serializes for display, every value as text, which doesn't
+        /// deserialize back; this serializes each value as its type does.
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct TransactionRow {
+            $($field: Option<$ty>,)*
+        }
+
+        impl From<TransactionQueryRes> for TransactionRow {
+            fn from(row: TransactionQueryRes) -> Self {
+                let TransactionQueryRes { $($field,)* } = row;
+                Self { $($field,)* }
+            }
+        }
+
+        impl From<TransactionRow> for TransactionQueryRes {
+            fn from(row: TransactionRow) -> Self {
+                let TransactionRow { $($field,)* } = row;
+                Self { $($field,)* }
+            }
+        }
+    };
+}
+
+transaction_row! {
+    chain: Chain,
+    r#type: u8,
+    hash: B256,
+    block_number: u64,
+    from_address: Address,
+    to_address: Address,
+    data: Bytes,
+    value: U256,
+    gas_price: u128,
+    gas_limit: u64,
+    effective_gas_price: u128,
+    status: bool,
+    gas_used: u64,
+    cumulative_gas_used: u64,
+    contract_address: Address,
+    logs_count: u64,
+    fee: U256,
+    chain_id: u64,
+    v: bool,
+    r: U256,
+    s: U256,
+    max_fee_per_blob_gas: u128,
+    blob_versioned_hashes: Vec<B256>,
+    blob_count: u64,
+    blob_gas_used: u64,
+    blob_gas_price: u128,
+    max_fee_per_gas: u128,
+    max_priority_fee_per_gas: u128,
+    access_list: AccessList,
+    y_parity: bool,
+    authorization_list: Vec<SignedAuthorization>,
+    l1_fee: U256,
+    l1_gas_used: u64,
+    l1_gas_price: u128,
+    l1_fee_scalar: String,
+    source_hash: B256,
+    mint: U256,
+    is_system_tx: bool,
+    gas_used_for_l1: u64,
+    from_ens: String,
+    to_ens: String,
+}
+
+fn segment_rows(path: &Path) -> Result<u64> {
+    let reader = SerializedFileReader::new(fs::File::open(path)?)?;
+    Ok(reader.metadata().file_metadata().num_rows() as u64)
+}
+
+/// The subdirectories of `dir`, sorted. A missing `dir` has none.
+fn sorted_dirs(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut dirs = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+    dirs.retain(|path| path.is_dir());
+    dirs.sort();
+    Ok(dirs)
+}
+
+fn segments(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    Ok(fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == SEGMENT_EXTENSION))
+        .collect())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn hex_hash(hash: &B256) -> String {
+    hash.to_string().trim_start_matches("0x").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{
+        account::{Account, AccountField},
+        block::{Block, BlockField},
+        ens::NameOrAddress,
+        logs::Logs,
+        query_result::{BlockQueryRes, LogQueryRes, StateDiffQueryRes},
+        state_diff::{StateDiffField, StateDiffKind, StateDiffs},
+        transaction::{Transaction, TransactionField, TransactionFilter},
+    };
+    use crate::interpreter::backend::resolve_portal::test_support::{eth, range};
+    use alloy::transports::http::reqwest::Url;
+    use alloy::{
+        eips::eip2930::AccessListItem,
+        primitives::{address, b256, bytes, Bloom},
+    };
+
+    const USDT: Address = address!("dac17f958d2ee523a2206206994597c13d831ec7");
+    const HASH: B256 = b256!("72546b3ca8ef0dfb85fe66d19645e44cb519858c72fbcad0e1c1699256fed890");
+
+    fn temp_cache(name: &str) -> ChainCache {
+        let dir = env::temp_dir()
+            .join(format!("eql-chain-cache-{}", std::process::id()))
+            .join(name);
+        let _ = fs::remove_dir_all(&dir);
+        ChainCache::new(dir)
+    }
+
+    fn number(n: u64) -> BlockId {
+        BlockId::Number(BlockNumberOrTag::Number(n))
+    }
+
+    fn blocks(ids: Vec<BlockId>, fields: Vec<BlockField>) -> Entity {
+        Entity::Block(Block::new(Some(ids), None, fields))
+    }
+
+    fn transactions(block: BlockId, fields: Vec<TransactionField>) -> Entity {
+        Entity::Transaction(Transaction::new(
+            None,
+            Some(vec![TransactionFilter::BlockId(block)]),
+            fields,
+        ))
+    }
+
+    fn logs(fields: Vec<LogField>) -> Entity {
+        Entity::Logs(Logs::new(
+            vec![
+                LogFilter::BlockRange(range(4_638_657, 4_638_758)),
+                LogFilter::EmitterAddress(USDT.into()),
+            ],
+            fields,
+        ))
+    }
+
+    async fn round_trip(cache: &ChainCache, entity: &Entity, result: ExpressionResult) {
+        let key = SegmentKey::new(entity, &eth()).expect("cacheable");
+        cache.write(&key, &result).await;
+        assert!(cache.contains(&key).await);
+        assert_eq!(cache.read(&key, entity).await, Some(result));
+    }
+
+    #[tokio::test]
+    async fn rows_round_trip_through_a_segment() {
+        let cache = temp_cache("round-trip");
+
+        let block = BlockQueryRes {
+            chain: Some(Chain::Ethereum),
+            number: Some(21_000_000),
+            timestamp: Some(1_729_873_907),
+            hash: Some(HASH),
+            size: Some(U256::from(54_326)),
+            parent_hash: Some(HASH),
+            miner: Some(USDT),
+            gas_used: Some(11_856_216),
+            gas_limit: Some(30_000_000),
+            nonce: Some(0),
+            difficulty: Some(U256::ZERO),
+            transaction_count: Some(174),
+            state_root: None,
+            transactions_root: None,
+            receipts_root: None,
+            logs_bloom: Some(Bloom::repeat_byte(0x01)),
+            extra_data: Some(bytes!("6265617665726275696c642e6f7267")),
+            mix_hash: None,
+            total_difficulty: Some(U256::from(58_750_003_716_598_352_816_469u128)),
+            base_fee_per_gas: Some(9_276_334_233),
+            withdrawals_root: None,
+            blob_gas_used: Some(0),
+            excess_blob_gas: Some(0),
+            parent_beacon_block_root: None,
+        };
+        round_trip(
+            &cache,
+            &blocks(
+                vec![number(21_000_000)],
+                BlockField::all_variants().to_vec(),
+            ),
+            ExpressionResult::Block(vec![block]),
+        )
+        .await;
+
+        let transaction = TransactionQueryRes {
+            chain: Some(Chain::Ethereum),
+            r#type: Some(2),
+            hash: Some(HASH),
+            block_number: Some(21_000_000),
+            from_address: Some(USDT),
+            to_address: None,
+            value: Some(U256::from(10).pow(U256::from(24))),
+            max_fee_per_gas: Some(20_000_000_000),
+            status: Some(true),
+            access_list: Some(AccessList(vec![AccessListItem {
+                address: USDT,
+                storage_keys: vec![HASH],
+            }])),
+            blob_versioned_hashes: Some(vec![]),
+            l1_fee_scalar: Some("0.684".to_string()),
+            ..Default::default()
+        };
+        round_trip(
+            &cache,
+            &transactions(number(21_000_000), TransactionField::wildcard()),
+            ExpressionResult::Transaction(vec![transaction]),
+        )
+        .await;
+
+        let log = LogQueryRes {
+            chain: Some(Chain::Ethereum),
+            address: Some(USDT),
+            topic0: Some(HASH),
+            topic1: None,
+            topic2: None,
+            topic3: None,
+            data: Some(bytes!("")),
+            block_hash: Some(HASH),
+            block_number: Some(4_638_657),
+            block_timestamp: None,
+            transaction_hash: Some(HASH),
+            transaction_index: Some(3),
+            log_index: Some(7),
+            removed: Some(false),
+            address_ens: None,
+        };
+        round_trip(
+            &cache,
+            &logs(vec![LogField::Address, LogField::Topic0]),
+            ExpressionResult::Log(vec![log.clone(), log]),
+        )
+        .await;
+
+        let state_diffs = Entity::StateDiffs(StateDiffs::new(
+            vec![number(21_000_000)],
+            vec![],
+            vec![],
+            vec![],
+            StateDiffField::all_variants().to_vec(),
+        ));
+        let diff = StateDiffQueryRes {
+            address: Some(USDT),
+            kind: Some(StateDiffKind::Balance),
+            prev: Some("1000000000000000000".to_string()),
+            next: None,
+            ..Default::default()
+        };
+        round_trip(
+            &cache,
+            &state_diffs,
+            ExpressionResult::StateDiff(vec![diff]),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn a_query_without_rows_is_cached_too() {
+        let cache = temp_cache("empty");
+        round_trip(
+            &cache,
+            &logs(vec![LogField::Address]),
+            ExpressionResult::Log(vec![]),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn rows_arrow_cant_hold_exactly_are_not_cached() {
+        let cache = temp_cache("lossy");
+        let entity = transactions(number(21_000_000), vec![TransactionField::GasPrice]);
+        let key = SegmentKey::new(&entity, &eth()).unwrap();
+        let transaction = TransactionQueryRes {
+            gas_price: Some(u128::MAX),
+            ..Default::default()
+        };
+        cache
+            .write(&key, &ExpressionResult::Transaction(vec![transaction]))
+            .await;
+        assert_eq!(cache.read(&key, &entity).await, None);
+        assert!(cache.stats().unwrap().is_empty());
+    }
+
+    #[test]
+    fn only_queries_pinned_to_block_numbers_are_cacheable() {
+        let fields = vec![BlockField::Number];
+        let key =
+            SegmentKey::new(&blocks(vec![number(5), number(2)], fields.clone()), &eth()).unwrap();
+        assert_eq!((key.first_block, key.last_block), (2, 5));
+        assert_eq!(key.entity, "blocks");
+
+        // Tags move, and a custom RPC might be a fork.
+        let latest = blocks(
+            vec![BlockId::Number(BlockNumberOrTag::Latest)],
+            fields.clone(),
+        );
+        assert_eq!(SegmentKey::new(&latest, &eth()), None);
+        let rpc = ChainOrRpc::Rpc(Url::parse("http://localhost:8545").unwrap());
+        assert_eq!(
+            SegmentKey::new(&blocks(vec![number(5)], fields.clone()), &rpc),
+            None
+        );
+
+        // State as of now, and ENS names, change.
+        let account = Entity::Account(Account::new(
+            Some(vec![USDT.into()]),
+            None,
+            vec![AccountField::Balance],
+        ));
+        assert_eq!(SegmentKey::new(&account, &eth()), None);
+        assert_eq!(
+            SegmentKey::new(&logs(vec![LogField::AddressEns]), &eth()),
+            None
+        );
+        let with_names = transactions(number(5), vec![TransactionField::FromEns]);
+        assert_eq!(SegmentKey::new(&with_names, &eth()), None);
+
+        // Other fields or filters over the same blocks are another segment.
+        let other = SegmentKey::new(&blocks(vec![number(5), number(2)], vec![]), &eth()).unwrap();
+        assert_ne!(key.query_hash, other.query_hash);
+        assert_ne!(
+            ChainCache::new("/c").segment_path(&key),
+            ChainCache::new("/c").segment_path(&other)
+        );
+        let file = format!("2-5-{}.parquet", hex_hash(&key.query_hash));
+        assert!(ChainCache::new("/c").segment_path(&key).ends_with(file));
+    }
+
+    #[test]
+    fn the_key_hashes_the_query_not_how_it_is_written() {
+        let block = || TransactionFilter::BlockId(number(21_000_000));
+        let from = |filter: fn(NameOrAddress) -> EqualityFilter<NameOrAddress>| {
+            TransactionFilter::From(filter(USDT.into()))
+        };
+        let entity =
+            |fields, filters| Entity::Transaction(Transaction::new(None, Some(filters), fields));
+        let hash = |entity: &Entity| SegmentKey::new(entity, &eth()).unwrap().query_hash;
+
+        let query = entity(
+            vec![TransactionField::Value, TransactionField::Hash],
+            vec![from(EqualityFilter::Eq), block()],
+        );
+        assert_eq!(
+            canonical_query(&Chain::Ethereum, &query),
+            "eql-segment-v1\n\
+             chain eth\n\
+             entity transactions\n\
+             field hash\n\
+             field value\n\
+             where block = 21000000\n\
+             where from_address = 0xdAC17F958D2ee523a2206206994597C13D831ec7"
+        );
+
+        // The order fields and filters are written in doesn't change the
+        // rows, so it doesn't change the key; another operator does.
+        let reordered = entity(
+            vec![TransactionField::Hash, TransactionField::Value],
+            vec![block(), from(EqualityFilter::Eq)],
+        );
+        assert_eq!(hash(&query), hash(&reordered));
+        let negated = entity(
+            vec![TransactionField::Value, TransactionField::Hash],
+            vec![from(EqualityFilter::Neq), block()],
+        );
+        assert_ne!(hash(&query), hash(&negated));
+    }
+
+    #[tokio::test]
+    async fn a_segment_written_for_another_query_is_a_miss() {
+        let cache = temp_cache("other-query");
+        let entity = logs(vec![LogField::Address]);
+        let key = SegmentKey::new(&entity, &eth()).unwrap();
+        let other = SegmentKey::new(&logs(vec![LogField::BlockNumber]), &eth()).unwrap();
+
+        cache.write(&other, &ExpressionResult::Log(vec![])).await;
+        fs::rename(cache.segment_path(&other), cache.segment_path(&key)).unwrap();
+        assert!(!cache.contains(&key).await);
+        assert_eq!(cache.read(&key, &entity).await, None);
+    }
+
+    #[tokio::test]
+    async fn stats_count_segments_and_clear_removes_them() {
+        let cache = temp_cache("stats");
+        assert!(cache.stats().unwrap().is_empty());
+
+        let log = LogQueryRes {
+            chain: Some(Chain::Ethereum),
+            address: Some(USDT),
+            topic0: None,
+            topic1: None,
+            topic2: None,
+            topic3: None,
+            data: None,
+            block_hash: None,
+            block_number: Some(4_638_657),
+            block_timestamp: None,
+            transaction_hash: None,
+            transaction_index: None,
+            log_index: None,
+            removed: None,
+            address_ens: None,
+        };
+        for fields in [vec![LogField::Address], vec![LogField::BlockNumber]] {
+            let entity = logs(fields);
+            let key = SegmentKey::new(&entity, &eth()).unwrap();
+            cache
+                .write(&key, &ExpressionResult::Log(vec![log.clone()]))
+                .await;
+        }
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(
+            (stats[0].chain.as_str(), stats[0].entity.as_str()),
+            ("eth", "logs")
+        );
+        assert_eq!((stats[0].segments, stats[0].rows), (2, 2));
+        assert!(stats[0].bytes > 0);
+
+        assert_eq!(cache.clear().unwrap(), 2);
+        assert!(cache.stats().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,1304 @@
+//! Chains queried from Parquet files on disk instead of Portal or the RPC.
+//! A chain's lake directory holds a `blocks`, a `transactions` and a `logs`
+//! directory of files in the layout `COPY ... TO '<file>.parquet'` writes,
+//! partitioned into as many files and subdirectories as they were exported
+//! in. A query reads the files covering its blocks, skipping the row groups
+//! whose statistics rule out its block range, addresses or topics, and never
+//! goes to the network. Blocks no file covers fail the query, unless the
+//! config file's `lake` section sets `fallback`. The lake is a data source
+//! (see `data_source`), offered block, transaction and log queries ahead of
+//! the chain cache, Portal and the RPC; other queries on a lake chain are
+//! fetched as on any other.
+
+use super::{
+    chain_cache::block_numbers,
+    data_source::{boxed, unsupported, DataSource, SourceFuture, SourceQuery},
+    resolve_block::filter_and_project_block_row,
+    resolve_explain::entity_name,
+    resolve_transaction::filter_and_project_transaction_row,
+};
+use crate::common::{
+    block::{Block, BlockFilter, BlockId},
+    chain::{Chain, ChainOrRpc},
+    config::Config,
+    entity::Entity,
+    filters::EqualityFilter,
+    logs::{LogField, LogFilter, Logs},
+    query_result::{BlockQueryRes, ExpressionResult, LogQueryRes, TransactionQueryRes},
+    transaction::{Transaction, TransactionField, TransactionFilter},
+};
+use alloy::{
+    eips::eip2930::{AccessList, AccessListItem},
+    primitives::{keccak256, B256},
+};
+use alloy_eip7702::{Authorization, SignedAuthorization};
+use anyhow::{anyhow, Result};
+use arrow::{
+    array::{Array, ArrayRef, AsArray, ListArray, StringArray, StructArray},
+    compute::cast,
+    datatypes::DataType,
+    record_batch::RecordBatch,
+};
+use parquet::{
+    arrow::arrow_reader::ParquetRecordBatchReaderBuilder,
+    file::{metadata::RowGroupMetaData, statistics::Statistics},
+};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use tokio::task;
+
+const FILE_EXTENSION: &str = "parquet";
+
+/// Why a lake can't answer a query. With `fallback` set, the query is
+/// fetched from Portal or the RPC instead.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum DataLakeError {
+    #[error("The {chain} data lake has no {table} for blocks {first} to {last}; export them, or set \"fallback\": true in the config file's lake section to fetch them")]
+    MissingBlocks {
+        chain: Chain,
+        table: &'static str,
+        first: u64,
+        last: u64,
+    },
+    #[error("The {chain} data lake holds blocks, transactions and logs, not {entity}")]
+    UnsupportedEntity { chain: Chain, entity: &'static str },
+    #[error("Queries on the {0} data lake must name their blocks by number")]
+    UnpinnedBlocks(Chain),
+}
+
+/// The lake directory of each chain that has one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataLake {
+    chains: HashMap<String, PathBuf>,
+    fallback: bool,
+}
+
+/// What a query reads from one table of a lake: the rows of some spans of
+/// blocks, whose columns in `equals` hold the given values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Scan {
+    table: &'static str,
+    block_column: &'static str,
+    spans: Vec<(u64, u64)>,
+    /// Values as the serializer writes them, so they compare with a row
+    /// group's statistics as the column's own values do.
+    equals: Vec<(&'static str, String)>,
+}
+
+/// A lake file and the blocks it covers: the span its name is, as in
+/// `18000000-18099999.parquet`, or else the lowest to the highest block
+/// number in it.
+struct LakeFile {
+    path: PathBuf,
+    blocks: Option<(u64, u64)>,
+    reader: ParquetRecordBatchReaderBuilder<fs::File>,
+}
+
+/// A lake file and the row groups a scan reads of it.
+type ScannedFile = (LakeFile, Vec<usize>);
+
+/// The columns of a record batch read from `path`, the flat ones as text,
+/// each parsed into the type of the field it fills.
+struct Columns<'a> {
+    path: &'a Path,
+    batch: &'a RecordBatch,
+    text: HashMap<&'a str, StringArray>,
+}
+
+impl DataLake {
+    pub const NAME: &'static str = "lake";
+
+    /// The lake the config file's `lake` section describes, or `None` when
+    /// it maps no chains. A config file that doesn't parse is an error, not
+    /// a lake-less config.
+    pub fn from_settings() -> Result<Option<Self>> {
+        let Some(config) = Config::new().lake_config()? else {
+            return Ok(None);
+        };
+        Ok((!config.chains.is_empty()).then(|| Self::new(config.chains, config.fallback)))
+    }
+
+    pub fn new(chains: HashMap<String, PathBuf>, fallback: bool) -> Self {
+        Self { chains, fallback }
+    }
+
+    /// The directory of `chain`'s files, if it has a lake. A custom RPC
+    /// never does.
+    pub fn chain_dir(&self, chain: &ChainOrRpc) -> Option<&Path> {
+        let ChainOrRpc::Chain(chain) = chain else {
+            return None;
+        };
+        self.chains.get(&chain.to_string()).map(PathBuf::as_path)
+    }
+
+    /// The rows of `entity` on `chain`, from its files. `None` when `chain`
+    /// has no lake, or the lake can't answer the query and `fallback` sends
+    /// it to Portal or the RPC.
+    pub async fn read(
+        &self,
+        entity: &Entity,
+        chain: &ChainOrRpc,
+    ) -> Option<Result<ExpressionResult>> {
+        let dir = self.chain_dir(chain)?.to_path_buf();
+        let ChainOrRpc::Chain(chain) = chain else {
+            return None;
+        };
+        self.or_fall_back(read_chain(dir, entity, chain).await)
+    }
+
+    /// The files and row groups `read` reads for `entity` on `chain`, as
+    /// `EXPLAIN` reports them, or `None` when `read` wouldn't read any.
+    pub(crate) async fn plan(
+        &self,
+        entity: &Entity,
+        chain: &ChainOrRpc,
+    ) -> Option<Result<LakePlan>> {
+        let dir = self.chain_dir(chain)?.to_path_buf();
+        let ChainOrRpc::Chain(chain) = chain else {
+            return None;
+        };
+        self.or_fall_back(plan_chain(dir, entity, chain).await)
+    }
+
+    fn or_fall_back<T>(&self, result: Result<T>) -> Option<Result<T>> {
+        match result {
+            Err(err) if self.fallback && err.is::<DataLakeError>() => None,
+            result => Some(result),
+        }
+    }
+
+    /// `read`, as a data source: a query `fallback` sends to Portal or the
+    /// RPC goes to the next source.
+    async fn fetch(&self, query: SourceQuery<'_>, chain: &ChainOrRpc) -> Result<ExpressionResult> {
+        match self.read(&query.entity(), chain).await {
+            Some(result) => result,
+            None => unsupported(Self::NAME, query, chain).await,
+        }
+    }
+}
+
+/// The lake is offered every block, transaction and log query on a chain
+/// it has a directory for, ahead of the cache, Portal and the RPC.
+impl DataSource for DataLake {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn can_fetch(&self, _query: SourceQuery<'_>, chain: &ChainOrRpc) -> bool {
+        self.chain_dir(chain).is_some()
+    }
+
+    fn holds<'a>(
+        &'a self,
+        query: SourceQuery<'a>,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, bool> {
+        boxed(async move {
+            let plan = self.plan(&query.entity(), chain).await.transpose()?;
+            Ok(plan.is_some())
+        })
+    }
+
+    fn fetch_blocks<'a>(
+        &'a self,
+        block: &'a Block,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, Vec<BlockQueryRes>> {
+        boxed(async move {
+            let ExpressionResult::Block(rows) =
+                self.fetch(SourceQuery::Blocks(block), chain).await?
+            else {
+                unreachable!("the lake reads a block query's rows as blocks");
+            };
+            Ok(rows)
+        })
+    }
+
+    fn fetch_transactions<'a>(
+        &'a self,
+        transaction: &'a Transaction,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, Vec<TransactionQueryRes>> {
+        boxed(async move {
+            let query = SourceQuery::Transactions(transaction);
+            let ExpressionResult::Transaction(rows) = self.fetch(query, chain).await? else {
+                unreachable!("the lake reads a transaction query's rows as transactions");
+            };
+            Ok(rows)
+        })
+    }
+
+    fn fetch_logs<'a>(
+        &'a self,
+        logs: &'a Logs,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, Vec<LogQueryRes>> {
+        boxed(async move {
+            let ExpressionResult::Log(rows) = self.fetch(SourceQuery::Logs(logs), chain).await?
+            else {
+                unreachable!("the lake reads a log query's rows as logs");
+            };
+            Ok(rows)
+        })
+    }
+}
+
+/// What a lake query reads: the blocks it asks for, and the row groups of
+/// each file statistics don't rule out, the path relative to the chain's
+/// lake directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LakePlan {
+    pub(crate) spans: Vec<(u64, u64)>,
+    pub(crate) files: Vec<(PathBuf, Vec<usize>)>,
+}
+
+/// The row groups `scan` reads of the files under `dir`. Blocks no file
+/// covers fail the scan.
+fn scanned_files(dir: &Path, scan: &Scan, chain: &Chain) -> Result<Vec<ScannedFile>> {
+    let files = lake_files(&dir.join(scan.table), scan.block_column)?;
+    if let Some((first, last)) = scan.missing_blocks(&files) {
+        return Err(DataLakeError::MissingBlocks {
+            chain: chain.clone(),
+            table: scan.table,
+            first,
+            last,
+        }
+        .into());
+    }
+    Ok(scan.prune(files))
+}
+
+/// The files and row groups `read_chain` reads. Like it, it lists and
+/// opens them on the blocking pool.
+async fn plan_chain(dir: PathBuf, entity: &Entity, chain: &Chain) -> Result<LakePlan> {
+    let scan = Scan::new(entity, chain)?;
+    let spans = scan.spans.clone();
+    let chain = chain.clone();
+    let files = task::spawn_blocking(move || {
+        let files = scanned_files(&dir, &scan, &chain)?;
+        let files = files.into_iter().map(|(file, row_groups)| {
+            let path = file.path.strip_prefix(&dir).unwrap_or(&file.path);
+            (path.to_path_buf(), row_groups)
+        });
+        anyhow::Ok(files.collect())
+    })
+    .await??;
+    Ok(LakePlan { spans, files })
+}
+
+/// The rows of `entity` in the lake directory `dir`. Listing, opening and
+/// reading its files is blocking IO, so it runs on the blocking pool, each
+/// chain's alongside the others'.
+async fn read_chain(dir: PathBuf, entity: &Entity, chain: &Chain) -> Result<ExpressionResult> {
+    let scan = Scan::new(entity, chain)?;
+    let batches = task::spawn_blocking({
+        let (scan, chain) = (scan.clone(), chain.clone());
+        move || read_row_groups(scanned_files(&dir, &scan, &chain)?)
+    })
+    .await??;
+    let pinned = |number: Option<u64>| number.is_some_and(|number| scan.covers(number));
+
+    Ok(match entity {
+        Entity::Block(block) => ExpressionResult::Block(
+            rows(&batches, block_row)?
+                .into_iter()
+                .filter(|row| pinned(row.number))
+                .filter_map(|mut row| {
+                    row.chain = Some(chain.clone());
+                    filter_and_project_block_row(block, &row)
+                })
+                .collect(),
+        ),
+        Entity::Transaction(transaction) => ExpressionResult::Transaction(
+            rows(&batches, transaction_row)?
+                .into_iter()
+                .filter(|row| pinned(row.block_number))
+                .filter_map(|mut row| {
+                    row.chain = Some(chain.clone());
+                    filter_and_project_transaction(transaction, row)
+                })
+                .collect(),
+        ),
+        Entity::Logs(logs) => ExpressionResult::Log(
+            rows(&batches, log_row)?
+                .into_iter()
+                .filter(|row| pinned(row.block_number))
+                .filter_map(|mut row| {
+                    row.chain = Some(chain.clone());
+                    filter_and_project_log(logs, &row)
+                })
+                .collect(),
+        ),
+        Entity::Account(_)
+        | Entity::Storage(_)
+        | Entity::TokenBalances(_)
+        | Entity::Call(_)
+        | Entity::Withdrawals(_)
+        | Entity::Authorizations(_)
+        | Entity::Contracts(_)
+        | Entity::StateDiffs(_)
+        | Entity::Ens(_) => unreachable!("Scan::new only accepts blocks, transactions and logs"),
+    })
+}
+
+impl Scan {
+    fn new(entity: &Entity, chain: &Chain) -> Result<Self, DataLakeError> {
+        let (block_column, ids, equals) = match entity {
+            Entity::Block(block) => {
+                let ranges =
+                    block
+                        .filters()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|filter| match filter {
+                            BlockFilter::Range(range) => Some(BlockId::Range(range.clone())),
+                            _ => None,
+                        });
+                let ids = block.ids().into_iter().flatten().cloned();
+                let equals = block
+                    .filters()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|filter| match filter {
+                        BlockFilter::Miner(EqualityFilter::Eq(miner)) => {
+                            Some(("miner", format!("{:#x}", miner.resolved())))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                ("number", ids.chain(ranges).collect::<Vec<_>>(), equals)
+            }
+            Entity::Transaction(transaction) => {
+                let block = transaction
+                    .get_block_id_filter()
+                    .map_err(|_| DataLakeError::UnpinnedBlocks(chain.clone()))?;
+                let equals = transaction
+                    .filters()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|filter| match filter {
+                        TransactionFilter::From(EqualityFilter::Eq(from)) => {
+                            Some(("from_address", format!("{:#x}", from.resolved())))
+                        }
+                        TransactionFilter::To(EqualityFilter::Eq(to)) => {
+                            Some(("to_address", format!("{:#x}", to.resolved())))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                ("block_number", vec![block.clone()], equals)
+            }
+            Entity::Logs(logs) => {
+                let mut ids = Vec::new();
+                let mut equals = Vec::new();
+                for filter in logs.filter() {
+                    match filter {
+                        LogFilter::BlockRange(range) => ids.push(BlockId::Range(range.clone())),
+                        LogFilter::EmitterAddress(address) => {
+                            equals.push(("address", format!("{:#x}", address.resolved())))
+                        }
+                        LogFilter::EventSignature(signature) => {
+                            equals.push(("topic0", format!("{:?}", keccak256(signature))))
+                        }
+                        LogFilter::Topic0(topic) => equals.push(("topic0", format!("{topic:?}"))),
+                        LogFilter::Topic1(topic) => equals.push(("topic1", format!("{topic:?}"))),
+                        LogFilter::Topic2(topic) => equals.push(("topic2", format!("{topic:?}"))),
+                        LogFilter::Topic3(topic) => equals.push(("topic3", format!("{topic:?}"))),
+                        LogFilter::BlockHash(_) => {}
+                    }
+                }
+                ("block_number", ids, equals)
+            }
+            Entity::Account(_)
+            | Entity::Storage(_)
+            | Entity::TokenBalances(_)
+            | Entity::Call(_)
+            | Entity::Withdrawals(_)
+            | Entity::Authorizations(_)
+            | Entity::Contracts(_)
+            | Entity::StateDiffs(_)
+            | Entity::Ens(_) => {
+                return Err(DataLakeError::UnsupportedEntity {
+                    chain: chain.clone(),
+                    entity: entity_name(entity),
+                })
+            }
+        };
+        // Tags like `latest` name blocks only the network knows.
+        let spans = ids
+            .iter()
+            .map(|id| block_numbers(id).ok_or_else(|| DataLakeError::UnpinnedBlocks(chain.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
+        if spans.is_empty() {
+            return Err(DataLakeError::UnpinnedBlocks(chain.clone()));
+        }
+        Ok(Self {
+            table: entity_name(entity),
+            block_column,
+            spans,
+            equals,
+        })
+    }
+
+    fn covers(&self, number: u64) -> bool {
+        self.spans
+            .iter()
+            .any(|&(first, last)| first <= number && number <= last)
+    }
+
+    fn overlaps(&self, (min, max): (u64, u64)) -> bool {
+        self.spans
+            .iter()
+            .any(|&(first, last)| first <= max && min <= last)
+    }
+
+    /// The first span of blocks the scan reads that no file covers.
+    fn missing_blocks(&self, files: &[LakeFile]) -> Option<(u64, u64)> {
+        let mut covered: Vec<(u64, u64)> = files.iter().filter_map(|file| file.blocks).collect();
+        covered.sort_unstable();
+        let mut spans = self.spans.clone();
+        spans.sort_unstable();
+        for (first, last) in spans {
+            // The first block of the span not yet known to be covered.
+            let mut next = Some(first);
+            for &(start, end) in &covered {
+                let Some(block) = next.filter(|&block| block <= last) else {
+                    break;
+                };
+                if start > block {
+                    break;
+                }
+                if end >= block {
+                    next = end.checked_add(1);
+                }
+            }
+            if let Some(block) = next.filter(|&block| block <= last) {
+                let resumes = covered
+                    .iter()
+                    .map(|&(start, _)| start)
+                    .filter(|&start| start > block)
+                    .min();
+                return Some((block, resumes.map_or(last, |start| last.min(start - 1))));
+            }
+        }
+        None
+    }
+
+    /// Whether `row_group`'s statistics allow a row of the scan: a block in
+    /// its spans and every value it compares. A column without statistics
+    /// allows anything.
+    fn keeps(&self, row_group: &RowGroupMetaData) -> bool {
+        let blocks =
+            block_bounds(row_group, self.block_column).map_or(true, |bounds| self.overlaps(bounds));
+        blocks
+            && self.equals.iter().all(|(column, value)| {
+                text_bounds(row_group, column).map_or(true, |(min, max)| {
+                    min <= value.as_bytes() && value.as_bytes() <= max
+                })
+            })
+    }
+
+    /// The row groups the scan keeps of the files covering its blocks,
+    /// leaving out the files it keeps none of.
+    fn prune(&self, files: Vec<LakeFile>) -> Vec<ScannedFile> {
+        files
+            .into_iter()
+            .filter(|file| file.blocks.is_some_and(|blocks| self.overlaps(blocks)))
+            .filter_map(|file| {
+                let row_groups: Vec<usize> = file
+                    .reader
+                    .metadata()
+                    .row_groups()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, row_group)| self.keeps(row_group))
+                    .map(|(index, _)| index)
+                    .collect();
+                (!row_groups.is_empty()).then_some((file, row_groups))
+            })
+            .collect()
+    }
+}
+
+fn read_row_groups(files: Vec<ScannedFile>) -> Result<Vec<(PathBuf, RecordBatch)>> {
+    let mut batches = Vec::new();
+    for (file, row_groups) in files {
+        for batch in file.reader.with_row_groups(row_groups).build()? {
+            batches.push((file.path.clone(), batch?));
+        }
+    }
+    Ok(batches)
+}
+
+/// The `.parquet` files under `dir`, in the order of the blocks they cover.
+/// A missing `dir` has none.
+fn lake_files(dir: &Path, block_column: &str) -> Result<Vec<LakeFile>> {
+    let mut paths = Vec::new();
+    collect_paths(dir, &mut paths)?;
+    let mut files = paths
+        .into_iter()
+        .map(|path| {
+            let reader = ParquetRecordBatchReaderBuilder::try_new(fs::File::open(&path)?)
+                .map_err(|err| anyhow!("{}: {err}", path.display()))?;
+            let blocks = named_blocks(&path).or_else(|| {
+                reader
+                    .metadata()
+                    .row_groups()
+                    .iter()
+                    .filter_map(|row_group| block_bounds(row_group, block_column))
+                    .reduce(|(min, max), (first, last)| (min.min(first), max.max(last)))
+            });
+            Ok(LakeFile {
+                path,
+                blocks,
+                reader,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    files.sort_by(|a, b| (a.blocks, &a.path).cmp(&(b.blocks, &b.path)));
+    Ok(files)
+}
+
+fn collect_paths(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_paths(&path, paths)?;
+        } else if path.extension().is_some_and(|ext| ext == FILE_EXTENSION) {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The blocks a file's name says it covers, when it's `<first>-<last>` and
+/// nothing else, which also counts the blocks it has no rows of.
+fn named_blocks(path: &Path) -> Option<(u64, u64)> {
+    let stem = path.file_stem()?.to_str()?;
+    let (first, last) = stem.split_once('-')?;
+    let number = |digits: &str| {
+        let all_digits = !digits.is_empty() && digits.bytes().all(|byte| byte.is_ascii_digit());
+        all_digits.then(|| digits.parse::<u64>().ok()).flatten()
+    };
+    let (first, last) = (number(first)?, number(last)?);
+    (first <= last).then_some((first, last))
+}
+
+fn statistics<'a>(row_group: &'a RowGroupMetaData, column: &str) -> Option<&'a Statistics> {
+    row_group
+        .columns()
+        .iter()
+        .find(|chunk| chunk.column_path().string() == column)?
+        .statistics()
+}
+
+/// The lowest and highest block number in `row_group`.
+fn block_bounds(row_group: &RowGroupMetaData, column: &str) -> Option<(u64, u64)> {
+    match statistics(row_group, column)? {
+        Statistics::Int64(stats) => Some((*stats.min_opt()? as u64, *stats.max_opt()? as u64)),
+        _ => None,
+    }
+}
+
+/// The lowest and highest value of the text `column` in `row_group`.
+fn text_bounds<'a>(row_group: &'a RowGroupMetaData, column: &str) -> Option<(&'a [u8], &'a [u8])> {
+    match statistics(row_group, column)? {
+        Statistics::ByteArray(stats) => Some((stats.min_bytes_opt()?, stats.max_bytes_opt()?)),
+        _ => None,
+    }
+}
+
+fn rows<T>(
+    batches: &[(PathBuf, RecordBatch)],
+    row: fn(&Columns, usize) -> Result<T>,
+) -> Result<Vec<T>> {
+    let mut rows = Vec::new();
+    for (path, batch) in batches {
+        let columns = Columns::new(path, batch)?;
+        for index in 0..batch.num_rows() {
+            rows.push(row(&columns, index)?);
+        }
+    }
+    Ok(rows)
+}
+
+impl<'a> Columns<'a> {
+    fn new(path: &'a Path, batch: &'a RecordBatch) -> Result<Self> {
+        let mut text = HashMap::new();
+        for (field, column) in batch.schema_ref().fields().iter().zip(batch.columns()) {
+            if !field.data_type().is_nested() {
+                text.insert(field.name().as_str(), as_text(column)?);
+            }
+        }
+        Ok(Self { path, batch, text })
+    }
+
+    /// The value of `column` in `row`, or `None` when it's null. A column
+    /// the file doesn't have is all nulls: COPY leaves out the columns
+    /// without a value.
+    fn get<T: FromStr>(&self, column: &str, row: usize) -> Result<Option<T>> {
+        let Some(values) = self.text.get(column).filter(|values| values.is_valid(row)) else {
+            return Ok(None);
+        };
+        parse(self.path, column, values.value(row)).map(Some)
+    }
+
+    /// The entries of the list `column` in `row`, or `None` when it's null.
+    fn list(&self, column: &str, row: usize) -> Option<ArrayRef> {
+        let list: &ListArray = self.batch.column_by_name(column)?.as_list_opt()?;
+        list.is_valid(row).then(|| list.value(row))
+    }
+
+    fn hashes(&self, column: &str, row: usize) -> Result<Option<Vec<B256>>> {
+        let Some(hashes) = self.list(column, row) else {
+            return Ok(None);
+        };
+        parse_all(self.path, column, &hashes).map(Some)
+    }
+
+    fn access_list(&self, row: usize) -> Result<Option<AccessList>> {
+        let Some(entries) = self.list("access_list", row) else {
+            return Ok(None);
+        };
+        let entries = self.entries("access_list", &entries)?;
+        let addresses = self.member("access_list", entries, "address")?;
+        let keys: Option<&ListArray> = entries
+            .column_by_name("storage_keys")
+            .and_then(|keys| keys.as_list_opt());
+        let items = (0..entries.len())
+            .map(|index| {
+                Ok(AccessListItem {
+                    address: parse(self.path, "access_list", addresses.value(index))?,
+                    storage_keys: match keys.filter(|keys| keys.is_valid(index)) {
+                        Some(keys) => parse_all(self.path, "access_list", &keys.value(index))?,
+                        None => vec![],
+                    },
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(AccessList(items)))
+    }
+
+    fn authorization_list(&self, row: usize) -> Result<Option<Vec<SignedAuthorization>>> {
+        const COLUMN: &str = "authorization_list";
+        let Some(entries) = self.list(COLUMN, row) else {
+            return Ok(None);
+        };
+        let entries = self.entries(COLUMN, &entries)?;
+        let [chain_id, address, nonce, y_parity, r, s] =
+            ["chain_id", "address", "nonce", "y_parity", "r", "s"]
+                .map(|member| self.member(COLUMN, entries, member));
+        let (chain_id, address, nonce) = (chain_id?, address?, nonce?);
+        let (y_parity, r, s) = (y_parity?, r?, s?);
+        let authorizations = (0..entries.len())
+            .map(|index| {
+                let authorization = Authorization {
+                    chain_id: parse(self.path, COLUMN, chain_id.value(index))?,
+                    address: parse(self.path, COLUMN, address.value(index))?,
+                    nonce: parse(self.path, COLUMN, nonce.value(index))?,
+                };
+                Ok(SignedAuthorization::new_unchecked(
+                    authorization,
+                    parse(self.path, COLUMN, y_parity.value(index))?,
+                    parse(self.path, COLUMN, r.value(index))?,
+                    parse(self.path, COLUMN, s.value(index))?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(authorizations))
+    }
+
+    fn entries<'b>(&self, column: &str, entries: &'b ArrayRef) -> Result<&'b StructArray> {
+        entries
+            .as_struct_opt()
+            .ok_or_else(|| anyhow!("{}: {column} isn't a list of structs", self.path.display()))
+    }
+
+    fn member(&self, column: &str, entries: &StructArray, member: &str) -> Result<StringArray> {
+        let values = entries
+            .column_by_name(member)
+            .ok_or_else(|| anyhow!("{}: {column} has no {member}", self.path.display()))?;
+        as_text(values)
+    }
+}
+
+fn as_text(array: &ArrayRef) -> Result<StringArray> {
+    Ok(cast(array, &DataType::Utf8)?.as_string::<i32>().clone())
+}
+
+fn parse<T: FromStr>(path: &Path, column: &str, value: &str) -> Result<T> {
+    value.parse().map_err(|_| {
+        anyhow!(
+            "{}: column {column} holds {value:?}, which isn't a valid value",
+            path.display()
+        )
+    })
+}
+
+fn parse_all<T: FromStr>(path: &Path, column: &str, values: &ArrayRef) -> Result<Vec<T>> {
+    as_text(values)?
+        .iter()
+        .flatten()
+        .map(|value| parse(path, column, value))
+        .collect()
+}
+
+fn block_row(columns: &Columns, row: usize) -> Result<BlockQueryRes> {
+    Ok(BlockQueryRes {
+        chain: None,
+        number: columns.get("number", row)?,
+        timestamp: columns.get("timestamp", row)?,
+        hash: columns.get("hash", row)?,
+        size: columns.get("size", row)?,
+        parent_hash: columns.get("parent_hash", row)?,
+        miner: columns.get("miner", row)?,
+        gas_used: columns.get("gas_used", row)?,
+        gas_limit: columns.get("gas_limit", row)?,
+        nonce: columns.get("nonce", row)?,
+        difficulty: columns.get("difficulty", row)?,
+        transaction_count: columns.get("transaction_count", row)?,
+        state_root: columns.get("state_root", row)?,
+        transactions_root: columns.get("transactions_root", row)?,
+        receipts_root: columns.get("receipts_root", row)?,
+        logs_bloom: columns.get("logs_bloom", row)?,
+        extra_data: columns.get("extra_data", row)?,
+        mix_hash: columns.get("mix_hash", row)?,
+        total_difficulty: columns.get("total_difficulty", row)?,
+        base_fee_per_gas: columns.get("base_fee_per_gas", row)?,
+        withdrawals_root: columns.get("withdrawals_root", row)?,
+        blob_gas_used: columns.get("blob_gas_used", row)?,
+        excess_blob_gas: columns.get("excess_blob_gas", row)?,
+        parent_beacon_block_root: columns.get("parent_beacon_block_root", row)?,
+    })
+}
+
+fn transaction_row(columns: &Columns, row: usize) -> Result<TransactionQueryRes> {
+    Ok(TransactionQueryRes {
+        chain: None,
+        r#type: columns.get("type", row)?,
+        hash: columns.get("hash", row)?,
+        block_number: columns.get("block_number", row)?,
+        from_address: columns.get("from_address", row)?,
+        to_address: columns.get("to_address", row)?,
+        data: columns.get("data", row)?,
+        value: columns.get("value", row)?,
+        gas_price: columns.get("gas_price", row)?,
+        gas_limit: columns.get("gas_limit", row)?,
+        effective_gas_price: columns.get("effective_gas_price", row)?,
+        status: columns.get("status", row)?,
+        gas_used: columns.get("gas_used", row)?,
+        cumulative_gas_used: columns.get("cumulative_gas_used", row)?,
+        contract_address: columns.get("contract_address", row)?,
+        logs_count: columns.get("logs_count", row)?,
+        fee: columns.get("fee", row)?,
+        chain_id: columns.get("chain_id", row)?,
+        v: columns.get("v", row)?,
+        r: columns.get("r", row)?,
+        s: columns.get("s", row)?,
+        max_fee_per_blob_gas: columns.get("max_fee_per_blob_gas", row)?,
+        blob_versioned_hashes: columns.hashes("blob_versioned_hashes", row)?,
+        blob_count: columns.get("blob_count", row)?,
+        blob_gas_used: columns.get("blob_gas_used", row)?,
+        blob_gas_price: columns.get("blob_gas_price", row)?,
+        max_fee_per_gas: columns.get("max_fee_per_gas", row)?,
+        max_priority_fee_per_gas: columns.get("max_priority_fee_per_gas", row)?,
+        access_list: columns.access_list(row)?,
+        y_parity: columns.get("y_parity", row)?,
+        authorization_list: columns.authorization_list(row)?,
+        l1_fee: columns.get("l1_fee", row)?,
+        l1_gas_used: columns.get("l1_gas_used", row)?,
+        l1_gas_price: columns.get("l1_gas_price", row)?,
+        l1_fee_scalar: columns.get("l1_fee_scalar", row)?,
+        source_hash: columns.get("source_hash", row)?,
+        mint: columns.get("mint", row)?,
+        is_system_tx: columns.get("is_system_tx", row)?,
+        gas_used_for_l1: columns.get("gas_used_for_l1", row)?,
+        from_ens: columns.get("from_ens", row)?,
+        to_ens: columns.get("to_ens", row)?,
+    })
+}
+
+fn log_row(columns: &Columns, row: usize) -> Result<LogQueryRes> {
+    Ok(LogQueryRes {
+        chain: None,
+        address: columns.get("address", row)?,
+        topic0: columns.get("topic0", row)?,
+        topic1: columns.get("topic1", row)?,
+        topic2: columns.get("topic2", row)?,
+        topic3: columns.get("topic3", row)?,
+        data: columns.get("data", row)?,
+        block_hash: columns.get("block_hash", row)?,
+        block_number: columns.get("block_number", row)?,
+        block_timestamp: columns.get("block_timestamp", row)?,
+        transaction_hash: columns.get("transaction_hash", row)?,
+        transaction_index: columns.get("transaction_index", row)?,
+        log_index: columns.get("log_index", row)?,
+        removed: columns.get("removed", row)?,
+        address_ens: columns.get("address_ens", row)?,
+    })
+}
+
+/// `row` as `transaction` selects it, if it passes the filters. The ENS
+/// names come from the file rather than a lookup, so the addresses
+/// `filter_and_project_transaction_row` keeps for one go unless selected.
+fn filter_and_project_transaction(
+    transaction: &Transaction,
+    row: TransactionQueryRes,
+) -> Option<TransactionQueryRes> {
+    let mut projected = filter_and_project_transaction_row(transaction, &row)?;
+    let fields = transaction.fields();
+    if fields.contains(&TransactionField::FromEns) {
+        projected.from_ens = row.from_ens;
+        if !fields.contains(&TransactionField::From) {
+            projected.from_address = None;
+        }
+    }
+    if fields.contains(&TransactionField::ToEns) {
+        projected.to_ens = row.to_ens;
+        if !fields.contains(&TransactionField::To) {
+            projected.to_address = None;
+        }
+    }
+    Some(projected)
+}
+
+/// `row` as `logs` selects it, if it passes the filters. Block ranges pick
+/// the rows read, so they pass here.
+fn filter_and_project_log(logs: &Logs, row: &LogQueryRes) -> Option<LogQueryRes> {
+    let passes = logs.filter().iter().all(|filter| match filter {
+        LogFilter::BlockRange(_) => true,
+        LogFilter::BlockHash(hash) => row.block_hash == Some(*hash),
+        LogFilter::EmitterAddress(address) => row.address == Some(address.resolved()),
+        LogFilter::EventSignature(signature) => row.topic0 == Some(keccak256(signature)),
+        LogFilter::Topic0(topic) => row.topic0 == Some(*topic),
+        LogFilter::Topic1(topic) => row.topic1 == Some(*topic),
+        LogFilter::Topic2(topic) => row.topic2 == Some(*topic),
+        LogFilter::Topic3(topic) => row.topic3 == Some(*topic),
+    });
+    if !passes {
+        return None;
+    }
+
+    let mut projected = LogQueryRes::default();
+    for field in logs.fields() {
+        match field {
+            LogField::Address => projected.address = row.address,
+            LogField::Topic0 => projected.topic0 = row.topic0,
+            LogField::Topic1 => projected.topic1 = row.topic1,
+            LogField::Topic2 => projected.topic2 = row.topic2,
+            LogField::Topic3 => projected.topic3 = row.topic3,
+            LogField::Data => projected.data = row.data.clone(),
+            LogField::BlockHash => projected.block_hash = row.block_hash,
+            LogField::BlockNumber => projected.block_number = row.block_number,
+            LogField::BlockTimestamp => projected.block_timestamp = row.block_timestamp,
+            LogField::TransactionHash => projected.transaction_hash = row.transaction_hash,
+            LogField::TransactionIndex => projected.transaction_index = row.transaction_index,
+            LogField::LogIndex => projected.log_index = row.log_index,
+            LogField::Removed => projected.removed = row.removed,
+            LogField::Chain => projected.chain = row.chain.clone(),
+            LogField::AddressEns => projected.address_ens = row.address_ens.clone(),
+        }
+    }
+    Some(projected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{
+        block::{Block, BlockField},
+        serializer::to_record_batch,
+    };
+    use crate::interpreter::backend::data_source::DataSourceError;
+    use crate::interpreter::backend::resolve_portal::test_support::{eth, range};
+    use alloy::{
+        eips::BlockNumberOrTag,
+        primitives::{address, b256, bytes, Address, Bloom, U256},
+    };
+    use parquet::{arrow::ArrowWriter, file::properties::WriterProperties};
+    use std::env;
+
+    const USDT: Address = address!("dac17f958d2ee523a2206206994597c13d831ec7");
+    const WETH: Address = address!("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+    const HASH: B256 = b256!("72546b3ca8ef0dfb85fe66d19645e44cb519858c72fbcad0e1c1699256fed890");
+    const TRANSFER: B256 =
+        b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+    fn temp_lake(name: &str) -> PathBuf {
+        let dir = env::temp_dir()
+            .join(format!("eql-data-lake-{}", std::process::id()))
+            .join(name);
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn lake(dir: &Path, fallback: bool) -> DataLake {
+        DataLake::new(
+            HashMap::from([("eth".to_string(), dir.to_path_buf())]),
+            fallback,
+        )
+    }
+
+    /// Writes `result` as COPY would, one row per row group.
+    fn export(dir: &Path, table: &str, file: &str, result: &ExpressionResult) {
+        let batch = to_record_batch(result).unwrap();
+        let path = dir.join(table).join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let properties = WriterProperties::builder()
+            .set_max_row_group_size(1)
+            .build();
+        let mut writer = ArrowWriter::try_new(
+            fs::File::create(path).unwrap(),
+            batch.schema(),
+            Some(properties),
+        )
+        .unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+    }
+
+    fn blocks(start: u64, end: u64) -> Entity {
+        Entity::Block(Block::new(
+            Some(vec![BlockId::Range(range(start, end))]),
+            None,
+            BlockField::all_variants().to_vec(),
+        ))
+    }
+
+    fn logs(filter: Vec<LogFilter>) -> Entity {
+        Entity::Logs(Logs::new(filter, LogField::all_variants().to_vec()))
+    }
+
+    fn block(number: u64) -> BlockQueryRes {
+        BlockQueryRes {
+            chain: Some(Chain::Ethereum),
+            number: Some(number),
+            timestamp: Some(1_729_873_907),
+            hash: Some(HASH),
+            size: Some(U256::from(54_326)),
+            miner: Some(USDT),
+            gas_used: Some(11_856_216),
+            logs_bloom: Some(Bloom::repeat_byte(0x01)),
+            extra_data: Some(bytes!("6265617665726275696c642e6f7267")),
+            total_difficulty: Some(U256::from(58_750_003_716_598_352_816_469u128)),
+            base_fee_per_gas: Some(9_276_334_233),
+            ..Default::default()
+        }
+    }
+
+    fn log(number: u64, address: Address) -> LogQueryRes {
+        LogQueryRes {
+            chain: Some(Chain::Ethereum),
+            address: Some(address),
+            topic0: Some(TRANSFER),
+            data: Some(bytes!("01")),
+            block_hash: Some(HASH),
+            block_number: Some(number),
+            log_index: Some(number % 7),
+            removed: Some(false),
+            ..Default::default()
+        }
+    }
+
+    async fn read(lake: &DataLake, entity: &Entity) -> Result<ExpressionResult> {
+        lake.read(entity, &eth()).await.expect("eth has a lake")
+    }
+
+    async fn lake_error(lake: &DataLake, entity: &Entity) -> DataLakeError {
+        read(lake, entity)
+            .await
+            .unwrap_err()
+            .downcast::<DataLakeError>()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn rows_read_back_as_copy_wrote_them() {
+        let dir = temp_lake("round-trip");
+        let lake = lake(&dir, false);
+
+        let rows = vec![block(100), block(101), block(102)];
+        export(
+            &dir,
+            "blocks",
+            "100-102.parquet",
+            &ExpressionResult::Block(rows.clone()),
+        );
+        assert_eq!(
+            read(&lake, &blocks(101, 102)).await.unwrap(),
+            ExpressionResult::Block(rows[1..].to_vec())
+        );
+
+        let transaction = TransactionQueryRes {
+            chain: Some(Chain::Ethereum),
+            r#type: Some(4),
+            hash: Some(HASH),
+            block_number: Some(100),
+            from_address: Some(USDT),
+            value: Some(U256::from(10).pow(U256::from(24))),
+            gas_price: Some(u128::MAX),
+            status: Some(true),
+            r: Some(U256::MAX),
+            blob_versioned_hashes: Some(vec![HASH]),
+            access_list: Some(AccessList(vec![AccessListItem {
+                address: USDT,
+                storage_keys: vec![HASH, TRANSFER],
+            }])),
+            authorization_list: Some(vec![SignedAuthorization::new_unchecked(
+                Authorization {
+                    chain_id: 1,
+                    address: WETH,
+                    nonce: 3,
+                },
+                1,
+                U256::from(7),
+                U256::from(9),
+            )]),
+            l1_fee_scalar: Some("0.684".to_string()),
+            from_ens: Some("tether.eth".to_string()),
+            ..Default::default()
+        };
+        let result = ExpressionResult::Transaction(vec![transaction]);
+        export(&dir, "transactions", "part-0.parquet", &result);
+        let entity = Entity::Transaction(Transaction::new(
+            None,
+            Some(vec![TransactionFilter::BlockId(BlockId::Number(
+                BlockNumberOrTag::Number(100),
+            ))]),
+            TransactionField::all_variants().to_vec(),
+        ));
+        assert_eq!(read(&lake, &entity).await.unwrap(), result);
+    }
+
+    #[tokio::test]
+    async fn filters_and_fields_apply_to_the_rows_read() {
+        let dir = temp_lake("filters");
+        let lake = lake(&dir, false);
+        let rows = vec![
+            log(100, USDT),
+            log(100, WETH),
+            log(101, USDT),
+            log(103, USDT),
+        ];
+        export(
+            &dir,
+            "logs",
+            "2024/100-103.parquet",
+            &ExpressionResult::Log(rows),
+        );
+
+        let entity = Entity::Logs(Logs::new(
+            vec![
+                LogFilter::BlockRange(range(100, 102)),
+                LogFilter::EmitterAddress(USDT.into()),
+                LogFilter::EventSignature("Transfer(address,address,uint256)".to_string()),
+            ],
+            vec![LogField::BlockNumber, LogField::Chain],
+        ));
+        let projected = |number| LogQueryRes {
+            chain: Some(Chain::Ethereum),
+            block_number: Some(number),
+            ..Default::default()
+        };
+        assert_eq!(
+            read(&lake, &entity).await.unwrap(),
+            ExpressionResult::Log(vec![projected(100), projected(101)])
+        );
+    }
+
+    #[test]
+    fn row_groups_ruled_out_by_statistics_are_skipped() {
+        let dir = temp_lake("pruning");
+        let rows = vec![
+            log(100, USDT),
+            log(101, WETH),
+            log(102, USDT),
+            log(200, USDT),
+        ];
+        export(&dir, "logs", "logs.parquet", &ExpressionResult::Log(rows));
+        let files = lake_files(&dir.join("logs"), "block_number").unwrap();
+        assert_eq!(files[0].blocks, Some((100, 200)));
+
+        let kept = |filter| {
+            let scan = Scan::new(&logs(filter), &Chain::Ethereum).unwrap();
+            let row_groups = files[0].reader.metadata().row_groups();
+            row_groups
+                .iter()
+                .enumerate()
+                .filter(|(_, row_group)| scan.keeps(row_group))
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(kept(vec![LogFilter::BlockRange(range(101, 150))]), [1, 2]);
+        assert_eq!(
+            kept(vec![
+                LogFilter::BlockRange(range(100, 200)),
+                LogFilter::EmitterAddress(USDT.into()),
+            ]),
+            [0, 2, 3]
+        );
+        assert_eq!(
+            kept(vec![
+                LogFilter::BlockRange(range(100, 200)),
+                LogFilter::Topic0(HASH),
+            ]),
+            [] as [usize; 0]
+        );
+    }
+
+    #[tokio::test]
+    async fn blocks_no_file_covers_fail_the_query() {
+        let dir = temp_lake("missing");
+        export(
+            &dir,
+            "blocks",
+            "100-102.parquet",
+            &ExpressionResult::Block(vec![block(100)]),
+        );
+        export(
+            &dir,
+            "blocks",
+            "106-110.parquet",
+            &ExpressionResult::Block(vec![block(110)]),
+        );
+        let lake = lake(&dir, false);
+
+        // Blocks without rows in a file still count when its name spans them.
+        assert!(read(&lake, &blocks(101, 102)).await.is_ok());
+        assert_eq!(
+            lake_error(&lake, &blocks(100, 120)).await,
+            DataLakeError::MissingBlocks {
+                chain: Chain::Ethereum,
+                table: "blocks",
+                first: 103,
+                last: 105,
+            }
+        );
+        assert_eq!(
+            lake_error(&lake, &blocks(108, 120)).await,
+            DataLakeError::MissingBlocks {
+                chain: Chain::Ethereum,
+                table: "blocks",
+                first: 111,
+                last: 120,
+            }
+        );
+        assert_eq!(
+            lake_error(&lake, &logs(vec![LogFilter::BlockRange(range(100, 100))])).await,
+            DataLakeError::MissingBlocks {
+                chain: Chain::Ethereum,
+                table: "logs",
+                first: 100,
+                last: 100,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn queries_the_lake_cant_answer_are_rejected() {
+        let dir = temp_lake("unanswerable");
+        let lake = lake(&dir, false);
+
+        let latest = Entity::Block(Block::new(
+            Some(vec![BlockId::Number(BlockNumberOrTag::Latest)]),
+            None,
+            vec![BlockField::Number],
+        ));
+        assert_eq!(
+            lake_error(&lake, &latest).await,
+            DataLakeError::UnpinnedBlocks(Chain::Ethereum)
+        );
+        assert_eq!(
+            lake_error(&lake, &logs(vec![LogFilter::BlockHash(HASH)])).await,
+            DataLakeError::UnpinnedBlocks(Chain::Ethereum)
+        );
+        let by_hash = Entity::Transaction(Transaction::new(
+            Some(vec![HASH]),
+            None,
+            vec![TransactionField::Hash],
+        ));
+        assert_eq!(
+            lake_error(&lake, &by_hash).await,
+            DataLakeError::UnpinnedBlocks(Chain::Ethereum)
+        );
+        let base = ChainOrRpc::Chain(Chain::Base);
+        assert!(lake.read(&blocks(1, 2), &base).await.is_none());
+
+        // With `fallback`, they're left to Portal or the RPC.
+        let fallback = self::lake(&dir, true);
+        assert!(fallback.read(&latest, &eth()).await.is_none());
+        assert!(fallback.plan(&by_hash, &eth()).await.is_none());
+
+        // As a data source, the lake passes them on to the next source.
+        let Entity::Block(block) = &latest else {
+            unreachable!()
+        };
+        let err = fallback.fetch_blocks(block, &eth()).await.unwrap_err();
+        assert!(err.is::<DataSourceError>());
+        let query = SourceQuery::Blocks(block);
+        assert!(!fallback.holds(query, &eth()).await.unwrap());
+        let err = lake.fetch_blocks(block, &eth()).await.unwrap_err();
+        assert!(err.is::<DataLakeError>());
+    }
+
+    #[tokio::test]
+    async fn plans_list_the_row_groups_read() {
+        let dir = temp_lake("plan");
+        let rows = vec![log(100, USDT), log(101, WETH), log(102, USDT)];
+        export(
+            &dir,
+            "logs",
+            "2024/100-102.parquet",
+            &ExpressionResult::Log(rows),
+        );
+        export(
+            &dir,
+            "logs",
+            "2024/103-200.parquet",
+            &ExpressionResult::Log(vec![log(150, USDT)]),
+        );
+        let lake = lake(&dir, false);
+
+        let entity = logs(vec![
+            LogFilter::BlockRange(range(100, 120)),
+            LogFilter::EmitterAddress(USDT.into()),
+        ]);
+        let plan = lake.plan(&entity, &eth()).await.unwrap().unwrap();
+        assert_eq!(
+            plan,
+            LakePlan {
+                spans: vec![(100, 120)],
+                files: vec![(PathBuf::from("logs/2024/100-102.parquet"), vec![0, 2])],
+            }
+        );
+        let read = read(&lake, &entity).await.unwrap();
+        assert_eq!(read.len(), 2);
+    }
+
+    #[test]
+    fn file_names_that_are_a_span_cover_it() {
+        let named = |name: &str| named_blocks(Path::new(name));
+        assert_eq!(named("100-199.parquet"), Some((100, 199)));
+        assert_eq!(
+            named("logs/2023/18000000-18099999.parquet"),
+            Some((18_000_000, 18_099_999))
+        );
+        // Only the whole name is read as a span.
+        assert_eq!(named("v2-7.parquet"), None);
+        assert_eq!(named("eth_18000000-18099999.parquet"), None);
+        assert_eq!(named("100-199-final.parquet"), None);
+        assert_eq!(named("+100-199.parquet"), None);
+        assert_eq!(named("part-0001.parquet"), None);
+        assert_eq!(named("200-100.parquet"), None);
+        assert_eq!(named("logs.parquet"), None);
+    }
+}
@@ -0,0 +1,645 @@
+//! Where blocks, transactions and logs are fetched from. A chain's rows are
+//! offered to each source in turn: sources added with `register_data_source`,
+//! newest first, then the chain's data lake, then the chain cache, then
+//! Portal, then the RPC, which can fetch anything. A source takes the
+//! queries its `can_fetch` accepts, and passes one on to the next source by
+//! failing it with `DataSourceError::Unsupported`, as a lake missing the
+//! blocks with `fallback` set, or a cache without the segment, does.
+//! `EXPLAIN` walks the same sources, and reports the first that accepts the
+//! query and `holds` its rows. A crate embedding EQL adds a source, like a
+//! local node or a database, by implementing `DataSource` and registering it
+//! once at startup.
+
+use super::{
+    chain_cache::{ChainCache, SegmentKey},
+    data_lake::DataLake,
+    resolve_block,
+    resolve_explain::ChainPlan,
+    resolve_logs, resolve_transaction,
+};
+use crate::common::{
+    block::Block,
+    chain::ChainOrRpc,
+    entity::Entity,
+    logs::Logs,
+    query_result::{BlockQueryRes, ExpressionResult, LogQueryRes, TransactionQueryRes},
+    transaction::Transaction,
+};
+use anyhow::Result;
+use futures::{future, TryFutureExt};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// What a source fetches, boxed so `DataSource` can be used as a trait
+/// object. Futures needn't be `Send` in the browser, where nothing is.
+#[cfg(not(target_arch = "wasm32"))]
+pub type SourceFuture<'a, T> = future::BoxFuture<'a, Result<T>>;
+#[cfg(target_arch = "wasm32")]
+pub type SourceFuture<'a, T> = future::LocalBoxFuture<'a, Result<T>>;
+
+/// The sources registered with `register_data_source`, newest first.
+static SOURCES: OnceLock<RwLock<Vec<Arc<dyn DataSource>>>> = OnceLock::new();
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum DataSourceError {
+    #[error("Data source {name} can't fetch {query} on {chain}")]
+    Unsupported {
+        name: &'static str,
+        query: &'static str,
+        chain: String,
+    },
+    #[error("No data source can fetch {query} on {chain}")]
+    NoSource { query: &'static str, chain: String },
+}
+
+/// A query a source is asked whether it can answer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SourceQuery<'a> {
+    Blocks(&'a Block),
+    Transactions(&'a Transaction),
+    Logs(&'a Logs),
+}
+
+/// A source of blocks, transactions and logs. A source fetches the rows of
+/// the queries `can_fetch` accepts, with the requested fields, and the
+/// filters applied. One it turns out not to serve fails with
+/// `DataSourceError::Unsupported`, and goes to the next source; the fetch
+/// methods default to that, so one for queries the source never accepts
+/// can be left out.
+pub trait DataSource: Send + Sync {
+    /// The source's name, as `EXPLAIN` and errors show it.
+    fn name(&self) -> &'static str;
+
+    /// Whether the source can fetch the rows of `query` on `chain`.
+    fn can_fetch(&self, query: SourceQuery<'_>, chain: &ChainOrRpc) -> bool;
+
+    /// Whether the source holds the rows of a query `can_fetch` accepts, for
+    /// a source that can only tell by looking, like a cache. `EXPLAIN` asks
+    /// it where a fetch would fail and go to the next source.
+    fn holds<'a>(
+        &'a self,
+        query: SourceQuery<'a>,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, bool> {
+        let _ = (query, chain);
+        Box::pin(future::ready(Ok(true)))
+    }
+
+    fn fetch_blocks<'a>(
+        &'a self,
+        block: &'a Block,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, Vec<BlockQueryRes>> {
+        unsupported(self.name(), SourceQuery::Blocks(block), chain)
+    }
+
+    fn fetch_transactions<'a>(
+        &'a self,
+        transaction: &'a Transaction,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, Vec<TransactionQueryRes>> {
+        unsupported(self.name(), SourceQuery::Transactions(transaction), chain)
+    }
+
+    fn fetch_logs<'a>(
+        &'a self,
+        logs: &'a Logs,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, Vec<LogQueryRes>> {
+        unsupported(self.name(), SourceQuery::Logs(logs), chain)
+    }
+}
+
+/// Portal, for the chains it has a dataset of and the block ranges,
+/// filters and fields its streams serve.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PortalSource;
+
+/// The chain's RPC, which can fetch any query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RpcSource;
+
+/// Where `EXPLAIN` reports a query's rows on a chain come from.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Route {
+    /// Portal, as the query's resolver plans it.
+    Portal,
+    /// The RPC, as the query's resolver plans it.
+    Rpc,
+    /// Any other source, and what it reads.
+    Planned(ChainPlan),
+}
+
+impl<'a> SourceQuery<'a> {
+    /// The query `entity` is, if it's one of blocks, transactions or logs.
+    pub(crate) fn of(entity: &'a Entity) -> Option<Self> {
+        match entity {
+            Entity::Block(block) => Some(Self::Blocks(block)),
+            Entity::Transaction(transaction) => Some(Self::Transactions(transaction)),
+            Entity::Logs(logs) => Some(Self::Logs(logs)),
+            Entity::Account(_)
+            | Entity::Storage(_)
+            | Entity::TokenBalances(_)
+            | Entity::Call(_)
+            | Entity::Withdrawals(_)
+            | Entity::Authorizations(_)
+            | Entity::Contracts(_)
+            | Entity::StateDiffs(_)
+            | Entity::Ens(_) => None,
+        }
+    }
+
+    /// The query as an entity, for the sources that read any entity: the
+    /// lake and the cache.
+    pub(crate) fn entity(self) -> Entity {
+        match self {
+            Self::Blocks(block) => Entity::Block(block.clone()),
+            Self::Transactions(transaction) => Entity::Transaction(transaction.clone()),
+            Self::Logs(logs) => Entity::Logs(logs.clone()),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Blocks(_) => "blocks",
+            Self::Transactions(_) => "transactions",
+            Self::Logs(_) => "logs",
+        }
+    }
+}
+
+impl PortalSource {
+    pub const NAME: &'static str = "portal";
+}
+
+impl RpcSource {
+    pub const NAME: &'static str = "rpc";
+}
+
+impl DataSource for PortalSource {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn can_fetch(&self, query: SourceQuery<'_>, chain: &ChainOrRpc) -> bool {
+        match query {
+            SourceQuery::Blocks(block) => block
+                .ids()
+                .is_some_and(|ids| resolve_block::should_use_portal(chain, ids)),
+            SourceQuery::Transactions(transaction) => {
+                resolve_transaction::should_use_portal(chain, transaction)
+            }
+            SourceQuery::Logs(logs) => resolve_logs::should_use_portal(chain, logs),
+        }
+    }
+
+    fn fetch_blocks<'a>(
+        &'a self,
+        block: &'a Block,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, Vec<BlockQueryRes>> {
+        let query = SourceQuery::Blocks(block);
+        if !self.can_fetch(query, chain) {
+            return unsupported(self.name(), query, chain);
+        }
+        boxed(resolve_block::resolve_blocks_via_portal(block, chain))
+    }
+
+    fn fetch_transactions<'a>(
+        &'a self,
+        transaction: &'a Transaction,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, Vec<TransactionQueryRes>> {
+        let query = SourceQuery::Transactions(transaction);
+        if !self.can_fetch(query, chain) {
+            return unsupported(self.name(), query, chain);
+        }
+        boxed(resolve_transaction::resolve_transactions_via_portal(
+            transaction,
+            chain,
+        ))
+    }
+
+    fn fetch_logs<'a>(
+        &'a self,
+        logs: &'a Logs,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, Vec<LogQueryRes>> {
+        let query = SourceQuery::Logs(logs);
+        if !self.can_fetch(query, chain) {
+            return unsupported(self.name(), query, chain);
+        }
+        boxed(resolve_logs::resolve_logs_via_portal(logs, chain))
+    }
+}
+
+impl DataSource for RpcSource {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn can_fetch(&self, _query: SourceQuery<'_>, _chain: &ChainOrRpc) -> bool {
+        true
+    }
+
+    fn fetch_blocks<'a>(
+        &'a self,
+        block: &'a Block,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, Vec<BlockQueryRes>> {
+        boxed(resolve_block::resolve_blocks_via_rpc(block, chain))
+    }
+
+    fn fetch_transactions<'a>(
+        &'a self,
+        transaction: &'a Transaction,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, Vec<TransactionQueryRes>> {
+        boxed(resolve_transaction::resolve_transactions_via_rpc(
+            transaction,
+            chain,
+        ))
+    }
+
+    fn fetch_logs<'a>(
+        &'a self,
+        logs: &'a Logs,
+        chain: &'a ChainOrRpc,
+    ) -> SourceFuture<'a, Vec<LogQueryRes>> {
+        boxed(resolve_logs::resolve_logs_via_rpc(logs, chain))
+    }
+}
+
+/// Adds `source` ahead of every source registered before it, and of the
+/// lake, the cache, Portal and the RPC, for the rest of the process.
+pub fn register_data_source(source: Arc<dyn DataSource>) {
+    SOURCES
+        .get_or_init(|| RwLock::new(Vec::new()))
+        .write()
+        .expect("data sources lock")
+        .insert(0, source);
+}
+
+/// The rows of `query` on `chain`, from the first source that fetches them.
+/// Rows Portal or the RPC fetch are kept in the chain cache when their
+/// blocks are finalized.
+pub(crate) async fn fetch(query: SourceQuery<'_>, chain: &ChainOrRpc) -> Result<ExpressionResult> {
+    let cache = ChainCache::from_settings();
+    let sources = sources(DataLake::from_settings()?, cache.clone());
+    fetch_first(&sources, cache.as_ref(), query, chain).await
+}
+
+/// The route `fetch` takes for `query` on `chain`, as `EXPLAIN` reports it.
+pub(crate) async fn plan_route(query: SourceQuery<'_>, chain: &ChainOrRpc) -> Result<Route> {
+    let lake = DataLake::from_settings()?;
+    let sources = sources(lake.clone(), ChainCache::from_settings());
+    first_route(&sources, lake.as_ref(), query, chain).await
+}
+
+/// The sources a query is offered to, in order.
+fn sources(lake: Option<DataLake>, cache: Option<ChainCache>) -> Vec<Arc<dyn DataSource>> {
+    let mut sources = SOURCES
+        .get()
+        .map(|sources| sources.read().expect("data sources lock").clone())
+        .unwrap_or_default();
+    if let Some(lake) = lake {
+        sources.push(Arc::new(lake));
+    }
+    if let Some(cache) = cache {
+        sources.push(Arc::new(cache));
+    }
+    sources.push(Arc::new(PortalSource));
+    sources.push(Arc::new(RpcSource));
+    sources
+}
+
+async fn fetch_first(
+    sources: &[Arc<dyn DataSource>],
+    cache: Option<&ChainCache>,
+    query: SourceQuery<'_>,
+    chain: &ChainOrRpc,
+) -> Result<ExpressionResult> {
+    for source in sources {
+        if !source.can_fetch(query, chain) {
+            continue;
+        }
+        let result = match fetch_from(source.as_ref(), query, chain).await {
+            Err(err) if is_unsupported(&err) => continue,
+            result => result?,
+        };
+        let live = matches!(source.name(), PortalSource::NAME | RpcSource::NAME);
+        if let Some(cache) = cache.filter(|_| live) {
+            cache.keep(&query.entity(), chain, &result).await;
+        }
+        return Ok(result);
+    }
+    Err(DataSourceError::NoSource {
+        query: query.name(),
+        chain: chain_text(chain),
+    }
+    .into())
+}
+
+async fn first_route(
+    sources: &[Arc<dyn DataSource>],
+    lake: Option<&DataLake>,
+    query: SourceQuery<'_>,
+    chain: &ChainOrRpc,
+) -> Result<Route> {
+    for source in sources {
+        if !source.can_fetch(query, chain) || !source.holds(query, chain).await? {
+            continue;
+        }
+        return Ok(match source.name() {
+            PortalSource::NAME => Route::Portal,
+            RpcSource::NAME => Route::Rpc,
+            DataLake::NAME => {
+                let plan = match lake {
+                    Some(lake) => lake.plan(&query.entity(), chain).await,
+                    None => None,
+                };
+                let Some(plan) = plan else {
+                    continue;
+                };
+                Route::Planned(ChainPlan::lake(plan?))
+            }
+            ChainCache::NAME => {
+                let Some(key) = SegmentKey::new(&query.entity(), chain) else {
+                    continue;
+                };
+                Route::Planned(ChainPlan::cache(&key))
+            }
+            name => Route::Planned(ChainPlan::other(name)),
+        });
+    }
+    Err(DataSourceError::NoSource {
+        query: query.name(),
+        chain: chain_text(chain),
+    }
+    .into())
+}
+
+/// `source`'s rows of `query` on `chain`, whichever kind they are.
+fn fetch_from<'a>(
+    source: &'a dyn DataSource,
+    query: SourceQuery<'a>,
+    chain: &'a ChainOrRpc,
+) -> SourceFuture<'a, ExpressionResult> {
+    match query {
+        SourceQuery::Blocks(block) => boxed(
+            source
+                .fetch_blocks(block, chain)
+                .map_ok(ExpressionResult::Block),
+        ),
+        SourceQuery::Transactions(transaction) => boxed(
+            source
+                .fetch_transactions(transaction, chain)
+                .map_ok(ExpressionResult::Transaction),
+        ),
+        SourceQuery::Logs(logs) => {
+            boxed(source.fetch_logs(logs, chain).map_ok(ExpressionResult::Log))
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn boxed<'a, T>(
+    fetch: impl std::future::Future<Output = Result<T>> + Send + 'a,
+) -> SourceFuture<'a, T> {
+    Box::pin(fetch)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn boxed<'a, T>(
+    fetch: impl std::future::Future<Output = Result<T>> + 'a,
+) -> SourceFuture<'a, T> {
+    Box::pin(fetch)
+}
+
+/// A fetch failing with `DataSourceError::Unsupported`, which passes `query`
+/// on to the next source.
+pub(crate) fn unsupported<'a, T: 'a>(
+    name: &'static str,
+    query: SourceQuery<'_>,
+    chain: &ChainOrRpc,
+) -> SourceFuture<'a, T> {
+    let err = DataSourceError::Unsupported {
+        name,
+        query: query.name(),
+        chain: chain_text(chain),
+    };
+    Box::pin(async move { Err(err.into()) })
+}
+
+fn is_unsupported(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref(),
+        Some(DataSourceError::Unsupported { .. })
+    )
+}
+
+fn chain_text(chain: &ChainOrRpc) -> String {
+    match chain {
+        ChainOrRpc::Chain(chain) => chain.to_string(),
+        ChainOrRpc::Rpc(url) => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{
+        block::{BlockId, BlockRange},
+        chain::Chain,
+        logs::{LogField, LogFilter},
+    };
+    use crate::interpreter::backend::resolve_logs::{plan_log_query, resolve_log_query};
+    use alloy::{eips::BlockNumberOrTag, primitives::b256};
+    use std::{env, fs};
+
+    /// Answers log queries on its own URL only, so registering it doesn't
+    /// change the source of any other test's queries.
+    struct StubSource;
+
+    /// Accepts every query on the stub's URL, like a cache, but holds none
+    /// of them.
+    struct EmptySource;
+
+    const STUB_URL: &str = "http://stub-data-source.test/";
+
+    impl DataSource for StubSource {
+        fn name(&self) -> &'static str {
+            "stub"
+        }
+
+        fn can_fetch(&self, query: SourceQuery<'_>, chain: &ChainOrRpc) -> bool {
+            matches!(query, SourceQuery::Logs(_))
+                && matches!(chain, ChainOrRpc::Rpc(url) if url.as_str() == STUB_URL)
+        }
+
+        fn fetch_logs<'a>(
+            &'a self,
+            logs: &'a Logs,
+            _chain: &'a ChainOrRpc,
+        ) -> SourceFuture<'a, Vec<LogQueryRes>> {
+            let row = LogQueryRes {
+                block_number: logs.fields().contains(&LogField::BlockNumber).then_some(7),
+                ..Default::default()
+            };
+            Box::pin(async move { Ok(vec![row]) })
+        }
+    }
+
+    impl DataSource for EmptySource {
+        fn name(&self) -> &'static str {
+            "empty"
+        }
+
+        fn can_fetch(&self, _query: SourceQuery<'_>, chain: &ChainOrRpc) -> bool {
+            matches!(chain, ChainOrRpc::Rpc(url) if url.as_str() == STUB_URL)
+        }
+
+        fn holds<'a>(
+            &'a self,
+            _query: SourceQuery<'a>,
+            _chain: &'a ChainOrRpc,
+        ) -> SourceFuture<'a, bool> {
+            Box::pin(async { Ok(false) })
+        }
+    }
+
+    fn stub_chain() -> ChainOrRpc {
+        ChainOrRpc::Rpc(STUB_URL.parse().unwrap())
+    }
+
+    fn logs(filter: LogFilter) -> Logs {
+        Logs::new(vec![filter], vec![LogField::BlockNumber])
+    }
+
+    fn block_range() -> LogFilter {
+        LogFilter::BlockRange(BlockRange::new(
+            BlockNumberOrTag::Number(4_638_657),
+            Some(BlockNumberOrTag::Number(4_638_758)),
+        ))
+    }
+
+    /// The route of `query` on `chain` through the registered sources,
+    /// Portal and the RPC, whatever lake or cache the settings name.
+    async fn route(query: SourceQuery<'_>, chain: &ChainOrRpc) -> Route {
+        first_route(&sources(None, None), None, query, chain)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn portal_fetches_what_it_serves_and_the_rpc_the_rest() {
+        let eth = ChainOrRpc::Chain(Chain::Ethereum);
+        let by_range = logs(block_range());
+        let by_hash = logs(LogFilter::BlockHash(b256!(
+            "72546b3ca8ef0dfb85fe66d19645e44cb519858c72fbcad0e1c1699256fed890"
+        )));
+
+        assert_eq!(
+            route(SourceQuery::Logs(&by_range), &eth).await,
+            Route::Portal
+        );
+        assert_eq!(route(SourceQuery::Logs(&by_hash), &eth).await, Route::Rpc);
+        let rpc = ChainOrRpc::Rpc("http://localhost:8545".parse().unwrap());
+        assert_eq!(route(SourceQuery::Logs(&by_range), &rpc).await, Route::Rpc);
+    }
+
+    #[tokio::test]
+    async fn registered_sources_fetch_what_they_can() {
+        register_data_source(Arc::new(StubSource));
+        let logs = logs(block_range());
+
+        let rows = resolve_log_query(&logs, &[stub_chain()]).await.unwrap();
+        assert_eq!(
+            rows,
+            vec![LogQueryRes {
+                block_number: Some(7),
+                ..Default::default()
+            }]
+        );
+        let plan = plan_log_query(&logs, &stub_chain()).await.unwrap();
+        assert_eq!(plan, ChainPlan::other("stub"));
+
+        // What it can't fetch still goes to Portal.
+        let eth = ChainOrRpc::Chain(Chain::Ethereum);
+        assert_eq!(route(SourceQuery::Logs(&logs), &eth).await, Route::Portal);
+    }
+
+    #[tokio::test]
+    async fn fetching_what_a_source_doesnt_serve_fails() {
+        let block = Block::new(
+            Some(vec![BlockId::Number(BlockNumberOrTag::Number(1))]),
+            None,
+            vec![],
+        );
+        let err = StubSource
+            .fetch_blocks(&block, &stub_chain())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<DataSourceError>().unwrap(),
+            DataSourceError::Unsupported {
+                name: "stub",
+                query: "blocks",
+                chain: STUB_URL.to_string(),
+            }
+        );
+
+        let rpc = ChainOrRpc::Rpc("http://localhost:8545".parse().unwrap());
+        let logs = logs(block_range());
+        assert!(PortalSource.fetch_logs(&logs, &rpc).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn queries_a_source_passes_on_go_to_the_next() {
+        let sources: [Arc<dyn DataSource>; 2] = [Arc::new(EmptySource), Arc::new(StubSource)];
+        let logs = logs(block_range());
+        let query = SourceQuery::Logs(&logs);
+
+        let rows = fetch_first(&sources, None, query, &stub_chain()).await;
+        assert_eq!(
+            rows.unwrap(),
+            ExpressionResult::Log(vec![LogQueryRes {
+                block_number: Some(7),
+                ..Default::default()
+            }])
+        );
+        let route = first_route(&sources, None, query, &stub_chain()).await;
+        assert_eq!(route.unwrap(), Route::Planned(ChainPlan::other("stub")));
+
+        let err = fetch_first(&sources[..1], None, query, &stub_chain())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<DataSourceError>().unwrap(),
+            DataSourceError::NoSource {
+                query: "logs",
+                chain: STUB_URL.to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn the_cache_serves_the_rows_it_holds() {
+        let dir = env::temp_dir().join(format!("eql-source-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let cache = ChainCache::new(dir);
+        let sources = sources(None, Some(cache.clone()));
+        let logs = Logs::new(vec![block_range()], vec![LogField::Address]);
+        let query = SourceQuery::Logs(&logs);
+        let eth = ChainOrRpc::Chain(Chain::Ethereum);
+
+        let route = first_route(&sources, None, query, &eth).await.unwrap();
+        assert_eq!(route, Route::Portal);
+
+        let key = SegmentKey::new(&query.entity(), &eth).unwrap();
+        cache.write(&key, &ExpressionResult::Log(vec![])).await;
+        let route = first_route(&sources, None, query, &eth).await.unwrap();
+        assert_eq!(route, Route::Planned(ChainPlan::cache(&key)));
+        let rows = fetch_first(&sources, Some(&cache), query, &eth).await;
+        assert_eq!(rows.unwrap(), ExpressionResult::Log(vec![]));
+    }
+}
@@ -0,0 +1,166 @@
+use alloy::primitives::Address;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::PathBuf,
+    sync::{Mutex, OnceLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// How long a looked-up primary name is trusted for, in seconds. Names
+/// change rarely, but they do change, and an address without one may set
+/// one at any time; a day keeps repeated queries off the RPC without
+/// hiding either for long.
+const NAME_TTL: u64 = 24 * 60 * 60;
+
+const CACHE_FILE: &str = "ens-names.json";
+
+/// The process-wide name cache, loaded from `cache_path` on first use.
+static ENS_NAMES: OnceLock<Mutex<EnsNameCache>> = OnceLock::new();
+
+/// The verified primary names of the addresses looked up lately, kept on
+/// disk so later queries, and later runs, skip the RPC for them. An address
+/// without a verified name is remembered too, as `None`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct EnsNameCache {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    names: BTreeMap<Address, CachedName>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedName {
+    name: Option<String>,
+    /// When the name was looked up, in seconds since the Unix epoch.
+    checked_at: u64,
+}
+
+impl EnsNameCache {
+    /// The cache stored at `path`. A file that's missing or unreadable makes
+    /// an empty cache: it only saves lookups, so it's never worth failing a
+    /// query over.
+    pub(crate) fn load(path: Option<PathBuf>) -> Self {
+        let names = path
+            .as_ref()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|bytes| serde_json::from_slice::<EnsNameCache>(&bytes).ok())
+            .map(|cache| cache.names)
+            .unwrap_or_default();
+        Self { path, names }
+    }
+
+    /// The name looked up for `address` within the last `NAME_TTL` seconds
+    /// of `now`: `Some(None)` when it has none, `None` when it needs looking
+    /// up again.
+    pub(crate) fn get(&self, address: &Address, now: u64) -> Option<Option<String>> {
+        self.names
+            .get(address)
+            .filter(|cached| now.saturating_sub(cached.checked_at) < NAME_TTL)
+            .map(|cached| cached.name.clone())
+    }
+
+    pub(crate) fn insert(&mut self, address: Address, name: Option<String>, now: u64) {
+        self.names.insert(
+            address,
+            CachedName {
+                name,
+                checked_at: now,
+            },
+        );
+    }
+
+    /// Writes the cache back to its file, dropping expired entries. Like
+    /// `load`, a failed write is ignored.
+    pub(crate) fn save(&mut self, now: u64) {
+        self.names
+            .retain(|_, cached| now.saturating_sub(cached.checked_at) < NAME_TTL);
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_vec(self) {
+            let _ = fs::write(path, json);
+        }
+    }
+}
+
+/// The process-wide name cache.
+pub(crate) fn ens_names() -> &'static Mutex<EnsNameCache> {
+    ENS_NAMES.get_or_init(|| Mutex::new(EnsNameCache::load(cache_path())))
+}
+
+/// Where the name cache lives: the `EQL_ENS_CACHE_PATH` environment
+/// variable, or `$HOME/.eql/ens-names.json`. Neither set keeps the cache in
+/// memory only.
+fn cache_path() -> Option<PathBuf> {
+    if let Ok(path) = env::var("EQL_ENS_CACHE_PATH") {
+        return Some(PathBuf::from(path));
+    }
+    env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".eql").join(CACHE_FILE))
+}
+
+/// Seconds since the Unix epoch.
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::address;
+
+    const VITALIK: Address = address!("d8da6bf26964af9d7eed9e03e53415d37aa96045");
+    const NOBODY: Address = address!("1000000000000000000000000000000000000001");
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir()
+            .join(format!("eql-ens-cache-{}", std::process::id()))
+            .join(name)
+    }
+
+    #[test]
+    fn names_and_their_absence_survive_a_reload() {
+        let path = temp_path("reload.json");
+        let mut cache = EnsNameCache::load(Some(path.clone()));
+        cache.insert(VITALIK, Some("vitalik.eth".to_string()), 1_000);
+        cache.insert(NOBODY, None, 1_000);
+        cache.save(1_000);
+
+        let reloaded = EnsNameCache::load(Some(path.clone()));
+        assert_eq!(
+            reloaded.get(&VITALIK, 1_001),
+            Some(Some("vitalik.eth".to_string()))
+        );
+        assert_eq!(reloaded.get(&NOBODY, 1_001), Some(None));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn expired_names_are_looked_up_again() {
+        let mut cache = EnsNameCache::load(None);
+        cache.insert(VITALIK, Some("vitalik.eth".to_string()), 1_000);
+        assert!(cache.get(&VITALIK, 1_000 + NAME_TTL - 1).is_some());
+        assert_eq!(cache.get(&VITALIK, 1_000 + NAME_TTL), None);
+
+        cache.save(1_000 + NAME_TTL);
+        assert!(cache.names.is_empty(), "expired entries aren't kept");
+    }
+
+    #[test]
+    fn an_unreadable_file_makes_an_empty_cache() {
+        let path = temp_path("garbage.json");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, b"not json").unwrap();
+        let cache = EnsNameCache::load(Some(path.clone()));
+        assert_eq!(cache.get(&VITALIK, 0), None);
+        let _ = fs::remove_file(path);
+    }
+}
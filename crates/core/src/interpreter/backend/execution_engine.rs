@@ -1,7 +1,22 @@
 use super::{
-    resolve_account::resolve_account_query, resolve_block::resolve_block_query,
-    resolve_logs::resolve_log_query, resolve_transaction::resolve_transaction_query,
+    chain_cache::{ChainCache, SegmentKey},
+    data_source::SourceQuery,
+    resolve_account::resolve_account_query,
+    resolve_authorizations::resolve_authorizations_query,
+    resolve_block::resolve_block_query,
+    resolve_call::resolve_call_query,
+    resolve_catalog::resolve_catalog_query,
+    resolve_contracts::resolve_contracts_query,
+    resolve_ens::{resolve_ens_query, resolve_query_names},
+    resolve_explain::resolve_explain_query,
+    resolve_logs::resolve_log_query,
+    resolve_state_diffs::resolve_state_diffs_query,
+    resolve_storage::resolve_storage_query,
+    resolve_token_balance::resolve_token_balance_query,
+    resolve_transaction::resolve_transaction_query,
+    resolve_withdrawals::resolve_withdrawals_query,
 };
+use crate::common::chain::ChainOrRpc;
 use crate::common::{
     entity::Entity,
     query_result::{ExpressionResult, QueryResult},
@@ -10,6 +25,8 @@ use crate::common::{
 };
 use crate::interpreter::frontend::sql::EqlSqlError;
 use anyhow::Result;
+use futures::future::try_join_all;
+use std::slice;
 
 pub struct ExecutionEngine;
 
@@ -31,9 +48,13 @@ impl ExecutionEngine {
 
         for expression in expressions {
             match expression {
-                Expression::Get(get_expr) => {
+                // ENS names in address filters are resolved up front, in one
+                // batch, so every resolver after this only sees addresses.
+                Expression::Get(mut get_expr) => {
+                    let resolved_names = resolve_query_names(&mut get_expr.entity).await?;
                     let result = self.run_get_expr(&get_expr).await?;
-                    query_results.push(QueryResult::new(result));
+                    query_results
+                        .push(QueryResult::new(result).with_resolved_names(resolved_names));
                 }
                 // `SET rpc_<chain> = '<url>'` applies a session-scoped RPC
                 // override rather than resolving into rows, so it produces
@@ -43,6 +64,18 @@ impl ExecutionEngine {
                 Expression::Set(set_expr) => {
                     crate::common::config::Config::set_session_rpc(&set_expr.chain, set_expr.url);
                 }
+                Expression::Catalog(catalog_expr) => {
+                    let result = resolve_catalog_query(&catalog_expr);
+                    query_results.push(QueryResult::new(result));
+                }
+                Expression::Explain(mut explain_expr) => {
+                    let resolved_names = resolve_query_names(&mut explain_expr.get.entity).await?;
+                    let plan = resolve_explain_query(&explain_expr).await?;
+                    query_results.push(
+                        QueryResult::new(ExpressionResult::Plan(plan))
+                            .with_resolved_names(resolved_names),
+                    );
+                }
             }
         }
 
@@ -50,23 +83,10 @@ impl ExecutionEngine {
     }
 
     async fn run_get_expr(&self, expr: &GetExpression) -> Result<ExpressionResult> {
-        let mut result = match &expr.entity {
-            Entity::Block(block) => {
-                ExpressionResult::Block(resolve_block_query(block, &expr.chains).await?)
-            }
-            Entity::Account(account) => {
-                ExpressionResult::Account(resolve_account_query(account, &expr.chains).await?)
-            }
-            Entity::Transaction(transaction) => ExpressionResult::Transaction(
-                resolve_transaction_query(transaction, &expr.chains).await?,
-            ),
-            Entity::Logs(logs) => {
-                ExpressionResult::Log(resolve_log_query(logs, &expr.chains).await?)
-            }
-        };
+        let mut result = resolve_entity(&expr.entity, &expr.chains).await?;
 
         // v1 shape: rows for every chain in `expr.chains` are already
-        // flattened into `result` by the resolvers above, so `LIMIT` caps
+        // flattened into `result` by `resolve_entity`, so `LIMIT` caps
         // the combined row count across all chains, not per chain. It also
         // truncates after the full fetch rather than pushing the limit down
         // to Portal.
@@ -103,6 +123,98 @@ impl ExecutionEngine {
     }
 }
 
+/// Fetches every row of `entity` across `chains`, before `LIMIT`. Blocks,
+/// transactions and logs come from the first data source that has them,
+/// the lake and the chain cache among them (see `data_source`). The other
+/// entities' rows of finalized blocks come from the chain cache when it has
+/// them, a chain at a time (see `chain_cache`).
+pub(crate) async fn resolve_entity(
+    entity: &Entity,
+    chains: &[ChainOrRpc],
+) -> Result<ExpressionResult> {
+    let cache = ChainCache::from_settings();
+    let cacheable = SourceQuery::of(entity).is_none()
+        && chains
+            .iter()
+            .any(|chain| SegmentKey::new(entity, chain).is_some());
+    let Some(cache) = cache.filter(|_| cacheable) else {
+        return fetch_entity(entity, chains).await;
+    };
+
+    let mut results = try_join_all(
+        chains
+            .iter()
+            .map(|chain| resolve_cached(&cache, entity, chain)),
+    )
+    .await?
+    .into_iter();
+    let Some(mut result) = results.next() else {
+        return fetch_entity(entity, chains).await;
+    };
+    for other in results {
+        result.append(other);
+    }
+    Ok(result)
+}
+
+/// The rows of `entity` on `chain`: its segment's, if it has one, else
+/// fetched, and kept for next time if their blocks are finalized.
+async fn resolve_cached(
+    cache: &ChainCache,
+    entity: &Entity,
+    chain: &ChainOrRpc,
+) -> Result<ExpressionResult> {
+    let Some(key) = SegmentKey::new(entity, chain) else {
+        return fetch_entity(entity, slice::from_ref(chain)).await;
+    };
+    if let Some(result) = cache.read(&key, entity).await {
+        return Ok(result);
+    }
+    let (result, finalized) = futures::join!(
+        fetch_entity(entity, slice::from_ref(chain)),
+        key.is_finalized()
+    );
+    let result = result?;
+    if finalized {
+        cache.write(&key, &result).await;
+    }
+    Ok(result)
+}
+
+/// Fetches every row of `entity` across `chains` with its resolver.
+async fn fetch_entity(entity: &Entity, chains: &[ChainOrRpc]) -> Result<ExpressionResult> {
+    Ok(match entity {
+        Entity::Block(block) => ExpressionResult::Block(resolve_block_query(block, chains).await?),
+        Entity::Account(account) => {
+            ExpressionResult::Account(resolve_account_query(account, chains).await?)
+        }
+        Entity::Transaction(transaction) => {
+            ExpressionResult::Transaction(resolve_transaction_query(transaction, chains).await?)
+        }
+        Entity::Logs(logs) => ExpressionResult::Log(resolve_log_query(logs, chains).await?),
+        Entity::Storage(storage) => {
+            ExpressionResult::Storage(resolve_storage_query(storage, chains).await?)
+        }
+        Entity::TokenBalances(balances) => {
+            ExpressionResult::TokenBalance(resolve_token_balance_query(balances, chains).await?)
+        }
+        Entity::Call(call) => ExpressionResult::Call(resolve_call_query(call, chains).await?),
+        Entity::Withdrawals(withdrawals) => {
+            ExpressionResult::Withdrawal(resolve_withdrawals_query(withdrawals, chains).await?)
+        }
+        Entity::Authorizations(authorizations) => ExpressionResult::Authorization(
+            resolve_authorizations_query(authorizations, chains).await?,
+        ),
+        Entity::Contracts(contracts) => {
+            ExpressionResult::Contract(resolve_contracts_query(contracts, chains).await?)
+        }
+        Entity::StateDiffs(state_diffs) => {
+            ExpressionResult::StateDiff(resolve_state_diffs_query(state_diffs, chains).await?)
+        }
+        Entity::Ens(ens) => ExpressionResult::Ens(resolve_ens_query(ens, chains).await?),
+    })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -118,7 +230,7 @@ mod test {
         types::{Expression, GetExpression},
     };
     use alloy::{
-        eips::BlockNumberOrTag,
+        eips::{eip2930::AccessList, BlockNumberOrTag},
         primitives::{address, b256, bloom, bytes, U256},
     };
     use pretty_assertions::assert_eq;
@@ -134,7 +246,9 @@ mod test {
                         BlockNumberOrTag::Number(4638757),
                         Some(BlockNumberOrTag::Number(4638758)),
                     )),
-                    LogFilter::EmitterAddress(address!("dac17f958d2ee523a2206206994597c13d831ec7")),
+                    LogFilter::EmitterAddress(
+                        address!("dac17f958d2ee523a2206206994597c13d831ec7").into(),
+                    ),
                     LogFilter::Topic0(b256!(
                         "cb8241adb0c3fdb35b70c24ce35c5eb0c17af7431c99f827d44a445ca624176a"
                     )),
@@ -170,6 +284,7 @@ mod test {
             log_index: Some(5),
             removed: Some(false),
             chain: Some(Chain::Ethereum),
+            address_ens: None,
         }];
 
         match execution_result {
@@ -208,6 +323,12 @@ mod test {
                     "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
                 )),
                 size: Some(U256::from(537)),
+                miner: Some(address!("05a56e2d52c817161883f50c441c3228cfe54d9f")),
+                gas_used: Some(0),
+                gas_limit: Some(5000),
+                nonce: Some(0x539bd4979fef1ec4),
+                difficulty: Some(U256::from(17171480576_u64)),
+                transaction_count: Some(0),
                 state_root: Some(b256!(
                     "d67e4d450343046425ae4271474353857ab860dbc0a1dde64b41b5cd3a532bf3"
                 )),
@@ -292,16 +413,37 @@ mod test {
                 gas_limit: Some(21000),
                 effective_gas_price: Some(10209184711_u128),
                 status: Some(true),
+                gas_used: Some(21000),
+                // Depends on the transaction's place in its block; checked below.
+                cumulative_gas_used: None,
+                contract_address: None,
+                logs_count: Some(0),
+                fee: Some(U256::from(214392878931000_u128)),
                 chain_id: Some(1),
                 v: Some(false),
                 r: Some(U256::from_str("105656622829170817033829205634607968479218860016837137132236076370603621041980").unwrap()),
                 s: Some(U256::from_str("15038977765364444198936700207894720753481416564436657360670639019817488048130").unwrap()),
                 max_fee_per_blob_gas: None,
+                blob_versioned_hashes: None,
+                blob_count: Some(0),
+                blob_gas_used: None,
+                blob_gas_price: None,
                 max_fee_per_gas: Some(10209184711),
                 max_priority_fee_per_gas: Some(0),
+                access_list: Some(AccessList::default()),
                 y_parity: Some(false),
                 chain: Some(Chain::Ethereum),
                 authorization_list: None,
+                l1_fee: None,
+                l1_gas_used: None,
+                l1_gas_price: None,
+                l1_fee_scalar: None,
+                source_hash: None,
+                mint: None,
+                is_system_tx: None,
+                gas_used_for_l1: None,
+                from_ens: None,
+                to_ens: None,
             },
             TransactionQueryRes {
                 r#type: Some(2),
@@ -317,22 +459,49 @@ mod test {
                 gas_limit: Some(21000),
                 effective_gas_price: Some(10209184711_u128),
                 status: Some(true),
+                gas_used: Some(21000),
+                // Depends on the transaction's place in its block; checked below.
+                cumulative_gas_used: None,
+                contract_address: None,
+                logs_count: Some(0),
+                fee: Some(U256::from(214392878931000_u128)),
                 chain_id: Some(1),
                 v: Some(false),
                 r: Some(U256::from_str("105656622829170817033829205634607968479218860016837137132236076370603621041980").unwrap()),
                 s: Some(U256::from_str("15038977765364444198936700207894720753481416564436657360670639019817488048130").unwrap()),
                 max_fee_per_blob_gas: None,
+                blob_versioned_hashes: None,
+                blob_count: Some(0),
+                blob_gas_used: None,
+                blob_gas_price: None,
                 max_fee_per_gas: Some(10209184711),
                 max_priority_fee_per_gas: Some(0),
+                access_list: Some(AccessList::default()),
                 y_parity: Some(false),
                 chain: Some(Chain::Ethereum),
                 authorization_list: None,
+                l1_fee: None,
+                l1_gas_used: None,
+                l1_gas_price: None,
+                l1_fee_scalar: None,
+                source_hash: None,
+                mint: None,
+                is_system_tx: None,
+                gas_used_for_l1: None,
+                from_ens: None,
+                to_ens: None,
             }])
         ];
 
         let result = execution_engine.run(expressions).await;
         match result {
-            Ok(results) => {
+            Ok(mut results) => {
+                if let ExpressionResult::Transaction(txs) = &mut results[0].result {
+                    for tx in txs {
+                        let cumulative = tx.cumulative_gas_used.take();
+                        assert!(cumulative >= tx.gas_used);
+                    }
+                }
                 assert_eq!(results[0].result, expected[0]);
             }
             Err(_) => panic!("Error"),
@@ -354,9 +523,9 @@ mod test {
                         BlockNumberOrTag::Number(20000000),
                         Some(BlockNumberOrTag::Number(20000000)),
                     ))),
-                    TransactionFilter::From(EqualityFilter::Eq(address!(
-                        "95222290dd7278aa3ddd389cc1e1d165cc4bafe5"
-                    ))),
+                    TransactionFilter::From(EqualityFilter::Eq(
+                        address!("95222290dd7278aa3ddd389cc1e1d165cc4bafe5").into(),
+                    )),
                 ]),
                 TransactionField::all_variants().to_vec(),
             )),
@@ -1,6 +1,22 @@
+mod ccip;
+pub mod chain_cache;
+mod data_lake;
+pub mod data_source;
+mod ens_cache;
+mod multicall;
 mod resolve_account;
+mod resolve_authorizations;
 mod resolve_block;
+mod resolve_call;
+mod resolve_catalog;
+mod resolve_contracts;
+mod resolve_ens;
+mod resolve_explain;
 mod resolve_logs;
 pub mod resolve_portal;
+mod resolve_state_diffs;
+mod resolve_storage;
+mod resolve_token_balance;
 mod resolve_transaction;
+mod resolve_withdrawals;
 pub mod execution_engine;
@@ -0,0 +1,140 @@
+//! Batches read-only contract calls through Multicall3's `aggregate3`, so a
+//! query touching hundreds of (contract, argument) pairs costs a handful of
+//! `eth_call`s instead of one each.
+
+use super::resolve_account::{chain_label, state_error};
+use crate::common::chain::ChainOrRpc;
+use alloy::{
+    eips::{BlockId as RpcBlockId, BlockNumberOrTag},
+    primitives::{address, Address, Bytes},
+    providers::{Provider, RootProvider},
+    rpc::types::TransactionRequest,
+    sol,
+    sol_types::SolCall,
+    transports::http::{Client, Http},
+};
+use anyhow::Result;
+
+sol! {
+    /// The subset of Multicall3 used here.
+    contract Multicall3 {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct Call3Result {
+            bool success;
+            bytes returnData;
+        }
+
+        function aggregate3(Call3[] calldata calls) external payable returns (Call3Result[] memory returnData);
+    }
+}
+
+/// Multicall3 (`0xcA11bde05977b3631167028862bE2a173976CA11`), deployed at the
+/// same address on nearly every EVM chain.
+pub(crate) const MULTICALL3_ADDRESS: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+/// Calls per `aggregate3`. Big enough that a typical query is one request,
+/// small enough to stay under providers' `eth_call` gas caps.
+pub(crate) const CALLS_PER_AGGREGATE: usize = 500;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MulticallError {
+    #[error("{rpc} has no Multicall3 ({MULTICALL3_ADDRESS}) at block {block}; token reads need it deployed")]
+    NotDeployed { rpc: String, block: String },
+}
+
+/// The `eth_call`s `aggregate` makes for `calls` calls.
+pub(crate) fn aggregate_calls(calls: u64) -> u64 {
+    calls.div_ceil(CALLS_PER_AGGREGATE as u64)
+}
+
+/// How one call of an `aggregate3` went: its output, or the data it
+/// reverted with.
+pub(crate) type CallResult = std::result::Result<Bytes, Bytes>;
+
+/// Runs every `(target, calldata)` pair at `block` (`None` is the latest
+/// block), returning each call's output in order. A call that reverts (or
+/// targets an address without code) is `None` rather than failing the rest.
+pub(crate) async fn aggregate(
+    provider: &RootProvider<Http<Client>>,
+    chain: &ChainOrRpc,
+    calls: &[(Address, Bytes)],
+    block: Option<u64>,
+) -> Result<Vec<Option<Bytes>>> {
+    let results = aggregate_results(provider, chain, calls, block).await?;
+    Ok(results
+        .into_iter()
+        .map(|result| result.ok().filter(|output| !output.is_empty()))
+        .collect())
+}
+
+/// `aggregate`, keeping the data failed calls reverted with, which is how
+/// an EIP-3668 contract asks for offchain data (see `ccip`).
+pub(crate) async fn aggregate_results(
+    provider: &RootProvider<Http<Client>>,
+    chain: &ChainOrRpc,
+    calls: &[(Address, Bytes)],
+    block: Option<u64>,
+) -> Result<Vec<CallResult>> {
+    let block_id =
+        RpcBlockId::Number(block.map_or(BlockNumberOrTag::Latest, BlockNumberOrTag::Number));
+    let mut outputs = Vec::with_capacity(calls.len());
+
+    for chunk in calls.chunks(CALLS_PER_AGGREGATE) {
+        let input = Multicall3::aggregate3Call {
+            calls: chunk
+                .iter()
+                .map(|(target, data)| Multicall3::Call3 {
+                    target: *target,
+                    allowFailure: true,
+                    callData: data.clone(),
+                })
+                .collect(),
+        }
+        .abi_encode();
+        let tx = TransactionRequest::default()
+            .to(MULTICALL3_ADDRESS)
+            .input(Bytes::from(input).into());
+        let output = provider
+            .call(&tx)
+            .block(block_id)
+            .await
+            .map_err(|err| state_error(err, block, chain))?;
+
+        // An address without code answers any call with empty output.
+        if output.is_empty() {
+            return Err(MulticallError::NotDeployed {
+                rpc: chain_label(chain),
+                block: block.map_or("latest".to_string(), |b| b.to_string()),
+            }
+            .into());
+        }
+        let results = Multicall3::aggregate3Call::abi_decode_returns(&output, true)?.returnData;
+        outputs.extend(results.into_iter().map(|result| {
+            if result.success {
+                Ok(result.returnData)
+            } else {
+                Err(result.returnData)
+            }
+        }));
+    }
+
+    Ok(outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_one_call_per_chunk() {
+        assert_eq!(aggregate_calls(0), 0);
+        assert_eq!(aggregate_calls(1), 1);
+        assert_eq!(aggregate_calls(CALLS_PER_AGGREGATE as u64), 1);
+        assert_eq!(aggregate_calls(CALLS_PER_AGGREGATE as u64 + 1), 2);
+    }
+}
@@ -1,16 +1,24 @@
+use super::resolve_ens::primary_names;
+use super::resolve_explain::{block_id_text, chain_id_lookups, ChainPlan};
 use crate::common::{
     account::{Account, AccountField},
+    block::{get_block_number_from_tag, BlockId},
     chain::{Chain, ChainOrRpc},
     ens::NameOrAddress,
     query_result::AccountQueryRes,
 };
 use alloy::{
-    primitives::Address,
+    eips::{BlockId as RpcBlockId, BlockNumberOrTag},
+    primitives::{Address, Bytes, B256, U256, U64},
     providers::{Provider, ProviderBuilder, RootProvider},
-    transports::http::{Client, Http},
+    rpc::client::{BatchRequest, Waiter},
+    transports::{
+        http::{Client, Http},
+        RpcError,
+    },
 };
 use anyhow::Result;
-use futures::future::try_join_all;
+use futures::{future::try_join_all, stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -18,92 +26,588 @@ use std::sync::Arc;
 pub enum AccountResolverErrors {
     #[error("Mismatch between Entity and EntityId, {0} can't be resolved as a account id")]
     MismatchEntityAndEntityId(String),
+    #[error("{rpc} has no state for block {block}; reading state at a past block needs an archive node ({message})")]
+    HistoricalStateUnavailable {
+        block: u64,
+        rpc: String,
+        message: String,
+    },
 }
 
-/// Resolve the query to get accounts after receiving an account entity expression
-/// Iterate through entity_ids and map them to a futures list. Execute all futures concurrently and collect the results.
+/// State reads sent per JSON-RPC batch. Providers cap batches somewhere
+/// between 100 and 1000 calls, so this stays at the low end.
+pub(crate) const CALLS_PER_BATCH: usize = 100;
+
+/// Batches in flight at once for one chain.
+pub(crate) const BATCHES_IN_FLIGHT: usize = 4;
+
+/// Resolve the query to get accounts after receiving an account entity expression.
+/// Each account is read once per block its `block_number` filters name, or
+/// once at the latest state when there are none. The reads for a chain are
+/// batched (see `get_accounts`), so a long `block_number BETWEEN` series
+/// costs a bounded number of requests in flight rather than one per read.
 pub async fn resolve_account_query(
     account: &Account,
     chains: &[ChainOrRpc],
 ) -> Result<Vec<AccountQueryRes>> {
-    let mut all_account_futures = Vec::new();
-
-    for chain in chains {
-        let provider = Arc::new(ProviderBuilder::new().on_http(chain.rpc_url()?));
-
-        // TODO: Handle filter
-        // TODO: Remove unwrap
-        for account_id in account.ids().unwrap() {
-            let fields = account.fields().clone();
-            let provider = provider.clone();
-
-            let account_future = async move {
-                match account_id {
-                    NameOrAddress::Address(address) => {
-                        get_account(address, fields, &provider, chain).await
-                    }
-                    NameOrAddress::Name(name) => {
-                        let address = to_address(name).await?;
-                        get_account(&address, fields, &provider, chain).await
-                    }
+    let fields = account.fields();
+    let pin_latest = fields.contains(&AccountField::BlockNumber);
+    // TODO: Remove unwrap
+    let ids = account.ids().unwrap();
+
+    let chain_futures = chains.iter().map(|chain| {
+        let fields = &fields;
+        async move {
+            let provider = Arc::new(ProviderBuilder::new().on_http(chain.rpc_url()?));
+            let chain_enum = chain.to_chain().await?;
+            let blocks = state_blocks(&account.block_ids(), pin_latest, &provider).await?;
+            let reads = ids
+                .iter()
+                .map(NameOrAddress::resolved)
+                .flat_map(|address| blocks.iter().map(move |block| (address, *block)))
+                .collect::<Vec<_>>();
+            get_accounts(&reads, fields, &provider, &chain_enum, chain).await
+        }
+    });
+
+    let mut account_res = try_join_all(chain_futures).await?.concat();
+    fill_ens_names(&fields, &mut account_res).await?;
+    Ok(account_res)
+}
+
+/// Fills in the `address_ens` column of `rows`, when selected, from the
+/// address read for it, then drops the address unless it's selected too.
+async fn fill_ens_names(fields: &[AccountField], rows: &mut [AccountQueryRes]) -> Result<()> {
+    if !fields.contains(&AccountField::AddressEns) {
+        return Ok(());
+    }
+    let names = primary_names(rows.iter().filter_map(|row| row.address)).await?;
+    for row in rows {
+        row.address_ens = row.address.and_then(|address| names.get(&address).cloned());
+        if !fields.contains(&AccountField::Address) {
+            row.address = None;
+        }
+    }
+    Ok(())
+}
+
+/// The blocks to read state at, in filter order. No `ids` reads the latest
+/// state, as `None`, unless `pin_latest` (`block_number` is selected): then
+/// the read is pinned to the current head so the column can say which block
+/// it was.
+pub(crate) async fn state_blocks(
+    ids: &[&BlockId],
+    pin_latest: bool,
+    provider: &Arc<RootProvider<Http<Client>>>,
+) -> Result<Vec<Option<u64>>> {
+    if ids.is_empty() {
+        if pin_latest {
+            return Ok(vec![Some(provider.get_block_number().await?)]);
+        }
+        return Ok(vec![None]);
+    }
+
+    let mut blocks = Vec::new();
+    for id in ids {
+        match id {
+            BlockId::Number(number) => {
+                blocks.push(Some(
+                    get_block_number_from_tag(provider.clone(), number).await?,
+                ));
+            }
+            BlockId::Range(range) => {
+                blocks.extend(
+                    range
+                        .resolve_block_numbers(provider)
+                        .await?
+                        .into_iter()
+                        .map(Some),
+                );
+            }
+        }
+    }
+    Ok(blocks)
+}
+
+/// The `EXPLAIN` plan for `account` on `chain`. Accounts are always read
+/// over RPC, one call per field that needs one per block. The calls travel batched, but providers meter them one by one, so that
+/// is how they are counted.
+pub(crate) fn plan_account_query(account: &Account, chain: &ChainOrRpc) -> ChainPlan {
+    let field_calls = account
+        .fields()
+        .iter()
+        .filter_map(StateRead::for_field)
+        .count() as u64;
+    let block_ids = account.block_ids();
+    let block_range = (!block_ids.is_empty()).then(|| {
+        block_ids
+            .iter()
+            .map(|id| block_id_text(id))
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+    let pin_latest = account.fields().contains(&AccountField::BlockNumber);
+    let calls = planned_blocks(&block_ids, pin_latest).map(|(blocks, lookups)| {
+        let addresses = account.ids().map_or(0, |ids| ids.len() as u64);
+        addresses * field_calls * blocks + lookups + chain_id_lookups(chain)
+    });
+    ChainPlan::rpc(block_range, vec![], calls, 0)
+}
+
+/// How many blocks `state_blocks` reads at for `ids`, and the calls spent
+/// resolving them to numbers. `None` when the count depends on a block tag.
+pub(crate) fn planned_blocks(ids: &[&BlockId], pin_latest: bool) -> Option<(u64, u64)> {
+    if ids.is_empty() {
+        return Some((1, u64::from(pin_latest)));
+    }
+    ids.iter()
+        .try_fold((0, 0), |(blocks, lookups), id| match id {
+            BlockId::Number(BlockNumberOrTag::Number(_)) => Some((blocks + 1, lookups)),
+            BlockId::Number(_) => Some((blocks + 1, lookups + 1)),
+            BlockId::Range(range) => match (range.start(), range.end()) {
+                (BlockNumberOrTag::Number(_), None) => Some((blocks + 1, lookups)),
+                (BlockNumberOrTag::Number(start), Some(BlockNumberOrTag::Number(end))) => {
+                    Some((blocks + end.saturating_sub(start) + 1, lookups))
                 }
-            };
+                _ => None,
+            },
+        })
+}
 
-            all_account_futures.push(account_future);
+/// The reads from state, each with its own RPC method: the account fields,
+/// and storage slots for `storage`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum StateRead {
+    Balance,
+    Nonce,
+    Code,
+    Storage(B256),
+}
+
+impl StateRead {
+    fn for_field(field: &AccountField) -> Option<StateRead> {
+        match field {
+            AccountField::Balance => Some(StateRead::Balance),
+            AccountField::Nonce => Some(StateRead::Nonce),
+            AccountField::Code => Some(StateRead::Code),
+            AccountField::Address
+            | AccountField::AddressEns
+            | AccountField::Chain
+            | AccountField::BlockNumber => None,
         }
     }
+}
 
-    let account_res = try_join_all(all_account_futures).await?;
-    Ok(account_res)
+/// One state read `send_state_calls` batches, for the row at `row`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct StateCall {
+    pub(crate) row: usize,
+    pub(crate) read: StateRead,
+    pub(crate) address: Address,
+    pub(crate) block: Option<u64>,
 }
 
-async fn get_account(
-    address: &Address,
-    fields: Vec<AccountField>,
+/// The RPC reads `reads` need for `fields`, in row order. Fields that need
+/// no call (`address`, `chain`, `block_number`) are filled in by `account_row`.
+fn state_calls(reads: &[(Address, Option<u64>)], fields: &[AccountField]) -> Vec<StateCall> {
+    reads
+        .iter()
+        .enumerate()
+        .flat_map(|(row, (address, block))| {
+            fields
+                .iter()
+                .filter_map(StateRead::for_field)
+                .map(move |read| StateCall {
+                    row,
+                    read,
+                    address: *address,
+                    block: *block,
+                })
+        })
+        .collect()
+}
+
+/// Reads `fields` for every (address, block) in `reads`, one row each, in
+/// order. The calls go out in batches of `CALLS_PER_BATCH`, with at most
+/// `BATCHES_IN_FLIGHT` of them waiting on the node at once.
+async fn get_accounts(
+    reads: &[(Address, Option<u64>)],
+    fields: &[AccountField],
     provider: &RootProvider<Http<Client>>,
-    chain: &ChainOrRpc,
-) -> Result<AccountQueryRes> {
-    let mut account = AccountQueryRes::default();
-    let chain = chain.to_chain().await?;
+    chain: &Chain,
+    chain_or_rpc: &ChainOrRpc,
+) -> Result<Vec<AccountQueryRes>> {
+    let mut rows = reads
+        .iter()
+        .map(|(address, block)| account_row(fields, address, chain, *block))
+        .collect::<Vec<_>>();
 
-    for field in &fields {
-        match field {
-            AccountField::Balance => {
-                if let Ok(balance) = provider.get_balance(*address).await {
-                    account.balance = Some(balance);
-                } else {
-                    account.balance = None;
-                }
+    let calls = state_calls(reads, fields);
+    // Batches are owned, not borrowed from `calls`: a stream of futures over
+    // borrowed slices isn't provably `Send`, which the CLI's servers need.
+    let mut batches = stream::iter(calls.chunks(CALLS_PER_BATCH).map(<[_]>::to_vec))
+        .map(|batch| send_state_calls(batch, provider, chain_or_rpc))
+        .buffered(BATCHES_IN_FLIGHT);
+    while let Some(batch) = batches.next().await {
+        for (call, value) in batch? {
+            let row = &mut rows[call.row];
+            match value {
+                StateValue::Balance(balance) => row.balance = Some(balance),
+                StateValue::Nonce(nonce) => row.nonce = Some(nonce.to()),
+                StateValue::Code(code) => row.code = Some(code),
+                StateValue::Storage(_) => unreachable!("accounts read no storage"),
             }
-            AccountField::Nonce => {
-                if let Ok(nonce) = provider.get_transaction_count(*address).await {
-                    account.nonce = Some(nonce);
-                } else {
-                    account.nonce = None;
-                }
+        }
+    }
+    Ok(rows)
+}
+
+pub(crate) enum StateValue {
+    Balance(U256),
+    Nonce(U64),
+    Code(Bytes),
+    Storage(U256),
+}
+
+enum StateWaiter {
+    Balance(Waiter<U256>),
+    Nonce(Waiter<U64>),
+    Code(Waiter<Bytes>),
+    Storage(Waiter<U256>),
+}
+
+/// Sends `calls` as one batch and pairs each with its value.
+pub(crate) async fn send_state_calls(
+    calls: Vec<StateCall>,
+    provider: &RootProvider<Http<Client>>,
+    chain_or_rpc: &ChainOrRpc,
+) -> Result<Vec<(StateCall, StateValue)>> {
+    let mut batch = BatchRequest::new(provider.client());
+    let mut waiters = Vec::with_capacity(calls.len());
+    for call in &calls {
+        let block_id = RpcBlockId::Number(
+            call.block
+                .map_or(BlockNumberOrTag::Latest, BlockNumberOrTag::Number),
+        );
+        let params = (call.address, block_id);
+        waiters.push(match call.read {
+            StateRead::Balance => StateWaiter::Balance(batch.add_call("eth_getBalance", &params)?),
+            StateRead::Nonce => {
+                StateWaiter::Nonce(batch.add_call("eth_getTransactionCount", &params)?)
             }
-            AccountField::Address => {
-                account.address = Some(*address);
+            StateRead::Code => StateWaiter::Code(batch.add_call("eth_getCode", &params)?),
+            StateRead::Storage(slot) => StateWaiter::Storage(batch.add_call(
+                "eth_getStorageAt",
+                &(call.address, U256::from_be_bytes(slot.0), block_id),
+            )?),
+        });
+    }
+    // A failed send has no single block to blame; the first call's stands in.
+    let first_block = calls.first().and_then(|call| call.block);
+    if let Err(err) = batch.send().await {
+        return Err(probed_state_error(err, first_block, chain_or_rpc, provider).await);
+    }
+
+    let mut values = Vec::with_capacity(calls.len());
+    for (call, waiter) in calls.into_iter().zip(waiters) {
+        let value = match waiter {
+            StateWaiter::Balance(waiter) => waiter.await.map(StateValue::Balance),
+            StateWaiter::Nonce(waiter) => waiter.await.map(StateValue::Nonce),
+            StateWaiter::Code(waiter) => waiter.await.map(StateValue::Code),
+            StateWaiter::Storage(waiter) => waiter.await.map(StateValue::Storage),
+        };
+        match value {
+            Ok(value) => values.push((call, value)),
+            Err(err) => {
+                return Err(probed_state_error(err, call.block, chain_or_rpc, provider).await)
             }
-            AccountField::Code => {
-                if let Ok(code) = provider.get_code_at(*address).await {
-                    account.code = Some(code);
-                } else {
-                    account.code = None;
-                }
+        }
+    }
+    Ok(values)
+}
+
+/// The row for `address` at `block`, with every field that needs no RPC
+/// call filled in.
+fn account_row(
+    fields: &[AccountField],
+    address: &Address,
+    chain: &Chain,
+    block: Option<u64>,
+) -> AccountQueryRes {
+    let mut account = AccountQueryRes::default();
+    for field in fields {
+        match field {
+            // Read by `send_state_calls`.
+            AccountField::Balance | AccountField::Nonce | AccountField::Code => {}
+            // The address the name is looked up for; fill_ens_names drops
+            // it again unless it's selected too.
+            AccountField::Address | AccountField::AddressEns => {
+                account.address = Some(*address);
             }
             AccountField::Chain => {
                 account.chain = Some(chain.clone());
             }
+            AccountField::BlockNumber => {
+                account.block_number = block;
+            }
+        }
+    }
+    account
+}
+
+/// Errors a node gives for state it has pruned. Geth reports a missing trie
+/// node; Nethermind, Erigon and hosted providers mostly say so in words.
+const PRUNED_STATE_ERRORS: &[&str] = &[
+    "missing trie node",
+    "state is not available",
+    "state not available",
+    "historical state",
+    "state histories",
+    "pruned",
+    "archive",
+];
+
+/// `state_error`, for a node whose wording `PRUNED_STATE_ERRORS` doesn't
+/// know. When a read at a past block fails with an error the node itself
+/// returned, it's asked for a balance at block 1: a node that can't answer
+/// that keeps no historical state, and the failure is reported as needing
+/// an archive node. If the probe succeeds, the error is passed through.
+pub(crate) async fn probed_state_error(
+    err: alloy::transports::TransportError,
+    block: Option<u64>,
+    chain: &ChainOrRpc,
+    provider: &RootProvider<Http<Client>>,
+) -> anyhow::Error {
+    let Some(past) = block else {
+        return state_error(err, block, chain);
+    };
+    if is_pruned_state(&err.to_string()) || !matches!(err, RpcError::ErrorResp(_)) {
+        return state_error(err, block, chain);
+    }
+    let genesis_state = provider
+        .get_balance(Address::ZERO)
+        .block_id(RpcBlockId::number(1))
+        .await;
+    match genesis_state {
+        Ok(_) => state_error(err, block, chain),
+        Err(_) => AccountResolverErrors::HistoricalStateUnavailable {
+            block: past,
+            rpc: chain_label(chain),
+            message: err.to_string(),
+        }
+        .into(),
+    }
+}
+
+fn is_pruned_state(message: &str) -> bool {
+    let lowercase = message.to_lowercase();
+    PRUNED_STATE_ERRORS.iter().any(|e| lowercase.contains(e))
+}
+
+/// Turns a failed state read into an error. A read at a past block that the
+/// node has pruned says that an archive node is needed, next to the node's
+/// own wording; anything else is passed through as is.
+pub(crate) fn state_error(
+    err: alloy::transports::TransportError,
+    block: Option<u64>,
+    chain: &ChainOrRpc,
+) -> anyhow::Error {
+    let message = err.to_string();
+    match block {
+        Some(block) if is_pruned_state(&message) => {
+            AccountResolverErrors::HistoricalStateUnavailable {
+                block,
+                rpc: chain_label(chain),
+                message,
+            }
+            .into()
         }
+        _ => err.into(),
     }
+}
 
-    Ok(account)
+pub(crate) fn chain_label(chain: &ChainOrRpc) -> String {
+    match chain {
+        ChainOrRpc::Chain(chain) => format!("the {chain} RPC"),
+        ChainOrRpc::Rpc(url) => url.to_string(),
+    }
 }
 
-async fn to_address(name: &String) -> Result<Address> {
-    let rpc_url = Chain::Ethereum.rpc_url()?;
-    let provider = ProviderBuilder::new().on_http(rpc_url);
-    let address = NameOrAddress::Name(name.clone()).resolve(&provider).await?;
-    Ok(address)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{account::AccountFilter, block::BlockRange};
+    use crate::interpreter::backend::resolve_portal::test_support::spawn_mock_portal;
+    use alloy::transports::TransportError;
+
+    fn rpc_error(message: &str) -> TransportError {
+        let payload = serde_json::json!({ "code": -32000, "message": message });
+        TransportError::ErrorResp(serde_json::from_value(payload).unwrap())
+    }
+
+    fn account_at(blocks: Vec<BlockId>, fields: Vec<AccountField>) -> Account {
+        Account::new(
+            Some(vec![NameOrAddress::Address(Address::ZERO)]),
+            Some(blocks.into_iter().map(AccountFilter::BlockNumber).collect()),
+            fields,
+        )
+    }
+
+    #[test]
+    fn pruned_state_at_a_past_block_asks_for_an_archive_node() {
+        let chain = ChainOrRpc::Chain(Chain::Ethereum);
+        let err = state_error(
+            rpc_error("missing trie node 1a2b (path ) state 0x1a2b is not available"),
+            Some(18_000_000),
+            &chain,
+        );
+        let err = err.to_string();
+        assert!(err.contains("archive node"), "{err}");
+        assert!(err.contains("18000000"), "{err}");
+        assert!(err.contains("missing trie node"), "{err}");
+    }
+
+    #[test]
+    fn other_errors_pass_through() {
+        let chain = ChainOrRpc::Chain(Chain::Ethereum);
+        let err = state_error(rpc_error("rate limited"), Some(18_000_000), &chain).to_string();
+        assert!(!err.contains("archive"), "{err}");
+
+        // At the latest block, pruning can't be the cause.
+        let err = state_error(rpc_error("missing trie node"), None, &chain).to_string();
+        assert!(!err.contains("archive node"), "{err}");
+    }
+
+    #[test]
+    fn state_reads_are_split_into_bounded_batches() {
+        let reads = (0..150)
+            .map(|block| (Address::ZERO, Some(block)))
+            .collect::<Vec<_>>();
+        let fields = [
+            AccountField::Balance,
+            AccountField::Address,
+            AccountField::Nonce,
+        ];
+        let calls = state_calls(&reads, &fields);
+        assert_eq!(calls.len(), 300);
+        assert_eq!(calls.chunks(CALLS_PER_BATCH).count(), 3);
+        assert_eq!(
+            calls[..2]
+                .iter()
+                .map(|c| (c.row, c.read))
+                .collect::<Vec<_>>(),
+            [(0, StateRead::Balance), (0, StateRead::Nonce)]
+        );
+        assert_eq!(calls[299].row, 149);
+    }
+
+    #[tokio::test]
+    async fn reads_state_in_one_batch_per_chunk() {
+        let (url, requests, handle) = spawn_mock_portal(vec![r#"[
+            {"jsonrpc":"2.0","id":0,"result":"0x64"},
+            {"jsonrpc":"2.0","id":1,"result":"0x7"}
+        ]"#
+        .to_string()]);
+        let provider = ProviderBuilder::new().on_http(url.parse().unwrap());
+        let rows = get_accounts(
+            &[(Address::ZERO, Some(5))],
+            &[AccountField::Balance, AccountField::Nonce],
+            &provider,
+            &Chain::Ethereum,
+            &ChainOrRpc::Chain(Chain::Ethereum),
+        )
+        .await
+        .unwrap();
+        handle.join().unwrap();
+
+        assert_eq!(rows[0].balance, Some(U256::from(100)));
+        assert_eq!(rows[0].nonce, Some(7));
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0][1]["method"], "eth_getTransactionCount");
+    }
+
+    #[tokio::test]
+    async fn storage_slots_are_read_with_get_storage_at() {
+        let (url, requests, handle) = spawn_mock_portal(vec![
+            r#"[{"jsonrpc":"2.0","id":0,"result":"0x2a"}]"#.to_string(),
+        ]);
+        let provider = ProviderBuilder::new().on_http(url.parse().unwrap());
+        let call = StateCall {
+            row: 0,
+            read: StateRead::Storage(B256::with_last_byte(3)),
+            address: Address::ZERO,
+            block: Some(5),
+        };
+        let values = send_state_calls(vec![call], &provider, &ChainOrRpc::Chain(Chain::Ethereum))
+            .await
+            .unwrap();
+        handle.join().unwrap();
+
+        assert!(matches!(values[..], [(_, StateValue::Storage(value))] if value == U256::from(42)));
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0][0]["method"], "eth_getStorageAt");
+        assert_eq!(requests[0][0]["params"][1], "0x3");
+        assert_eq!(requests[0][0]["params"][2], "0x5");
+    }
+
+    #[tokio::test]
+    async fn an_unknown_error_is_probed_for_missing_history() {
+        let chain = ChainOrRpc::Chain(Chain::Ethereum);
+        let probe = |response: &str| {
+            let (url, _, handle) = spawn_mock_portal(vec![response.to_string()]);
+            (ProviderBuilder::new().on_http(url.parse().unwrap()), handle)
+        };
+
+        // The node can't read block 1 either: it keeps no history.
+        let (provider, handle) = probe(
+            r#"{"jsonrpc":"2.0","id":0,"error":{"code":-32000,"message":"header not found"}}"#,
+        );
+        let err = probed_state_error(rpc_error("header not found"), Some(10), &chain, &provider)
+            .await
+            .to_string();
+        handle.join().unwrap();
+        assert!(err.contains("archive node"), "{err}");
+
+        // It can: the error was about something else.
+        let (provider, handle) = probe(r#"{"jsonrpc":"2.0","id":0,"result":"0x0"}"#);
+        let err = probed_state_error(rpc_error("execution reverted"), Some(10), &chain, &provider)
+            .await
+            .to_string();
+        handle.join().unwrap();
+        assert!(!err.contains("archive node"), "{err}");
+    }
+
+    #[test]
+    fn plans_one_read_per_field_per_block() {
+        let chain = ChainOrRpc::Chain(Chain::Ethereum);
+        let account = account_at(
+            vec![
+                BlockId::Number(BlockNumberOrTag::Number(10)),
+                BlockId::Range(BlockRange::new(
+                    BlockNumberOrTag::Number(20),
+                    Some(BlockNumberOrTag::Number(24)),
+                )),
+            ],
+            vec![AccountField::Balance, AccountField::Nonce],
+        );
+        let plan = plan_account_query(&account, &chain);
+        assert_eq!(plan.rpc_calls, Some(2 * 6));
+        assert_eq!(plan.block_range.as_deref(), Some("10, 20..24"));
+
+        // A tag costs a lookup; a range ending at one has no fixed size.
+        let account = account_at(
+            vec![BlockId::Number(BlockNumberOrTag::Finalized)],
+            vec![AccountField::Balance],
+        );
+        assert_eq!(plan_account_query(&account, &chain).rpc_calls, Some(2));
+        let account = account_at(
+            vec![BlockId::Range(BlockRange::new(
+                BlockNumberOrTag::Number(20),
+                Some(BlockNumberOrTag::Latest),
+            ))],
+            vec![AccountField::Balance],
+        );
+        assert_eq!(plan_account_query(&account, &chain).rpc_calls, None);
+    }
 }
@@ -0,0 +1,234 @@
+use super::{
+    resolve_account::{planned_blocks, state_blocks},
+    resolve_block::batch_get_full_blocks,
+    resolve_explain::{block_id_text, chain_id_lookups, ChainPlan},
+    resolve_transaction::rpc_tx_hash,
+};
+use crate::common::{
+    authorization::{AuthorizationField, Authorizations},
+    chain::{Chain, ChainOrRpc},
+    ens::NameOrAddress,
+    query_result::AuthorizationQueryRes,
+};
+use alloy::{
+    consensus::Transaction as ConsensusTransaction,
+    primitives::{Address, B256},
+    providers::ProviderBuilder,
+};
+use alloy_eip7702::SignedAuthorization;
+use anyhow::Result;
+use futures::future::try_join_all;
+use std::sync::Arc;
+
+/// Resolve the query to list EIP-7702 authorizations. They are read from the
+/// authorization lists of each block's transactions over RPC (Portal doesn't
+/// serve them), and each authority is recovered from its signature locally.
+pub async fn resolve_authorizations_query(
+    authorizations: &Authorizations,
+    chains: &[ChainOrRpc],
+) -> Result<Vec<AuthorizationQueryRes>> {
+    let fields = authorizations.fields();
+    let authorities = authorizations
+        .authorities()
+        .iter()
+        .map(NameOrAddress::resolved)
+        .collect::<Vec<_>>();
+    let delegates = authorizations
+        .delegates()
+        .iter()
+        .map(NameOrAddress::resolved)
+        .collect::<Vec<_>>();
+    let mut all_authorization_futures = Vec::new();
+
+    for chain in chains {
+        let fields = &fields;
+        let authorities = &authorities;
+        let delegates = &delegates;
+
+        let authorization_future = async move {
+            let provider = Arc::new(ProviderBuilder::new().on_http(chain.rpc_url()?));
+            let chain_enum = chain.to_chain().await?;
+            let numbers = state_blocks(&authorizations.block_ids(), false, &provider)
+                .await?
+                .into_iter()
+                .flatten()
+                .collect();
+            let blocks = batch_get_full_blocks(numbers, &provider).await?;
+
+            let mut rows = Vec::new();
+            for block in blocks {
+                for tx in block.transactions.txns() {
+                    let Some(list) = tx.inner.authorization_list() else {
+                        continue;
+                    };
+                    for (index, auth) in list.iter().enumerate() {
+                        let authority = auth.recover_authority().ok();
+                        if is_selected(authority, auth.address, authorities, delegates) {
+                            rows.push(authorization_row(
+                                fields,
+                                &chain_enum,
+                                block.header.number,
+                                rpc_tx_hash(tx),
+                                index as u64,
+                                authority,
+                                auth,
+                            ));
+                        }
+                    }
+                }
+            }
+            Ok::<_, anyhow::Error>(rows)
+        };
+
+        all_authorization_futures.push(authorization_future);
+    }
+
+    let authorization_res = try_join_all(all_authorization_futures).await?;
+    Ok(authorization_res.concat())
+}
+
+/// The `EXPLAIN` plan for `authorizations` on `chain`: one hydrated
+/// `eth_getBlockByNumber` per block. Authority and delegate filters are applied to the
+/// fetched authorizations.
+pub(crate) fn plan_authorizations_query(
+    authorizations: &Authorizations,
+    chain: &ChainOrRpc,
+) -> ChainPlan {
+    let block_ids = authorizations.block_ids();
+    let block_range = Some(
+        block_ids
+            .iter()
+            .map(|id| block_id_text(id))
+            .collect::<Vec<_>>()
+            .join(", "),
+    );
+    let mut client_filters = Vec::new();
+    if !authorizations.authorities().is_empty() {
+        client_filters.push(AuthorizationField::Authority.to_string());
+    }
+    if !authorizations.delegates().is_empty() {
+        client_filters.push(AuthorizationField::Delegate.to_string());
+    }
+    let calls = planned_blocks(&block_ids, false)
+        .map(|(blocks, lookups)| blocks + lookups + chain_id_lookups(chain));
+    ChainPlan::rpc(block_range, client_filters, calls, 0)
+}
+
+/// Whether an authorization passes the authority and delegate filters. One
+/// whose signature doesn't recover never matches an authority filter.
+fn is_selected(
+    authority: Option<Address>,
+    delegate: Address,
+    authorities: &[Address],
+    delegates: &[Address],
+) -> bool {
+    (authorities.is_empty() || authority.is_some_and(|a| authorities.contains(&a)))
+        && (delegates.is_empty() || delegates.contains(&delegate))
+}
+
+fn authorization_row(
+    fields: &[AuthorizationField],
+    chain: &Chain,
+    block_number: u64,
+    transaction_hash: B256,
+    index: u64,
+    authority: Option<Address>,
+    auth: &SignedAuthorization,
+) -> AuthorizationQueryRes {
+    let mut row = AuthorizationQueryRes::default();
+    for field in fields {
+        match field {
+            AuthorizationField::BlockNumber => row.block_number = Some(block_number),
+            AuthorizationField::TransactionHash => row.transaction_hash = Some(transaction_hash),
+            AuthorizationField::Index => row.index = Some(index),
+            AuthorizationField::Authority => row.authority = authority,
+            AuthorizationField::Delegate => row.delegate = Some(auth.address),
+            AuthorizationField::Nonce => row.nonce = Some(auth.nonce),
+            AuthorizationField::ChainId => row.chain_id = Some(auth.chain_id),
+            AuthorizationField::Chain => row.chain = Some(chain.clone()),
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::block::{BlockId, BlockRange};
+    use alloy::{
+        eips::BlockNumberOrTag,
+        primitives::{b256, U256},
+    };
+    use alloy_eip7702::Authorization;
+
+    #[test]
+    fn plans_one_block_fetch_per_block() {
+        let chain = ChainOrRpc::Chain(Chain::Ethereum);
+        let authorizations = Authorizations::new(
+            vec![BlockId::Range(BlockRange::new(
+                BlockNumberOrTag::Number(10),
+                Some(BlockNumberOrTag::Number(12)),
+            ))],
+            vec![NameOrAddress::Address(Address::ZERO)],
+            vec![NameOrAddress::Address(Address::ZERO)],
+            AuthorizationField::all_variants().to_vec(),
+        );
+        let plan = plan_authorizations_query(&authorizations, &chain);
+        assert_eq!(plan.rpc_calls, Some(3));
+        assert_eq!(plan.block_range.as_deref(), Some("10..12"));
+        assert_eq!(plan.client_filters, ["authority", "delegate"]);
+    }
+
+    #[test]
+    fn filters_by_authority_and_delegate() {
+        let authority = Address::with_last_byte(1);
+        let delegate = Address::with_last_byte(2);
+        assert!(is_selected(None, delegate, &[], &[]));
+        assert!(is_selected(Some(authority), delegate, &[authority], &[]));
+        assert!(!is_selected(None, delegate, &[authority], &[]));
+        assert!(is_selected(
+            Some(authority),
+            delegate,
+            &[authority],
+            &[delegate]
+        ));
+        assert!(!is_selected(Some(authority), delegate, &[], &[authority]));
+    }
+
+    #[test]
+    fn rows_carry_the_recovered_authority() {
+        let auth = SignedAuthorization::new_unchecked(
+            Authorization {
+                chain_id: 1,
+                address: Address::with_last_byte(2),
+                nonce: 7,
+            },
+            0,
+            U256::from(1u64),
+            U256::from(1u64),
+        );
+        let hash = b256!("0000000000000000000000000000000000000000000000000000000000000001");
+        let row = authorization_row(
+            &AuthorizationField::all_variants()[1..],
+            &Chain::Ethereum,
+            100,
+            hash,
+            3,
+            Some(Address::with_last_byte(1)),
+            &auth,
+        );
+        assert_eq!(
+            row,
+            AuthorizationQueryRes {
+                chain: Some(Chain::Ethereum),
+                transaction_hash: Some(hash),
+                index: Some(3),
+                authority: Some(Address::with_last_byte(1)),
+                delegate: Some(Address::with_last_byte(2)),
+                nonce: Some(7),
+                chain_id: Some(1),
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -1,14 +1,19 @@
+use super::data_source::{fetch, plan_route, Route, SourceQuery};
+use super::resolve_explain::{
+    block_id_text, chain_id_lookups, portal_dataset, range_text, rpc_block_fetches, ChainPlan,
+};
 use super::resolve_portal::{
     block_id_is_portal_eligible, portal_query, portal_query_with_base_url, resolve_block_id_range,
-    value_to_b256, value_to_bloom, value_to_bytes, value_to_u256, value_to_u64,
+    value_to_address, value_to_b256, value_to_bloom, value_to_bytes, value_to_u256, value_to_u64,
 };
 use crate::common::{
     block::{get_block_number_from_tag, Block, BlockField, BlockId},
     chain::{Chain, ChainOrRpc},
-    query_result::BlockQueryRes,
+    query_result::{BlockQueryRes, ExpressionResult},
 };
 use alloy::{
     eips::BlockNumberOrTag,
+    network::AnyRpcBlock,
     providers::{Provider, ProviderBuilder, RootProvider},
     rpc::types::{Block as RpcBlock, BlockTransactionsKind},
     transports::http::{Client, Http},
@@ -30,7 +35,11 @@ pub enum BlockResolverErrors {
 }
 
 /// Determines if a block query for a given chain should use the Portal.
-fn should_use_portal(chain: &ChainOrRpc, ids: &[BlockId]) -> bool {
+/// The columns a `WHERE` predicate on is answered by Portal itself; every
+/// other predicate is evaluated over rows already fetched.
+pub(crate) const PORTAL_FILTER_FIELDS: &[BlockField] = &[BlockField::Number];
+
+pub(crate) fn should_use_portal(chain: &ChainOrRpc, ids: &[BlockId]) -> bool {
     let dataset = match chain {
         ChainOrRpc::Chain(c) => c.portal_dataset(),
         ChainOrRpc::Rpc(_) => None,
@@ -63,25 +72,146 @@ pub async fn resolve_block_query(
         }
     }
 
+    let results = try_join_all(
+        chains
+            .iter()
+            .map(|chain| fetch(SourceQuery::Blocks(block), chain)),
+    )
+    .await?;
     let mut all_results = Vec::new();
-
-    for chain in chains {
-        let results = if should_use_portal(chain, ids) {
-            resolve_blocks_via_portal(block, chain).await?
-        } else {
-            resolve_blocks_via_rpc(block, chain).await?
+    for result in results {
+        let ExpressionResult::Block(rows) = result else {
+            unreachable!("a block query fetches blocks");
         };
-        all_results.extend(results);
+        all_results.extend(rows);
     }
 
     Ok(all_results)
 }
 
+/// The `EXPLAIN` plan for `block` on `chain`: the route `resolve_block_query`
+/// takes, without fetching any block.
+pub(crate) async fn plan_block_query(block: &Block, chain: &ChainOrRpc) -> Result<ChainPlan> {
+    let ids = block.ids().ok_or(BlockResolverErrors::IdsNotSet)?;
+
+    let route = plan_route(SourceQuery::Blocks(block), chain).await?;
+    if let Route::Planned(plan) = route {
+        return Ok(plan);
+    }
+    if route == Route::Portal {
+        let dataset = portal_dataset(chain);
+        let mut ranges = Vec::new();
+        let mut queries = Vec::new();
+        for id in ids {
+            let (from_block, to_block) = resolve_block_id_range(dataset, id).await?;
+            ranges.push(range_text(from_block, to_block));
+            queries.push(block_portal_query(block.fields(), from_block, to_block));
+        }
+        return Ok(ChainPlan::portal(
+            ranges.join(", "),
+            queries,
+            client_filter_columns(block),
+        ));
+    }
+
+    // One eth_getBlockByNumber per block (plus one per tag to resolve), and
+    // an eth_chainId for an ad-hoc endpoint.
+    let mut calls = Some(chain_id_lookups(chain));
+    for id in ids {
+        calls = calls.zip(rpc_block_fetches(id)?).map(|(a, b)| a + b);
+    }
+    Ok(ChainPlan::rpc(
+        Some(ids.iter().map(block_id_text).collect::<Vec<_>>().join(", ")),
+        client_filter_columns(block),
+        calls,
+        0,
+    ))
+}
+
+/// The columns `Block::filter` evaluates over fetched blocks, on either route.
+fn client_filter_columns(block: &Block) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for field in block
+        .filters()
+        .into_iter()
+        .flatten()
+        .filter_map(|f| f.field())
+    {
+        let name = field.to_string();
+        if !columns.contains(&name) {
+            columns.push(name);
+        }
+    }
+    columns
+}
+
+/// The requested fields plus the ones `block`'s filters read.
+fn block_internal_fields(block: &Block) -> Vec<BlockField> {
+    let mut fields = block.fields().clone();
+    for field in block
+        .filters()
+        .into_iter()
+        .flatten()
+        .filter_map(|f| f.field())
+    {
+        if !fields.contains(&field) {
+            fields.push(field);
+        }
+    }
+    fields
+}
+
+fn project_block_row(row: &BlockQueryRes, fields: &[BlockField]) -> BlockQueryRes {
+    let mut projected = BlockQueryRes::default();
+
+    for field in fields {
+        match field {
+            BlockField::Number => projected.number = row.number,
+            BlockField::Timestamp => projected.timestamp = row.timestamp,
+            BlockField::Size => projected.size = row.size,
+            BlockField::Hash => projected.hash = row.hash,
+            BlockField::ParentHash => projected.parent_hash = row.parent_hash,
+            BlockField::Miner => projected.miner = row.miner,
+            BlockField::GasUsed => projected.gas_used = row.gas_used,
+            BlockField::GasLimit => projected.gas_limit = row.gas_limit,
+            BlockField::Nonce => projected.nonce = row.nonce,
+            BlockField::Difficulty => projected.difficulty = row.difficulty,
+            BlockField::TransactionCount => projected.transaction_count = row.transaction_count,
+            BlockField::StateRoot => projected.state_root = row.state_root,
+            BlockField::TransactionsRoot => projected.transactions_root = row.transactions_root,
+            BlockField::ReceiptsRoot => projected.receipts_root = row.receipts_root,
+            BlockField::LogsBloom => projected.logs_bloom = row.logs_bloom,
+            BlockField::ExtraData => projected.extra_data = row.extra_data.clone(),
+            BlockField::MixHash => projected.mix_hash = row.mix_hash,
+            BlockField::TotalDifficulty => projected.total_difficulty = row.total_difficulty,
+            BlockField::BaseFeePerGas => projected.base_fee_per_gas = row.base_fee_per_gas,
+            BlockField::WithdrawalsRoot => projected.withdrawals_root = row.withdrawals_root,
+            BlockField::BlobGasUsed => projected.blob_gas_used = row.blob_gas_used,
+            BlockField::ExcessBlobGas => projected.excess_blob_gas = row.excess_blob_gas,
+            BlockField::ParentBeaconBlockRoot => {
+                projected.parent_beacon_block_root = row.parent_beacon_block_root
+            }
+            BlockField::Chain => projected.chain = row.chain.clone(),
+        }
+    }
+
+    projected
+}
+
+pub(crate) fn filter_and_project_block_row(
+    block: &Block,
+    row: &BlockQueryRes,
+) -> Option<BlockQueryRes> {
+    block
+        .filter(row)
+        .then(|| project_block_row(row, block.fields()))
+}
+
 // ---------------------------------------------------------------------------
 // Portal path
 // ---------------------------------------------------------------------------
 
-async fn resolve_blocks_via_portal(
+pub(crate) async fn resolve_blocks_via_portal(
     block: &Block,
     chain: &ChainOrRpc,
 ) -> Result<Vec<BlockQueryRes>> {
@@ -98,33 +228,14 @@ async fn resolve_blocks_via_portal_with_base_url(
         _ => unreachable!("should_use_portal guards against Rpc variant"),
     };
     let dataset = chain_enum.portal_dataset().unwrap();
-    let fields = block.fields();
+    let fields = block_internal_fields(block);
     let ids = block.ids().unwrap();
 
     let mut all_results = Vec::new();
 
     for id in ids {
         let (from_block, to_block) = resolve_block_id_range(dataset, id).await?;
-
-        // Build field selection for Portal
-        let mut block_fields = serde_json::Map::new();
-        // Always request number so we can identify blocks
-        block_fields.insert("number".into(), json!(true));
-        for field in fields {
-            if let Some(portal_name) = block_field_to_portal_name(field) {
-                block_fields.insert(portal_name.into(), json!(true));
-            }
-        }
-
-        let query = json!({
-            "type": "evm",
-            "fromBlock": from_block,
-            "toBlock": to_block,
-            "includeAllBlocks": true,
-            "fields": {
-                "block": block_fields
-            }
-        });
+        let query = block_portal_query(&fields, from_block, to_block);
 
         let response = match base_url {
             Some(base_url) => portal_query_with_base_url(base_url, dataset, &query).await?,
@@ -137,14 +248,52 @@ async fn resolve_blocks_via_portal_with_base_url(
                 None => continue,
             };
 
-            let result = parse_portal_block_header(header, fields, &chain_enum);
-            all_results.push(result);
+            let mut row = parse_portal_block_header(header, &fields, &chain_enum);
+            if fields.contains(&BlockField::TransactionCount) {
+                row.transaction_count = Some(
+                    portal_block
+                        .get("transactions")
+                        .and_then(|t| t.as_array())
+                        .map_or(0, |t| t.len() as u64),
+                );
+            }
+            all_results.extend(filter_and_project_block_row(block, &row));
         }
     }
 
     Ok(all_results)
 }
 
+/// The Portal stream query for the headers of `from_block..=to_block`.
+fn block_portal_query(fields: &[BlockField], from_block: u64, to_block: u64) -> serde_json::Value {
+    // Build field selection for Portal
+    let mut block_fields = serde_json::Map::new();
+    // Always request number so we can identify blocks
+    block_fields.insert("number".into(), json!(true));
+    for field in fields {
+        if let Some(portal_name) = block_field_to_portal_name(field) {
+            block_fields.insert(portal_name.into(), json!(true));
+        }
+    }
+
+    let mut query = json!({
+        "type": "evm",
+        "fromBlock": from_block,
+        "toBlock": to_block,
+        "includeAllBlocks": true,
+        "fields": {
+            "block": block_fields
+        }
+    });
+    // Headers carry no transaction count: select every transaction by its
+    // index alone and count them.
+    if fields.contains(&BlockField::TransactionCount) {
+        query["fields"]["transaction"] = json!({ "transactionIndex": true });
+        query["transactions"] = json!([{}]);
+    }
+    query
+}
+
 /// Maps an EQL BlockField to the Portal JSON field name.
 fn block_field_to_portal_name(field: &BlockField) -> Option<&'static str> {
     match field {
@@ -152,6 +301,13 @@ fn block_field_to_portal_name(field: &BlockField) -> Option<&'static str> {
         BlockField::Timestamp => Some("timestamp"),
         BlockField::Hash => Some("hash"),
         BlockField::ParentHash => Some("parentHash"),
+        BlockField::Miner => Some("miner"),
+        BlockField::GasUsed => Some("gasUsed"),
+        BlockField::GasLimit => Some("gasLimit"),
+        BlockField::Nonce => Some("nonce"),
+        BlockField::Difficulty => Some("difficulty"),
+        // Counted from the block's transactions; see block_portal_query.
+        BlockField::TransactionCount => None,
         BlockField::StateRoot => Some("stateRoot"),
         BlockField::TransactionsRoot => Some("transactionsRoot"),
         BlockField::ReceiptsRoot => Some("receiptsRoot"),
@@ -191,6 +347,24 @@ fn parse_portal_block_header(
             BlockField::ParentHash => {
                 result.parent_hash = header.get("parentHash").and_then(value_to_b256);
             }
+            BlockField::Miner => {
+                result.miner = header.get("miner").and_then(value_to_address);
+            }
+            BlockField::GasUsed => {
+                result.gas_used = header.get("gasUsed").and_then(value_to_u64);
+            }
+            BlockField::GasLimit => {
+                result.gas_limit = header.get("gasLimit").and_then(value_to_u64);
+            }
+            BlockField::Nonce => {
+                result.nonce = header.get("nonce").and_then(value_to_u64);
+            }
+            BlockField::Difficulty => {
+                result.difficulty = header.get("difficulty").and_then(value_to_u256);
+            }
+            BlockField::TransactionCount => {
+                // Filled by the caller, which sees the block's transactions.
+            }
             BlockField::StateRoot => {
                 result.state_root = header.get("stateRoot").and_then(value_to_b256);
             }
@@ -244,8 +418,11 @@ fn parse_portal_block_header(
 // RPC path (original logic, extracted)
 // ---------------------------------------------------------------------------
 
-async fn resolve_blocks_via_rpc(block: &Block, chain: &ChainOrRpc) -> Result<Vec<BlockQueryRes>> {
-    let fields = block.fields().clone();
+pub(crate) async fn resolve_blocks_via_rpc(
+    block: &Block,
+    chain: &ChainOrRpc,
+) -> Result<Vec<BlockQueryRes>> {
+    let fields = block_internal_fields(block);
     let ids = block.ids().unwrap();
 
     let provider = Arc::new(ProviderBuilder::new().on_http(chain.rpc_url()?));
@@ -265,7 +442,11 @@ async fn resolve_blocks_via_rpc(block: &Block, chain: &ChainOrRpc) -> Result<Vec
     }
 
     let chain_blocks = try_join_all(all_block_futures).await?;
-    Ok(chain_blocks.concat())
+    Ok(chain_blocks
+        .concat()
+        .iter()
+        .filter_map(|row| filter_and_project_block_row(block, row))
+        .collect())
 }
 
 async fn resolve_block_id(
@@ -350,6 +531,31 @@ pub async fn get_block(
     }
 }
 
+/// Fetch blocks with their full transactions, like `batch_get_blocks`, but
+/// as catch-all network blocks: L2 transaction types such as OP Stack
+/// deposits don't decode as Ethereum transactions, and their extra fields
+/// are kept.
+pub async fn batch_get_full_blocks(
+    block_numbers: Vec<u64>,
+    provider: &Arc<RootProvider<Http<Client>>>,
+) -> Result<Vec<AnyRpcBlock>> {
+    let block_futures = block_numbers
+        .into_iter()
+        .map(|block_number| get_full_block(BlockNumberOrTag::Number(block_number), provider));
+    try_join_all(block_futures).await
+}
+
+pub async fn get_full_block(
+    block_id: BlockNumberOrTag,
+    provider: &RootProvider<Http<Client>>,
+) -> Result<AnyRpcBlock> {
+    let block: Option<AnyRpcBlock> = provider
+        .client()
+        .request("eth_getBlockByNumber", (block_id, true))
+        .await?;
+    block.ok_or_else(|| BlockResolverErrors::UnableToFetchBlockNumber(block_id).into())
+}
+
 fn filter_rpc_fields(block: RpcBlock, fields: &[BlockField], chain: &Chain) -> BlockQueryRes {
     let mut result = BlockQueryRes::default();
 
@@ -367,6 +573,24 @@ fn filter_rpc_fields(block: RpcBlock, fields: &[BlockField], chain: &Chain) -> B
             BlockField::ParentHash => {
                 result.parent_hash = Some(block.header.parent_hash);
             }
+            BlockField::Miner => {
+                result.miner = Some(block.header.beneficiary);
+            }
+            BlockField::GasUsed => {
+                result.gas_used = Some(block.header.gas_used);
+            }
+            BlockField::GasLimit => {
+                result.gas_limit = Some(block.header.gas_limit);
+            }
+            BlockField::Nonce => {
+                result.nonce = Some(u64::from_be_bytes(block.header.nonce.0));
+            }
+            BlockField::Difficulty => {
+                result.difficulty = Some(block.header.difficulty);
+            }
+            BlockField::TransactionCount => {
+                result.transaction_count = Some(block.transactions.len() as u64);
+            }
             BlockField::Size => {
                 result.size = block.header.size;
             }
@@ -452,7 +676,7 @@ mod tests {
         // all_variants() returns &'static [BlockField], so `field` is already &BlockField.
         for field in BlockField::all_variants() {
             let mapped = block_field_to_portal_name(field).is_some();
-            let local = matches!(field, BlockField::Chain);
+            let local = matches!(field, BlockField::Chain | BlockField::TransactionCount);
             assert!(
                 mapped || local,
                 "BlockField {:?} not Portal-serviceable",
@@ -526,4 +750,51 @@ mod tests {
         assert_eq!(requests[0]["toBlock"], json!(52));
         assert_eq!(requests[0]["includeAllBlocks"], json!(true));
     }
+
+    #[tokio::test]
+    async fn test_portal_blocks_are_filtered_by_miner_and_count_transactions() {
+        use crate::common::{block::BlockFilter, filters::EqualityFilter};
+        use alloy::primitives::address;
+
+        let builder = address!("95222290dd7278aa3ddd389cc1e1d165cc4bafe5");
+        let block = Block::new(
+            Some(vec![BlockId::Range(BlockRange::new(
+                BlockNumberOrTag::Number(60),
+                Some(BlockNumberOrTag::Number(61)),
+            ))]),
+            Some(vec![BlockFilter::Miner(EqualityFilter::Eq(builder.into()))]),
+            vec![BlockField::Number, BlockField::TransactionCount],
+        );
+        let (base_url, requests, handle) =
+            super::super::resolve_portal::test_support::spawn_mock_portal(vec![concat!(
+                "{\"header\":{\"number\":60,\"miner\":\"0x95222290dd7278aa3ddd389cc1e1d165cc4bafe5\"},",
+                "\"transactions\":[{\"transactionIndex\":0},{\"transactionIndex\":1}]}\n",
+                "{\"header\":{\"number\":61,\"miner\":\"0x0000000000000000000000000000000000000001\"},",
+                "\"transactions\":[{\"transactionIndex\":0}]}\n"
+            )
+            .to_string()]);
+
+        let results = resolve_blocks_via_portal_with_base_url(
+            &block,
+            &ChainOrRpc::Chain(Chain::Ethereum),
+            Some(&base_url),
+        )
+        .await
+        .unwrap();
+        handle.join().expect("mock Portal thread");
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].number, Some(60));
+        assert_eq!(results[0].transaction_count, Some(2));
+        // Fetched for the filter, not returned.
+        assert_eq!(results[0].miner, None);
+
+        let requests = requests.lock().expect("captured requests");
+        assert_eq!(requests[0]["fields"]["block"]["miner"], json!(true));
+        assert_eq!(requests[0]["transactions"], json!([{}]));
+        assert_eq!(
+            requests[0]["fields"]["transaction"]["transactionIndex"],
+            json!(true)
+        );
+    }
 }
@@ -0,0 +1,406 @@
+use super::{
+    resolve_account::{
+        planned_blocks, probed_state_error, state_blocks, BATCHES_IN_FLIGHT, CALLS_PER_BATCH,
+    },
+    resolve_explain::{block_id_text, chain_id_lookups, ChainPlan},
+};
+use crate::common::{
+    call::{argument_columns, output_columns, Call, CallField},
+    chain::{Chain, ChainOrRpc},
+    ens::NameOrAddress,
+    query_result::{CallQueryRes, CallValue},
+};
+use alloy::{
+    dyn_abi::{DynSolValue, FunctionExt, JsonAbiExt},
+    eips::{BlockId as RpcBlockId, BlockNumberOrTag},
+    hex,
+    json_abi::Function,
+    primitives::{Address, Bytes},
+    providers::{Provider, ProviderBuilder, RootProvider},
+    rpc::{client::BatchRequest, types::TransactionRequest},
+    transports::{
+        http::{Client, Http},
+        TransportError,
+    },
+};
+use anyhow::Result;
+use futures::{future::try_join_all, stream, StreamExt};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// One `eth_call`: the address, the index of its argument set in
+/// `Call::argument_sets`, the encoded calldata, and the block to call at.
+#[derive(Debug, Clone, PartialEq)]
+struct PlannedCall {
+    address: Address,
+    set: usize,
+    input: Bytes,
+    block: Option<u64>,
+}
+
+/// Resolve the query to call a view function. Per chain, the call for every
+/// address, argument set and block goes out in batches of `CALLS_PER_BATCH`,
+/// with at most `BATCHES_IN_FLIGHT` of them waiting on the node at once.
+pub async fn resolve_call_query(call: &Call, chains: &[ChainOrRpc]) -> Result<Vec<CallQueryRes>> {
+    let layout = Layout::new(call);
+    let pin_latest = layout.fields.contains(&CallField::BlockNumber);
+    let argument_sets = call.argument_sets();
+    let addresses = call
+        .addresses()
+        .iter()
+        .map(NameOrAddress::resolved)
+        .collect::<Vec<_>>();
+
+    let chain_futures = chains.iter().map(|chain| {
+        let (layout, addresses, argument_sets) = (&layout, &addresses, &argument_sets);
+        async move {
+            let provider = Arc::new(ProviderBuilder::new().on_http(chain.rpc_url()?));
+            let chain_enum = chain.to_chain().await?;
+            let blocks = state_blocks(&call.block_ids(), pin_latest, &provider).await?;
+            let calls = planned_calls(call.function(), argument_sets, addresses, &blocks)?;
+
+            let mut rows = Vec::with_capacity(calls.len());
+            // Owned batches, as in `get_accounts`, so the stream stays `Send`.
+            let mut batches = stream::iter(calls.chunks(CALLS_PER_BATCH).map(<[_]>::to_vec))
+                .map(|batch| eth_calls(batch, &provider, chain))
+                .buffered(BATCHES_IN_FLIGHT);
+            while let Some(batch) = batches.next().await {
+                for (planned, output) in batch? {
+                    rows.push(layout.row(
+                        &chain_enum,
+                        &planned.address,
+                        &argument_sets[planned.set],
+                        output.as_ref().map(|o| &o[..]),
+                        planned.block,
+                    ));
+                }
+            }
+            Ok::<_, anyhow::Error>(rows)
+        }
+    });
+
+    Ok(try_join_all(chain_futures).await?.concat())
+}
+
+/// Every call the query makes on one chain, block by block, and within a
+/// block address by address and argument set by argument set.
+fn planned_calls(
+    function: &Function,
+    argument_sets: &[Vec<DynSolValue>],
+    addresses: &[Address],
+    blocks: &[Option<u64>],
+) -> Result<Vec<PlannedCall>> {
+    let inputs = argument_sets
+        .iter()
+        .map(|arguments| Ok(Bytes::from(function.abi_encode_input(arguments)?)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(blocks
+        .iter()
+        .flat_map(|block| {
+            addresses.iter().flat_map(|address| {
+                inputs.iter().enumerate().map(|(set, input)| PlannedCall {
+                    address: *address,
+                    set,
+                    input: input.clone(),
+                    block: *block,
+                })
+            })
+        })
+        .collect())
+}
+
+/// The `EXPLAIN` plan for `call` on `chain`. Every address and argument set
+/// is one `eth_call` per block; they travel batched, but providers meter
+/// them one by one, so that is how they are counted.
+pub(crate) fn plan_call_query(call: &Call, chain: &ChainOrRpc) -> ChainPlan {
+    let block_ids = call.block_ids();
+    let block_range = (!block_ids.is_empty()).then(|| {
+        block_ids
+            .iter()
+            .map(|id| block_id_text(id))
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+    let argument_sets: u64 = call.arguments().iter().map(|v| v.len() as u64).product();
+    let pin_latest = call.fields().contains(&CallField::BlockNumber);
+    let calls = planned_blocks(&block_ids, pin_latest).map(|(blocks, lookups)| {
+        let reads = call.addresses().len() as u64 * argument_sets * blocks;
+        reads + lookups + chain_id_lookups(chain)
+    });
+    ChainPlan::rpc(block_range, vec![], calls, 0)
+}
+
+/// Sends `calls` as one batch and pairs each with its output. A call that
+/// reverts is `None` rather than failing the rest.
+async fn eth_calls(
+    calls: Vec<PlannedCall>,
+    provider: &RootProvider<Http<Client>>,
+    chain_or_rpc: &ChainOrRpc,
+) -> Result<Vec<(PlannedCall, Option<Bytes>)>> {
+    let mut batch = BatchRequest::new(provider.client());
+    let waiters = calls
+        .iter()
+        .map(|call| {
+            let block_id = RpcBlockId::Number(
+                call.block
+                    .map_or(BlockNumberOrTag::Latest, BlockNumberOrTag::Number),
+            );
+            let tx = TransactionRequest::default()
+                .to(call.address)
+                .input(call.input.clone().into());
+            batch.add_call::<_, Bytes>("eth_call", &(tx, block_id))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // A failed send has no single block to blame; the first call's stands in.
+    let first_block = calls.first().and_then(|call| call.block);
+    if let Err(err) = batch.send().await {
+        return Err(probed_state_error(err, first_block, chain_or_rpc, provider).await);
+    }
+
+    let mut outputs = Vec::with_capacity(calls.len());
+    for (call, waiter) in calls.into_iter().zip(waiters) {
+        match waiter.await {
+            Ok(output) => outputs.push((call, Some(output))),
+            Err(err) if is_revert(&err) => outputs.push((call, None)),
+            Err(err) => {
+                return Err(probed_state_error(err, call.block, chain_or_rpc, provider).await)
+            }
+        }
+    }
+    Ok(outputs)
+}
+
+/// Whether a failed `eth_call` reverted. Geth answers with code 3 (or
+/// -32000 on older versions), other clients mostly just say so.
+fn is_revert(err: &TransportError) -> bool {
+    err.as_error_resp().is_some_and(|payload| {
+        payload.code == 3 || payload.message.to_lowercase().contains("revert")
+    })
+}
+
+/// What each row of a call carries, worked out once per query.
+struct Layout<'a> {
+    function: &'a Function,
+    fields: Vec<CallField>,
+    arguments: Vec<String>,
+    outputs: Vec<String>,
+}
+
+impl<'a> Layout<'a> {
+    fn new(call: &'a Call) -> Self {
+        Self {
+            function: call.function(),
+            fields: call.fields(),
+            arguments: argument_columns(call.function()),
+            outputs: output_columns(call.function()),
+        }
+    }
+
+    /// The row for one call. An output that is missing (the call reverted)
+    /// or doesn't decode as the signature says leaves every output null.
+    fn row(
+        &self,
+        chain: &Chain,
+        address: &Address,
+        arguments: &[DynSolValue],
+        output: Option<&[u8]>,
+        block: Option<u64>,
+    ) -> CallQueryRes {
+        let outputs = output.and_then(|data| self.function.abi_decode_output(data, true).ok());
+        let mut row = CallQueryRes::default();
+        for field in &self.fields {
+            match *field {
+                CallField::Address => row.address = Some(*address),
+                CallField::Argument(i) => row.values.push(CallValue {
+                    column: self.arguments[i].clone(),
+                    ty: self.function.inputs[i].selector_type().into_owned(),
+                    value: Some(cell(&arguments[i])),
+                }),
+                CallField::Output(i) => row.values.push(CallValue {
+                    column: self.outputs[i].clone(),
+                    ty: self.function.outputs[i].selector_type().into_owned(),
+                    value: outputs.as_ref().map(|outputs| cell(&outputs[i])),
+                }),
+                CallField::BlockNumber => row.block_number = block,
+                CallField::Chain => row.chain = Some(chain.clone()),
+            }
+        }
+        row
+    }
+}
+
+/// A value as one flat cell (see `CallValue::value`).
+fn cell(value: &DynSolValue) -> Value {
+    match json_value(value) {
+        composite @ Value::Array(_) => Value::String(composite.to_string()),
+        scalar => scalar,
+    }
+}
+
+fn json_value(value: &DynSolValue) -> Value {
+    match value {
+        DynSolValue::Bool(b) => json!(b),
+        DynSolValue::Uint(v, bits) if *bits <= 64 => json!(v.to::<u64>()),
+        DynSolValue::Int(v, bits) if *bits <= 64 => json!(v.as_i64()),
+        DynSolValue::Uint(v, _) => json!(v.to_string()),
+        DynSolValue::Int(v, _) => json!(v.to_string()),
+        DynSolValue::Address(address) => json!(address),
+        DynSolValue::Function(function) => json!(function),
+        DynSolValue::FixedBytes(word, size) => json!(hex::encode_prefixed(&word[..*size])),
+        DynSolValue::Bytes(bytes) => json!(hex::encode_prefixed(bytes)),
+        DynSolValue::String(s) => json!(s),
+        DynSolValue::Array(items) | DynSolValue::FixedArray(items) | DynSolValue::Tuple(items) => {
+            Value::Array(items.iter().map(json_value).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{
+        block::{BlockId, BlockRange},
+        call::call_fields,
+    };
+    use alloy::primitives::{address, U256};
+
+    const PAIR: Address = address!("0d4a11d5EEaaC28EC3F61d100daF4d40471f1852");
+
+    fn get_reserves() -> Call {
+        let function = Function::parse("getReserves()(uint112 reserve0, uint112, uint32)").unwrap();
+        let fields = call_fields(&function);
+        Call::new(
+            vec![NameOrAddress::Address(PAIR)],
+            function,
+            vec![],
+            vec![],
+            fields,
+        )
+    }
+
+    #[test]
+    fn rows_decode_outputs_by_the_signature() {
+        let call = get_reserves();
+        let layout = Layout::new(&call);
+        let output = DynSolValue::Tuple(vec![
+            DynSolValue::Uint(U256::from(10).pow(U256::from(30)), 112),
+            DynSolValue::Uint(U256::from(5), 112),
+            DynSolValue::Uint(U256::from(1_700_000_000), 32),
+        ])
+        .abi_encode_params();
+        let row = layout.row(&Chain::Ethereum, &PAIR, &[], Some(&output), Some(7));
+        let values: Vec<_> = row
+            .values
+            .iter()
+            .map(|v| (v.column.as_str(), v.ty.as_str(), v.value.clone()))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                (
+                    "reserve0",
+                    "uint112",
+                    Some(json!("1000000000000000000000000000000"))
+                ),
+                ("output1", "uint112", Some(json!("5"))),
+                ("output2", "uint32", Some(json!(1_700_000_000))),
+            ]
+        );
+        assert_eq!(row.address, Some(PAIR));
+        assert_eq!(row.block_number, Some(7));
+
+        // A reverted call keeps its columns, all null.
+        let row = layout.row(&Chain::Ethereum, &PAIR, &[], None, None);
+        assert_eq!(row.values.len(), 3);
+        assert!(row.values.iter().all(|v| v.value.is_none()));
+    }
+
+    #[test]
+    fn composite_values_are_json_text() {
+        let value = DynSolValue::Array(vec![
+            DynSolValue::Address(Address::ZERO),
+            DynSolValue::Address(PAIR),
+        ]);
+        let Value::String(text) = cell(&value) else {
+            panic!("not text")
+        };
+        let parsed: Vec<Address> = serde_json::from_str(&text).unwrap();
+        assert_eq!(parsed, [Address::ZERO, PAIR]);
+        assert_eq!(
+            cell(&DynSolValue::FixedBytes(alloy::primitives::B256::ZERO, 4)),
+            json!("0x00000000")
+        );
+    }
+
+    #[test]
+    fn calls_are_planned_block_by_block() {
+        let function = Function::parse("balanceOf(address)(uint256)").unwrap();
+        let argument_sets: Vec<_> = (0..3u8)
+            .map(|i| vec![DynSolValue::Address(Address::repeat_byte(i))])
+            .collect();
+        let addresses = [PAIR, Address::ZERO];
+        let blocks: Vec<_> = (0..20).map(Some).collect();
+        let calls = planned_calls(&function, &argument_sets, &addresses, &blocks).unwrap();
+        assert_eq!(calls.len(), 2 * 3 * 20);
+        assert_eq!(calls.chunks(CALLS_PER_BATCH).count(), 2);
+        let first: Vec<_> = calls[..4]
+            .iter()
+            .map(|call| (call.address, call.set, call.block))
+            .collect();
+        assert_eq!(
+            first,
+            [
+                (PAIR, 0, Some(0)),
+                (PAIR, 1, Some(0)),
+                (PAIR, 2, Some(0)),
+                (Address::ZERO, 0, Some(0)),
+            ]
+        );
+        assert_eq!(
+            calls[1].input,
+            Bytes::from(function.abi_encode_input(&argument_sets[1]).unwrap())
+        );
+    }
+
+    #[test]
+    fn reverts_are_told_apart_from_other_errors() {
+        let error = |code: i64, message: &str| {
+            let payload = json!({ "code": code, "message": message });
+            TransportError::ErrorResp(serde_json::from_value(payload).unwrap())
+        };
+        assert!(is_revert(&error(3, "execution reverted: paused")));
+        assert!(is_revert(&error(-32000, "execution reverted")));
+        assert!(!is_revert(&error(-32000, "missing trie node")));
+    }
+
+    #[test]
+    fn plans_one_call_per_address_argument_set_and_block() {
+        let chain = ChainOrRpc::Chain(Chain::Ethereum);
+        let function = Function::parse("balanceOf(address)(uint256)").unwrap();
+        let call = Call::new(
+            vec![
+                NameOrAddress::Address(PAIR),
+                NameOrAddress::Address(Address::ZERO),
+            ],
+            function,
+            vec![vec![
+                DynSolValue::Address(Address::ZERO),
+                DynSolValue::Address(PAIR),
+                DynSolValue::Address(Address::ZERO),
+            ]],
+            vec![BlockId::Range(BlockRange::new(
+                BlockNumberOrTag::Number(10),
+                Some(BlockNumberOrTag::Number(11)),
+            ))],
+            vec![CallField::Output(0)],
+        );
+        let plan = plan_call_query(&call, &chain);
+        // 2 addresses x 3 holders x 2 blocks.
+        assert_eq!(plan.rpc_calls, Some(2 * 3 * 2));
+        assert_eq!(plan.block_range.as_deref(), Some("10..11"));
+
+        // No inputs is one call per address, here after pinning the head
+        // for the block_number column.
+        assert_eq!(plan_call_query(&get_reserves(), &chain).rpc_calls, Some(2));
+    }
+}
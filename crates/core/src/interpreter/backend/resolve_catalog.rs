@@ -0,0 +1,294 @@
+//! Answers `SHOW TABLES`, `DESCRIBE <entity>` and the `information_schema`
+//! views from the field enums themselves, so the catalog can't drift from
+//! what a `SELECT` actually accepts and returns.
+
+use super::{
+    resolve_block, resolve_contracts, resolve_logs, resolve_state_diffs, resolve_transaction,
+};
+use crate::common::{
+    account::AccountField,
+    authorization::AuthorizationField,
+    block::BlockField,
+    call::CALL_COLUMNS,
+    contract::ContractField,
+    ens::EnsField,
+    logs::LogField,
+    query_result::{ColumnInfoRes, ExpressionResult, TableInfoRes},
+    state_diff::StateDiffField,
+    storage::StorageField,
+    token_balance::TokenBalanceField,
+    transaction::TransactionField,
+    types::{CatalogExpression, CatalogTarget},
+    withdrawal::WithdrawalField,
+};
+use crate::interpreter::frontend::sql::schema::EntityKind;
+use arrow::datatypes::DataType;
+
+pub fn resolve_catalog_query(expr: &CatalogExpression) -> ExpressionResult {
+    let mut result = match &expr.target {
+        CatalogTarget::Tables => ExpressionResult::Table(
+            EntityKind::ALL
+                .iter()
+                .map(|kind| TableInfoRes {
+                    table_name: kind.table_name().to_string(),
+                })
+                .collect(),
+        ),
+        CatalogTarget::Columns(kinds) => {
+            let kinds: &[EntityKind] = if kinds.is_empty() {
+                &EntityKind::ALL
+            } else {
+                kinds
+            };
+            ExpressionResult::Column(kinds.iter().flat_map(|kind| columns(*kind)).collect())
+        }
+    };
+    if let Some(limit) = expr.limit {
+        result.truncate(limit);
+    }
+    result
+}
+
+/// One row per field, in the enum's declaration order (the order `SELECT *`
+/// returns them in).
+fn columns(kind: EntityKind) -> Vec<ColumnInfoRes> {
+    // (column name, aliases, pushed down to Portal)
+    let fields: Vec<(String, &[&str], bool)> = match kind {
+        // Accounts are only ever resolved over RPC.
+        EntityKind::Accounts => AccountField::all_variants()
+            .iter()
+            .map(|f| (f.to_string(), &[][..], false))
+            .collect(),
+        EntityKind::Blocks => BlockField::all_variants()
+            .iter()
+            .map(|f| {
+                let pushdown = resolve_block::PORTAL_FILTER_FIELDS.contains(f);
+                (f.to_string(), &[][..], pushdown)
+            })
+            .collect(),
+        EntityKind::Transactions => TransactionField::all_variants()
+            .iter()
+            .map(|f| {
+                let pushdown = resolve_transaction::PORTAL_FILTER_FIELDS.contains(f);
+                (f.to_string(), f.aliases(), pushdown)
+            })
+            .collect(),
+        EntityKind::Logs => LogField::all_variants()
+            .iter()
+            .map(|f| {
+                let pushdown = resolve_logs::PORTAL_FILTER_FIELDS.contains(f);
+                (f.to_string(), &[][..], pushdown)
+            })
+            .collect(),
+        // Storage is only ever read over RPC.
+        EntityKind::Storage => StorageField::all_variants()
+            .iter()
+            .map(|f| (f.to_string(), &[][..], false))
+            .collect(),
+        // So are token balances, through Multicall3.
+        EntityKind::TokenBalances => TokenBalanceField::all_variants()
+            .iter()
+            .map(|f| (f.to_string(), &[][..], false))
+            .collect(),
+        // A call's other columns come from the signature it is given.
+        EntityKind::Call => CALL_COLUMNS
+            .iter()
+            .map(|c| (c.to_string(), &[][..], false))
+            .collect(),
+        // Withdrawals come from RPC block bodies; Portal doesn't serve them.
+        EntityKind::Withdrawals => WithdrawalField::all_variants()
+            .iter()
+            .map(|f| (f.to_string(), &[][..], false))
+            .collect(),
+        // Authorizations come from RPC block bodies too.
+        EntityKind::Authorizations => AuthorizationField::all_variants()
+            .iter()
+            .map(|f| (f.to_string(), &[][..], false))
+            .collect(),
+        EntityKind::Contracts => ContractField::all_variants()
+            .iter()
+            .map(|f| {
+                let pushdown = resolve_contracts::PORTAL_FILTER_FIELDS.contains(f);
+                (f.to_string(), &[][..], pushdown)
+            })
+            .collect(),
+        EntityKind::StateDiffs => StateDiffField::all_variants()
+            .iter()
+            .map(|f| {
+                let pushdown = resolve_state_diffs::PORTAL_FILTER_FIELDS.contains(f);
+                (f.to_string(), f.aliases(), pushdown)
+            })
+            .collect(),
+        // ENS is read from its contracts through Multicall3.
+        EntityKind::Ens => EnsField::all_variants()
+            .iter()
+            .map(|f| (f.to_string(), f.aliases(), false))
+            .collect(),
+    };
+
+    let schema = kind.arrow_schema();
+    fields
+        .into_iter()
+        .map(|(column_name, aliases, portal_pushdown)| {
+            let data_type = schema
+                .field_with_name(&column_name)
+                .map(|field| sql_type_name(field.data_type()))
+                .unwrap_or_else(|_| "VARCHAR".to_string());
+            ColumnInfoRes {
+                table_name: kind.table_name().to_string(),
+                column_name,
+                data_type,
+                aliases: aliases.join(", "),
+                portal_pushdown,
+            }
+        })
+        .collect()
+}
+
+/// The DuckDB spelling of the Arrow type a column is exported as, so the
+/// names read the same as they would after loading an EQL Parquet file.
+fn sql_type_name(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Boolean => "BOOLEAN".to_string(),
+        DataType::UInt8 => "UTINYINT".to_string(),
+        DataType::UInt64 => "UBIGINT".to_string(),
+        DataType::Utf8 => "VARCHAR".to_string(),
+        DataType::Decimal128(precision, scale) => format!("DECIMAL({precision},{scale})"),
+        DataType::List(item) => format!("{}[]", sql_type_name(item.data_type())),
+        DataType::Struct(fields) => {
+            let members: Vec<_> = fields
+                .iter()
+                .map(|f| format!("{} {}", f.name(), sql_type_name(f.data_type())))
+                .collect();
+            format!("STRUCT({})", members.join(", "))
+        }
+        other => other.to_string().to_ascii_uppercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column<'a>(rows: &'a [ColumnInfoRes], table: &str, name: &str) -> &'a ColumnInfoRes {
+        rows.iter()
+            .find(|r| r.table_name == table && r.column_name == name)
+            .unwrap_or_else(|| panic!("no column {table}.{name}"))
+    }
+
+    fn all_columns() -> Vec<ColumnInfoRes> {
+        match resolve_catalog_query(&CatalogExpression {
+            target: CatalogTarget::Columns(vec![]),
+            limit: None,
+        }) {
+            ExpressionResult::Column(rows) => rows,
+            other => panic!("expected columns, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn lists_every_table() {
+        let result = resolve_catalog_query(&CatalogExpression {
+            target: CatalogTarget::Tables,
+            limit: None,
+        });
+        let ExpressionResult::Table(rows) = result else {
+            panic!("expected tables");
+        };
+        let names: Vec<_> = rows.iter().map(|r| r.table_name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "accounts",
+                "blocks",
+                "transactions",
+                "logs",
+                "storage",
+                "token_balances",
+                "call",
+                "withdrawals",
+                "authorizations",
+                "contracts",
+                "state_diffs",
+                "ens"
+            ]
+        );
+    }
+
+    #[test]
+    fn columns_carry_type_aliases_and_pushdown() {
+        let rows = all_columns();
+
+        let from = column(&rows, "transactions", "from_address");
+        assert_eq!(from.aliases, "from");
+        assert_eq!(from.data_type, "VARCHAR");
+        assert!(from.portal_pushdown);
+
+        let hash = column(&rows, "transactions", "hash");
+        assert!(!hash.portal_pushdown);
+
+        let blobs = column(&rows, "transactions", "blob_versioned_hashes");
+        assert_eq!(blobs.data_type, "VARCHAR[]");
+        assert!(column(&rows, "transactions", "blob_count").portal_pushdown);
+        assert_eq!(
+            column(&rows, "transactions", "access_list").data_type,
+            "STRUCT(address VARCHAR, storage_keys VARCHAR[])[]"
+        );
+
+        let number = column(&rows, "blocks", "number");
+        assert_eq!(number.data_type, "UBIGINT");
+        assert!(number.portal_pushdown);
+
+        assert_eq!(
+            column(&rows, "accounts", "balance").data_type,
+            "DECIMAL(38,0)"
+        );
+        assert!(rows
+            .iter()
+            .filter(|r| r.table_name == "accounts")
+            .all(|r| !r.portal_pushdown));
+
+        assert!(column(&rows, "logs", "topic2").portal_pushdown);
+        assert!(!column(&rows, "logs", "block_hash").portal_pushdown);
+    }
+
+    #[test]
+    fn every_field_of_every_entity_is_listed() {
+        let rows = all_columns();
+        let count = |table: &str| rows.iter().filter(|r| r.table_name == table).count();
+        assert_eq!(count("accounts"), AccountField::all_variants().len());
+        assert_eq!(count("blocks"), BlockField::all_variants().len());
+        assert_eq!(
+            count("transactions"),
+            TransactionField::all_variants().len()
+        );
+        assert_eq!(count("logs"), LogField::all_variants().len());
+        assert_eq!(count("storage"), StorageField::all_variants().len());
+        assert_eq!(
+            count("token_balances"),
+            TokenBalanceField::all_variants().len()
+        );
+        assert_eq!(count("call"), CALL_COLUMNS.len());
+        assert_eq!(count("withdrawals"), WithdrawalField::all_variants().len());
+        assert_eq!(
+            count("authorizations"),
+            AuthorizationField::all_variants().len()
+        );
+        assert_eq!(count("contracts"), ContractField::all_variants().len());
+        assert_eq!(count("state_diffs"), StateDiffField::all_variants().len());
+        assert_eq!(count("ens"), EnsField::all_variants().len());
+    }
+
+    #[test]
+    fn respects_the_entity_list_and_limit() {
+        let result = resolve_catalog_query(&CatalogExpression {
+            target: CatalogTarget::Columns(vec![EntityKind::Logs]),
+            limit: Some(3),
+        });
+        let ExpressionResult::Column(rows) = result else {
+            panic!("expected columns");
+        };
+        let names: Vec<_> = rows.iter().map(|r| r.column_name.as_str()).collect();
+        assert_eq!(names, ["address", "topic0", "topic1"]);
+    }
+}
@@ -0,0 +1,579 @@
+use super::{
+    resolve_account::{planned_blocks, state_blocks, state_error},
+    resolve_block::get_full_block,
+    resolve_explain::{block_id_text, chain_id_lookups, portal_dataset, range_text, ChainPlan},
+    resolve_portal::{
+        block_id_is_portal_eligible, portal_query, portal_query_with_base_url,
+        resolve_block_id_range, value_to_address, value_to_b256, value_to_bytes, value_to_u64,
+    },
+    resolve_transaction::rpc_tx_hash,
+};
+use crate::common::{
+    block::BlockId,
+    chain::{Chain, ChainOrRpc},
+    contract::{ContractField, Contracts},
+    ens::NameOrAddress,
+    query_result::ContractQueryRes,
+};
+use alloy::{
+    consensus::Transaction as ConsensusTransaction,
+    eips::{BlockId as RpcBlockId, BlockNumberOrTag},
+    primitives::{keccak256, Address, Bytes, B256},
+    providers::{Provider, ProviderBuilder, RootProvider},
+    rpc::types::AnyTransactionReceipt,
+    transports::http::{Client, Http},
+};
+use anyhow::Result;
+use futures::future::try_join_all;
+use serde_json::json;
+use std::{collections::HashMap, future::Future, sync::Arc};
+
+/// A contract creation, before projection to the selected fields.
+#[derive(Debug, Default, PartialEq, Clone)]
+struct Deployment {
+    address: Address,
+    deployer: Option<Address>,
+    transaction_hash: Option<B256>,
+    block_number: u64,
+    init_code_hash: Option<B256>,
+    runtime_code_hash: Option<B256>,
+}
+
+/// The columns a `WHERE` predicate on is sent to Portal for: the blocks it
+/// scans and the `createFrom` trace filter. Address filters are applied to
+/// the fetched deployments.
+pub(crate) const PORTAL_FILTER_FIELDS: &[ContractField] =
+    &[ContractField::BlockNumber, ContractField::Deployer];
+
+/// Determines if a block scan for contract deployments on `chain` should
+/// use the Portal, whose traces also catch contracts created by other
+/// contracts.
+fn should_use_portal(chain: &ChainOrRpc, ids: &[&BlockId]) -> bool {
+    let has_traces = match chain {
+        ChainOrRpc::Chain(c) => c.portal_has_traces(),
+        ChainOrRpc::Rpc(_) => false,
+    };
+    has_traces && ids.iter().all(|id| block_id_is_portal_eligible(id))
+}
+
+/// Resolve the query to list contract deployments. A block scan reads the
+/// `create` traces of Portal where the chain has a dataset, and otherwise
+/// the `contractAddress` of each block's receipts over RPC, which only
+/// covers top-level deployments. Without blocks, each address's creation
+/// block is found by binary search over `eth_getCode`, then read over RPC.
+pub async fn resolve_contracts_query(
+    contracts: &Contracts,
+    chains: &[ChainOrRpc],
+) -> Result<Vec<ContractQueryRes>> {
+    let fields = contracts.fields();
+    let addresses = contracts
+        .addresses()
+        .iter()
+        .map(NameOrAddress::resolved)
+        .collect::<Vec<_>>();
+    let deployers = contracts
+        .deployers()
+        .iter()
+        .map(NameOrAddress::resolved)
+        .collect::<Vec<_>>();
+    let mut all_contract_futures = Vec::new();
+
+    for chain in chains {
+        let fields = &fields;
+        let addresses = &addresses;
+        let deployers = &deployers;
+
+        let contract_future = async move {
+            let chain_enum = chain.to_chain().await?;
+            let block_ids = contracts.block_ids();
+            let deployments = if block_ids.is_empty() {
+                find_creations(addresses, fields, chain).await?
+            } else if should_use_portal(chain, &block_ids) {
+                portal_deployments(&block_ids, deployers, fields, &chain_enum, None).await?
+            } else {
+                let provider = Arc::new(ProviderBuilder::new().on_http(chain.rpc_url()?));
+                let numbers = state_blocks(&block_ids, false, &provider)
+                    .await?
+                    .into_iter()
+                    .flatten();
+                let provider = &provider;
+                try_join_all(numbers.map(|n| rpc_deployments(n, fields, provider, chain)))
+                    .await?
+                    .concat()
+            };
+
+            Ok::<_, anyhow::Error>(
+                deployments
+                    .iter()
+                    .filter(|d| is_selected(d, addresses, deployers))
+                    .map(|d| contract_row(fields, &chain_enum, d))
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        all_contract_futures.push(contract_future);
+    }
+
+    let contract_res = try_join_all(all_contract_futures).await?;
+    Ok(contract_res.concat())
+}
+
+/// The `EXPLAIN` plan for `contracts` on `chain`: the Portal trace queries
+/// of a block scan, or its RPC calls — one `eth_getBlockReceipts` per
+/// block, plus its body when `init_code_hash` is selected and an
+/// `eth_getCode` per row for `runtime_code_hash`. A creation search's call
+/// count depends on the chain head, so it isn't estimated.
+pub(crate) async fn plan_contracts_query(
+    contracts: &Contracts,
+    chain: &ChainOrRpc,
+) -> Result<ChainPlan> {
+    let block_ids = contracts.block_ids();
+    let fields = contracts.fields();
+    let mut client_filters = Vec::new();
+    if !contracts.addresses().is_empty() && !block_ids.is_empty() {
+        client_filters.push(ContractField::Address.to_string());
+    }
+
+    if should_use_portal(chain, &block_ids) && !block_ids.is_empty() {
+        let deployers = contracts
+            .deployers()
+            .iter()
+            .map(NameOrAddress::resolved)
+            .collect::<Vec<_>>();
+        let dataset = portal_dataset(chain);
+        let mut ranges = Vec::new();
+        let mut queries = Vec::new();
+        for id in &block_ids {
+            let (from_block, to_block) = resolve_block_id_range(dataset, id).await?;
+            ranges.push(range_text(from_block, to_block));
+            queries.push(contract_portal_query(
+                &deployers, &fields, from_block, to_block,
+            ));
+        }
+        return Ok(ChainPlan::portal(
+            ranges.join(", "),
+            queries,
+            client_filters,
+        ));
+    }
+
+    if !contracts.deployers().is_empty() {
+        client_filters.push(ContractField::Deployer.to_string());
+    }
+    let per_row = u64::from(fields.contains(&ContractField::RuntimeCodeHash));
+    if block_ids.is_empty() {
+        return Ok(ChainPlan::rpc(None, client_filters, None, per_row));
+    }
+    let reads_per_block = 1 + u64::from(fields.contains(&ContractField::InitCodeHash));
+    let calls = planned_blocks(&block_ids, false)
+        .map(|(blocks, lookups)| blocks * reads_per_block + lookups + chain_id_lookups(chain));
+    Ok(ChainPlan::rpc(
+        Some(
+            block_ids
+                .iter()
+                .map(|id| block_id_text(id))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        client_filters,
+        calls,
+        per_row,
+    ))
+}
+
+fn is_selected(deployment: &Deployment, addresses: &[Address], deployers: &[Address]) -> bool {
+    (addresses.is_empty() || addresses.contains(&deployment.address))
+        && (deployers.is_empty()
+            || deployment
+                .deployer
+                .is_some_and(|deployer| deployers.contains(&deployer)))
+}
+
+fn contract_row(
+    fields: &[ContractField],
+    chain: &Chain,
+    deployment: &Deployment,
+) -> ContractQueryRes {
+    let mut row = ContractQueryRes::default();
+    for field in fields {
+        match field {
+            ContractField::Address => row.address = Some(deployment.address),
+            ContractField::Deployer => row.deployer = deployment.deployer,
+            ContractField::TransactionHash => row.transaction_hash = deployment.transaction_hash,
+            ContractField::BlockNumber => row.block_number = Some(deployment.block_number),
+            ContractField::InitCodeHash => row.init_code_hash = deployment.init_code_hash,
+            ContractField::RuntimeCodeHash => row.runtime_code_hash = deployment.runtime_code_hash,
+            ContractField::Chain => row.chain = Some(chain.clone()),
+        }
+    }
+    row
+}
+
+/// The hash of some code, `None` when there is none.
+fn code_hash(code: &Bytes) -> Option<B256> {
+    (!code.is_empty()).then(|| keccak256(code))
+}
+
+// ---------------------------------------------------------------------------
+// Portal path
+// ---------------------------------------------------------------------------
+
+async fn portal_deployments(
+    block_ids: &[&BlockId],
+    deployers: &[Address],
+    fields: &[ContractField],
+    chain: &Chain,
+    base_url: Option<&str>,
+) -> Result<Vec<Deployment>> {
+    let dataset = chain.portal_dataset().unwrap();
+    let mut deployments = Vec::new();
+    for id in block_ids {
+        let (from_block, to_block) = resolve_block_id_range(dataset, id).await?;
+        let query = contract_portal_query(deployers, fields, from_block, to_block);
+        let response = match base_url {
+            Some(base_url) => portal_query_with_base_url(base_url, dataset, &query).await?,
+            None => portal_query(dataset, &query).await?,
+        };
+        for portal_block in &response {
+            deployments.extend(parse_portal_deployments(portal_block));
+        }
+    }
+    Ok(deployments)
+}
+
+/// The Portal stream query for the successful `create` traces over
+/// `from_block..=to_block`, sent by `deployers` when there are some. The
+/// code itself is only requested when one of its hashes is selected.
+fn contract_portal_query(
+    deployers: &[Address],
+    fields: &[ContractField],
+    from_block: u64,
+    to_block: u64,
+) -> serde_json::Value {
+    let mut trace_fields = json!({
+        "transactionIndex": true,
+        "createFrom": true,
+        "createResultAddress": true,
+        "error": true,
+    });
+    if fields.contains(&ContractField::InitCodeHash) {
+        trace_fields["createInit"] = json!(true);
+    }
+    if fields.contains(&ContractField::RuntimeCodeHash) {
+        trace_fields["createResultCode"] = json!(true);
+    }
+    // The parent transactions come along for their hashes.
+    let mut trace_filter = json!({ "type": ["create"], "transaction": true });
+    if !deployers.is_empty() {
+        let deployers: Vec<_> = deployers.iter().map(|d| format!("{d:?}")).collect();
+        trace_filter["createFrom"] = json!(deployers);
+    }
+
+    json!({
+        "type": "evm",
+        "fromBlock": from_block,
+        "toBlock": to_block,
+        "fields": {
+            "block": { "number": true },
+            "transaction": { "transactionIndex": true, "hash": true },
+            "trace": trace_fields
+        },
+        "traces": [trace_filter]
+    })
+}
+
+/// The deployments of a Portal block: its `create` traces that didn't fail.
+fn parse_portal_deployments(portal_block: &serde_json::Value) -> Vec<Deployment> {
+    let Some(block_number) = portal_block
+        .get("header")
+        .and_then(|h| h.get("number"))
+        .and_then(value_to_u64)
+    else {
+        return vec![];
+    };
+    let hashes: HashMap<u64, B256> = portal_block
+        .get("transactions")
+        .and_then(|t| t.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|tx| {
+            let index = tx.get("transactionIndex").and_then(value_to_u64)?;
+            Some((index, tx.get("hash").and_then(value_to_b256)?))
+        })
+        .collect();
+
+    let traces = portal_block.get("traces").and_then(|t| t.as_array());
+    traces
+        .into_iter()
+        .flatten()
+        .filter(|trace| trace.get("error").map_or(true, |e| e.is_null()))
+        .filter_map(|trace| {
+            let address = trace
+                .get("createResultAddress")
+                .and_then(value_to_address)?;
+            let code_field = |key: &str| trace.get(key).and_then(value_to_bytes);
+            Some(Deployment {
+                address,
+                deployer: trace.get("createFrom").and_then(value_to_address),
+                transaction_hash: trace
+                    .get("transactionIndex")
+                    .and_then(value_to_u64)
+                    .and_then(|index| hashes.get(&index).copied()),
+                block_number,
+                init_code_hash: code_field("createInit").as_ref().and_then(code_hash),
+                runtime_code_hash: code_field("createResultCode").as_ref().and_then(code_hash),
+            })
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// RPC path
+// ---------------------------------------------------------------------------
+
+/// The top-level deployments of `block`: the successful receipts with a
+/// `contractAddress`. Their init code is the transaction input, and their
+/// runtime code is read at the end of the block.
+async fn rpc_deployments(
+    block: u64,
+    fields: &[ContractField],
+    provider: &RootProvider<Http<Client>>,
+    chain: &ChainOrRpc,
+) -> Result<Vec<Deployment>> {
+    let receipts: Option<Vec<AnyTransactionReceipt>> = provider
+        .client()
+        .request("eth_getBlockReceipts", (BlockNumberOrTag::Number(block),))
+        .await?;
+    let created: Vec<_> = receipts
+        .into_iter()
+        .flatten()
+        .filter(|r| r.inner.inner.status())
+        .filter_map(|r| Some((r.contract_address?, r.from, r.transaction_hash)))
+        .collect();
+    if created.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let inputs: HashMap<B256, Bytes> = if fields.contains(&ContractField::InitCodeHash) {
+        let body = get_full_block(BlockNumberOrTag::Number(block), provider).await?;
+        body.transactions
+            .txns()
+            .map(|tx| (rpc_tx_hash(tx), tx.inner.input().clone()))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    let mut deployments = Vec::new();
+    for (address, deployer, transaction_hash) in created {
+        let runtime_code_hash = if fields.contains(&ContractField::RuntimeCodeHash) {
+            code_hash(&code_at(address, block, provider, chain).await?)
+        } else {
+            None
+        };
+        deployments.push(Deployment {
+            address,
+            deployer: Some(deployer),
+            transaction_hash: Some(transaction_hash),
+            block_number: block,
+            init_code_hash: inputs.get(&transaction_hash).and_then(code_hash),
+            runtime_code_hash,
+        });
+    }
+    Ok(deployments)
+}
+
+/// The creation of each of `addresses` that has code today: its creation
+/// block, found by `creation_block`, read like any other block. A contract
+/// another contract created has no receipt of its own, so only its address
+/// and block (and runtime code hash) are known.
+async fn find_creations(
+    addresses: &[Address],
+    fields: &[ContractField],
+    chain: &ChainOrRpc,
+) -> Result<Vec<Deployment>> {
+    let provider = ProviderBuilder::new().on_http(chain.rpc_url()?);
+    let head = provider.get_block_number().await?;
+    let mut deployments = Vec::new();
+
+    for address in addresses {
+        let Some(block) = creation_block(*address, head, &provider, chain).await? else {
+            continue;
+        };
+        let found = rpc_deployments(block, fields, &provider, chain)
+            .await?
+            .into_iter()
+            .find(|d| d.address == *address);
+        let deployment = match found {
+            Some(deployment) => deployment,
+            None => Deployment {
+                address: *address,
+                block_number: block,
+                runtime_code_hash: if fields.contains(&ContractField::RuntimeCodeHash) {
+                    code_hash(&code_at(*address, block, &provider, chain).await?)
+                } else {
+                    None
+                },
+                ..Default::default()
+            },
+        };
+        deployments.push(deployment);
+    }
+    Ok(deployments)
+}
+
+/// The first block at which `address` has code, or `None` if it has none
+/// at `head`. Reading state at old blocks needs an archive node.
+async fn creation_block(
+    address: Address,
+    head: u64,
+    provider: &RootProvider<Http<Client>>,
+    chain: &ChainOrRpc,
+) -> Result<Option<u64>> {
+    first_block_where(head, |block| async move {
+        Ok(!code_at(address, block, provider, chain).await?.is_empty())
+    })
+    .await
+}
+
+/// Binary search for the first block in `0..=head` where `holds` is true,
+/// assuming it stays true from then on; `None` when it's false at `head`.
+/// Costs about log2(head) calls of `holds`.
+async fn first_block_where<F, Fut>(head: u64, mut holds: F) -> Result<Option<u64>>
+where
+    F: FnMut(u64) -> Fut,
+    Fut: Future<Output = Result<bool>>,
+{
+    if !holds(head).await? {
+        return Ok(None);
+    }
+    let (mut low, mut high) = (0, head);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if holds(mid).await? {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    Ok(Some(low))
+}
+
+async fn code_at(
+    address: Address,
+    block: u64,
+    provider: &RootProvider<Http<Client>>,
+    chain: &ChainOrRpc,
+) -> Result<Bytes> {
+    provider
+        .get_code_at(address)
+        .block_id(RpcBlockId::Number(BlockNumberOrTag::Number(block)))
+        .await
+        .map_err(|err| state_error(err, Some(block), chain))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::block::BlockRange;
+    use alloy::primitives::address;
+
+    #[tokio::test]
+    async fn binary_search_finds_the_first_block_with_code() {
+        let created_at = |block: u64| async move { Ok(block >= 12_369_621) };
+        assert_eq!(
+            first_block_where(20_000_000, created_at).await.unwrap(),
+            Some(12_369_621)
+        );
+        assert_eq!(first_block_where(0, created_at).await.unwrap(), None);
+        let genesis = |_| async { Ok(true) };
+        assert_eq!(first_block_where(100, genesis).await.unwrap(), Some(0));
+
+        let mut calls = 0;
+        first_block_where(20_000_000, |block| {
+            calls += 1;
+            async move { Ok(block >= 1) }
+        })
+        .await
+        .unwrap();
+        assert!(calls <= 26, "{calls} calls");
+    }
+
+    #[tokio::test]
+    async fn portal_traces_become_deployments() {
+        let deployer = address!("1000000000000000000000000000000000000001");
+        let block = BlockId::Range(BlockRange::new(BlockNumberOrTag::Number(60), None));
+        let (base_url, requests, handle) =
+            super::super::resolve_portal::test_support::spawn_mock_portal(vec![concat!(
+                "{\"header\":{\"number\":\"0x3c\"},",
+                "\"transactions\":[{\"transactionIndex\":2,",
+                "\"hash\":\"0x00000000000000000000000000000000000000000000000000000000000000aa\"}],",
+                "\"traces\":[",
+                "{\"transactionIndex\":2,\"createFrom\":\"0x1000000000000000000000000000000000000001\",",
+                "\"createResultAddress\":\"0x2000000000000000000000000000000000000002\",",
+                "\"createInit\":\"0x6000\",\"createResultCode\":\"0x00\"},",
+                "{\"transactionIndex\":2,\"createFrom\":\"0x1000000000000000000000000000000000000001\",",
+                "\"error\":\"out of gas\"}]}\n"
+            )
+            .to_string()]);
+
+        let deployments = portal_deployments(
+            &[&block],
+            &[deployer],
+            ContractField::all_variants(),
+            &Chain::Ethereum,
+            Some(&base_url),
+        )
+        .await
+        .unwrap();
+        handle.join().expect("mock Portal thread");
+
+        // The failed create is left out.
+        assert_eq!(
+            deployments,
+            [Deployment {
+                address: address!("2000000000000000000000000000000000000002"),
+                deployer: Some(deployer),
+                transaction_hash: Some(B256::with_last_byte(0xaa)),
+                block_number: 60,
+                init_code_hash: Some(keccak256([0x60, 0x00])),
+                runtime_code_hash: Some(keccak256([0x00])),
+            }]
+        );
+
+        let requests = requests.lock().expect("captured requests");
+        let request = &requests[0];
+        assert_eq!(request["traces"][0]["type"], json!(["create"]));
+        assert_eq!(
+            request["traces"][0]["createFrom"],
+            json!(["0x1000000000000000000000000000000000000001"])
+        );
+        assert_eq!(request["fields"]["trace"]["createInit"], json!(true));
+    }
+
+    #[tokio::test]
+    async fn rpc_plan_counts_receipts_and_bodies_per_block() {
+        let contracts = Contracts::new(
+            vec![],
+            vec![NameOrAddress::Address(Address::ZERO)],
+            vec![BlockId::Range(BlockRange::new(
+                BlockNumberOrTag::Number(10),
+                Some(BlockNumberOrTag::Number(11)),
+            ))],
+            vec![ContractField::Address, ContractField::InitCodeHash],
+        );
+        let chain = ChainOrRpc::Rpc("http://localhost:8545".parse().unwrap());
+        let plan = plan_contracts_query(&contracts, &chain).await.unwrap();
+        // 2 blocks × (receipts + body), plus the chain id.
+        assert_eq!(plan.rpc_calls, Some(2 * 2 + 1));
+        assert_eq!(plan.rpc_calls_per_row, 0);
+        assert_eq!(plan.client_filters, ["deployer"]);
+
+        let deployment = Deployment {
+            deployer: None,
+            ..Default::default()
+        };
+        assert!(is_selected(&deployment, &[], &[]));
+        assert!(!is_selected(&deployment, &[], &[Address::ZERO]));
+    }
+}
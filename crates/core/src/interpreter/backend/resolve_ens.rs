@@ -0,0 +1,1012 @@
+use super::{
+    ccip::aggregate_offchain,
+    ens_cache::{ens_names, unix_now},
+    multicall::{aggregate, aggregate_calls},
+    resolve_account::{planned_blocks, state_blocks},
+    resolve_explain::{block_id_text, chain_id_lookups, ChainPlan},
+};
+use crate::common::{
+    chain::{Chain, ChainOrRpc},
+    ens::{
+        dns_encode, eth_label_id, namehash, normalize, reverse_address, Ens, EnsError, EnsField,
+        EnsRegistry, EnsResolver, EthRegistrar, ExtendedResolver, NameOrAddress, NameWrapper,
+        NamesAddresses, ENS_ADDRESS, ETH_REGISTRAR_ADDRESS, EXTENDED_RESOLVER_INTERFACE_ID,
+        NAME_WRAPPER_ADDRESS, SEPOLIA_NAME_WRAPPER_ADDRESS,
+    },
+    entity::Entity,
+    query_result::EnsQueryRes,
+};
+use alloy::{
+    primitives::{Address, Bytes, B256, U256},
+    providers::{ProviderBuilder, RootProvider},
+    sol_types::SolCall,
+    transports::http::{Client, Http},
+};
+use anyhow::Result;
+use futures::future::try_join_all;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    sync::Arc,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum EnsResolverErrors {
+    #[error("ENS is deployed on eth and sepolia, not {0}")]
+    NotDeployed(Chain),
+}
+
+/// One contract read. Records are read from the name's own resolver, so
+/// they carry its address.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+enum Read {
+    Resolver(B256),
+    Owner(B256),
+    Expiry(B256),
+    WrappedOwner(Address, B256),
+    Addr(Address, B256),
+    Name(Address, B256),
+    Contenthash(Address, B256),
+    Text(Address, B256, &'static str),
+    /// Whether a resolver implements ENSIP-10's `resolve`.
+    SupportsWildcard(Address),
+}
+
+impl Read {
+    fn call(&self) -> (Address, Bytes) {
+        match self {
+            Read::Resolver(node) => (
+                ENS_ADDRESS,
+                EnsRegistry::resolverCall { node: *node }
+                    .abi_encode()
+                    .into(),
+            ),
+            Read::Owner(node) => (
+                ENS_ADDRESS,
+                EnsRegistry::ownerCall { node: *node }.abi_encode().into(),
+            ),
+            Read::Expiry(label) => (
+                ETH_REGISTRAR_ADDRESS,
+                EthRegistrar::nameExpiresCall {
+                    id: U256::from_be_bytes(label.0),
+                }
+                .abi_encode()
+                .into(),
+            ),
+            Read::WrappedOwner(wrapper, node) => (
+                *wrapper,
+                NameWrapper::ownerOfCall {
+                    id: U256::from_be_bytes(node.0),
+                }
+                .abi_encode()
+                .into(),
+            ),
+            Read::Addr(resolver, node) => (
+                *resolver,
+                EnsResolver::addrCall { node: *node }.abi_encode().into(),
+            ),
+            Read::Name(resolver, node) => (
+                *resolver,
+                EnsResolver::nameCall { node: *node }.abi_encode().into(),
+            ),
+            Read::Contenthash(resolver, node) => (
+                *resolver,
+                EnsResolver::contenthashCall { node: *node }
+                    .abi_encode()
+                    .into(),
+            ),
+            Read::Text(resolver, node, key) => (
+                *resolver,
+                EnsResolver::textCall {
+                    node: *node,
+                    key: key.to_string(),
+                }
+                .abi_encode()
+                .into(),
+            ),
+            Read::SupportsWildcard(resolver) => (
+                *resolver,
+                ExtendedResolver::supportsInterfaceCall {
+                    interfaceID: EXTENDED_RESOLVER_INTERFACE_ID.into(),
+                }
+                .abi_encode()
+                .into(),
+            ),
+        }
+    }
+
+    /// The node a record read on a resolver is for. Registry, registrar and
+    /// NameWrapper reads have none.
+    fn resolver_node(&self) -> Option<B256> {
+        match self {
+            Read::Addr(_, node)
+            | Read::Name(_, node)
+            | Read::Contenthash(_, node)
+            | Read::Text(_, node, _) => Some(*node),
+            Read::Resolver(_)
+            | Read::Owner(_)
+            | Read::Expiry(_)
+            | Read::WrappedOwner(..)
+            | Read::SupportsWildcard(_) => None,
+        }
+    }
+}
+
+/// The outputs of a block's reads, decoded on lookup. A read whose call
+/// reverted, or whose output doesn't decode, reads as `None`; so does a
+/// zero address or an empty string or content hash, which is how the
+/// contracts say "not set".
+#[derive(Default)]
+struct Outputs(HashMap<Read, Bytes>);
+
+impl Outputs {
+    fn address(&self, read: &Read) -> Option<Address> {
+        let output = self.0.get(read)?;
+        let address = match read {
+            Read::Resolver(_) => {
+                EnsRegistry::resolverCall::abi_decode_returns(output, true)
+                    .ok()?
+                    ._0
+            }
+            Read::Owner(_) => {
+                EnsRegistry::ownerCall::abi_decode_returns(output, true)
+                    .ok()?
+                    ._0
+            }
+            Read::WrappedOwner(..) => {
+                NameWrapper::ownerOfCall::abi_decode_returns(output, true)
+                    .ok()?
+                    ._0
+            }
+            Read::Addr(..) => {
+                EnsResolver::addrCall::abi_decode_returns(output, true)
+                    .ok()?
+                    ._0
+            }
+            Read::Expiry(_)
+            | Read::Name(..)
+            | Read::Contenthash(..)
+            | Read::Text(..)
+            | Read::SupportsWildcard(_) => return None,
+        };
+        (address != Address::ZERO).then_some(address)
+    }
+
+    fn text(&self, read: &Read) -> Option<String> {
+        let output = self.0.get(read)?;
+        let text = match read {
+            Read::Name(..) => {
+                EnsResolver::nameCall::abi_decode_returns(output, true)
+                    .ok()?
+                    ._0
+            }
+            Read::Text(..) => {
+                EnsResolver::textCall::abi_decode_returns(output, true)
+                    .ok()?
+                    ._0
+            }
+            _ => return None,
+        };
+        (!text.is_empty()).then_some(text)
+    }
+
+    fn contenthash(&self, read: &Read) -> Option<Bytes> {
+        let output = self.0.get(read)?;
+        let hash = EnsResolver::contenthashCall::abi_decode_returns(output, true)
+            .ok()?
+            ._0;
+        (!hash.is_empty()).then_some(hash)
+    }
+
+    /// The address `name` resolves to: its resolver's `addr` record.
+    fn resolved(&self, name: &str) -> Option<Address> {
+        let node = namehash(name);
+        let resolver = self.address(&Read::Resolver(node))?;
+        self.address(&Read::Addr(resolver, node))
+    }
+
+    /// Whether `name` is `address`'s primary name, given that its reverse
+    /// record claims it: the name must be normalized, as ENSIP-15 asks of
+    /// reverse records, and resolve back to the address.
+    fn is_primary_name(&self, name: &str, address: Address) -> bool {
+        normalize(name).is_ok_and(|normalized| normalized == name)
+            && self.resolved(name) == Some(address)
+    }
+
+    fn supports_wildcard(&self, resolver: Address) -> bool {
+        self.0
+            .get(&Read::SupportsWildcard(resolver))
+            .and_then(|output| {
+                ExtendedResolver::supportsInterfaceCall::abi_decode_returns(output, true).ok()
+            })
+            .is_some_and(|supported| supported._0)
+    }
+
+    fn expiry(&self, read: &Read) -> Option<u64> {
+        let output = self.0.get(read)?;
+        let expiry = EthRegistrar::nameExpiresCall::abi_decode_returns(output, true)
+            .ok()?
+            ._0;
+        u64::try_from(expiry).ok().filter(|e| *e != 0)
+    }
+
+    /// Runs `reads` through Multicall3, adding their outputs to these.
+    async fn read(
+        &mut self,
+        reads: Vec<Read>,
+        provider: &RootProvider<Http<Client>>,
+        chain: &ChainOrRpc,
+        block: Option<u64>,
+    ) -> Result<()> {
+        let calls: Vec<_> = reads.iter().map(Read::call).collect();
+        let results = aggregate(provider, chain, &calls, block).await?;
+        self.0.extend(
+            reads
+                .into_iter()
+                .zip(results)
+                .filter_map(|(read, output)| Some((read, output?))),
+        );
+        Ok(())
+    }
+
+    /// ENSIP-10's wildcard step, for the names among `names` without a
+    /// resolver of their own: the nearest ancestor's resolver answers for
+    /// them, if it implements `resolve`. It's recorded as the name's
+    /// resolver, so rows and `resolved` read through it like any other.
+    /// Returns the DNS-encoded name of each node found this way, for
+    /// `read_resolvers`.
+    async fn read_wildcards(
+        &mut self,
+        names: &[&str],
+        provider: &RootProvider<Http<Client>>,
+        chain: &ChainOrRpc,
+        block: Option<u64>,
+    ) -> Result<HashMap<B256, Bytes>> {
+        let unresolved: Vec<&str> = names
+            .iter()
+            .copied()
+            .filter(|name| self.address(&Read::Resolver(namehash(name))).is_none())
+            .collect();
+        let ancestor_reads: HashSet<_> = unresolved
+            .iter()
+            .flat_map(|name| ancestors(name))
+            .map(|ancestor| Read::Resolver(namehash(ancestor)))
+            .collect();
+        if ancestor_reads.is_empty() {
+            return Ok(HashMap::new());
+        }
+        self.read(ancestor_reads.into_iter().collect(), provider, chain, block)
+            .await?;
+
+        let nearest: Vec<(&str, Address)> = unresolved
+            .into_iter()
+            .filter_map(|name| {
+                let resolver = ancestors(name)
+                    .find_map(|ancestor| self.address(&Read::Resolver(namehash(ancestor))))?;
+                Some((name, resolver))
+            })
+            .collect();
+        let support_reads: HashSet<_> = nearest
+            .iter()
+            .map(|(_, resolver)| Read::SupportsWildcard(*resolver))
+            .collect();
+        if support_reads.is_empty() {
+            return Ok(HashMap::new());
+        }
+        self.read(support_reads.into_iter().collect(), provider, chain, block)
+            .await?;
+
+        let mut wildcards = HashMap::new();
+        for (name, resolver) in nearest {
+            if self.supports_wildcard(resolver) {
+                let node = namehash(name);
+                self.0
+                    .insert(Read::Resolver(node), resolver.into_word().into());
+                wildcards.insert(node, dns_encode(name)?);
+            }
+        }
+        Ok(wildcards)
+    }
+
+    /// Runs resolver `reads` like `read`, except those for the nodes in
+    /// `wildcards`, which go through their resolver's `resolve`, following
+    /// any CCIP-read lookups (see `ccip`). Their outputs are unwrapped and
+    /// stored as the plain read's would be.
+    async fn read_resolvers(
+        &mut self,
+        reads: Vec<Read>,
+        wildcards: &HashMap<B256, Bytes>,
+        provider: &RootProvider<Http<Client>>,
+        chain: &ChainOrRpc,
+        block: Option<u64>,
+    ) -> Result<()> {
+        let (wrapped, direct): (Vec<_>, Vec<_>) = reads.into_iter().partition(|read| {
+            read.resolver_node()
+                .is_some_and(|node| wildcards.contains_key(&node))
+        });
+        if !direct.is_empty() {
+            self.read(direct, provider, chain, block).await?;
+        }
+        if wrapped.is_empty() {
+            return Ok(());
+        }
+
+        let calls: Vec<_> = wrapped
+            .iter()
+            .filter_map(|read| {
+                let (resolver, data) = read.call();
+                let name = wildcards.get(&read.resolver_node()?)?.clone();
+                let call = ExtendedResolver::resolveCall { name, data };
+                Some((resolver, call.abi_encode().into()))
+            })
+            .collect();
+        let results = aggregate_offchain(provider, chain, &calls, block).await?;
+        self.0.extend(
+            wrapped
+                .into_iter()
+                .zip(results)
+                .filter_map(|(read, output)| {
+                    let output = ExtendedResolver::resolveCall::abi_decode_returns(&output?, true)
+                        .ok()?
+                        ._0;
+                    (!output.is_empty()).then_some((read, output))
+                }),
+        );
+        Ok(())
+    }
+}
+
+/// The names `name` is under, nearest first, up to its top-level domain.
+fn ancestors(name: &str) -> impl Iterator<Item = &str> {
+    name.match_indices('.').map(|(dot, _)| &name[dot + 1..])
+}
+
+/// The NameWrapper on `chain`, or an error where ENS isn't deployed.
+fn name_wrapper(chain: &Chain) -> Result<Address> {
+    match chain {
+        Chain::Ethereum => Ok(NAME_WRAPPER_ADDRESS),
+        Chain::Sepolia => Ok(SEPOLIA_NAME_WRAPPER_ADDRESS),
+        other => Err(EnsResolverErrors::NotDeployed(other.clone()).into()),
+    }
+}
+
+/// Resolve the query to read ENS records. Per chain and block, up to four
+/// rounds of Multicall3 reads: the reverse records of the addresses and
+/// the names they point to, then the registry entries of every name, then
+/// the records on their resolvers.
+pub async fn resolve_ens_query(ens: &Ens, chains: &[ChainOrRpc]) -> Result<Vec<EnsQueryRes>> {
+    let fields = ens.fields();
+    let pin_latest = fields.contains(&EnsField::BlockNumber);
+    let mut all_ens_futures = Vec::new();
+
+    for chain in chains {
+        let chain_enum = chain.to_chain().await?;
+        let wrapper = name_wrapper(&chain_enum)?;
+        let provider = Arc::new(ProviderBuilder::new().on_http(chain.rpc_url()?));
+        let blocks = state_blocks(&ens.block_ids(), pin_latest, &provider).await?;
+
+        for block in blocks {
+            let provider = provider.clone();
+            let chain_enum = chain_enum.clone();
+            let fields = &fields;
+
+            let ens_future = async move {
+                let primary_names = reverse_names(ens.addresses(), &provider, chain, block).await?;
+                let names: Vec<&str> = ens
+                    .names()
+                    .iter()
+                    .map(String::as_str)
+                    .chain(primary_names.iter().flatten().map(String::as_str))
+                    .collect();
+                let verify = !ens.addresses().is_empty();
+                let outputs =
+                    read_records(&names, fields, verify, wrapper, &provider, chain, block).await?;
+
+                let mut rows: Vec<_> = ens
+                    .names()
+                    .iter()
+                    .map(|name| ens_row(fields, Some(name), wrapper, &outputs, &chain_enum, block))
+                    .collect();
+                for (address, name) in ens.addresses().iter().zip(&primary_names) {
+                    // A reverse record only counts if the name points back.
+                    let verified = name
+                        .as_ref()
+                        .filter(|name| outputs.is_primary_name(name, *address));
+                    let name = verified.map(String::as_str);
+                    let mut row = ens_row(fields, name, wrapper, &outputs, &chain_enum, block);
+                    if fields.contains(&EnsField::Address) {
+                        row.address = Some(*address);
+                    }
+                    rows.push(row);
+                }
+                Ok::<_, anyhow::Error>(rows)
+            };
+
+            all_ens_futures.push(ens_future);
+        }
+    }
+
+    let ens_res = try_join_all(all_ens_futures).await?;
+    Ok(ens_res.concat())
+}
+
+/// Resolves every ENS name among the addresses `entity` filters on, in
+/// place, returning the address each one resolved to. Names resolve on
+/// Ethereum whatever chains the query reads, in two Multicall3 rounds however
+/// many there are: the registry for their resolvers, then the resolvers for
+/// their `addr` records. Names without a resolver of their own take two more
+/// to find a wildcard resolver, and one per CCIP-read lookup it asks for.
+pub(crate) async fn resolve_query_names(entity: &mut Entity) -> Result<BTreeMap<String, Address>> {
+    let mut names: Vec<&str> = Vec::new();
+    let ids = entity.name_or_addresses_mut();
+    for id in &ids {
+        if let NameOrAddress::Name(name) = &**id {
+            if !names.contains(&name.as_str()) {
+                names.push(name);
+            }
+        }
+    }
+    if names.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+
+    let chain = ChainOrRpc::Chain(Chain::Ethereum);
+    let provider = ProviderBuilder::new().on_http(chain.rpc_url()?);
+    let fields = [EnsField::Address];
+    let outputs = read_records(
+        &names,
+        &fields,
+        false,
+        NAME_WRAPPER_ADDRESS,
+        &provider,
+        &chain,
+        None,
+    )
+    .await?;
+    let resolved = names
+        .iter()
+        .map(|name| {
+            let address = outputs
+                .resolved(name)
+                .ok_or_else(|| EnsError::Unresolvable(name.to_string()))?;
+            Ok((name.to_string(), address))
+        })
+        .collect::<Result<BTreeMap<_, _>>>()?;
+    replace_names(ids, &resolved);
+    Ok(resolved)
+}
+
+/// Swaps every name among `ids` for the address it resolved to.
+fn replace_names(ids: Vec<&mut NameOrAddress>, resolved: &BTreeMap<String, Address>) {
+    for id in ids {
+        if let NameOrAddress::Name(name) = id {
+            *id = NameOrAddress::Address(resolved[name.as_str()]);
+        }
+    }
+}
+
+/// The primary name of each of `addresses` that has one, for the `_ens`
+/// columns. As ENS requires, a reverse record only counts if its name
+/// resolves back to the address. Names are read on Ethereum at the latest
+/// block, in four Multicall3 rounds however many addresses there are, and
+/// kept in the name cache (see `ens_cache`), so an address looked up lately
+/// isn't read again.
+pub(crate) async fn primary_names(
+    addresses: impl IntoIterator<Item = Address>,
+) -> Result<HashMap<Address, String>> {
+    let now = unix_now();
+    let mut names = HashMap::new();
+    let mut missing = Vec::new();
+    {
+        let cache = ens_names().lock().unwrap();
+        for address in addresses.into_iter().collect::<BTreeSet<_>>() {
+            match cache.get(&address, now) {
+                Some(name) => names.extend(name.map(|name| (address, name))),
+                None => missing.push(address),
+            }
+        }
+    }
+    if missing.is_empty() {
+        return Ok(names);
+    }
+
+    let chain = ChainOrRpc::Chain(Chain::Ethereum);
+    let provider = ProviderBuilder::new().on_http(chain.rpc_url()?);
+    let claimed = reverse_names(&missing, &provider, &chain, None).await?;
+    let claimed_names: Vec<&str> = claimed.iter().flatten().map(String::as_str).collect();
+    let outputs = if claimed_names.is_empty() {
+        Outputs::default()
+    } else {
+        read_records(
+            &claimed_names,
+            &[],
+            true,
+            NAME_WRAPPER_ADDRESS,
+            &provider,
+            &chain,
+            None,
+        )
+        .await?
+    };
+
+    let mut cache = ens_names().lock().unwrap();
+    for (address, name) in missing.into_iter().zip(claimed) {
+        let verified = name.filter(|name| outputs.is_primary_name(name, address));
+        cache.insert(address, verified.clone(), now);
+        names.extend(verified.map(|name| (address, name)));
+    }
+    cache.save(now);
+    Ok(names)
+}
+
+/// The name each of `addresses` claims through its reverse record, before
+/// verification: the reverse node's resolver, then that resolver's `name`.
+async fn reverse_names(
+    addresses: &[Address],
+    provider: &RootProvider<Http<Client>>,
+    chain: &ChainOrRpc,
+    block: Option<u64>,
+) -> Result<Vec<Option<String>>> {
+    if addresses.is_empty() {
+        return Ok(vec![]);
+    }
+    let nodes: Vec<_> = addresses
+        .iter()
+        .map(|address| namehash(&reverse_address(address)))
+        .collect();
+    let mut outputs = Outputs::default();
+    outputs
+        .read(
+            nodes.iter().map(|node| Read::Resolver(*node)).collect(),
+            provider,
+            chain,
+            block,
+        )
+        .await?;
+    let name_reads: Vec<_> = nodes
+        .iter()
+        .map(|node| {
+            let resolver = outputs.address(&Read::Resolver(*node))?;
+            Some(Read::Name(resolver, *node))
+        })
+        .collect();
+    outputs
+        .read(
+            name_reads.iter().flatten().cloned().collect(),
+            provider,
+            chain,
+            block,
+        )
+        .await?;
+    Ok(name_reads
+        .iter()
+        .map(|read| read.as_ref().and_then(|read| outputs.text(read)))
+        .collect())
+}
+
+/// The registry reads for `names` the selected fields need, the wildcard
+/// step for names without a resolver of their own, then the resolver
+/// reads. `verify` reads `addr` even when `address` isn't selected, to
+/// check reverse records against it.
+async fn read_records(
+    names: &[&str],
+    fields: &[EnsField],
+    verify: bool,
+    wrapper: Address,
+    provider: &RootProvider<Http<Client>>,
+    chain: &ChainOrRpc,
+    block: Option<u64>,
+) -> Result<Outputs> {
+    let mut outputs = Outputs::default();
+    outputs
+        .read(registry_reads(names, fields), provider, chain, block)
+        .await?;
+    let wildcards = outputs
+        .read_wildcards(names, provider, chain, block)
+        .await?;
+    let reads = resolver_reads(names, fields, verify, wrapper, &outputs);
+    if !reads.is_empty() {
+        outputs
+            .read_resolvers(reads, &wildcards, provider, chain, block)
+            .await?;
+    }
+    Ok(outputs)
+}
+
+/// Every name's resolver, plus its owner and expiry when selected.
+fn registry_reads(names: &[&str], fields: &[EnsField]) -> Vec<Read> {
+    let mut reads = Vec::new();
+    for name in names {
+        let node = namehash(name);
+        reads.push(Read::Resolver(node));
+        if fields.contains(&EnsField::Owner) {
+            reads.push(Read::Owner(node));
+        }
+        if fields.contains(&EnsField::Expiry) {
+            reads.extend(eth_label_id(name).map(Read::Expiry));
+        }
+    }
+    reads
+}
+
+/// The reads that depend on the registry's answers: records on each name's
+/// resolver, and the token owner of a name the NameWrapper owns.
+fn resolver_reads(
+    names: &[&str],
+    fields: &[EnsField],
+    verify: bool,
+    wrapper: Address,
+    registry: &Outputs,
+) -> Vec<Read> {
+    let mut reads = Vec::new();
+    for name in names {
+        let node = namehash(name);
+        if fields.contains(&EnsField::Owner)
+            && registry.address(&Read::Owner(node)) == Some(wrapper)
+        {
+            reads.push(Read::WrappedOwner(wrapper, node));
+        }
+        let Some(resolver) = registry.address(&Read::Resolver(node)) else {
+            continue;
+        };
+        if verify || fields.contains(&EnsField::Address) {
+            reads.push(Read::Addr(resolver, node));
+        }
+        if fields.contains(&EnsField::Contenthash) {
+            reads.push(Read::Contenthash(resolver, node));
+        }
+        reads.extend(
+            fields
+                .iter()
+                .filter_map(EnsField::text_key)
+                .map(|key| Read::Text(resolver, node, key)),
+        );
+    }
+    reads
+}
+
+/// The row of `name`, or of no name: an address without a verified
+/// primary name.
+fn ens_row(
+    fields: &[EnsField],
+    name: Option<&str>,
+    wrapper: Address,
+    outputs: &Outputs,
+    chain: &Chain,
+    block: Option<u64>,
+) -> EnsQueryRes {
+    let node = name.map(namehash);
+    let resolver = node.and_then(|node| outputs.address(&Read::Resolver(node)));
+    let on_resolver = |read: fn(Address, B256) -> Read| resolver.zip(node).map(|(r, n)| read(r, n));
+    let mut row = EnsQueryRes::default();
+    for field in fields {
+        match field {
+            EnsField::Name => row.name = name.map(str::to_string),
+            EnsField::Node => row.node = node,
+            EnsField::Address => {
+                row.address = on_resolver(Read::Addr).and_then(|read| outputs.address(&read))
+            }
+            EnsField::Resolver => row.resolver = resolver,
+            EnsField::Owner => {
+                row.owner = match node.and_then(|node| outputs.address(&Read::Owner(node))) {
+                    Some(owner) if owner == wrapper => {
+                        node.and_then(|node| outputs.address(&Read::WrappedOwner(wrapper, node)))
+                    }
+                    owner => owner,
+                }
+            }
+            EnsField::Expiry => {
+                row.expiry = name
+                    .and_then(eth_label_id)
+                    .and_then(|id| outputs.expiry(&Read::Expiry(id)))
+            }
+            EnsField::Contenthash => {
+                row.contenthash =
+                    on_resolver(Read::Contenthash).and_then(|read| outputs.contenthash(&read))
+            }
+            EnsField::Avatar | EnsField::Url | EnsField::Twitter => {
+                let key = field.text_key().expect("a text record field");
+                let text = resolver
+                    .zip(node)
+                    .and_then(|(r, node)| outputs.text(&Read::Text(r, node, key)));
+                match field {
+                    EnsField::Avatar => row.avatar = text,
+                    EnsField::Url => row.url = text,
+                    _ => row.twitter = text,
+                }
+            }
+            EnsField::BlockNumber => row.block_number = block,
+            EnsField::Chain => row.chain = Some(chain.clone()),
+        }
+    }
+    row
+}
+
+/// The `EXPLAIN` plan for `ens` on `chain`: per block, two Multicall3
+/// rounds for the reverse records when there are addresses, then one for
+/// the registry and one for the resolvers. Reads that turn out not to be
+/// needed (a wrapped owner, a name without a resolver) are counted anyway,
+/// so this is an upper bound for names with resolvers of their own; the
+/// wildcard and CCIP-read rounds of those without depend on what's found,
+/// and aren't counted.
+pub(crate) fn plan_ens_query(ens: &Ens, chain: &ChainOrRpc) -> ChainPlan {
+    let block_ids = ens.block_ids();
+    let block_range = (!block_ids.is_empty()).then(|| {
+        block_ids
+            .iter()
+            .map(|id| block_id_text(id))
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+    let fields = ens.fields();
+    let selected = |wanted: &[EnsField]| wanted.iter().filter(|f| fields.contains(f)).count();
+    let addresses = ens.addresses().len() as u64;
+    let names = ens.names().len() as u64 + addresses;
+    let registry_reads = names * (1 + selected(&[EnsField::Owner, EnsField::Expiry]) as u64);
+    let resolver_reads = names
+        * (1 + selected(&[
+            EnsField::Owner,
+            EnsField::Contenthash,
+            EnsField::Avatar,
+            EnsField::Url,
+            EnsField::Twitter,
+        ]) as u64);
+    let rounds = 2 * aggregate_calls(addresses)
+        + aggregate_calls(registry_reads)
+        + aggregate_calls(resolver_reads);
+    let pin_latest = fields.contains(&EnsField::BlockNumber);
+    let calls = planned_blocks(&block_ids, pin_latest)
+        .map(|(blocks, lookups)| blocks * rounds + lookups + chain_id_lookups(chain));
+    ChainPlan::rpc(block_range, vec![], calls, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::block::BlockId;
+    use alloy::eips::BlockNumberOrTag;
+
+    const RESOLVER: Address = Address::repeat_byte(0x11);
+    const OWNER: Address = Address::repeat_byte(0x22);
+
+    fn outputs(entries: Vec<(Read, Vec<u8>)>) -> Outputs {
+        Outputs(
+            entries
+                .into_iter()
+                .map(|(read, output)| (read, output.into()))
+                .collect(),
+        )
+    }
+
+    fn returns_address(address: Address) -> Vec<u8> {
+        EnsRegistry::resolverCall::abi_encode_returns(&(address,))
+    }
+
+    fn returns_text(text: &str) -> Vec<u8> {
+        EnsResolver::textCall::abi_encode_returns(&(text.to_string(),))
+    }
+
+    #[test]
+    fn registry_reads_follow_the_selected_fields() {
+        let names = ["vitalik.eth", "pay.vitalik.eth"];
+        let reads = registry_reads(&names, &[EnsField::Name]);
+        assert_eq!(
+            reads,
+            vec![
+                Read::Resolver(namehash("vitalik.eth")),
+                Read::Resolver(namehash("pay.vitalik.eth")),
+            ]
+        );
+
+        // Only a .eth second-level name has a registrar expiry.
+        let reads = registry_reads(&names, &[EnsField::Owner, EnsField::Expiry]);
+        assert_eq!(reads.len(), 5);
+        assert!(reads.contains(&Read::Expiry(eth_label_id("vitalik.eth").unwrap())));
+    }
+
+    #[test]
+    fn resolver_reads_skip_names_without_a_resolver() {
+        let node = namehash("vitalik.eth");
+        let wrapper = NAME_WRAPPER_ADDRESS;
+        let registry = outputs(vec![
+            (Read::Resolver(node), returns_address(RESOLVER)),
+            (Read::Owner(node), returns_address(wrapper)),
+            (
+                Read::Resolver(namehash("unset.eth")),
+                returns_address(Address::ZERO),
+            ),
+        ]);
+        let fields = [EnsField::Owner, EnsField::Avatar, EnsField::Twitter];
+        let reads = resolver_reads(
+            &["vitalik.eth", "unset.eth"],
+            &fields,
+            false,
+            wrapper,
+            &registry,
+        );
+        assert_eq!(
+            reads,
+            vec![
+                Read::WrappedOwner(wrapper, node),
+                Read::Text(RESOLVER, node, "avatar"),
+                Read::Text(RESOLVER, node, "com.twitter"),
+            ]
+        );
+
+        // Verifying a reverse record reads `addr` even when it isn't selected.
+        let reads = resolver_reads(&["vitalik.eth"], &[], true, wrapper, &registry);
+        assert_eq!(reads, vec![Read::Addr(RESOLVER, node)]);
+    }
+
+    #[test]
+    fn rows_decode_records_and_unwrap_wrapped_owners() {
+        let name = "vitalik.eth";
+        let node = namehash(name);
+        let wrapper = NAME_WRAPPER_ADDRESS;
+        let outputs = outputs(vec![
+            (Read::Resolver(node), returns_address(RESOLVER)),
+            (Read::Owner(node), returns_address(wrapper)),
+            (Read::WrappedOwner(wrapper, node), returns_address(OWNER)),
+            (Read::Addr(RESOLVER, node), returns_address(OWNER)),
+            (
+                Read::Text(RESOLVER, node, "url"),
+                returns_text("https://vitalik.ca"),
+            ),
+            (Read::Text(RESOLVER, node, "avatar"), returns_text("")),
+        ]);
+        let fields = [
+            EnsField::Name,
+            EnsField::Node,
+            EnsField::Address,
+            EnsField::Resolver,
+            EnsField::Owner,
+            EnsField::Url,
+            EnsField::Avatar,
+            EnsField::Contenthash,
+            EnsField::BlockNumber,
+        ];
+        let row = ens_row(
+            &fields,
+            Some(name),
+            wrapper,
+            &outputs,
+            &Chain::Ethereum,
+            Some(21_000_000),
+        );
+        assert_eq!(row.name.as_deref(), Some(name));
+        assert_eq!(row.node, Some(node));
+        assert_eq!(row.address, Some(OWNER));
+        assert_eq!(row.resolver, Some(RESOLVER));
+        assert_eq!(row.owner, Some(OWNER));
+        assert_eq!(row.url.as_deref(), Some("https://vitalik.ca"));
+        // An empty record, or one never read, is null.
+        assert_eq!(row.avatar, None);
+        assert_eq!(row.contenthash, None);
+        assert_eq!(row.block_number, Some(21_000_000));
+        assert_eq!(row.chain, None);
+
+        let row = ens_row(&fields, None, wrapper, &outputs, &Chain::Ethereum, None);
+        assert_eq!(row.name, None);
+        assert_eq!(row.resolver, None);
+    }
+
+    #[test]
+    fn wildcard_resolvers_are_looked_up_nearest_ancestor_first() {
+        assert_eq!(
+            ancestors("alice.cb.id").collect::<Vec<_>>(),
+            vec!["cb.id", "id"]
+        );
+        assert_eq!(ancestors("eth").count(), 0);
+
+        // Only record reads on a resolver go through `resolve`.
+        let node = namehash("alice.cb.id");
+        assert_eq!(Read::Addr(RESOLVER, node).resolver_node(), Some(node));
+        assert_eq!(
+            Read::Text(RESOLVER, node, "url").resolver_node(),
+            Some(node)
+        );
+        assert_eq!(Read::Resolver(node).resolver_node(), None);
+        assert_eq!(Read::WrappedOwner(OWNER, node).resolver_node(), None);
+
+        let supported = ExtendedResolver::supportsInterfaceCall::abi_encode_returns(&(true,));
+        let outputs = outputs(vec![(Read::SupportsWildcard(RESOLVER), supported)]);
+        assert!(outputs.supports_wildcard(RESOLVER));
+        assert!(!outputs.supports_wildcard(OWNER));
+    }
+
+    #[test]
+    fn primary_names_must_be_normalized() {
+        let outputs = outputs(vec![
+            (
+                Read::Resolver(namehash("vitalik.eth")),
+                returns_address(RESOLVER),
+            ),
+            (
+                Read::Addr(RESOLVER, namehash("vitalik.eth")),
+                returns_address(OWNER),
+            ),
+            (
+                Read::Resolver(namehash("Vitalik.eth")),
+                returns_address(RESOLVER),
+            ),
+            (
+                Read::Addr(RESOLVER, namehash("Vitalik.eth")),
+                returns_address(OWNER),
+            ),
+        ]);
+        assert!(outputs.is_primary_name("vitalik.eth", OWNER));
+        assert!(!outputs.is_primary_name("vitalik.eth", RESOLVER));
+        assert!(!outputs.is_primary_name("Vitalik.eth", OWNER));
+    }
+
+    #[test]
+    fn names_are_replaced_in_every_address_filter() {
+        use crate::common::{
+            filters::EqualityFilter,
+            transaction::{Transaction, TransactionField, TransactionFilter},
+        };
+        let vitalik = || NameOrAddress::Name("vitalik.eth".into());
+        let mut entity = Entity::Transaction(Transaction::new(
+            None,
+            Some(vec![
+                TransactionFilter::From(EqualityFilter::Eq(vitalik())),
+                TransactionFilter::To(EqualityFilter::Neq(vitalik())),
+                TransactionFilter::ContractAddress(EqualityFilter::Eq(RESOLVER.into())),
+            ]),
+            vec![TransactionField::Hash],
+        ));
+        assert_eq!(
+            entity.name_or_addresses_mut(),
+            [&mut vitalik(), &mut vitalik(), &mut RESOLVER.into()]
+        );
+
+        let resolved = BTreeMap::from([("vitalik.eth".to_string(), OWNER)]);
+        replace_names(entity.name_or_addresses_mut(), &resolved);
+        let Entity::Transaction(transaction) = entity else {
+            panic!()
+        };
+        assert_eq!(
+            transaction.filters().unwrap(),
+            &vec![
+                TransactionFilter::From(EqualityFilter::Eq(OWNER.into())),
+                TransactionFilter::To(EqualityFilter::Neq(OWNER.into())),
+                TransactionFilter::ContractAddress(EqualityFilter::Eq(RESOLVER.into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn ens_is_only_on_ethereum_and_sepolia() {
+        assert_eq!(
+            name_wrapper(&Chain::Ethereum).unwrap(),
+            NAME_WRAPPER_ADDRESS
+        );
+        assert_eq!(
+            name_wrapper(&Chain::Sepolia).unwrap(),
+            SEPOLIA_NAME_WRAPPER_ADDRESS
+        );
+        let err = name_wrapper(&Chain::Base).unwrap_err().to_string();
+        assert!(err.contains("not base"), "{err}");
+    }
+
+    #[test]
+    fn plans_the_multicall_rounds_per_block() {
+        let chain = ChainOrRpc::Chain(Chain::Ethereum);
+        let ens = Ens::new(
+            vec!["vitalik.eth".to_string()],
+            vec![],
+            vec![BlockId::Number(BlockNumberOrTag::Number(21_000_000))],
+            vec![EnsField::Address, EnsField::Owner],
+        );
+        // One registry round and one resolver round.
+        assert_eq!(plan_ens_query(&ens, &chain).rpc_calls, Some(2));
+
+        let ens = Ens::new(
+            vec![],
+            vec![Address::ZERO],
+            vec![BlockId::Number(BlockNumberOrTag::Number(21_000_000))],
+            vec![EnsField::Name],
+        );
+        // The reverse records take two more.
+        assert_eq!(plan_ens_query(&ens, &chain).rpc_calls, Some(4));
+    }
+}
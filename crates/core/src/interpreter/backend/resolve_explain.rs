@@ -0,0 +1,576 @@
+//! `EXPLAIN [ANALYZE]`: reports, per chain, the route each resolver would
+//! take — Portal or RPC — together with the exact Portal query, the block
+//! range, the predicates left to the client and the RPC calls to expect.
+//!
+//! The plans themselves are built by each resolver's `plan_*` function next
+//! to its `should_use_portal`, from the same query builders execution uses,
+//! so a plan can't describe a route the resolver wouldn't take. Blocks,
+//! transactions and logs take the route `data_source::plan_route` picks,
+//! walking the data sources in the order a fetch does.
+
+use super::{
+    chain_cache::{ChainCache, SegmentKey},
+    data_lake::LakePlan,
+    data_source::SourceQuery,
+    execution_engine::resolve_entity,
+    resolve_account::plan_account_query,
+    resolve_authorizations::plan_authorizations_query,
+    resolve_block::plan_block_query,
+    resolve_call::plan_call_query,
+    resolve_contracts::plan_contracts_query,
+    resolve_ens::plan_ens_query,
+    resolve_logs::plan_log_query,
+    resolve_portal::{metered, PortalTraffic},
+    resolve_state_diffs::plan_state_diffs_query,
+    resolve_storage::plan_storage_query,
+    resolve_token_balance::plan_token_balance_query,
+    resolve_transaction::plan_transaction_query,
+    resolve_withdrawals::plan_withdrawals_query,
+};
+use crate::common::{
+    block::{BlockId, BlockRange, BlockRangeError},
+    chain::ChainOrRpc,
+    entity::Entity,
+    query_result::PlanRes,
+    types::ExplainExpression,
+};
+use alloy::eips::BlockNumberOrTag;
+use anyhow::Result;
+use serde_json::Value;
+use std::{slice, sync::Arc, time::Instant};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Source {
+    Portal,
+    Rpc,
+    /// A segment of the chain cache written by an earlier run.
+    Cache,
+    /// The chain's data lake, the files its config names.
+    Lake,
+    /// A source registered with `register_data_source`, by name.
+    Other(&'static str),
+}
+
+/// How one chain's share of a query is fetched.
+#[derive(Debug, PartialEq)]
+pub(crate) struct ChainPlan {
+    pub(crate) source: Source,
+    pub(crate) block_range: Option<String>,
+    /// One query per Portal request the resolver sends; empty for RPC.
+    pub(crate) portal_queries: Vec<Value>,
+    pub(crate) client_filters: Vec<String>,
+    /// The lake files read, each with the row groups kept of it.
+    pub(crate) lake_files: Vec<String>,
+    pub(crate) rpc_calls: Option<u64>,
+    pub(crate) rpc_calls_per_row: u64,
+}
+
+impl ChainPlan {
+    pub(crate) fn portal(
+        block_range: String,
+        portal_queries: Vec<Value>,
+        client_filters: Vec<String>,
+    ) -> Self {
+        ChainPlan {
+            source: Source::Portal,
+            block_range: Some(block_range),
+            portal_queries,
+            client_filters,
+            lake_files: vec![],
+            rpc_calls: Some(0),
+            rpc_calls_per_row: 0,
+        }
+    }
+
+    pub(crate) fn rpc(
+        block_range: Option<String>,
+        client_filters: Vec<String>,
+        rpc_calls: Option<u64>,
+        rpc_calls_per_row: u64,
+    ) -> Self {
+        ChainPlan {
+            source: Source::Rpc,
+            block_range,
+            portal_queries: vec![],
+            client_filters,
+            lake_files: vec![],
+            rpc_calls,
+            rpc_calls_per_row,
+        }
+    }
+
+    /// The plan of rows the chain cache already holds: they're read from
+    /// disk, with no request at all.
+    pub(crate) fn cache(key: &SegmentKey) -> Self {
+        let (first_block, last_block) = key.blocks();
+        ChainPlan {
+            source: Source::Cache,
+            block_range: Some(range_text(first_block, last_block)),
+            portal_queries: vec![],
+            client_filters: vec![],
+            lake_files: vec![],
+            rpc_calls: Some(0),
+            rpc_calls_per_row: 0,
+        }
+    }
+
+    /// The plan of a query a data lake answers: the row groups statistics
+    /// don't rule out, read from disk, with no request at all.
+    pub(crate) fn lake(plan: LakePlan) -> Self {
+        let block_range = plan
+            .spans
+            .iter()
+            .map(|&(first, last)| range_text(first, last))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let lake_files = plan
+            .files
+            .iter()
+            .map(|(path, row_groups)| {
+                let row_groups = row_groups.iter().map(ToString::to_string);
+                let row_groups = row_groups.collect::<Vec<_>>().join(", ");
+                format!("{} (row groups {row_groups})", path.display())
+            })
+            .collect();
+        ChainPlan {
+            source: Source::Lake,
+            block_range: Some(block_range),
+            portal_queries: vec![],
+            client_filters: vec![],
+            lake_files,
+            rpc_calls: Some(0),
+            rpc_calls_per_row: 0,
+        }
+    }
+
+    /// The plan of a registered source, which EQL knows nothing about but
+    /// its name.
+    pub(crate) fn other(name: &'static str) -> Self {
+        ChainPlan {
+            source: Source::Other(name),
+            block_range: None,
+            portal_queries: vec![],
+            client_filters: vec![],
+            lake_files: vec![],
+            rpc_calls: None,
+            rpc_calls_per_row: 0,
+        }
+    }
+
+    fn into_row(self, chain: &ChainOrRpc, entity: &Entity) -> PlanRes {
+        let portal_query = match self.portal_queries.as_slice() {
+            [] => None,
+            [query] => Some(query.to_string()),
+            queries => Some(Value::from(queries.to_vec()).to_string()),
+        };
+        PlanRes {
+            chain: match chain {
+                ChainOrRpc::Chain(chain) => chain.to_string(),
+                ChainOrRpc::Rpc(url) => url.to_string(),
+            },
+            entity: entity_name(entity).to_string(),
+            source: match self.source {
+                Source::Portal => "portal",
+                Source::Rpc => "rpc",
+                Source::Cache => "cache",
+                Source::Lake => "lake",
+                Source::Other(name) => name,
+            }
+            .to_string(),
+            block_range: self.block_range,
+            portal_query,
+            client_filters: (!self.client_filters.is_empty())
+                .then(|| self.client_filters.join(", ")),
+            lake_files: (!self.lake_files.is_empty()).then(|| self.lake_files.join("; ")),
+            estimated_rpc_calls: self.rpc_calls,
+            rpc_calls_per_row: self.rpc_calls_per_row,
+            elapsed_ms: None,
+            rows: None,
+            portal_pages: None,
+            portal_bytes: None,
+        }
+    }
+}
+
+pub async fn resolve_explain_query(expr: &ExplainExpression) -> Result<Vec<PlanRes>> {
+    let get = &expr.get;
+    let cache = ChainCache::from_settings();
+    let mut rows = Vec::new();
+
+    for chain in &get.chains {
+        let plan = fetch_plan(&get.entity, chain, cache.as_ref()).await?;
+        let mut row = plan.into_row(chain, &get.entity);
+
+        if expr.analyze {
+            // Run this chain alone so its timing and traffic aren't mixed
+            // with the other chains'. `LIMIT` is left out: rows are counted
+            // as fetched, before it truncates them.
+            let traffic = Arc::new(PortalTraffic::default());
+            let started = Instant::now();
+            let result = metered(
+                traffic.clone(),
+                resolve_entity(&get.entity, slice::from_ref(chain)),
+            )
+            .await?;
+            row.elapsed_ms = Some(started.elapsed().as_millis() as u64);
+            row.rows = Some(result.len() as u64);
+            row.portal_pages = Some(traffic.pages());
+            row.portal_bytes = Some(traffic.bytes());
+        }
+
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// The plan of `entity` on `chain`: the route its resolver takes, which for
+/// blocks, transactions and logs covers the lake and the chain cache (see
+/// `data_source`). The other entities' rows are read from the chain cache,
+/// as `resolve_entity` reads them, when it holds them.
+async fn fetch_plan(
+    entity: &Entity,
+    chain: &ChainOrRpc,
+    cache: Option<&ChainCache>,
+) -> Result<ChainPlan> {
+    let plan = match entity {
+        Entity::Account(account) => plan_account_query(account, chain),
+        Entity::Block(block) => plan_block_query(block, chain).await?,
+        Entity::Transaction(transaction) => plan_transaction_query(transaction, chain).await?,
+        Entity::Logs(logs) => plan_log_query(logs, chain).await?,
+        Entity::Storage(storage) => plan_storage_query(storage, chain),
+        Entity::TokenBalances(balances) => plan_token_balance_query(balances, chain),
+        Entity::Call(call) => plan_call_query(call, chain),
+        Entity::Withdrawals(withdrawals) => plan_withdrawals_query(withdrawals, chain),
+        Entity::Authorizations(authorizations) => plan_authorizations_query(authorizations, chain),
+        Entity::Contracts(contracts) => plan_contracts_query(contracts, chain).await?,
+        Entity::StateDiffs(state_diffs) => plan_state_diffs_query(state_diffs, chain).await?,
+        Entity::Ens(ens) => plan_ens_query(ens, chain),
+    };
+    Ok(match cache {
+        Some(cache) if SourceQuery::of(entity).is_none() => {
+            cached_plan(cache, entity, chain, plan).await
+        }
+        _ => plan,
+    })
+}
+
+/// `plan`, unless `cache` holds `entity`'s rows on `chain`, which are then
+/// read from it instead.
+async fn cached_plan(
+    cache: &ChainCache,
+    entity: &Entity,
+    chain: &ChainOrRpc,
+    plan: ChainPlan,
+) -> ChainPlan {
+    match SegmentKey::new(entity, chain) {
+        Some(key) if cache.contains(&key).await => ChainPlan::cache(&key),
+        _ => plan,
+    }
+}
+
+pub(crate) fn entity_name(entity: &Entity) -> &'static str {
+    match entity {
+        Entity::Account(_) => "accounts",
+        Entity::Block(_) => "blocks",
+        Entity::Transaction(_) => "transactions",
+        Entity::Logs(_) => "logs",
+        Entity::Storage(_) => "storage",
+        Entity::TokenBalances(_) => "token_balances",
+        Entity::Call(_) => "call",
+        Entity::Withdrawals(_) => "withdrawals",
+        Entity::Authorizations(_) => "authorizations",
+        Entity::Contracts(_) => "contracts",
+        Entity::StateDiffs(_) => "state_diffs",
+        Entity::Ens(_) => "ens",
+    }
+}
+
+/// The Portal dataset of a chain `should_use_portal` already accepted.
+pub(crate) fn portal_dataset(chain: &ChainOrRpc) -> &str {
+    match chain {
+        ChainOrRpc::Chain(c) => c.portal_dataset().unwrap(),
+        _ => unreachable!("should_use_portal guards against Rpc variant"),
+    }
+}
+
+/// The `eth_chainId` lookups `ChainOrRpc::to_chain` makes: one for an ad-hoc
+/// RPC endpoint, none for a named chain.
+pub(crate) fn chain_id_lookups(chain: &ChainOrRpc) -> u64 {
+    match chain {
+        ChainOrRpc::Chain(_) => 0,
+        ChainOrRpc::Rpc(_) => 1,
+    }
+}
+
+/// A resolved, inclusive block range.
+pub(crate) fn range_text(from_block: u64, to_block: u64) -> String {
+    if from_block == to_block {
+        from_block.to_string()
+    } else {
+        format!("{from_block}..{to_block}")
+    }
+}
+
+fn tag_text(tag: &BlockNumberOrTag) -> String {
+    match tag {
+        BlockNumberOrTag::Number(n) => n.to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// A block range as written, tags unresolved.
+pub(crate) fn block_range_text(range: &BlockRange) -> String {
+    match range.end() {
+        Some(end) => format!("{}..{}", tag_text(&range.start()), tag_text(&end)),
+        None => tag_text(&range.start()),
+    }
+}
+
+pub(crate) fn block_id_text(id: &BlockId) -> String {
+    match id {
+        BlockId::Number(tag) => tag_text(tag),
+        BlockId::Range(range) => block_range_text(range),
+    }
+}
+
+/// The `eth_getBlockByNumber` calls the RPC route makes for `id`: one per
+/// block, plus one per tag it first has to resolve to a number. `None` when
+/// the block count itself depends on a tag (`latest`, ...).
+pub(crate) fn rpc_block_fetches(id: &BlockId) -> Result<Option<u64>> {
+    match id {
+        BlockId::Number(BlockNumberOrTag::Number(_)) => Ok(Some(1)),
+        BlockId::Number(_) => Ok(Some(2)),
+        BlockId::Range(range) => match (range.start(), range.end()) {
+            (BlockNumberOrTag::Number(_), None) => Ok(Some(1)),
+            (BlockNumberOrTag::Number(start), Some(BlockNumberOrTag::Number(end))) => {
+                if start > end {
+                    return Err(BlockRangeError::StartBlockMustBeLessThanEndBlock.into());
+                }
+                Ok(Some(end - start + 1))
+            }
+            _ => Ok(None),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{
+        account::{Account, AccountField},
+        block::{Block, BlockField},
+        chain::Chain,
+        ens::NameOrAddress,
+        filters::{ComparisonFilter, EqualityFilter, FilterType},
+        logs::{LogField, LogFilter, Logs},
+        query_result::ExpressionResult,
+        transaction::{Transaction, TransactionField, TransactionFilter},
+        types::GetExpression,
+        withdrawal::{WithdrawalField, Withdrawals},
+    };
+    use crate::interpreter::backend::resolve_portal::test_support::range;
+    use alloy::primitives::{address, U256};
+    use std::{env, fs, path::PathBuf};
+
+    async fn explain(entity: Entity, chains: Vec<ChainOrRpc>) -> Vec<PlanRes> {
+        let expr = ExplainExpression {
+            get: GetExpression {
+                entity,
+                chains,
+                dump: None,
+                limit: None,
+                aliases: None,
+            },
+            analyze: false,
+        };
+        resolve_explain_query(&expr).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn logs_with_a_numeric_range_go_to_portal_with_the_exact_query() {
+        let logs = Logs::new(
+            vec![
+                LogFilter::BlockRange(range(4638657, 4638758)),
+                LogFilter::EmitterAddress(
+                    address!("dac17f958d2ee523a2206206994597c13d831ec7").into(),
+                ),
+            ],
+            vec![LogField::Address],
+        );
+        let rows = explain(Entity::Logs(logs), vec![ChainOrRpc::Chain(Chain::Ethereum)]).await;
+
+        assert_eq!(rows.len(), 1);
+        let row = &rows[0];
+        assert_eq!(row.source, "portal");
+        assert_eq!(row.block_range.as_deref(), Some("4638657..4638758"));
+        assert_eq!(row.estimated_rpc_calls, Some(0));
+        let query: Value = serde_json::from_str(row.portal_query.as_deref().unwrap()).unwrap();
+        assert_eq!(query["fromBlock"], 4638657);
+        assert_eq!(query["toBlock"], 4638758);
+        assert_eq!(
+            query["logs"][0]["address"][0],
+            "0xdac17f958d2ee523a2206206994597c13d831ec7"
+        );
+    }
+
+    #[tokio::test]
+    async fn rows_the_chain_cache_holds_are_read_from_it() {
+        let dir = env::temp_dir().join(format!("eql-explain-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let cache = ChainCache::new(dir);
+        let entity = Entity::Withdrawals(Withdrawals::new(
+            vec![BlockId::Range(range(4638657, 4638758))],
+            vec![],
+            vec![],
+            vec![WithdrawalField::Amount],
+        ));
+        let chain = ChainOrRpc::Chain(Chain::Ethereum);
+        let fetched = || ChainPlan::rpc(None, vec![], Some(1), 0);
+
+        let plan = cached_plan(&cache, &entity, &chain, fetched()).await;
+        assert_eq!(plan, fetched());
+
+        let key = SegmentKey::new(&entity, &chain).unwrap();
+        cache
+            .write(&key, &ExpressionResult::Withdrawal(vec![]))
+            .await;
+        let row = cached_plan(&cache, &entity, &chain, fetched())
+            .await
+            .into_row(&chain, &entity);
+        assert_eq!(row.source, "cache");
+        assert_eq!(row.block_range.as_deref(), Some("4638657..4638758"));
+        assert_eq!(row.estimated_rpc_calls, Some(0));
+    }
+
+    #[test]
+    fn lake_plans_list_the_files_and_row_groups_read() {
+        let plan = ChainPlan::lake(LakePlan {
+            spans: vec![(100, 120), (150, 150)],
+            files: vec![
+                (PathBuf::from("logs/2024/100-102.parquet"), vec![0, 2]),
+                (PathBuf::from("logs/2024/103-200.parquet"), vec![1]),
+            ],
+        });
+        let entity = Entity::Logs(Logs::new(vec![], vec![LogField::Address]));
+        let row = plan.into_row(&ChainOrRpc::Chain(Chain::Ethereum), &entity);
+        assert_eq!(row.source, "lake");
+        assert_eq!(row.block_range.as_deref(), Some("100..120, 150"));
+        assert_eq!(
+            row.lake_files.as_deref(),
+            Some("logs/2024/100-102.parquet (row groups 0, 2); logs/2024/103-200.parquet (row groups 1)")
+        );
+        assert_eq!(row.estimated_rpc_calls, Some(0));
+    }
+
+    #[tokio::test]
+    async fn transactions_report_client_filters_per_route() {
+        let transaction = || {
+            Transaction::new(
+                None,
+                Some(vec![
+                    TransactionFilter::BlockId(BlockId::Range(range(10, 19))),
+                    TransactionFilter::From(EqualityFilter::Eq(
+                        address!("dac17f958d2ee523a2206206994597c13d831ec7").into(),
+                    )),
+                    TransactionFilter::Value(FilterType::Comparison(ComparisonFilter::Gt(
+                        U256::from(1),
+                    ))),
+                ]),
+                vec![TransactionField::Hash, TransactionField::Status],
+            )
+        };
+
+        let portal = &explain(
+            Entity::Transaction(transaction()),
+            vec![ChainOrRpc::Chain(Chain::Ethereum)],
+        )
+        .await[0];
+        assert_eq!(portal.source, "portal");
+        assert_eq!(portal.client_filters.as_deref(), Some("value"));
+
+        let rpc = &explain(
+            Entity::Transaction(transaction()),
+            vec![ChainOrRpc::Rpc("http://localhost:8545".parse().unwrap())],
+        )
+        .await[0];
+        assert_eq!(rpc.source, "rpc");
+        assert_eq!(rpc.client_filters.as_deref(), Some("from_address, value"));
+        assert_eq!(rpc.block_range.as_deref(), Some("10..19"));
+        // A block and its receipts (for `status`) per block, plus the ad-hoc
+        // endpoint's chain id lookup per row.
+        assert_eq!(rpc.estimated_rpc_calls, Some(20));
+        assert_eq!(rpc.rpc_calls_per_row, 1);
+    }
+
+    #[tokio::test]
+    async fn blocks_with_a_pending_tag_fall_back_to_rpc() {
+        let block = Block::new(
+            Some(vec![
+                BlockId::Number(BlockNumberOrTag::Pending),
+                BlockId::Range(range(1, 5)),
+            ]),
+            None,
+            vec![BlockField::Hash],
+        );
+        let row = &explain(
+            Entity::Block(block),
+            vec![ChainOrRpc::Chain(Chain::Ethereum)],
+        )
+        .await[0];
+        assert_eq!(row.source, "rpc");
+        assert_eq!(row.block_range.as_deref(), Some("pending, 1..5"));
+        assert_eq!(row.estimated_rpc_calls, Some(2 + 5));
+        assert_eq!(row.portal_query, None);
+    }
+
+    #[tokio::test]
+    async fn accounts_count_field_calls_per_chain() {
+        let account = Account::new(
+            Some(vec![
+                NameOrAddress::Address(address!("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045")),
+                NameOrAddress::Address(address!("dac17f958d2ee523a2206206994597c13d831ec7")),
+            ]),
+            None,
+            vec![
+                AccountField::Balance,
+                AccountField::Nonce,
+                AccountField::Address,
+            ],
+        );
+        let rows = explain(
+            Entity::Account(account),
+            vec![
+                ChainOrRpc::Chain(Chain::Ethereum),
+                ChainOrRpc::Chain(Chain::Base),
+            ],
+        )
+        .await;
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].chain, "base");
+        assert!(rows.iter().all(|r| r.source == "rpc"));
+        assert!(rows.iter().all(|r| r.estimated_rpc_calls == Some(2 * 2)));
+    }
+
+    #[test]
+    fn rpc_block_fetches_counts_blocks_and_tag_lookups() {
+        let id = |start, end| BlockId::Range(BlockRange::new(start, end));
+        assert_eq!(
+            rpc_block_fetches(&BlockId::Number(BlockNumberOrTag::Number(7))).unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            rpc_block_fetches(&BlockId::Number(BlockNumberOrTag::Latest)).unwrap(),
+            Some(2)
+        );
+        assert_eq!(
+            rpc_block_fetches(&id(
+                BlockNumberOrTag::Number(3),
+                Some(BlockNumberOrTag::Latest)
+            ))
+            .unwrap(),
+            None
+        );
+        assert!(rpc_block_fetches(&BlockId::Range(range(5, 4))).is_err());
+    }
+}
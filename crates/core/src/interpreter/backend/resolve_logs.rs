@@ -1,3 +1,8 @@
+use super::data_source::{fetch, plan_route, Route, SourceQuery};
+use super::resolve_ens::primary_names;
+use super::resolve_explain::{
+    block_range_text, chain_id_lookups, portal_dataset, range_text, ChainPlan,
+};
 use super::resolve_portal::{
     block_range_is_portal_eligible, portal_query, portal_query_with_base_url, resolve_portal_range,
     value_to_address, value_to_b256, value_to_bytes, value_to_u64,
@@ -6,11 +11,12 @@ use crate::common::{
     block::BlockRange,
     chain::{Chain, ChainOrRpc},
     logs::{LogField, LogFilter, Logs},
-    query_result::LogQueryRes,
+    query_result::{ExpressionResult, LogQueryRes},
 };
 use alloy::primitives::keccak256;
 use alloy::providers::{Provider, ProviderBuilder};
 use anyhow::Result;
+use futures::future::try_join_all;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::sync::Arc;
@@ -23,6 +29,18 @@ pub enum LogResolverErrors {
     ConflictingTopic0Filters,
 }
 
+/// The columns a `WHERE` predicate on is sent to Portal for — the column
+/// side of `filter_supported_by_portal` (`event_signature` hashes down to
+/// `topic0`).
+pub(crate) const PORTAL_FILTER_FIELDS: &[LogField] = &[
+    LogField::Address,
+    LogField::Topic0,
+    LogField::Topic1,
+    LogField::Topic2,
+    LogField::Topic3,
+    LogField::BlockNumber,
+];
+
 /// Returns true if a LogFilter is supported by Portal.
 /// Portal does not support BlockHash filters.
 fn filter_supported_by_portal(filter: &LogFilter) -> bool {
@@ -46,8 +64,8 @@ fn find_block_range(filters: &[LogFilter]) -> Option<&BlockRange> {
     })
 }
 
-/// Determines if a log query for a given chain should use the Portal.
-fn should_use_portal(chain: &ChainOrRpc, logs: &Logs) -> bool {
+/// Whether Portal can fetch a log query on `chain`, as `PortalSource` reports.
+pub(crate) fn should_use_portal(chain: &ChainOrRpc, logs: &Logs) -> bool {
     let dataset = match chain {
         ChainOrRpc::Chain(c) => c.portal_dataset(),
         ChainOrRpc::Rpc(_) => None,
@@ -73,10 +91,7 @@ fn should_use_portal(chain: &ChainOrRpc, logs: &Logs) -> bool {
     }
 }
 
-pub async fn resolve_log_query(
-    logs: &Logs,
-    chain_or_rpcs: &[ChainOrRpc],
-) -> Result<Vec<LogQueryRes>> {
+fn check_topic0_filters(logs: &Logs) -> Result<()> {
     let has_event_signature = logs
         .filter()
         .iter()
@@ -88,30 +103,94 @@ pub async fn resolve_log_query(
     if has_event_signature && has_topic0 {
         return Err(LogResolverErrors::ConflictingTopic0Filters.into());
     }
+    Ok(())
+}
 
-    let mut all_results = Vec::new();
+pub async fn resolve_log_query(
+    logs: &Logs,
+    chain_or_rpcs: &[ChainOrRpc],
+) -> Result<Vec<LogQueryRes>> {
+    check_topic0_filters(logs)?;
 
-    for chain_or_rpc in chain_or_rpcs {
-        let results = if should_use_portal(chain_or_rpc, logs) {
-            resolve_logs_via_portal(logs, chain_or_rpc).await?
-        } else {
-            resolve_logs_via_rpc(logs, chain_or_rpc).await?
+    let results = try_join_all(
+        chain_or_rpcs
+            .iter()
+            .map(|chain_or_rpc| fetch(SourceQuery::Logs(logs), chain_or_rpc)),
+    )
+    .await?;
+    let mut all_results = Vec::new();
+    for result in results {
+        let ExpressionResult::Log(rows) = result else {
+            unreachable!("a log query fetches logs");
         };
-        all_results.extend(results);
+        all_results.extend(rows);
     }
 
     if all_results.is_empty() {
         return Err(LogResolverErrors::NoLogsFound.into());
     }
+    fill_ens_names(logs.fields(), &mut all_results).await?;
 
     Ok(all_results)
 }
 
+/// Fills in the `address_ens` column of `logs`, when selected, from the
+/// emitting address read for it, then drops the address unless it's
+/// selected too.
+async fn fill_ens_names(fields: &[LogField], logs: &mut [LogQueryRes]) -> Result<()> {
+    if !fields.contains(&LogField::AddressEns) {
+        return Ok(());
+    }
+    let names = primary_names(logs.iter().filter_map(|log| log.address)).await?;
+    for log in logs {
+        log.address_ens = log.address.and_then(|address| names.get(&address).cloned());
+        if !fields.contains(&LogField::Address) {
+            log.address = None;
+        }
+    }
+    Ok(())
+}
+
+/// The `EXPLAIN` plan for `logs` on `chain`. Both routes apply every log
+/// filter at the source (`eth_getLogs` takes the same address/topic/range
+/// filters Portal does), so nothing is ever filtered client-side.
+pub(crate) async fn plan_log_query(logs: &Logs, chain: &ChainOrRpc) -> Result<ChainPlan> {
+    check_topic0_filters(logs)?;
+
+    let route = plan_route(SourceQuery::Logs(logs), chain).await?;
+    if let Route::Planned(plan) = route {
+        return Ok(plan);
+    }
+    if route == Route::Portal {
+        let range =
+            find_block_range(logs.filter()).expect("should_use_portal guarantees a block range");
+        let (from_block, to_block) = resolve_portal_range(portal_dataset(chain), range).await?;
+        return Ok(ChainPlan::portal(
+            range_text(from_block, to_block),
+            vec![log_portal_query(logs, from_block, to_block)],
+            vec![],
+        ));
+    }
+
+    let block_range = logs.filter().iter().find_map(|f| match f {
+        LogFilter::BlockRange(range) => Some(block_range_text(range)),
+        LogFilter::BlockHash(hash) => Some(format!("block_hash {hash}")),
+        _ => None,
+    });
+    // A single eth_getLogs, plus an eth_chainId for an ad-hoc endpoint.
+    Ok(ChainPlan::rpc(
+        block_range,
+        vec![],
+        Some(1 + chain_id_lookups(chain)),
+        0,
+    ))
+}
+
 // ---------------------------------------------------------------------------
 // Portal path
 // ---------------------------------------------------------------------------
 
-async fn resolve_logs_via_portal(
+pub(crate) async fn resolve_logs_via_portal(
     logs: &Logs,
     chain_or_rpc: &ChainOrRpc,
 ) -> Result<Vec<LogQueryRes>> {
@@ -134,6 +213,45 @@ async fn resolve_logs_via_portal_with_base_url(
     let range = find_block_range(filters).expect("should_use_portal guarantees a block range");
     let (from_block, to_block) = resolve_portal_range(dataset, range).await?;
 
+    let query = log_portal_query(logs, from_block, to_block);
+
+    let response = match base_url {
+        Some(base_url) => portal_query_with_base_url(base_url, dataset, &query).await?,
+        None => portal_query(dataset, &query).await?,
+    };
+
+    let mut results = Vec::new();
+    for portal_block in &response {
+        let header = portal_block.get("header");
+        let block_number = header.and_then(|h| h.get("number")).and_then(value_to_u64);
+        let block_timestamp = header
+            .and_then(|h| h.get("timestamp"))
+            .and_then(value_to_u64);
+        let block_hash = header.and_then(|h| h.get("hash")).and_then(value_to_b256);
+
+        if let Some(portal_logs) = portal_block.get("logs").and_then(|l| l.as_array()) {
+            for log in portal_logs {
+                let result = parse_portal_log(
+                    log,
+                    fields,
+                    &chain_enum,
+                    block_number,
+                    block_timestamp,
+                    block_hash,
+                );
+                results.push(result);
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// The Portal stream query for `logs` over `from_block..=to_block`.
+fn log_portal_query(logs: &Logs, from_block: u64, to_block: u64) -> serde_json::Value {
+    let fields = logs.fields();
+    let filters = logs.filter();
+
     // Build log filter object for Portal
     let mut log_filter = serde_json::Map::new();
     for filter in filters {
@@ -141,7 +259,7 @@ async fn resolve_logs_via_portal_with_base_url(
             LogFilter::EmitterAddress(addr) => {
                 log_filter.insert(
                     "address".into(),
-                    json!([format!("{:?}", addr)]),
+                    json!([format!("{:?}", addr.resolved())]),
                 );
             }
             LogFilter::Topic0(topic) => {
@@ -182,7 +300,7 @@ async fn resolve_logs_via_portal_with_base_url(
 
     for field in fields {
         match field {
-            LogField::Address => {
+            LogField::Address | LogField::AddressEns => {
                 log_fields.insert("address".into(), json!(true));
             }
             LogField::Topic0 | LogField::Topic1 | LogField::Topic2 | LogField::Topic3 => {
@@ -216,44 +334,13 @@ async fn resolve_logs_via_portal_with_base_url(
         fields_obj.insert("block".into(), serde_json::Value::Object(block_fields));
     }
 
-    let query = json!({
+    json!({
         "type": "evm",
         "fromBlock": from_block,
         "toBlock": to_block,
         "fields": fields_obj,
         "logs": [log_filter]
-    });
-
-    let response = match base_url {
-        Some(base_url) => portal_query_with_base_url(base_url, dataset, &query).await?,
-        None => portal_query(dataset, &query).await?,
-    };
-
-    let mut results = Vec::new();
-    for portal_block in &response {
-        let header = portal_block.get("header");
-        let block_number = header.and_then(|h| h.get("number")).and_then(value_to_u64);
-        let block_timestamp = header
-            .and_then(|h| h.get("timestamp"))
-            .and_then(value_to_u64);
-        let block_hash = header.and_then(|h| h.get("hash")).and_then(value_to_b256);
-
-        if let Some(portal_logs) = portal_block.get("logs").and_then(|l| l.as_array()) {
-            for log in portal_logs {
-                let result = parse_portal_log(
-                    log,
-                    fields,
-                    &chain_enum,
-                    block_number,
-                    block_timestamp,
-                    block_hash,
-                );
-                results.push(result);
-            }
-        }
-    }
-
-    Ok(results)
+    })
 }
 
 fn parse_portal_log(
@@ -270,7 +357,8 @@ fn parse_portal_log(
 
     for field in fields {
         match field {
-            LogField::Address => {
+            // The address the name is looked up for; see fill_ens_names.
+            LogField::Address | LogField::AddressEns => {
                 result.address = log.get("address").and_then(value_to_address);
             }
             LogField::Topic0 => {
@@ -330,7 +418,7 @@ fn parse_portal_log(
 // RPC path (original logic, extracted)
 // ---------------------------------------------------------------------------
 
-async fn resolve_logs_via_rpc(
+pub(crate) async fn resolve_logs_via_rpc(
     logs: &Logs,
     chain_or_rpc: &ChainOrRpc,
 ) -> Result<Vec<LogQueryRes>> {
@@ -345,7 +433,9 @@ async fn resolve_logs_via_rpc(
 
             for field in logs.fields() {
                 match field {
-                    LogField::Address => result.address = Some(log.inner.address),
+                    LogField::Address | LogField::AddressEns => {
+                        result.address = Some(log.inner.address)
+                    }
                     LogField::Topic0 => result.topic0 = log.topic0().copied(),
                     LogField::Topic1 => {
                         result.topic1 = log.inner.data.topics().get(1).copied()
@@ -436,7 +526,7 @@ mod tests {
                     BlockNumberOrTag::Number(30),
                     Some(BlockNumberOrTag::Number(31)),
                 )),
-                LogFilter::EmitterAddress(emitter),
+                LogFilter::EmitterAddress(emitter.into()),
             ],
             vec![LogField::Address],
         );
@@ -2,8 +2,10 @@ use alloy::eips::BlockNumberOrTag;
 use alloy::primitives::{Address, Bloom, Bytes, B256, U256};
 use anyhow::Result;
 use serde_json::Value;
+use std::future::Future;
 use std::str::FromStr;
-use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
 use crate::common::block::{BlockId, BlockRange, BlockRangeError};
@@ -26,6 +28,42 @@ fn portal_client() -> &'static reqwest::Client {
     })
 }
 
+/// Stream pages and response bytes received from Portal while a future runs
+/// under [`metered`] — what `EXPLAIN ANALYZE` reports. Outside `metered`
+/// nothing is recorded.
+#[derive(Debug, Default)]
+pub(crate) struct PortalTraffic {
+    pages: AtomicU64,
+    bytes: AtomicU64,
+}
+
+impl PortalTraffic {
+    pub(crate) fn pages(&self) -> u64 {
+        self.pages.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+}
+
+tokio::task_local! {
+    static PORTAL_TRAFFIC: Arc<PortalTraffic>;
+}
+
+/// Runs `future`, adding every Portal page it fetches to `traffic`.
+pub(crate) async fn metered<F: Future>(traffic: Arc<PortalTraffic>, future: F) -> F::Output {
+    PORTAL_TRAFFIC.scope(traffic, future).await
+}
+
+fn record_page(bytes: usize) {
+    // Not inside `metered`: nobody is counting.
+    let _ = PORTAL_TRAFFIC.try_with(|traffic| {
+        traffic.pages.fetch_add(1, Ordering::Relaxed);
+        traffic.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    });
+}
+
 fn next_portal_page_start(
     page: &[Value],
     current_from_block: u64,
@@ -108,6 +146,7 @@ pub(crate) async fn portal_query_with_base_url(
             .text()
             .await
             .map_err(|e| anyhow::anyhow!("Failed to read Portal response: {}", e))?;
+        record_page(body.len());
 
         let page: Vec<Value> = body
             .lines()
@@ -341,6 +380,11 @@ pub async fn resolve_block_id_range(dataset: &str, id: &BlockId) -> Result<(u64,
 
 #[cfg(test)]
 pub(crate) mod test_support {
+    use crate::common::{
+        block::BlockRange,
+        chain::{Chain, ChainOrRpc},
+    };
+    use alloy::eips::BlockNumberOrTag;
     use serde_json::Value;
     use std::{
         io::{BufRead, BufReader, Read, Write},
@@ -349,6 +393,18 @@ pub(crate) mod test_support {
         thread::{self, JoinHandle},
     };
 
+    pub(crate) fn eth() -> ChainOrRpc {
+        ChainOrRpc::Chain(Chain::Ethereum)
+    }
+
+    /// `start..end`, both ends included.
+    pub(crate) fn range(start: u64, end: u64) -> BlockRange {
+        BlockRange::new(
+            BlockNumberOrTag::Number(start),
+            Some(BlockNumberOrTag::Number(end)),
+        )
+    }
+
     pub(crate) fn spawn_mock_portal(
         responses: Vec<String>,
     ) -> (String, Arc<Mutex<Vec<Value>>>, JoinHandle<()>) {
@@ -491,6 +547,28 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_metered_counts_every_page_and_its_bytes() {
+        let first = "{\"header\":{\"number\":1}}\n".to_string();
+        let second = "{\"header\":{\"number\":2}}\n".to_string();
+        let bytes = (first.len() + second.len()) as u64;
+        let (base_url, _requests, handle) = test_support::spawn_mock_portal(vec![first, second]);
+        let query = json!({ "type": "evm", "fromBlock": 1, "toBlock": 2 });
+
+        let traffic = Arc::new(PortalTraffic::default());
+        let blocks = metered(
+            traffic.clone(),
+            portal_query_with_base_url(&base_url, "test", &query),
+        )
+        .await
+        .unwrap();
+        handle.join().expect("mock Portal thread");
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(traffic.pages(), 2);
+        assert_eq!(traffic.bytes(), bytes);
+    }
+
     #[test]
     fn test_value_to_bloom_parses_hex_string() {
         let zeros = format!("0x{}", "0".repeat(512));
@@ -0,0 +1,662 @@
+use super::{
+    resolve_account::{planned_blocks, state_blocks},
+    resolve_explain::{block_id_text, chain_id_lookups, portal_dataset, range_text, ChainPlan},
+    resolve_portal::{
+        block_id_is_portal_eligible, portal_query, portal_query_with_base_url,
+        resolve_block_id_range, value_to_address, value_to_b256, value_to_u256, value_to_u64,
+    },
+};
+use crate::common::{
+    block::BlockId,
+    chain::{Chain, ChainOrRpc},
+    ens::NameOrAddress,
+    query_result::StateDiffQueryRes,
+    state_diff::{StateDiffField, StateDiffKind, StateDiffs},
+};
+use alloy::{
+    eips::BlockNumberOrTag,
+    primitives::{Address, B256},
+    providers::{Provider, ProviderBuilder, RootProvider},
+    transports::http::{Client, Http},
+};
+use anyhow::Result;
+use futures::future::try_join_all;
+use serde_json::{json, Map, Value};
+use std::{collections::HashMap, sync::Arc};
+
+#[derive(thiserror::Error, Debug)]
+pub enum StateDiffResolverErrors {
+    #[error("state_diffs are not available on {0}: its Portal dataset has no stateDiffs table. Pass the URL of a node serving debug_traceBlockByNumber as the chain to read them over RPC")]
+    NoStateDiffs(Chain),
+}
+
+/// A change to one piece of an account's state, before projection to the
+/// selected fields.
+#[derive(Debug, PartialEq, Clone)]
+struct Diff {
+    address: Address,
+    kind: StateDiffKind,
+    key: Option<B256>,
+    prev: Option<String>,
+    next: Option<String>,
+    transaction_hash: Option<B256>,
+    block_number: u64,
+}
+
+/// The columns a `WHERE` predicate on is sent to Portal for: the blocks it
+/// scans and the `address`/`key` filters of its `stateDiffs` request. A
+/// `kind = 'storage'` filter can't be written as a Portal key, so it's
+/// applied to the fetched diffs.
+pub(crate) const PORTAL_FILTER_FIELDS: &[StateDiffField] = &[
+    StateDiffField::BlockNumber,
+    StateDiffField::Address,
+    StateDiffField::Key,
+];
+
+/// Determines if `chain` should read state diffs from the Portal. A named
+/// chain whose dataset doesn't serve them can't read them at all: its
+/// default RPC endpoints don't expose the `debug` namespace, so rather than
+/// an empty result this is an error, like any other coverage gap.
+fn should_use_portal(chain: &ChainOrRpc, ids: &[&BlockId]) -> Result<bool> {
+    match chain {
+        ChainOrRpc::Rpc(_) => Ok(false),
+        ChainOrRpc::Chain(c) if !c.portal_has_traces() => {
+            Err(StateDiffResolverErrors::NoStateDiffs(c.clone()).into())
+        }
+        ChainOrRpc::Chain(_) => Ok(ids.iter().all(|id| block_id_is_portal_eligible(id))),
+    }
+}
+
+/// Resolve the query to list state changes. Portal's `stateDiffs` table is
+/// read where the chain has one; an RPC endpoint given as the chain is asked
+/// for `debug_traceBlockByNumber` with the prestate tracer in diff mode.
+pub async fn resolve_state_diffs_query(
+    state_diffs: &StateDiffs,
+    chains: &[ChainOrRpc],
+) -> Result<Vec<StateDiffQueryRes>> {
+    let fields = state_diffs.fields();
+    let addresses = state_diffs
+        .addresses()
+        .iter()
+        .map(NameOrAddress::resolved)
+        .collect::<Vec<_>>();
+    let mut all_diff_futures = Vec::new();
+
+    for chain in chains {
+        let fields = &fields;
+        let addresses = &addresses;
+
+        let diff_future = async move {
+            let block_ids = state_diffs.block_ids();
+            let use_portal = should_use_portal(chain, &block_ids)?;
+            let chain_enum = chain.to_chain().await?;
+            let diffs = if use_portal {
+                portal_diffs(&block_ids, addresses, state_diffs, &chain_enum, None).await?
+            } else {
+                let provider = Arc::new(ProviderBuilder::new().on_http(chain.rpc_url()?));
+                let numbers = state_blocks(&block_ids, false, &provider)
+                    .await?
+                    .into_iter()
+                    .flatten();
+                let provider = &provider;
+                try_join_all(numbers.map(|n| rpc_diffs(n, provider)))
+                    .await?
+                    .concat()
+            };
+
+            Ok::<_, anyhow::Error>(
+                diffs
+                    .iter()
+                    .filter(|d| is_selected(d, addresses, state_diffs))
+                    .map(|d| state_diff_row(fields, &chain_enum, d))
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        all_diff_futures.push(diff_future);
+    }
+
+    let diff_res = try_join_all(all_diff_futures).await?;
+    Ok(diff_res.concat())
+}
+
+/// The `EXPLAIN` plan for `state_diffs` on `chain`: the Portal queries of
+/// the scan, or one `debug_traceBlockByNumber` per block over RPC.
+pub(crate) async fn plan_state_diffs_query(
+    state_diffs: &StateDiffs,
+    chain: &ChainOrRpc,
+) -> Result<ChainPlan> {
+    let block_ids = state_diffs.block_ids();
+    let mut client_filters = Vec::new();
+    if !state_diffs.kinds().is_empty() {
+        client_filters.push(StateDiffField::Kind.to_string());
+    }
+
+    if should_use_portal(chain, &block_ids)? {
+        let addresses = state_diffs
+            .addresses()
+            .iter()
+            .map(NameOrAddress::resolved)
+            .collect::<Vec<_>>();
+        let dataset = portal_dataset(chain);
+        let mut ranges = Vec::new();
+        let mut queries = Vec::new();
+        for id in &block_ids {
+            let (from_block, to_block) = resolve_block_id_range(dataset, id).await?;
+            ranges.push(range_text(from_block, to_block));
+            queries.push(state_diff_portal_query(
+                &addresses,
+                state_diffs,
+                from_block,
+                to_block,
+            ));
+        }
+        return Ok(ChainPlan::portal(
+            ranges.join(", "),
+            queries,
+            client_filters,
+        ));
+    }
+
+    if !state_diffs.addresses().is_empty() {
+        client_filters.push(StateDiffField::Address.to_string());
+    }
+    if !state_diffs.keys().is_empty() {
+        client_filters.push(StateDiffField::Key.to_string());
+    }
+    let calls = planned_blocks(&block_ids, false)
+        .map(|(blocks, lookups)| blocks + lookups + chain_id_lookups(chain));
+    Ok(ChainPlan::rpc(
+        Some(
+            block_ids
+                .iter()
+                .map(|id| block_id_text(id))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        client_filters,
+        calls,
+        0,
+    ))
+}
+
+/// A `key` filter only matches storage diffs, so it's checked along with
+/// the kinds.
+fn is_selected(diff: &Diff, addresses: &[Address], state_diffs: &StateDiffs) -> bool {
+    let kinds = state_diffs.kinds();
+    let keys = state_diffs.keys();
+    (addresses.is_empty() || addresses.contains(&diff.address))
+        && (kinds.is_empty() || kinds.contains(&diff.kind))
+        && (keys.is_empty() || diff.key.is_some_and(|key| keys.contains(&key)))
+}
+
+fn state_diff_row(fields: &[StateDiffField], chain: &Chain, diff: &Diff) -> StateDiffQueryRes {
+    let mut row = StateDiffQueryRes::default();
+    for field in fields {
+        match field {
+            StateDiffField::Address => row.address = Some(diff.address),
+            StateDiffField::Key => row.key = diff.key,
+            StateDiffField::Kind => row.kind = Some(diff.kind),
+            StateDiffField::Prev => row.prev = diff.prev.clone(),
+            StateDiffField::Next => row.next = diff.next.clone(),
+            StateDiffField::TransactionHash => row.transaction_hash = diff.transaction_hash,
+            StateDiffField::BlockNumber => row.block_number = Some(diff.block_number),
+            StateDiffField::Chain => row.chain = Some(chain.clone()),
+        }
+    }
+    row
+}
+
+/// A value of `kind` as the `prev`/`next` columns show it: balances and
+/// nonces in decimal, code and storage words as the hex they came in.
+fn value_text(kind: StateDiffKind, value: &Value) -> Option<String> {
+    match kind {
+        StateDiffKind::Balance | StateDiffKind::Nonce => {
+            value_to_u256(value).map(|n| n.to_string())
+        }
+        StateDiffKind::Code | StateDiffKind::Storage => value.as_str().map(str::to_string),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Portal path
+// ---------------------------------------------------------------------------
+
+async fn portal_diffs(
+    block_ids: &[&BlockId],
+    addresses: &[Address],
+    state_diffs: &StateDiffs,
+    chain: &Chain,
+    base_url: Option<&str>,
+) -> Result<Vec<Diff>> {
+    let dataset = chain.portal_dataset().unwrap();
+    let mut diffs = Vec::new();
+    for id in block_ids {
+        let (from_block, to_block) = resolve_block_id_range(dataset, id).await?;
+        let query = state_diff_portal_query(addresses, state_diffs, from_block, to_block);
+        let response = match base_url {
+            Some(base_url) => portal_query_with_base_url(base_url, dataset, &query).await?,
+            None => portal_query(dataset, &query).await?,
+        };
+        for portal_block in &response {
+            diffs.extend(parse_portal_diffs(portal_block));
+        }
+    }
+    Ok(diffs)
+}
+
+/// The Portal `key` filter for `state_diffs`: the storage slots it keeps,
+/// or else the names of the account fields, or `None` to keep every key,
+/// which is the only way to ask for every storage slot.
+fn portal_keys(state_diffs: &StateDiffs) -> Option<Vec<String>> {
+    let kinds = state_diffs.kinds();
+    let keys = state_diffs.keys();
+    if !keys.is_empty() {
+        return Some(keys.iter().map(|key| format!("{key:?}")).collect());
+    }
+    if kinds.is_empty() || kinds.contains(&StateDiffKind::Storage) {
+        return None;
+    }
+    Some(kinds.iter().map(|kind| kind.to_string()).collect())
+}
+
+/// The Portal stream query for the state changes over
+/// `from_block..=to_block`. Portal also lists the accounts a transaction
+/// only touched (kind `=`), which aren't asked for.
+fn state_diff_portal_query(
+    addresses: &[Address],
+    state_diffs: &StateDiffs,
+    from_block: u64,
+    to_block: u64,
+) -> Value {
+    // The parent transactions come along for their hashes.
+    let mut diff_filter = json!({ "kind": ["+", "*", "-"], "transaction": true });
+    if !addresses.is_empty() {
+        let addresses: Vec<_> = addresses.iter().map(|a| format!("{a:?}")).collect();
+        diff_filter["address"] = json!(addresses);
+    }
+    if let Some(keys) = portal_keys(state_diffs) {
+        diff_filter["key"] = json!(keys);
+    }
+
+    json!({
+        "type": "evm",
+        "fromBlock": from_block,
+        "toBlock": to_block,
+        "fields": {
+            "block": { "number": true },
+            "transaction": { "transactionIndex": true, "hash": true },
+            "stateDiff": {
+                "transactionIndex": true,
+                "address": true,
+                "key": true,
+                "kind": true,
+                "prev": true,
+                "next": true
+            }
+        },
+        "stateDiffs": [diff_filter]
+    })
+}
+
+/// The diffs of a Portal block. Portal names the changed account field in
+/// `key` (`balance`, `nonce`, `code`) or gives the storage slot.
+fn parse_portal_diffs(portal_block: &Value) -> Vec<Diff> {
+    let Some(block_number) = portal_block
+        .get("header")
+        .and_then(|h| h.get("number"))
+        .and_then(value_to_u64)
+    else {
+        return vec![];
+    };
+    let hashes: HashMap<u64, B256> = portal_block
+        .get("transactions")
+        .and_then(|t| t.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|tx| {
+            let index = tx.get("transactionIndex").and_then(value_to_u64)?;
+            Some((index, tx.get("hash").and_then(value_to_b256)?))
+        })
+        .collect();
+
+    let state_diffs = portal_block.get("stateDiffs").and_then(|d| d.as_array());
+    state_diffs
+        .into_iter()
+        .flatten()
+        .filter(|diff| diff.get("kind").and_then(Value::as_str) != Some("="))
+        .filter_map(|diff| {
+            let (kind, key) = match diff.get("key").and_then(Value::as_str)? {
+                "balance" => (StateDiffKind::Balance, None),
+                "nonce" => (StateDiffKind::Nonce, None),
+                "code" => (StateDiffKind::Code, None),
+                _ => (
+                    StateDiffKind::Storage,
+                    Some(diff.get("key").and_then(value_to_b256)?),
+                ),
+            };
+            let value = |name: &str| {
+                diff.get(name)
+                    .filter(|v| !v.is_null())
+                    .and_then(|v| value_text(kind, v))
+            };
+            Some(Diff {
+                address: diff.get("address").and_then(value_to_address)?,
+                kind,
+                key,
+                prev: value("prev"),
+                next: value("next"),
+                transaction_hash: diff
+                    .get("transactionIndex")
+                    .and_then(value_to_u64)
+                    .and_then(|index| hashes.get(&index).copied()),
+                block_number,
+            })
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// RPC path
+// ---------------------------------------------------------------------------
+
+/// The diffs of `block` from the prestate tracer in diff mode, which needs
+/// a node serving the `debug` namespace (and an archive one for old blocks).
+async fn rpc_diffs(block: u64, provider: &RootProvider<Http<Client>>) -> Result<Vec<Diff>> {
+    let traces: Vec<Value> = provider
+        .client()
+        .request(
+            "debug_traceBlockByNumber",
+            (
+                BlockNumberOrTag::Number(block),
+                json!({ "tracer": "prestateTracer", "tracerConfig": { "diffMode": true } }),
+            ),
+        )
+        .await?;
+    Ok(traces
+        .iter()
+        .flat_map(|trace| parse_prestate_diffs(trace, block))
+        .collect())
+}
+
+/// The diffs of one transaction's prestate trace. `pre` holds the accounts
+/// it modified as they were, less the storage slots it left alone; `post`
+/// only the fields it changed. An account missing from `pre` was created,
+/// one missing from `post` destroyed, and a slot missing from `post` was
+/// zeroed.
+fn parse_prestate_diffs(trace: &Value, block_number: u64) -> Vec<Diff> {
+    let transaction_hash = trace.get("txHash").and_then(value_to_b256);
+    let empty = Map::new();
+    let accounts = |side: &str| {
+        trace
+            .get("result")
+            .and_then(|r| r.get(side))
+            .and_then(Value::as_object)
+            .unwrap_or(&empty)
+    };
+    let (pre, post) = (accounts("pre"), accounts("post"));
+    let mut addresses: Vec<&String> = pre.keys().chain(post.keys()).collect();
+    addresses.sort();
+    addresses.dedup();
+
+    let mut diffs = Vec::new();
+    for raw_address in addresses {
+        let Ok(address) = raw_address.parse::<Address>() else {
+            continue;
+        };
+        let before = pre.get(raw_address);
+        let after = post.get(raw_address);
+        let mut push = |kind, key, prev, next| {
+            diffs.push(Diff {
+                address,
+                kind,
+                key,
+                prev,
+                next,
+                transaction_hash,
+                block_number,
+            })
+        };
+
+        for (kind, name, unset) in [
+            (StateDiffKind::Balance, "balance", "0"),
+            (StateDiffKind::Nonce, "nonce", "0"),
+            (StateDiffKind::Code, "code", "0x"),
+        ] {
+            // The tracer leaves out a zero nonce and empty code.
+            let prev = before.map(|account| {
+                account
+                    .get(name)
+                    .and_then(|v| value_text(kind, v))
+                    .unwrap_or_else(|| unset.to_string())
+            });
+            match after {
+                Some(account) => {
+                    if let Some(next) = account.get(name).and_then(|v| value_text(kind, v)) {
+                        push(kind, None, prev, Some(next));
+                    }
+                }
+                None if prev.as_deref().is_some_and(|p| p != unset) => {
+                    push(kind, None, prev, None);
+                }
+                None => {}
+            }
+        }
+
+        let slots = |account: Option<&Value>| {
+            account
+                .and_then(|a| a.get("storage"))
+                .and_then(Value::as_object)
+                .cloned()
+                .unwrap_or_default()
+        };
+        let (slots_before, slots_after) = (slots(before), slots(after));
+        let mut keys: Vec<&String> = slots_before.keys().chain(slots_after.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for raw_key in keys {
+            let Ok(key) = raw_key.parse::<B256>() else {
+                continue;
+            };
+            let text = |v: &Value| value_text(StateDiffKind::Storage, v);
+            let prev = slots_before.get(raw_key).and_then(text);
+            let next = after.map(|_| {
+                slots_after
+                    .get(raw_key)
+                    .and_then(text)
+                    .unwrap_or_else(|| format!("{:?}", B256::ZERO))
+            });
+            push(StateDiffKind::Storage, Some(key), prev, next);
+        }
+    }
+    diffs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::block::BlockRange;
+    use alloy::primitives::address;
+
+    fn state_diffs(kinds: Vec<StateDiffKind>, keys: Vec<B256>) -> StateDiffs {
+        StateDiffs::new(
+            vec![BlockId::Range(BlockRange::new(
+                BlockNumberOrTag::Number(60),
+                None,
+            ))],
+            vec![],
+            kinds,
+            keys,
+            StateDiffField::all_variants().to_vec(),
+        )
+    }
+
+    #[test]
+    fn chains_without_state_diffs_are_an_error() {
+        let block = BlockId::Number(BlockNumberOrTag::Number(1));
+        let err = should_use_portal(&ChainOrRpc::Chain(Chain::Celo), &[&block])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("celo"), "{err}");
+        assert!(should_use_portal(&ChainOrRpc::Chain(Chain::Ethereum), &[&block]).unwrap());
+        let url = ChainOrRpc::Rpc("http://localhost:8545".parse().unwrap());
+        assert!(!should_use_portal(&url, &[&block]).unwrap());
+    }
+
+    #[test]
+    fn portal_keys_name_account_fields_and_slots() {
+        assert_eq!(portal_keys(&state_diffs(vec![], vec![])), None);
+        assert_eq!(
+            portal_keys(&state_diffs(vec![StateDiffKind::Storage], vec![])),
+            None
+        );
+        assert_eq!(
+            portal_keys(&state_diffs(
+                vec![StateDiffKind::Balance, StateDiffKind::Nonce],
+                vec![]
+            )),
+            Some(vec!["balance".to_string(), "nonce".to_string()])
+        );
+        assert_eq!(
+            portal_keys(&state_diffs(vec![], vec![B256::ZERO])),
+            Some(vec![format!("{:?}", B256::ZERO)])
+        );
+    }
+
+    #[tokio::test]
+    async fn portal_state_diffs_become_rows() {
+        let holder = address!("1000000000000000000000000000000000000001");
+        let (base_url, requests, handle) =
+            super::super::resolve_portal::test_support::spawn_mock_portal(vec![concat!(
+                "{\"header\":{\"number\":\"0x3c\"},",
+                "\"transactions\":[{\"transactionIndex\":2,",
+                "\"hash\":\"0x00000000000000000000000000000000000000000000000000000000000000aa\"}],",
+                "\"stateDiffs\":[",
+                "{\"transactionIndex\":2,\"address\":\"0x1000000000000000000000000000000000000001\",",
+                "\"key\":\"balance\",\"kind\":\"*\",\"prev\":\"0x10\",\"next\":\"0x08\"},",
+                "{\"transactionIndex\":2,\"address\":\"0x1000000000000000000000000000000000000001\",",
+                "\"key\":\"0x0000000000000000000000000000000000000000000000000000000000000001\",",
+                "\"kind\":\"+\",\"prev\":null,",
+                "\"next\":\"0x0000000000000000000000000000000000000000000000000000000000000005\"}]}\n"
+            )
+            .to_string()]);
+
+        let diffs = portal_diffs(
+            &[&BlockId::Range(BlockRange::new(
+                BlockNumberOrTag::Number(60),
+                None,
+            ))],
+            &[holder],
+            &state_diffs(vec![], vec![]),
+            &Chain::Ethereum,
+            Some(&base_url),
+        )
+        .await
+        .unwrap();
+        handle.join().expect("mock Portal thread");
+
+        let tx = Some(B256::with_last_byte(0xaa));
+        assert_eq!(
+            diffs,
+            [
+                Diff {
+                    address: holder,
+                    kind: StateDiffKind::Balance,
+                    key: None,
+                    prev: Some("16".into()),
+                    next: Some("8".into()),
+                    transaction_hash: tx,
+                    block_number: 60,
+                },
+                Diff {
+                    address: holder,
+                    kind: StateDiffKind::Storage,
+                    key: Some(B256::with_last_byte(1)),
+                    prev: None,
+                    next: Some(format!("{:?}", B256::with_last_byte(5))),
+                    transaction_hash: tx,
+                    block_number: 60,
+                },
+            ]
+        );
+
+        let requests = requests.lock().expect("captured requests");
+        let request = &requests[0];
+        assert_eq!(
+            request["stateDiffs"][0]["address"],
+            json!(["0x1000000000000000000000000000000000000001"])
+        );
+        assert!(request["stateDiffs"][0].get("key").is_none());
+    }
+
+    #[test]
+    fn prestate_diff_mode_becomes_rows() {
+        let trace = json!({
+            "txHash": "0x00000000000000000000000000000000000000000000000000000000000000bb",
+            "result": {
+                "pre": {
+                    "0x1000000000000000000000000000000000000001": {
+                        "balance": "0x10",
+                        "nonce": 1,
+                        "storage": {
+                            "0x0000000000000000000000000000000000000000000000000000000000000001":
+                                "0x0000000000000000000000000000000000000000000000000000000000000005"
+                        }
+                    }
+                },
+                "post": {
+                    "0x1000000000000000000000000000000000000001": {
+                        "balance": "0x08",
+                        "nonce": 2
+                    },
+                    "0x2000000000000000000000000000000000000002": {
+                        "balance": "0x08",
+                        "code": "0x6000"
+                    }
+                }
+            }
+        });
+        let diffs = parse_prestate_diffs(&trace, 7);
+        let rows: Vec<_> = diffs
+            .iter()
+            .map(|d| (d.address, d.kind, d.prev.as_deref(), d.next.as_deref()))
+            .collect();
+        let sender = address!("1000000000000000000000000000000000000001");
+        let created = address!("2000000000000000000000000000000000000002");
+        let zero = format!("{:?}", B256::ZERO);
+        assert_eq!(
+            rows,
+            [
+                (sender, StateDiffKind::Balance, Some("16"), Some("8")),
+                (sender, StateDiffKind::Nonce, Some("1"), Some("2")),
+                (
+                    sender,
+                    StateDiffKind::Storage,
+                    Some("0x0000000000000000000000000000000000000000000000000000000000000005"),
+                    Some(zero.as_str()),
+                ),
+                (created, StateDiffKind::Balance, None, Some("8")),
+                (created, StateDiffKind::Code, None, Some("0x6000")),
+            ]
+        );
+        assert!(
+            diffs
+                .iter()
+                .all(|d| d.transaction_hash == Some(B256::with_last_byte(0xbb))
+                    && d.block_number == 7)
+        );
+
+        let selection = StateDiffs::new(
+            vec![],
+            vec![NameOrAddress::Address(sender)],
+            vec![StateDiffKind::Storage],
+            vec![],
+            vec![],
+        );
+        let kept: Vec<_> = diffs
+            .iter()
+            .filter(|d| is_selected(d, &[sender], &selection))
+            .collect();
+        assert_eq!(kept.len(), 1);
+    }
+}
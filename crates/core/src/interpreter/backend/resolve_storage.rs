@@ -0,0 +1,215 @@
+use super::{
+    resolve_account::{
+        planned_blocks, send_state_calls, state_blocks, StateCall, StateRead, StateValue,
+        BATCHES_IN_FLIGHT, CALLS_PER_BATCH,
+    },
+    resolve_explain::{block_id_text, chain_id_lookups, ChainPlan},
+};
+use crate::common::{
+    chain::{Chain, ChainOrRpc},
+    ens::NameOrAddress,
+    query_result::StorageQueryRes,
+    storage::{Storage, StorageField},
+};
+use alloy::{
+    primitives::{Address, B256},
+    providers::ProviderBuilder,
+};
+use anyhow::Result;
+use futures::{future::try_join_all, stream, StreamExt};
+use std::sync::Arc;
+
+/// Resolve the query to read storage slots. Each slot of each address is
+/// read once per block its `block_number` filters name (or once at the
+/// latest state). The reads for a chain go out as the account reads do (see
+/// `send_state_calls`): in batches of `CALLS_PER_BATCH`, with at most
+/// `BATCHES_IN_FLIGHT` of them waiting on the node at once.
+pub async fn resolve_storage_query(
+    storage: &Storage,
+    chains: &[ChainOrRpc],
+) -> Result<Vec<StorageQueryRes>> {
+    let fields = storage.fields();
+    let pin_latest = fields.contains(&StorageField::BlockNumber);
+    let addresses = storage
+        .addresses()
+        .iter()
+        .map(NameOrAddress::resolved)
+        .collect::<Vec<_>>();
+
+    let chain_futures = chains.iter().map(|chain| {
+        let (fields, addresses) = (&fields, &addresses);
+        async move {
+            let provider = Arc::new(ProviderBuilder::new().on_http(chain.rpc_url()?));
+            let chain_enum = chain.to_chain().await?;
+            let blocks = state_blocks(&storage.block_ids(), pin_latest, &provider).await?;
+            let calls = storage_calls(addresses, storage.slots(), &blocks);
+
+            let mut rows = Vec::with_capacity(calls.len());
+            // Owned batches, as in `get_accounts`, so the stream stays `Send`.
+            let mut batches = stream::iter(calls.chunks(CALLS_PER_BATCH).map(<[_]>::to_vec))
+                .map(|batch| send_state_calls(batch, &provider, chain))
+                .buffered(BATCHES_IN_FLIGHT);
+            while let Some(batch) = batches.next().await {
+                for (call, value) in batch? {
+                    let (StateRead::Storage(slot), StateValue::Storage(value)) = (call.read, value)
+                    else {
+                        unreachable!("storage reads only storage");
+                    };
+                    rows.push(storage_row(
+                        fields,
+                        &chain_enum,
+                        &call.address,
+                        &slot,
+                        B256::from(value),
+                        call.block,
+                    ));
+                }
+            }
+            Ok::<_, anyhow::Error>(rows)
+        }
+    });
+
+    Ok(try_join_all(chain_futures).await?.concat())
+}
+
+/// The reads for every slot of every address at every block, one row each:
+/// address by address, then block by block, then slot by slot.
+fn storage_calls(addresses: &[Address], slots: &[B256], blocks: &[Option<u64>]) -> Vec<StateCall> {
+    addresses
+        .iter()
+        .flat_map(|address| {
+            blocks
+                .iter()
+                .flat_map(move |block| slots.iter().map(move |slot| (*address, *block, *slot)))
+        })
+        .enumerate()
+        .map(|(row, (address, block, slot))| StateCall {
+            row,
+            read: StateRead::Storage(slot),
+            address,
+            block,
+        })
+        .collect()
+}
+
+/// The `EXPLAIN` plan for `storage` on `chain`. Every slot is one
+/// `eth_getStorageAt` call per address per block; they travel batched, but
+/// providers meter them one by one, so that is how they are counted.
+pub(crate) fn plan_storage_query(storage: &Storage, chain: &ChainOrRpc) -> ChainPlan {
+    let block_ids = storage.block_ids();
+    let block_range = (!block_ids.is_empty()).then(|| {
+        block_ids
+            .iter()
+            .map(|id| block_id_text(id))
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+    let slots = storage.slots().len() as u64;
+    let pin_latest = storage.fields().contains(&StorageField::BlockNumber);
+    let calls = planned_blocks(&block_ids, pin_latest).map(|(blocks, lookups)| {
+        let addresses = storage.addresses().len() as u64;
+        addresses * slots * blocks + lookups + chain_id_lookups(chain)
+    });
+    ChainPlan::rpc(block_range, vec![], calls, 0)
+}
+
+fn storage_row(
+    fields: &[StorageField],
+    chain: &Chain,
+    address: &Address,
+    slot: &B256,
+    value: B256,
+    block: Option<u64>,
+) -> StorageQueryRes {
+    let mut row = StorageQueryRes::default();
+    for field in fields {
+        match field {
+            StorageField::Address => row.address = Some(*address),
+            StorageField::Slot => row.slot = Some(*slot),
+            StorageField::Value => row.value = Some(value),
+            StorageField::BlockNumber => row.block_number = block,
+            StorageField::Chain => row.chain = Some(chain.clone()),
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::block::{BlockId, BlockRange};
+    use alloy::eips::BlockNumberOrTag;
+
+    #[test]
+    fn plans_one_read_per_slot_per_block() {
+        let chain = ChainOrRpc::Chain(Chain::Ethereum);
+        let storage = Storage::new(
+            vec![
+                NameOrAddress::Address(Address::ZERO),
+                NameOrAddress::Address(Address::with_last_byte(1)),
+            ],
+            vec![B256::ZERO, B256::with_last_byte(1), B256::with_last_byte(2)],
+            vec![BlockId::Range(BlockRange::new(
+                BlockNumberOrTag::Number(10),
+                Some(BlockNumberOrTag::Number(11)),
+            ))],
+            StorageField::all_variants().to_vec(),
+        );
+        let plan = plan_storage_query(&storage, &chain);
+        // 3 slots x 2 blocks per address.
+        assert_eq!(plan.rpc_calls, Some(2 * 3 * 2));
+        assert_eq!(plan.block_range.as_deref(), Some("10..11"));
+
+        // At the latest state, selecting block_number pins it to the head.
+        let storage = Storage::new(
+            vec![NameOrAddress::Address(Address::ZERO)],
+            vec![B256::ZERO],
+            vec![],
+            vec![StorageField::Value, StorageField::BlockNumber],
+        );
+        assert_eq!(plan_storage_query(&storage, &chain).rpc_calls, Some(2));
+    }
+
+    #[test]
+    fn slot_reads_are_split_into_bounded_batches() {
+        let addresses = [Address::ZERO, Address::with_last_byte(1)];
+        let slots = [B256::ZERO, B256::with_last_byte(1), B256::with_last_byte(2)];
+        let blocks: Vec<_> = (0..50).map(Some).collect();
+        let calls = storage_calls(&addresses, &slots, &blocks);
+        assert_eq!(calls.len(), 2 * 3 * 50);
+        assert_eq!(calls.chunks(CALLS_PER_BATCH).count(), 3);
+        assert_eq!(
+            calls[..4]
+                .iter()
+                .map(|c| (c.row, c.read, c.address, c.block))
+                .collect::<Vec<_>>(),
+            [
+                (0, StateRead::Storage(B256::ZERO), Address::ZERO, Some(0)),
+                (1, StateRead::Storage(slots[1]), Address::ZERO, Some(0)),
+                (2, StateRead::Storage(slots[2]), Address::ZERO, Some(0)),
+                (3, StateRead::Storage(B256::ZERO), Address::ZERO, Some(1)),
+            ]
+        );
+        assert_eq!(calls[150].address, addresses[1]);
+    }
+
+    #[test]
+    fn rows_carry_only_the_selected_fields() {
+        let row = storage_row(
+            &[StorageField::Slot, StorageField::Value],
+            &Chain::Ethereum,
+            &Address::ZERO,
+            &B256::with_last_byte(1),
+            B256::with_last_byte(7),
+            Some(100),
+        );
+        assert_eq!(
+            row,
+            StorageQueryRes {
+                slot: Some(B256::with_last_byte(1)),
+                value: Some(B256::with_last_byte(7)),
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,423 @@
+use super::{
+    multicall::{aggregate, aggregate_calls},
+    resolve_account::{planned_blocks, state_blocks},
+    resolve_explain::{block_id_text, chain_id_lookups, ChainPlan},
+};
+use crate::common::{
+    chain::{Chain, ChainOrRpc},
+    ens::NameOrAddress,
+    query_result::TokenBalanceQueryRes,
+    token_balance::{TokenBalanceField, TokenBalances},
+};
+use alloy::{
+    primitives::{utils::format_units, Address, Bytes, U256},
+    providers::ProviderBuilder,
+    sol,
+    sol_types::SolCall,
+};
+use anyhow::Result;
+use futures::future::try_join_all;
+use std::{collections::HashMap, sync::Arc};
+
+sol! {
+    interface IERC20 {
+        function balanceOf(address owner) external view returns (uint256);
+        function allowance(address owner, address spender) external view returns (uint256);
+        function decimals() external view returns (uint8);
+    }
+
+    interface IERC721 {
+        function ownerOf(uint256 tokenId) external view returns (address);
+    }
+}
+
+/// One contract read. `balanceOf` has the same selector on ERC-20 and
+/// ERC-721, so it serves both (a count of tokens for the latter).
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+enum Read {
+    Decimals(Address),
+    Balance(Address, Address),
+    Allowance(Address, Address, Address),
+    Owner(Address, U256),
+}
+
+impl Read {
+    fn call(&self) -> (Address, Bytes) {
+        match *self {
+            Read::Decimals(token) => (token, IERC20::decimalsCall {}.abi_encode().into()),
+            Read::Balance(token, owner) => {
+                (token, IERC20::balanceOfCall { owner }.abi_encode().into())
+            }
+            Read::Allowance(token, owner, spender) => (
+                token,
+                IERC20::allowanceCall { owner, spender }.abi_encode().into(),
+            ),
+            Read::Owner(token, token_id) => (
+                token,
+                IERC721::ownerOfCall { tokenId: token_id }
+                    .abi_encode()
+                    .into(),
+            ),
+        }
+    }
+}
+
+/// The outputs of one block's reads, decoded on lookup. A read whose call
+/// reverted, or whose output doesn't decode (e.g. `decimals()` on an
+/// ERC-721), reads as `None`.
+struct Outputs(HashMap<Read, Bytes>);
+
+impl Outputs {
+    fn u256(&self, read: Read) -> Option<U256> {
+        let output = self.0.get(&read)?;
+        match read {
+            Read::Balance(..) => IERC20::balanceOfCall::abi_decode_returns(output, true)
+                .ok()
+                .map(|r| r._0),
+            Read::Allowance(..) => IERC20::allowanceCall::abi_decode_returns(output, true)
+                .ok()
+                .map(|r| r._0),
+            Read::Decimals(_) | Read::Owner(..) => None,
+        }
+    }
+
+    fn decimals(&self, token: Address) -> Option<u8> {
+        let output = self.0.get(&Read::Decimals(token))?;
+        IERC20::decimalsCall::abi_decode_returns(output, true)
+            .ok()
+            .map(|r| r._0)
+    }
+
+    fn owner(&self, token: Address, token_id: U256) -> Option<Address> {
+        let output = self.0.get(&Read::Owner(token, token_id))?;
+        IERC721::ownerOfCall::abi_decode_returns(output, true)
+            .ok()
+            .map(|r| r._0)
+    }
+}
+
+/// Resolve the query to read token balances. Per chain and block, every read
+/// goes out through Multicall3.
+pub async fn resolve_token_balance_query(
+    balances: &TokenBalances,
+    chains: &[ChainOrRpc],
+) -> Result<Vec<TokenBalanceQueryRes>> {
+    let fields = balances.fields();
+    let pin_latest = fields.contains(&TokenBalanceField::BlockNumber);
+    let tokens = balances
+        .tokens()
+        .iter()
+        .map(NameOrAddress::resolved)
+        .collect::<Vec<_>>();
+    let holders = balances
+        .holders()
+        .iter()
+        .map(NameOrAddress::resolved)
+        .collect::<Vec<_>>();
+    let spenders = balances
+        .spenders()
+        .iter()
+        .map(NameOrAddress::resolved)
+        .collect::<Vec<_>>();
+    let reads = planned_reads(balances, &fields, &tokens, &holders, &spenders);
+    let calls: Vec<_> = reads.iter().map(Read::call).collect();
+    let mut all_balance_futures = Vec::new();
+
+    for chain in chains {
+        let provider = Arc::new(ProviderBuilder::new().on_http(chain.rpc_url()?));
+        let chain_enum = chain.to_chain().await?;
+        let blocks = state_blocks(&balances.block_ids(), pin_latest, &provider).await?;
+
+        for block in blocks {
+            let provider = provider.clone();
+            let chain_enum = chain_enum.clone();
+            let (tokens, holders, spenders) = (&tokens, &holders, &spenders);
+            let (reads, calls) = (&reads, &calls);
+
+            let balance_future = async move {
+                let results = aggregate(&provider, chain, calls, block).await?;
+                let outputs = Outputs(
+                    reads
+                        .iter()
+                        .zip(results)
+                        .filter_map(|(read, output)| Some((*read, output?)))
+                        .collect(),
+                );
+                Ok::<_, anyhow::Error>(rows(
+                    balances,
+                    tokens,
+                    holders,
+                    spenders,
+                    &outputs,
+                    &chain_enum,
+                    block,
+                ))
+            };
+
+            all_balance_futures.push(balance_future);
+        }
+    }
+
+    let balance_res = try_join_all(all_balance_futures).await?;
+    Ok(balance_res.concat())
+}
+
+/// The reads the selected fields need, each once: `decimals()` per token,
+/// `balanceOf` per holder, `allowance` per holder and spender, and
+/// `ownerOf` per token id.
+fn planned_reads(
+    balances: &TokenBalances,
+    fields: &[TokenBalanceField],
+    tokens: &[Address],
+    holders: &[Address],
+    spenders: &[Address],
+) -> Vec<Read> {
+    let wants = |wanted: &[TokenBalanceField]| fields.iter().any(|f| wanted.contains(f));
+    let decimals = fields.iter().any(TokenBalanceField::needs_decimals);
+    let balance = wants(&[TokenBalanceField::Balance, TokenBalanceField::ScaledBalance]);
+    let allowance = wants(&[
+        TokenBalanceField::Allowance,
+        TokenBalanceField::ScaledAllowance,
+    ]);
+    let owner = wants(&[TokenBalanceField::Owner]);
+
+    let mut reads = Vec::new();
+    for &token in tokens {
+        if decimals {
+            reads.push(Read::Decimals(token));
+        }
+        for &holder in holders {
+            if balance {
+                reads.push(Read::Balance(token, holder));
+            }
+            if allowance {
+                reads.extend(
+                    spenders
+                        .iter()
+                        .map(|&spender| Read::Allowance(token, holder, spender)),
+                );
+            }
+        }
+        if owner {
+            reads.extend(
+                balances
+                    .token_ids()
+                    .iter()
+                    .map(|&id| Read::Owner(token, id)),
+            );
+        }
+    }
+    reads
+}
+
+/// One row per token × holder × spender × token id, leaving out whichever
+/// of the last three the query didn't name.
+fn rows(
+    balances: &TokenBalances,
+    tokens: &[Address],
+    holders: &[Address],
+    spenders: &[Address],
+    outputs: &Outputs,
+    chain: &Chain,
+    block: Option<u64>,
+) -> Vec<TokenBalanceQueryRes> {
+    let holders = or_none(holders);
+    let spenders = or_none(spenders);
+    let token_ids = or_none(balances.token_ids());
+    let fields = balances.fields();
+
+    let mut rows = Vec::new();
+    for &token in tokens {
+        let decimals = outputs.decimals(token);
+        let scaled = |amount: Option<U256>| {
+            amount
+                .zip(decimals)
+                .and_then(|(amount, decimals)| format_units(amount, decimals).ok())
+        };
+        for &holder in &holders {
+            let balance = holder.and_then(|holder| outputs.u256(Read::Balance(token, holder)));
+            for &spender in &spenders {
+                let allowance = holder.zip(spender).and_then(|(holder, spender)| {
+                    outputs.u256(Read::Allowance(token, holder, spender))
+                });
+                for &token_id in &token_ids {
+                    let mut row = TokenBalanceQueryRes::default();
+                    for field in &fields {
+                        match field {
+                            TokenBalanceField::Holder => row.holder = holder,
+                            TokenBalanceField::Token => row.token = Some(token),
+                            TokenBalanceField::Balance => row.balance = balance,
+                            TokenBalanceField::Decimals => row.decimals = decimals,
+                            TokenBalanceField::ScaledBalance => {
+                                row.scaled_balance = scaled(balance)
+                            }
+                            TokenBalanceField::Spender => row.spender = spender,
+                            TokenBalanceField::Allowance => row.allowance = allowance,
+                            TokenBalanceField::ScaledAllowance => {
+                                row.scaled_allowance = scaled(allowance)
+                            }
+                            TokenBalanceField::TokenId => row.token_id = token_id,
+                            TokenBalanceField::Owner => {
+                                row.owner = token_id.and_then(|id| outputs.owner(token, id))
+                            }
+                            TokenBalanceField::BlockNumber => row.block_number = block,
+                            TokenBalanceField::Chain => row.chain = Some(chain.clone()),
+                        }
+                    }
+                    rows.push(row);
+                }
+            }
+        }
+    }
+    rows
+}
+
+/// `values` as `Some`s, or a single `None` when there are none, so an
+/// unnamed dimension doesn't multiply the rows away.
+fn or_none<T: Copy>(values: &[T]) -> Vec<Option<T>> {
+    if values.is_empty() {
+        vec![None]
+    } else {
+        values.iter().copied().map(Some).collect()
+    }
+}
+
+/// The `EXPLAIN` plan for `balances` on `chain`: the Multicall3 `eth_call`s
+/// for every block.
+pub(crate) fn plan_token_balance_query(balances: &TokenBalances, chain: &ChainOrRpc) -> ChainPlan {
+    let block_ids = balances.block_ids();
+    let block_range = (!block_ids.is_empty()).then(|| {
+        block_ids
+            .iter()
+            .map(|id| block_id_text(id))
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+    let fields = balances.fields();
+    // Only the number of reads matters here, so any addresses will do.
+    let reads = planned_reads(
+        balances,
+        &fields,
+        &vec![Address::ZERO; balances.tokens().len()],
+        &vec![Address::ZERO; balances.holders().len()],
+        &vec![Address::ZERO; balances.spenders().len()],
+    )
+    .len() as u64;
+    let pin_latest = fields.contains(&TokenBalanceField::BlockNumber);
+    let calls = planned_blocks(&block_ids, pin_latest).map(|(blocks, lookups)| {
+        blocks * aggregate_calls(reads) + lookups + chain_id_lookups(chain)
+    });
+    ChainPlan::rpc(block_range, vec![], calls, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::address;
+
+    const USDC: Address = address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+    const HOLDER: Address = address!("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+
+    fn balances(fields: Vec<TokenBalanceField>) -> TokenBalances {
+        TokenBalances::new(
+            vec![NameOrAddress::Address(HOLDER)],
+            vec![USDC.into()],
+            vec![],
+            vec![],
+            vec![],
+            fields,
+        )
+    }
+
+    #[test]
+    fn reads_only_what_the_fields_need() {
+        let query = balances(vec![TokenBalanceField::Balance]);
+        let reads = planned_reads(&query, &query.fields(), &[USDC], &[HOLDER], &[]);
+        assert_eq!(reads, [Read::Balance(USDC, HOLDER)]);
+
+        let query = balances(vec![TokenBalanceField::ScaledBalance]);
+        let reads = planned_reads(&query, &query.fields(), &[USDC], &[HOLDER], &[]);
+        assert_eq!(reads, [Read::Decimals(USDC), Read::Balance(USDC, HOLDER)]);
+    }
+
+    #[test]
+    fn rows_decode_and_scale_the_outputs() {
+        let query = balances(vec![
+            TokenBalanceField::Holder,
+            TokenBalanceField::Balance,
+            TokenBalanceField::Decimals,
+            TokenBalanceField::ScaledBalance,
+        ]);
+        let outputs = Outputs(HashMap::from([
+            (
+                Read::Balance(USDC, HOLDER),
+                Bytes::from(U256::from(1_500_000).to_be_bytes_vec()),
+            ),
+            (
+                Read::Decimals(USDC),
+                Bytes::from(U256::from(6).to_be_bytes_vec()),
+            ),
+        ]));
+        let rows = rows(
+            &query,
+            &[USDC],
+            &[HOLDER],
+            &[],
+            &outputs,
+            &Chain::Ethereum,
+            None,
+        );
+        assert_eq!(
+            rows,
+            [TokenBalanceQueryRes {
+                holder: Some(HOLDER),
+                balance: Some(U256::from(1_500_000)),
+                decimals: Some(6),
+                scaled_balance: Some("1.500000".to_string()),
+                ..Default::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn a_failed_read_is_null() {
+        let query = balances(vec![
+            TokenBalanceField::Balance,
+            TokenBalanceField::Decimals,
+        ]);
+        let rows = rows(
+            &query,
+            &[USDC],
+            &[HOLDER],
+            &[],
+            &Outputs(HashMap::new()),
+            &Chain::Ethereum,
+            None,
+        );
+        assert_eq!(rows, [TokenBalanceQueryRes::default()]);
+    }
+
+    #[test]
+    fn plans_one_multicall_per_block() {
+        let chain = ChainOrRpc::Chain(Chain::Ethereum);
+        let query = TokenBalances::new(
+            vec![
+                NameOrAddress::Address(HOLDER),
+                NameOrAddress::Address(Address::ZERO),
+            ],
+            vec![USDC.into()],
+            vec![],
+            vec![],
+            vec![crate::common::block::BlockId::Range(
+                crate::common::block::BlockRange::new(
+                    alloy::eips::BlockNumberOrTag::Number(10),
+                    Some(alloy::eips::BlockNumberOrTag::Number(12)),
+                ),
+            )],
+            vec![TokenBalanceField::Balance],
+        );
+        let plan = plan_token_balance_query(&query, &chain);
+        assert_eq!(plan.rpc_calls, Some(3));
+        assert_eq!(plan.block_range.as_deref(), Some("10..12"));
+    }
+}
@@ -1,4 +1,10 @@
-use super::resolve_block::{batch_get_blocks, get_block};
+use super::data_source::{fetch, plan_route, Route, SourceQuery};
+use super::resolve_account::CALLS_PER_BATCH;
+use super::resolve_block::{batch_get_full_blocks, get_full_block};
+use super::resolve_ens::primary_names;
+use super::resolve_explain::{
+    block_id_text, chain_id_lookups, portal_dataset, range_text, rpc_block_fetches, ChainPlan,
+};
 use super::resolve_portal::{
     block_id_is_portal_eligible, portal_query, portal_query_with_base_url, resolve_block_id_range,
     value_to_address, value_to_b256, value_to_bytes, value_to_parity_bool, value_to_status_bool,
@@ -7,21 +13,30 @@ use super::resolve_portal::{
 use crate::common::{
     block::BlockId,
     chain::{Chain, ChainOrRpc},
-    query_result::TransactionQueryRes,
+    filters::{ComparisonFilter, EqualityFilter, Filter, FilterType},
+    query_result::{ExpressionResult, TransactionQueryRes},
     transaction::{Transaction, TransactionField, TransactionFilter},
 };
 use alloy::{
     consensus::Transaction as ConsensusTransaction,
-    primitives::FixedBytes,
+    eips::{eip4844::DATA_GAS_PER_BLOB, BlockNumberOrTag},
+    network::{AnyRpcTransaction, AnyTxEnvelope},
+    primitives::{Address, FixedBytes, PrimitiveSignature, B256, U256},
     providers::{Provider, ProviderBuilder, RootProvider},
-    rpc::types::{BlockTransactions, Transaction as RpcTransaction},
+    rpc::{
+        client::BatchRequest,
+        types::{AnyTransactionReceipt, BlockTransactions},
+    },
     transports::http::{Client, Http},
 };
 use anyhow::{Ok, Result};
 use futures::future::try_join_all;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::json;
-use std::sync::Arc;
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
+
+/// The EIP-2718 type of OP Stack deposit transactions.
+const DEPOSIT_TX_TYPE: u8 = 0x7e;
 
 #[derive(Debug, Serialize, Deserialize, thiserror::Error)]
 pub enum TransactionResolverErrors {
@@ -31,10 +46,19 @@ pub enum TransactionResolverErrors {
     MissingTransactionHashOrFilter,
 }
 
+/// The columns a `WHERE` predicate on is sent to Portal for: the block range
+/// it scans, the from/to equalities `extract_address_filters` forwards and
+/// the blob filters `requests_blob_transactions` turns into a type filter.
+/// Hash lookups are RPC-only.
+pub(crate) const PORTAL_FILTER_FIELDS: &[TransactionField] = &[
+    TransactionField::BlockNumber,
+    TransactionField::From,
+    TransactionField::To,
+    TransactionField::BlobCount,
+];
+
 /// Extract from/to address filters from TransactionFilters for Portal server-side filtering.
 fn extract_address_filters(filters: Option<&Vec<TransactionFilter>>) -> (Vec<String>, Vec<String>) {
-    use crate::common::filters::EqualityFilter;
-
     let mut from_addrs = Vec::new();
     let mut to_addrs = Vec::new();
 
@@ -42,10 +66,10 @@ fn extract_address_filters(filters: Option<&Vec<TransactionFilter>>) -> (Vec<Str
         for filter in filters {
             match filter {
                 TransactionFilter::From(EqualityFilter::Eq(addr)) => {
-                    from_addrs.push(format!("{:?}", addr));
+                    from_addrs.push(format!("{:?}", addr.resolved()));
                 }
                 TransactionFilter::To(EqualityFilter::Eq(addr)) => {
-                    to_addrs.push(format!("{:?}", addr));
+                    to_addrs.push(format!("{:?}", addr.resolved()));
                 }
                 _ => {}
             }
@@ -55,19 +79,43 @@ fn extract_address_filters(filters: Option<&Vec<TransactionFilter>>) -> (Vec<Str
     (from_addrs, to_addrs)
 }
 
-/// Determines if a transaction query for a given chain should use the Portal.
-fn should_use_portal(chain: &ChainOrRpc, transaction: &Transaction) -> bool {
-    let dataset = match chain {
-        ChainOrRpc::Chain(c) => c.portal_dataset(),
-        ChainOrRpc::Rpc(_) => None,
+/// Whether a `blob_count` filter keeps only transactions with blobs, so
+/// Portal can be asked for blob (type 3) transactions alone: each carries at
+/// least one blob, and no other type carries any.
+fn requests_blob_transactions(filter: &FilterType<u64>) -> bool {
+    !filter.compare(&0)
+}
+
+/// Whether a `blob_count` filter says no more than "has blobs", so Portal's
+/// type filter applies it in full.
+fn is_blob_type_filter(filter: &FilterType<u64>) -> bool {
+    matches!(
+        filter,
+        FilterType::Comparison(ComparisonFilter::Gt(0))
+            | FilterType::Comparison(ComparisonFilter::Gte(1))
+            | FilterType::Equality(EqualityFilter::Neq(0))
+    )
+}
+
+/// Whether Portal can fetch a transaction query on `chain`, as `PortalSource`
+/// reports.
+pub(crate) fn should_use_portal(chain: &ChainOrRpc, transaction: &Transaction) -> bool {
+    let chain = match chain {
+        ChainOrRpc::Chain(c) if c.portal_dataset().is_some() => c,
+        _ => return false,
     };
-    if dataset.is_none() {
-        return false;
-    }
     // Portal has no transaction-by-hash filter.
     if transaction.ids().is_some() {
         return false;
     }
+    // Nor the fields only RPC serves: the blob gas price, which only receipts
+    // carry, and the deposit and Arbitrum fields on the chains that have them.
+    if portal_internal_fields(transaction, chain)
+        .iter()
+        .any(is_rpc_only)
+    {
+        return false;
+    }
     // Portal needs a block range to scan.
     if !transaction.has_block_filter() {
         return false;
@@ -78,6 +126,104 @@ fn should_use_portal(chain: &ChainOrRpc, transaction: &Transaction) -> bool {
     }
 }
 
+/// Whether a field Portal doesn't serve is selected, so the query goes to RPC.
+fn is_rpc_only(field: &TransactionField) -> bool {
+    matches!(
+        field,
+        TransactionField::BlobGasPrice
+            | TransactionField::SourceHash
+            | TransactionField::Mint
+            | TransactionField::IsSystemTx
+            | TransactionField::GasUsedForL1
+    )
+}
+
+/// The columns whose filters `filter_and_project_transaction_row` evaluates
+/// over fetched rows — every filter except the block id, minus the from/to
+/// equalities and "has blobs" filters Portal applies itself on the Portal
+/// route.
+fn client_filter_columns(transaction: &Transaction, via_portal: bool) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for filter in transaction.filters().into_iter().flatten() {
+        let pushed_down = via_portal
+            && match filter {
+                TransactionFilter::From(EqualityFilter::Eq(_))
+                | TransactionFilter::To(EqualityFilter::Eq(_)) => true,
+                TransactionFilter::BlobCount(f) => is_blob_type_filter(f),
+                _ => false,
+            };
+        if pushed_down {
+            continue;
+        }
+        if let Some(field) = tx_filter_field(filter) {
+            let name = field.to_string();
+            if !columns.contains(&name) {
+                columns.push(name);
+            }
+        }
+    }
+    columns
+}
+
+/// The `EXPLAIN` plan for `transaction` on `chain`: the route
+/// `resolve_transaction_query` takes, without fetching anything.
+pub(crate) async fn plan_transaction_query(
+    transaction: &Transaction,
+    chain: &ChainOrRpc,
+) -> Result<ChainPlan> {
+    if transaction.ids().is_none() && !transaction.has_block_filter() {
+        return Err(TransactionResolverErrors::MissingTransactionHashOrFilter.into());
+    }
+    let internal_fields = transaction_internal_fields(transaction);
+
+    let route = plan_route(SourceQuery::Transactions(transaction), chain).await?;
+    if let Route::Planned(plan) = route {
+        return Ok(plan);
+    }
+    if route == Route::Portal {
+        let ChainOrRpc::Chain(chain_enum) = chain else {
+            unreachable!("should_use_portal guards against Rpc variant");
+        };
+        let block_id = transaction.get_block_id_filter()?;
+        let (from_block, to_block) =
+            resolve_block_id_range(portal_dataset(chain), block_id).await?;
+        return Ok(ChainPlan::portal(
+            range_text(from_block, to_block),
+            vec![transaction_portal_query(
+                transaction,
+                &portal_internal_fields(transaction, chain_enum),
+                from_block,
+                to_block,
+            )],
+            client_filter_columns(transaction, true),
+        ));
+    }
+
+    // Up front: one eth_getTransactionByHash per hash, or one
+    // eth_getBlockByNumber per block, and as many receipt reads again
+    // (eth_getTransactionReceipt / eth_getBlockReceipts) when a receipt field
+    // is needed. Per row: `pick_transaction_fields`'s eth_chainId for an
+    // ad-hoc endpoint.
+    let (block_range, calls) = match transaction.ids() {
+        Some(ids) => (None, Some(ids.len() as u64)),
+        None => {
+            let block_id = transaction.get_block_id_filter()?;
+            (Some(block_id_text(block_id)), rpc_block_fetches(block_id)?)
+        }
+    };
+    let receipt_reads = if internal_fields.iter().any(TransactionField::needs_receipt) {
+        2
+    } else {
+        1
+    };
+    Ok(ChainPlan::rpc(
+        block_range,
+        client_filter_columns(transaction, false),
+        calls.map(|calls| calls * receipt_reads),
+        chain_id_lookups(chain),
+    ))
+}
+
 pub async fn resolve_transaction_query(
     transaction: &Transaction,
     chains: &[ChainOrRpc],
@@ -86,25 +232,67 @@ pub async fn resolve_transaction_query(
         return Err(TransactionResolverErrors::MissingTransactionHashOrFilter.into());
     }
 
+    let results = try_join_all(
+        chains
+            .iter()
+            .map(|chain| fetch(SourceQuery::Transactions(transaction), chain)),
+    )
+    .await?;
     let mut all_results = Vec::new();
-
-    for chain in chains {
-        let results = if should_use_portal(chain, transaction) {
-            resolve_transactions_via_portal(transaction, chain).await?
-        } else {
-            resolve_transactions_via_rpc(transaction, chain).await?
+    for result in results {
+        let ExpressionResult::Transaction(rows) = result else {
+            unreachable!("a transaction query fetches transactions");
         };
-        all_results.extend(results);
+        all_results.extend(rows);
     }
+    fill_ens_names(transaction.fields(), &mut all_results).await?;
 
     Ok(all_results)
 }
 
+/// Fills in the selected `from_ens` and `to_ens` columns of `rows` from the
+/// addresses projected for them (see `project_transaction_row`), then drops
+/// those addresses unless they're selected too.
+async fn fill_ens_names(
+    fields: &[TransactionField],
+    rows: &mut [TransactionQueryRes],
+) -> Result<()> {
+    if !fields.iter().any(TransactionField::is_ens_name) {
+        return Ok(());
+    }
+    let from_ens = fields.contains(&TransactionField::FromEns);
+    let to_ens = fields.contains(&TransactionField::ToEns);
+    let addresses = rows.iter().flat_map(|row| {
+        [
+            row.from_address.filter(|_| from_ens),
+            row.to_address.filter(|_| to_ens),
+        ]
+    });
+    let names = primary_names(addresses.flatten().collect::<Vec<_>>()).await?;
+
+    let name_of = |address: Option<Address>| names.get(&address?).cloned();
+    for row in rows {
+        if from_ens {
+            row.from_ens = name_of(row.from_address);
+            if !fields.contains(&TransactionField::From) {
+                row.from_address = None;
+            }
+        }
+        if to_ens {
+            row.to_ens = name_of(row.to_address);
+            if !fields.contains(&TransactionField::To) {
+                row.to_address = None;
+            }
+        }
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Portal path
 // ---------------------------------------------------------------------------
 
-async fn resolve_transactions_via_portal(
+pub(crate) async fn resolve_transactions_via_portal(
     transaction: &Transaction,
     chain: &ChainOrRpc,
 ) -> Result<Vec<TransactionQueryRes>> {
@@ -121,41 +309,11 @@ async fn resolve_transactions_via_portal_with_base_url(
         _ => unreachable!("should_use_portal guards against Rpc variant"),
     };
     let dataset = chain_enum.portal_dataset().unwrap();
-    let internal_fields = transaction_internal_fields(transaction);
+    let internal_fields = portal_internal_fields(transaction, &chain_enum);
 
     let block_id = transaction.get_block_id_filter()?;
     let (from_block, to_block) = resolve_block_id_range(dataset, block_id).await?;
-
-    // Build Portal transaction field selection
-    let mut tx_fields = serde_json::Map::new();
-    for field in &internal_fields {
-        if let Some(portal_name) = tx_field_to_portal_name(field) {
-            tx_fields.insert(portal_name.into(), json!(true));
-        }
-    }
-    // Always include hash for dedup/identification
-    tx_fields.insert("hash".into(), json!(true));
-
-    // Build Portal transaction filter with from/to if available
-    let mut tx_filter = serde_json::Map::new();
-    let (from_addrs, to_addrs) = extract_address_filters(transaction.filters());
-    if !from_addrs.is_empty() {
-        tx_filter.insert("from".into(), json!(from_addrs));
-    }
-    if !to_addrs.is_empty() {
-        tx_filter.insert("to".into(), json!(to_addrs));
-    }
-
-    let query = json!({
-        "type": "evm",
-        "fromBlock": from_block,
-        "toBlock": to_block,
-        "fields": {
-            "block": { "number": true },
-            "transaction": tx_fields
-        },
-        "transactions": [tx_filter]
-    });
+    let query = transaction_portal_query(transaction, &internal_fields, from_block, to_block);
 
     let response = match base_url {
         Some(base_url) => portal_query_with_base_url(base_url, dataset, &query).await?,
@@ -168,11 +326,20 @@ async fn resolve_transactions_via_portal_with_base_url(
             .get("header")
             .and_then(|h| h.get("number"))
             .and_then(value_to_u64);
+        let logs_counts = internal_fields
+            .contains(&TransactionField::LogsCount)
+            .then(|| portal_logs_counts(portal_block));
 
         if let Some(txs) = portal_block.get("transactions").and_then(|t| t.as_array()) {
             for tx in txs {
-                let internal_row =
+                let mut internal_row =
                     parse_portal_transaction(tx, &internal_fields, &chain_enum, block_number);
+                if let Some(counts) = &logs_counts {
+                    internal_row.logs_count = tx
+                        .get("transactionIndex")
+                        .and_then(value_to_u64)
+                        .map(|index| counts.get(&index).copied().unwrap_or_default());
+                }
                 if let Some(projected_row) =
                     filter_and_project_transaction_row(transaction, &internal_row)
                 {
@@ -185,6 +352,20 @@ async fn resolve_transactions_via_portal_with_base_url(
     Ok(results)
 }
 
+/// How many logs each transaction of a Portal block emitted, by transaction
+/// index. The block carries its selected transactions' logs (see
+/// `transaction_portal_query`), so a transaction without any is absent.
+fn portal_logs_counts(portal_block: &serde_json::Value) -> HashMap<u64, u64> {
+    let mut counts = HashMap::new();
+    let logs = portal_block.get("logs").and_then(|l| l.as_array());
+    for log in logs.into_iter().flatten() {
+        if let Some(index) = log.get("transactionIndex").and_then(value_to_u64) {
+            *counts.entry(index).or_default() += 1;
+        }
+    }
+    counts
+}
+
 fn tx_filter_field(filter: &TransactionFilter) -> Option<TransactionField> {
     match filter {
         TransactionFilter::Type(_) => Some(TransactionField::Type),
@@ -199,16 +380,33 @@ fn tx_filter_field(filter: &TransactionFilter) -> Option<TransactionField> {
         TransactionFilter::ChainId(_) => Some(TransactionField::ChainId),
         TransactionFilter::BlockId(_) => None,
         TransactionFilter::Status(_) => Some(TransactionField::Status),
+        TransactionFilter::GasUsed(_) => Some(TransactionField::GasUsed),
+        TransactionFilter::CumulativeGasUsed(_) => Some(TransactionField::CumulativeGasUsed),
+        TransactionFilter::ContractAddress(_) => Some(TransactionField::ContractAddress),
+        TransactionFilter::LogsCount(_) => Some(TransactionField::LogsCount),
+        TransactionFilter::Fee(_) => Some(TransactionField::Fee),
         TransactionFilter::V(_) => Some(TransactionField::V),
         TransactionFilter::R(_) => Some(TransactionField::R),
         TransactionFilter::S(_) => Some(TransactionField::S),
         TransactionFilter::MaxFeePerBlobGas(_) => Some(TransactionField::MaxFeePerBlobGas),
+        TransactionFilter::BlobCount(_) => Some(TransactionField::BlobCount),
+        TransactionFilter::BlobGasUsed(_) => Some(TransactionField::BlobGasUsed),
+        TransactionFilter::BlobGasPrice(_) => Some(TransactionField::BlobGasPrice),
         TransactionFilter::MaxFeePerGas(_) => Some(TransactionField::MaxFeePerGas),
         TransactionFilter::MaxPriorityFeePerGas(_) => Some(TransactionField::MaxPriorityFeePerGas),
         TransactionFilter::YParity(_) => Some(TransactionField::YParity),
     }
 }
 
+/// `transaction_internal_fields` without the L2 fields `chain` never fills
+/// in: their columns stay NULL, and Portal datasets of other chains don't
+/// have them to request.
+fn portal_internal_fields(transaction: &Transaction, chain: &Chain) -> Vec<TransactionField> {
+    let mut fields = transaction_internal_fields(transaction);
+    fields.retain(|field| field.applies_to(chain));
+    fields
+}
+
 fn transaction_internal_fields(transaction: &Transaction) -> Vec<TransactionField> {
     let mut fields = transaction.fields().clone();
 
@@ -228,10 +426,36 @@ fn transaction_internal_fields(transaction: &Transaction) -> Vec<TransactionFiel
     if fields.contains(&TransactionField::YParity) && !fields.contains(&TransactionField::V) {
         fields.push(TransactionField::V);
     }
+    // The ENS names are looked up for the sender and recipient.
+    for (name, address) in [
+        (TransactionField::FromEns, TransactionField::From),
+        (TransactionField::ToEns, TransactionField::To),
+    ] {
+        if fields.contains(&name) && !fields.contains(&address) {
+            fields.push(address);
+        }
+    }
+    // Likewise `fee` is derived from gas used and the effective gas price.
+    if fields.contains(&TransactionField::Fee) {
+        for dependency in [
+            TransactionField::GasUsed,
+            TransactionField::EffectiveGasPrice,
+        ] {
+            if !fields.contains(&dependency) {
+                fields.push(dependency);
+            }
+        }
+    }
 
     fields
 }
 
+/// What the transaction paid for execution: gas used times the effective
+/// gas price. L2 data fees are not included.
+fn transaction_fee(gas_used: Option<u64>, effective_gas_price: Option<u128>) -> Option<U256> {
+    Some(U256::from(gas_used?) * U256::from(effective_gas_price?))
+}
+
 fn project_transaction_row(
     row: &TransactionQueryRes,
     fields: &[TransactionField],
@@ -253,6 +477,13 @@ fn project_transaction_row(
                 projected.effective_gas_price = row.effective_gas_price
             }
             TransactionField::Status => projected.status = row.status,
+            TransactionField::GasUsed => projected.gas_used = row.gas_used,
+            TransactionField::CumulativeGasUsed => {
+                projected.cumulative_gas_used = row.cumulative_gas_used
+            }
+            TransactionField::ContractAddress => projected.contract_address = row.contract_address,
+            TransactionField::LogsCount => projected.logs_count = row.logs_count,
+            TransactionField::Fee => projected.fee = row.fee,
             TransactionField::ChainId => projected.chain_id = row.chain_id,
             TransactionField::V => projected.v = row.v,
             TransactionField::R => projected.r = row.r,
@@ -260,22 +491,41 @@ fn project_transaction_row(
             TransactionField::MaxFeePerBlobGas => {
                 projected.max_fee_per_blob_gas = row.max_fee_per_blob_gas
             }
+            TransactionField::BlobVersionedHashes => {
+                projected.blob_versioned_hashes = row.blob_versioned_hashes.clone()
+            }
+            TransactionField::BlobCount => projected.blob_count = row.blob_count,
+            TransactionField::BlobGasUsed => projected.blob_gas_used = row.blob_gas_used,
+            TransactionField::BlobGasPrice => projected.blob_gas_price = row.blob_gas_price,
             TransactionField::MaxFeePerGas => projected.max_fee_per_gas = row.max_fee_per_gas,
             TransactionField::MaxPriorityFeePerGas => {
                 projected.max_priority_fee_per_gas = row.max_priority_fee_per_gas
             }
+            TransactionField::AccessList => projected.access_list = row.access_list.clone(),
             TransactionField::YParity => projected.y_parity = row.y_parity,
             TransactionField::Chain => projected.chain = row.chain.clone(),
             TransactionField::AuthorizationList => {
                 projected.authorization_list = row.authorization_list.clone()
             }
+            TransactionField::L1Fee => projected.l1_fee = row.l1_fee,
+            TransactionField::L1GasUsed => projected.l1_gas_used = row.l1_gas_used,
+            TransactionField::L1GasPrice => projected.l1_gas_price = row.l1_gas_price,
+            TransactionField::L1FeeScalar => projected.l1_fee_scalar = row.l1_fee_scalar.clone(),
+            TransactionField::SourceHash => projected.source_hash = row.source_hash,
+            TransactionField::Mint => projected.mint = row.mint,
+            TransactionField::IsSystemTx => projected.is_system_tx = row.is_system_tx,
+            TransactionField::GasUsedForL1 => projected.gas_used_for_l1 = row.gas_used_for_l1,
+            // The address the name is looked up for; fill_ens_names drops it
+            // again unless it's selected too.
+            TransactionField::FromEns => projected.from_address = row.from_address,
+            TransactionField::ToEns => projected.to_address = row.to_address,
         }
     }
 
     projected
 }
 
-fn filter_and_project_transaction_row(
+pub(crate) fn filter_and_project_transaction_row(
     transaction: &Transaction,
     internal_row: &TransactionQueryRes,
 ) -> Option<TransactionQueryRes> {
@@ -284,6 +534,60 @@ fn filter_and_project_transaction_row(
         .then(|| project_transaction_row(internal_row, transaction.fields()))
 }
 
+/// The Portal stream query for `transaction` over `from_block..=to_block`,
+/// selecting `internal_fields` (see `transaction_internal_fields`).
+fn transaction_portal_query(
+    transaction: &Transaction,
+    internal_fields: &[TransactionField],
+    from_block: u64,
+    to_block: u64,
+) -> serde_json::Value {
+    // Build Portal transaction field selection
+    let mut tx_fields = serde_json::Map::new();
+    for field in internal_fields {
+        if let Some(portal_name) = tx_field_to_portal_name(field) {
+            tx_fields.insert(portal_name.into(), json!(true));
+        }
+    }
+    // Always include hash for dedup/identification
+    tx_fields.insert("hash".into(), json!(true));
+
+    // Build Portal transaction filter with from/to if available
+    let mut tx_filter = serde_json::Map::new();
+    // Portal has no log count per transaction: have it return the selected
+    // transactions' logs, and count them by transaction index.
+    let mut fields = json!({
+        "block": { "number": true },
+        "transaction": tx_fields
+    });
+    if internal_fields.contains(&TransactionField::LogsCount) {
+        fields["transaction"]["transactionIndex"] = json!(true);
+        fields["log"] = json!({ "transactionIndex": true });
+        tx_filter.insert("logs".into(), json!(true));
+    }
+    let (from_addrs, to_addrs) = extract_address_filters(transaction.filters());
+    if !from_addrs.is_empty() {
+        tx_filter.insert("from".into(), json!(from_addrs));
+    }
+    if !to_addrs.is_empty() {
+        tx_filter.insert("to".into(), json!(to_addrs));
+    }
+    let blobs_only = transaction.filters().into_iter().flatten().any(
+        |filter| matches!(filter, TransactionFilter::BlobCount(f) if requests_blob_transactions(f)),
+    );
+    if blobs_only {
+        tx_filter.insert("type".into(), json!([3]));
+    }
+
+    json!({
+        "type": "evm",
+        "fromBlock": from_block,
+        "toBlock": to_block,
+        "fields": fields,
+        "transactions": [tx_filter]
+    })
+}
+
 /// Maps an EQL TransactionField to the Portal JSON field name.
 fn tx_field_to_portal_name(field: &TransactionField) -> Option<&'static str> {
     match field {
@@ -296,6 +600,9 @@ fn tx_field_to_portal_name(field: &TransactionField) -> Option<&'static str> {
         TransactionField::GasPrice => Some("gasPrice"),
         TransactionField::GasLimit => Some("gas"),
         TransactionField::Status => Some("status"),
+        TransactionField::GasUsed => Some("gasUsed"),
+        TransactionField::CumulativeGasUsed => Some("cumulativeGasUsed"),
+        TransactionField::ContractAddress => Some("contractAddress"),
         TransactionField::ChainId => Some("chainId"),
         TransactionField::MaxFeePerGas => Some("maxFeePerGas"),
         TransactionField::MaxPriorityFeePerGas => Some("maxPriorityFeePerGas"),
@@ -304,10 +611,30 @@ fn tx_field_to_portal_name(field: &TransactionField) -> Option<&'static str> {
         TransactionField::R => Some("r"),
         TransactionField::S => Some("s"),
         TransactionField::MaxFeePerBlobGas => Some("maxFeePerBlobGas"),
+        TransactionField::BlobVersionedHashes => Some("blobVersionedHashes"),
+        // Both follow from the hashes; see parse_portal_transaction.
+        TransactionField::BlobCount => Some("blobVersionedHashes"),
+        TransactionField::BlobGasUsed => Some("blobVersionedHashes"),
         TransactionField::YParity => Some("yParity"),
+        // OP Stack datasets only; see portal_internal_fields.
+        TransactionField::L1Fee => Some("l1Fee"),
+        TransactionField::L1GasUsed => Some("l1GasUsed"),
+        TransactionField::L1GasPrice => Some("l1GasPrice"),
+        TransactionField::L1FeeScalar => Some("l1FeeScalar"),
         // Not requested from Portal:
         TransactionField::Chain => None,             // set locally
         TransactionField::AuthorizationList => None, // no Portal field (EIP-7702)
+        TransactionField::AccessList => None,        // no Portal field (EIP-2930)
+        TransactionField::Fee => None,               // derived from gasUsed/effectiveGasPrice
+        TransactionField::BlobGasPrice => None,      // receipt-only; routed to RPC
+        TransactionField::SourceHash => None,        // deposit-only; routed to RPC
+        TransactionField::Mint => None,              // deposit-only; routed to RPC
+        TransactionField::IsSystemTx => None,        // deposit-only; routed to RPC
+        TransactionField::GasUsedForL1 => None,      // Arbitrum receipt-only; routed to RPC
+        TransactionField::FromEns => None,           // looked up on Ethereum; see fill_ens_names
+        TransactionField::ToEns => None,             // looked up on Ethereum; see fill_ens_names
+        // Counted from the block's logs; see transaction_portal_query.
+        TransactionField::LogsCount => None,
         // Block-derived: portal_query forces `fields.block.number` on for every query (for
         // pagination), so this is read from the block header in resolve_transactions_via_portal
         // rather than requested as a transaction-level field here.
@@ -362,6 +689,21 @@ fn parse_portal_transaction(
             TransactionField::Status => {
                 result.status = tx.get("status").and_then(value_to_status_bool);
             }
+            TransactionField::GasUsed => {
+                result.gas_used = tx.get("gasUsed").and_then(value_to_u64);
+            }
+            TransactionField::CumulativeGasUsed => {
+                result.cumulative_gas_used = tx.get("cumulativeGasUsed").and_then(value_to_u64);
+            }
+            TransactionField::ContractAddress => {
+                result.contract_address = tx.get("contractAddress").and_then(value_to_address);
+            }
+            TransactionField::LogsCount => {
+                // Filled by the caller, which sees the block's logs.
+            }
+            TransactionField::Fee => {
+                // Derived below, once gas used and the effective gas price are in.
+            }
             TransactionField::ChainId => {
                 result.chain_id = tx.get("chainId").and_then(value_to_u64);
             }
@@ -384,6 +726,20 @@ fn parse_portal_transaction(
             TransactionField::MaxFeePerBlobGas => {
                 result.max_fee_per_blob_gas = tx.get("maxFeePerBlobGas").and_then(value_to_u128);
             }
+            TransactionField::BlobVersionedHashes => {
+                result.blob_versioned_hashes = portal_blob_hashes(tx);
+            }
+            TransactionField::BlobCount => {
+                result.blob_count = Some(portal_blob_hashes(tx).map_or(0, |h| h.len() as u64));
+            }
+            TransactionField::BlobGasUsed => {
+                // Every blob costs the same fixed amount of blob gas.
+                result.blob_gas_used =
+                    portal_blob_hashes(tx).map(|h| h.len() as u64 * DATA_GAS_PER_BLOB);
+            }
+            TransactionField::BlobGasPrice => {
+                // Not on Portal; should_use_portal sends these queries to RPC.
+            }
             TransactionField::YParity => {
                 result.y_parity = tx
                     .get("yParity")
@@ -396,17 +752,66 @@ fn parse_portal_transaction(
             TransactionField::AuthorizationList => {
                 // Not available on Portal (EIP-7702); left as None. By-hash queries (RPC) fill it.
             }
+            TransactionField::AccessList => {
+                // Not available on Portal either; left as None like the authorization list.
+            }
+            TransactionField::L1Fee => {
+                result.l1_fee = tx.get("l1Fee").and_then(value_to_u256);
+            }
+            TransactionField::L1GasUsed => {
+                result.l1_gas_used = tx.get("l1GasUsed").and_then(value_to_u64);
+            }
+            TransactionField::L1GasPrice => {
+                result.l1_gas_price = tx.get("l1GasPrice").and_then(value_to_u128);
+            }
+            TransactionField::L1FeeScalar => {
+                result.l1_fee_scalar = tx.get("l1FeeScalar").and_then(value_to_decimal_text);
+            }
+            TransactionField::SourceHash
+            | TransactionField::Mint
+            | TransactionField::IsSystemTx
+            | TransactionField::GasUsedForL1 => {
+                // Not on Portal; should_use_portal sends these queries to RPC.
+            }
+            TransactionField::FromEns | TransactionField::ToEns => {
+                // Looked up once the rows are fetched; see fill_ens_names.
+            }
         }
     }
+    if fields.contains(&TransactionField::Fee) {
+        result.fee = transaction_fee(result.gas_used, result.effective_gas_price);
+    }
 
     result
 }
 
+/// A Portal transaction's blob versioned hashes, `None` for a transaction
+/// without blobs (Portal may send an empty list or nothing for those).
+fn portal_blob_hashes(tx: &serde_json::Value) -> Option<Vec<B256>> {
+    let hashes: Vec<B256> = tx
+        .get("blobVersionedHashes")
+        .and_then(|h| h.as_array())?
+        .iter()
+        .filter_map(value_to_b256)
+        .collect();
+    (!hashes.is_empty()).then_some(hashes)
+}
+
+/// An L1 fee scalar as decimal text. Pre-Ecotone OP receipts report it as a
+/// decimal string ("0.684"); it's kept as is rather than rounded.
+fn value_to_decimal_text(v: &serde_json::Value) -> Option<String> {
+    match v {
+        serde_json::Value::String(s) if !s.is_empty() => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // RPC path (original logic, extracted)
 // ---------------------------------------------------------------------------
 
-async fn resolve_transactions_via_rpc(
+pub(crate) async fn resolve_transactions_via_rpc(
     transaction: &Transaction,
     chain: &ChainOrRpc,
 ) -> Result<Vec<TransactionQueryRes>> {
@@ -421,9 +826,15 @@ async fn resolve_transactions_via_rpc(
     };
 
     let internal_fields = transaction_internal_fields(transaction);
-    let result_futures = rpc_transactions
-        .iter()
-        .map(|t| pick_transaction_fields(t, &internal_fields, &provider, chain));
+    let receipts = if internal_fields.iter().any(TransactionField::needs_receipt) {
+        get_receipts(&rpc_transactions, transaction.ids().is_some(), &provider).await?
+    } else {
+        HashMap::new()
+    };
+    let result_futures = rpc_transactions.iter().map(|t| {
+        let receipt = receipts.get(&rpc_tx_hash(t));
+        pick_transaction_fields(t, &internal_fields, receipt, chain)
+    });
     let internal_rows = try_join_all(result_futures).await?;
 
     let filtered_tx_res = internal_rows
@@ -434,14 +845,54 @@ async fn resolve_transactions_via_rpc(
     Ok(filtered_tx_res)
 }
 
+/// A transaction's hash, whatever its type.
+pub(crate) fn rpc_tx_hash(tx: &AnyRpcTransaction) -> B256 {
+    match &tx.inner.inner {
+        AnyTxEnvelope::Ethereum(envelope) => *envelope.tx_hash(),
+        AnyTxEnvelope::Unknown(envelope) => envelope.hash,
+    }
+}
+
+/// A transaction's signature. OP Stack deposits aren't signed.
+fn rpc_tx_signature(tx: &AnyRpcTransaction) -> Option<PrimitiveSignature> {
+    match &tx.inner.inner {
+        AnyTxEnvelope::Ethereum(envelope) => Some(*envelope.signature()),
+        AnyTxEnvelope::Unknown(_) => None,
+    }
+}
+
+/// A field of a transaction type alloy doesn't know, such as an OP Stack
+/// deposit's `sourceHash`. Catch-all decoding may leave it on either the
+/// envelope or the outer response.
+fn rpc_tx_other_field<'a>(tx: &'a AnyRpcTransaction, key: &str) -> Option<&'a serde_json::Value> {
+    let envelope_fields = match &tx.inner.inner {
+        AnyTxEnvelope::Unknown(envelope) => envelope.inner.fields.get(key),
+        AnyTxEnvelope::Ethereum(_) => None,
+    };
+    envelope_fields.or_else(|| tx.other.get(key))
+}
+
+/// A receipt field only L2 nodes report, such as `l1Fee`.
+fn receipt_other_field<'a>(
+    receipt: Option<&'a AnyTransactionReceipt>,
+    key: &str,
+) -> Option<&'a serde_json::Value> {
+    receipt.and_then(|r| r.other.get(key))
+}
+
 async fn get_transactions_by_ids(
     ids: &Vec<FixedBytes<32>>,
     provider: &RootProvider<Http<Client>>,
-) -> Result<Vec<RpcTransaction>> {
+) -> Result<Vec<AnyRpcTransaction>> {
     let mut tx_futures = Vec::new();
     for id in ids {
         let provider = provider.clone();
-        let tx_future = async move { provider.get_transaction_by_hash(*id).await };
+        let tx_future = async move {
+            provider
+                .client()
+                .request::<_, Option<AnyRpcTransaction>>("eth_getTransactionByHash", (*id,))
+                .await
+        };
         tx_futures.push(tx_future);
     }
 
@@ -453,10 +904,10 @@ async fn get_transactions_by_ids(
 async fn get_transactions_by_block_id(
     block_id: &BlockId,
     provider: &Arc<RootProvider<Http<Client>>>,
-) -> Result<Vec<RpcTransaction>> {
+) -> Result<Vec<AnyRpcTransaction>> {
     match block_id {
         BlockId::Number(n) => {
-            let block = get_block(n.clone(), provider.clone(), true).await?;
+            let block = get_full_block(*n, provider).await?;
             match &block.transactions {
                 BlockTransactions::Full(txs) => Ok(txs.clone()),
                 _ => panic!("Block transactions should be full"),
@@ -464,7 +915,7 @@ async fn get_transactions_by_block_id(
         }
         BlockId::Range(r) => {
             let block_numbers = r.resolve_block_numbers(provider).await?;
-            let blocks = batch_get_blocks(block_numbers, provider, true).await?;
+            let blocks = batch_get_full_blocks(block_numbers, provider).await?;
             let txs = blocks
                 .iter()
                 .flat_map(|b| match &b.transactions {
@@ -478,25 +929,100 @@ async fn get_transactions_by_block_id(
     }
 }
 
+/// The receipts of `txs`, by transaction hash: one `eth_getBlockReceipts`
+/// per block the transactions span, or, when the transactions were looked
+/// up by hash, one `eth_getTransactionReceipt` each.
+async fn get_receipts(
+    txs: &[AnyRpcTransaction],
+    by_hash: bool,
+    provider: &RootProvider<Http<Client>>,
+) -> Result<HashMap<B256, AnyTransactionReceipt>> {
+    let receipts: Vec<AnyTransactionReceipt> = if by_hash {
+        let hashes: Vec<(B256,)> = txs.iter().map(|tx| (rpc_tx_hash(tx),)).collect();
+        batched_calls::<_, Option<AnyTransactionReceipt>>(
+            provider,
+            "eth_getTransactionReceipt",
+            &hashes,
+        )
+        .await?
+        .into_iter()
+        .flatten()
+        .collect()
+    } else {
+        let mut blocks: Vec<u64> = txs.iter().filter_map(|tx| tx.block_number).collect();
+        blocks.dedup();
+        let blocks: Vec<(BlockNumberOrTag,)> = blocks
+            .into_iter()
+            .map(|block| (BlockNumberOrTag::Number(block),))
+            .collect();
+        batched_calls::<_, Option<Vec<AnyTransactionReceipt>>>(
+            provider,
+            "eth_getBlockReceipts",
+            &blocks,
+        )
+        .await?
+        .into_iter()
+        .flatten()
+        .flatten()
+        .collect()
+    };
+
+    Ok(receipts
+        .into_iter()
+        .map(|receipt| (receipt.transaction_hash, receipt))
+        .collect())
+}
+
+/// Calls `method` once per entry of `params`, `CALLS_PER_BATCH` calls to a
+/// JSON-RPC batch, one batch at a time, and returns the results in order.
+async fn batched_calls<P, R>(
+    provider: &RootProvider<Http<Client>>,
+    method: &'static str,
+    params: &[P],
+) -> Result<Vec<R>>
+where
+    P: Serialize + Clone + Debug + Send + Sync + Unpin,
+    R: DeserializeOwned + Debug + Send + Sync + Unpin + 'static,
+{
+    let mut results = Vec::with_capacity(params.len());
+    for chunk in params.chunks(CALLS_PER_BATCH) {
+        let mut batch = BatchRequest::new(provider.client());
+        let waiters = chunk
+            .iter()
+            .map(|params| batch.add_call::<_, R>(method, params))
+            .collect::<Result<Vec<_>, _>>()?;
+        batch.send().await?;
+        for waiter in waiters {
+            results.push(waiter.await?);
+        }
+    }
+    Ok(results)
+}
+
 async fn pick_transaction_fields(
-    tx: &RpcTransaction,
+    tx: &AnyRpcTransaction,
     fields: &[TransactionField],
-    provider: &Arc<RootProvider<Http<Client>>>,
+    receipt: Option<&AnyTransactionReceipt>,
     chain: &ChainOrRpc,
 ) -> Result<TransactionQueryRes> {
     let mut result = TransactionQueryRes::default();
     let chain = chain.to_chain().await?;
+    let signature = rpc_tx_signature(tx);
 
-    for field in fields {
+    // L2 fields of another rollup stack stay NULL.
+    for field in fields.iter().filter(|field| field.applies_to(&chain)) {
         match field {
             TransactionField::Type => {
-                result.r#type = Some(tx.inner.tx_type().into());
+                result.r#type = Some(tx.inner.ty());
             }
             TransactionField::AuthorizationList => {
                 result.authorization_list = tx.inner.authorization_list().map(|a| a.to_vec());
             }
+            TransactionField::AccessList => {
+                result.access_list = tx.inner.access_list().cloned();
+            }
             TransactionField::Hash => {
-                result.hash = Some(tx.inner.tx_hash().clone());
+                result.hash = Some(rpc_tx_hash(tx));
             }
             TransactionField::BlockNumber => {
                 result.block_number = tx.block_number;
@@ -517,39 +1043,64 @@ async fn pick_transaction_fields(
                 result.gas_price = tx.inner.gas_price();
             }
             TransactionField::EffectiveGasPrice => {
-                result.effective_gas_price = tx.effective_gas_price;
+                // Block bodies usually leave it out; the receipt always has it.
+                result.effective_gas_price = tx
+                    .effective_gas_price
+                    .or(receipt.map(|r| r.effective_gas_price));
             }
             TransactionField::GasLimit => {
                 result.gas_limit = Some(tx.inner.gas_limit());
             }
             TransactionField::Status => {
-                match provider
-                    .get_transaction_receipt(tx.inner.tx_hash().clone())
-                    .await?
-                {
-                    Some(receipt) => {
-                        result.status = Some(receipt.status());
-                    }
-                    None => {
-                        result.status = None;
-                    }
-                }
+                result.status = receipt.map(|r| r.inner.inner.status());
+            }
+            TransactionField::GasUsed => {
+                result.gas_used = receipt.and_then(|r| u64::try_from(r.gas_used).ok());
+            }
+            TransactionField::CumulativeGasUsed => {
+                result.cumulative_gas_used =
+                    receipt.and_then(|r| u64::try_from(r.inner.inner.cumulative_gas_used()).ok());
+            }
+            TransactionField::ContractAddress => {
+                result.contract_address = receipt.and_then(|r| r.contract_address);
+            }
+            TransactionField::LogsCount => {
+                result.logs_count = receipt.map(|r| r.inner.inner.logs().len() as u64);
+            }
+            TransactionField::Fee => {
+                // Derived below, once gas used and the effective gas price are in.
             }
             TransactionField::ChainId => {
                 result.chain_id = tx.inner.chain_id();
             }
             TransactionField::V => {
-                result.v = Some(tx.inner.signature().v());
+                result.v = signature.map(|s| s.v());
             }
             TransactionField::R => {
-                result.r = Some(tx.inner.signature().r());
+                result.r = signature.map(|s| s.r());
             }
             TransactionField::S => {
-                result.s = Some(tx.inner.signature().s());
+                result.s = signature.map(|s| s.s());
             }
             TransactionField::MaxFeePerBlobGas => {
                 result.max_fee_per_blob_gas = tx.inner.max_fee_per_blob_gas();
             }
+            TransactionField::BlobVersionedHashes => {
+                result.blob_versioned_hashes = tx.inner.blob_versioned_hashes().map(<[_]>::to_vec);
+            }
+            TransactionField::BlobCount => {
+                result.blob_count = Some(
+                    tx.inner
+                        .blob_versioned_hashes()
+                        .map_or(0, |h| h.len() as u64),
+                );
+            }
+            TransactionField::BlobGasUsed => {
+                result.blob_gas_used = receipt.and_then(|r| u64::try_from(r.blob_gas_used?).ok());
+            }
+            TransactionField::BlobGasPrice => {
+                result.blob_gas_price = receipt.and_then(|r| r.blob_gas_price);
+            }
             TransactionField::MaxFeePerGas => {
                 result.max_fee_per_gas = Some(tx.inner.max_fee_per_gas());
             }
@@ -557,13 +1108,52 @@ async fn pick_transaction_fields(
                 result.max_priority_fee_per_gas = tx.inner.max_priority_fee_per_gas();
             }
             TransactionField::YParity => {
-                result.y_parity = Some(tx.inner.signature().v());
+                result.y_parity = signature.map(|s| s.v());
             }
             TransactionField::Chain => {
                 result.chain = Some(chain.clone());
             }
+            TransactionField::L1Fee => {
+                result.l1_fee = receipt_other_field(receipt, "l1Fee").and_then(value_to_u256);
+            }
+            TransactionField::L1GasUsed => {
+                result.l1_gas_used =
+                    receipt_other_field(receipt, "l1GasUsed").and_then(value_to_u64);
+            }
+            TransactionField::L1GasPrice => {
+                result.l1_gas_price =
+                    receipt_other_field(receipt, "l1GasPrice").and_then(value_to_u128);
+            }
+            TransactionField::L1FeeScalar => {
+                result.l1_fee_scalar =
+                    receipt_other_field(receipt, "l1FeeScalar").and_then(value_to_decimal_text);
+            }
+            TransactionField::SourceHash => {
+                result.source_hash = rpc_tx_other_field(tx, "sourceHash").and_then(value_to_b256);
+            }
+            TransactionField::Mint => {
+                result.mint = rpc_tx_other_field(tx, "mint").and_then(value_to_u256);
+            }
+            TransactionField::IsSystemTx => {
+                // Only deposits carry the flag; it's false when they leave it out.
+                result.is_system_tx = (tx.inner.ty() == DEPOSIT_TX_TYPE).then(|| {
+                    rpc_tx_other_field(tx, "isSystemTx")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false)
+                });
+            }
+            TransactionField::GasUsedForL1 => {
+                result.gas_used_for_l1 =
+                    receipt_other_field(receipt, "gasUsedForL1").and_then(value_to_u64);
+            }
+            TransactionField::FromEns | TransactionField::ToEns => {
+                // Looked up once the rows are fetched; see fill_ens_names.
+            }
         }
     }
+    if fields.contains(&TransactionField::Fee) {
+        result.fee = transaction_fee(result.gas_used, result.effective_gas_price);
+    }
 
     Ok(result)
 }
@@ -577,9 +1167,45 @@ mod tests {
     };
     use alloy::{
         eips::BlockNumberOrTag,
-        primitives::{address, U256},
+        primitives::{address, b256, U256},
     };
 
+    #[tokio::test]
+    async fn receipt_calls_are_split_at_the_batch_limit() {
+        let nulls = |ids: std::ops::Range<usize>| {
+            let responses = ids
+                .map(|id| format!(r#"{{"jsonrpc":"2.0","id":{id},"result":null}}"#))
+                .collect::<Vec<_>>();
+            format!("[{}]", responses.join(","))
+        };
+        let (url, requests, handle) =
+            super::super::resolve_portal::test_support::spawn_mock_portal(vec![
+                nulls(0..CALLS_PER_BATCH),
+                nulls(CALLS_PER_BATCH..CALLS_PER_BATCH + 1),
+            ]);
+        let provider = ProviderBuilder::new().on_http(url.parse().unwrap());
+        let blocks = (0..=CALLS_PER_BATCH as u64)
+            .map(|block| (BlockNumberOrTag::Number(block),))
+            .collect::<Vec<_>>();
+
+        let receipts = batched_calls::<_, Option<Vec<AnyTransactionReceipt>>>(
+            &provider,
+            "eth_getBlockReceipts",
+            &blocks,
+        )
+        .await
+        .unwrap();
+        handle.join().expect("mock RPC thread");
+
+        assert_eq!(receipts.len(), CALLS_PER_BATCH + 1);
+        let requests = requests.lock().unwrap();
+        let sizes = requests
+            .iter()
+            .map(|r| r.as_array().unwrap().len())
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, [CALLS_PER_BATCH, 1]);
+    }
+
     #[test]
     fn test_parse_portal_transaction_decodes_signature_fields() {
         use serde_json::json;
@@ -612,10 +1238,18 @@ mod tests {
             let block_derived = matches!(field, TransactionField::BlockNumber);
             let local = matches!(
                 field,
-                TransactionField::Chain | TransactionField::AuthorizationList
+                TransactionField::Chain
+                    | TransactionField::AuthorizationList
+                    | TransactionField::AccessList
+                    | TransactionField::Fee
+                    | TransactionField::LogsCount
+                    | TransactionField::FromEns
+                    | TransactionField::ToEns
             );
+            // Queries selecting these are sent to RPC instead.
+            let rpc_only = is_rpc_only(field);
             assert!(
-                mapped || block_derived || local,
+                mapped || block_derived || local || rpc_only,
                 "TransactionField {:?} not Portal-serviceable",
                 field
             );
@@ -632,7 +1266,7 @@ mod tests {
                     BlockNumberOrTag::Number(10),
                     Some(BlockNumberOrTag::Number(11)),
                 ))),
-                TransactionFilter::From(EqualityFilter::Eq(sender)),
+                TransactionFilter::From(EqualityFilter::Eq(sender.into())),
                 TransactionFilter::Value(FilterType::Comparison(ComparisonFilter::Gte(
                     U256::from(50),
                 ))),
@@ -685,7 +1319,9 @@ mod tests {
         let sender = address!("1000000000000000000000000000000000000001");
         let transaction = Transaction::new(
             None,
-            Some(vec![TransactionFilter::From(EqualityFilter::Eq(sender))]),
+            Some(vec![TransactionFilter::From(EqualityFilter::Eq(
+                sender.into(),
+            ))]),
             vec![TransactionField::AuthorizationList],
         );
 
@@ -748,6 +1384,120 @@ mod tests {
         assert_eq!(requests[0]["fields"]["transaction"]["v"], json!(true));
     }
 
+    #[tokio::test]
+    async fn test_portal_receipt_fields_count_logs_and_derive_the_fee() {
+        let transaction = Transaction::new(
+            None,
+            Some(vec![
+                TransactionFilter::BlockId(BlockId::Range(BlockRange::new(
+                    BlockNumberOrTag::Number(30),
+                    None,
+                ))),
+                TransactionFilter::GasUsed(FilterType::Comparison(ComparisonFilter::Gt(21_000))),
+            ]),
+            vec![TransactionField::LogsCount, TransactionField::Fee],
+        );
+        let (base_url, requests, handle) =
+            super::super::resolve_portal::test_support::spawn_mock_portal(vec![concat!(
+                "{\"header\":{\"number\":\"0x1e\"},\"transactions\":[",
+                "{\"transactionIndex\":0,\"gasUsed\":\"0x5208\",\"effectiveGasPrice\":\"0x2\"},",
+                "{\"transactionIndex\":1,\"gasUsed\":\"0xc350\",\"effectiveGasPrice\":\"0x2\"},",
+                "{\"transactionIndex\":2,\"gasUsed\":\"0x7530\",\"effectiveGasPrice\":\"0x3\"}],",
+                "\"logs\":[{\"transactionIndex\":1},{\"transactionIndex\":1},{\"transactionIndex\":0}]}\n"
+            )
+            .to_string()]);
+
+        let results = resolve_transactions_via_portal_with_base_url(
+            &transaction,
+            &ChainOrRpc::Chain(Chain::Ethereum),
+            Some(&base_url),
+        )
+        .await
+        .unwrap();
+        handle.join().expect("mock Portal thread");
+
+        // The 21000-gas transfer is filtered out; fee is gas used × price.
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].logs_count, Some(2));
+        assert_eq!(results[0].fee, Some(U256::from(100_000)));
+        assert_eq!(results[0].gas_used, None);
+        assert_eq!(results[1].logs_count, Some(0));
+        assert_eq!(results[1].fee, Some(U256::from(90_000)));
+
+        let requests = requests.lock().expect("captured requests");
+        let request = &requests[0];
+        assert_eq!(request["transactions"][0]["logs"], json!(true));
+        assert_eq!(request["fields"]["log"]["transactionIndex"], json!(true));
+        assert_eq!(request["fields"]["transaction"]["gasUsed"], json!(true));
+        assert_eq!(
+            request["fields"]["transaction"]["effectiveGasPrice"],
+            json!(true)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_portal_blob_filter_requests_type_3_and_decodes_blobs() {
+        let has_blobs = FilterType::Comparison(ComparisonFilter::Gt(0));
+        let transaction = Transaction::new(
+            None,
+            Some(vec![
+                TransactionFilter::BlockId(BlockId::Range(BlockRange::new(
+                    BlockNumberOrTag::Number(40),
+                    None,
+                ))),
+                TransactionFilter::BlobCount(has_blobs.clone()),
+            ]),
+            vec![
+                TransactionField::BlobVersionedHashes,
+                TransactionField::BlobGasUsed,
+            ],
+        );
+        assert!(client_filter_columns(&transaction, true).is_empty());
+        assert_eq!(client_filter_columns(&transaction, false), ["blob_count"]);
+
+        let (base_url, requests, handle) =
+            super::super::resolve_portal::test_support::spawn_mock_portal(vec![concat!(
+                "{\"header\":{\"number\":\"0x28\"},\"transactions\":[",
+                "{\"blobVersionedHashes\":[",
+                "\"0x0100000000000000000000000000000000000000000000000000000000000001\",",
+                "\"0x0100000000000000000000000000000000000000000000000000000000000002\"]}]}\n"
+            )
+            .to_string()]);
+
+        let results = resolve_transactions_via_portal_with_base_url(
+            &transaction,
+            &ChainOrRpc::Chain(Chain::Ethereum),
+            Some(&base_url),
+        )
+        .await
+        .unwrap();
+        handle.join().expect("mock Portal thread");
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].blob_versioned_hashes.as_ref().map(Vec::len),
+            Some(2)
+        );
+        assert_eq!(results[0].blob_gas_used, Some(2 * DATA_GAS_PER_BLOB));
+        assert_eq!(results[0].blob_count, None);
+
+        let requests = requests.lock().expect("captured requests");
+        assert_eq!(requests[0]["transactions"][0]["type"], json!([3]));
+        assert_eq!(
+            requests[0]["fields"]["transaction"]["blobVersionedHashes"],
+            json!(true)
+        );
+
+        // A filter that also keeps blob-less transactions can't be pushed down.
+        assert!(requests_blob_transactions(&has_blobs));
+        assert!(!requests_blob_transactions(&FilterType::Comparison(
+            ComparisonFilter::Lt(2)
+        )));
+        assert!(!is_blob_type_filter(&FilterType::Comparison(
+            ComparisonFilter::Gt(1)
+        )));
+    }
+
     #[test]
     fn test_should_use_portal_accepts_the_e2e_block_range_shape() {
         // Pins the routing decision the execution_engine Portal e2e tests rely
@@ -812,4 +1562,247 @@ mod tests {
             &eligible
         ));
     }
+
+    #[test]
+    fn test_blob_gas_price_routes_to_rpc() {
+        let transaction = Transaction::new(
+            None,
+            Some(vec![TransactionFilter::BlockId(BlockId::Range(
+                BlockRange::new(
+                    BlockNumberOrTag::Number(1),
+                    Some(BlockNumberOrTag::Number(2)),
+                ),
+            ))]),
+            vec![TransactionField::BlobGasPrice],
+        );
+        assert!(!should_use_portal(
+            &ChainOrRpc::Chain(Chain::Ethereum),
+            &transaction
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_portal_decodes_op_stack_l1_fee_fields() {
+        let transaction = Transaction::new(
+            None,
+            Some(vec![TransactionFilter::BlockId(BlockId::Range(
+                BlockRange::new(BlockNumberOrTag::Number(50), None),
+            ))]),
+            vec![
+                TransactionField::L1Fee,
+                TransactionField::L1GasUsed,
+                TransactionField::L1GasPrice,
+                TransactionField::L1FeeScalar,
+                TransactionField::GasUsedForL1,
+            ],
+        );
+        let base = ChainOrRpc::Chain(Chain::Base);
+        assert!(should_use_portal(&base, &transaction));
+
+        let (base_url, requests, handle) =
+            super::super::resolve_portal::test_support::spawn_mock_portal(vec![concat!(
+                "{\"header\":{\"number\":\"0x32\"},\"transactions\":[{",
+                "\"l1Fee\":\"0x2386f26fc10000\",\"l1GasUsed\":\"0x640\",",
+                "\"l1GasPrice\":\"0x3b9aca00\",\"l1FeeScalar\":\"0.684\"}]}\n"
+            )
+            .to_string()]);
+
+        let results =
+            resolve_transactions_via_portal_with_base_url(&transaction, &base, Some(&base_url))
+                .await
+                .unwrap();
+        handle.join().expect("mock Portal thread");
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].l1_fee,
+            Some(U256::from(10_000_000_000_000_000u64))
+        );
+        assert_eq!(results[0].l1_gas_used, Some(1600));
+        assert_eq!(results[0].l1_gas_price, Some(1_000_000_000));
+        assert_eq!(results[0].l1_fee_scalar.as_deref(), Some("0.684"));
+        // An Arbitrum column is NULL on Base, and isn't asked for.
+        assert_eq!(results[0].gas_used_for_l1, None);
+
+        let requests = requests.lock().expect("captured requests");
+        let tx_fields = &requests[0]["fields"]["transaction"];
+        assert_eq!(tx_fields["l1Fee"], json!(true));
+        assert_eq!(tx_fields["l1FeeScalar"], json!(true));
+        assert!(tx_fields.get("gasUsedForL1").is_none());
+    }
+
+    #[test]
+    fn test_l2_fields_are_only_requested_on_their_chains() {
+        let transaction = Transaction::new(
+            None,
+            Some(vec![TransactionFilter::BlockId(BlockId::Range(
+                BlockRange::new(BlockNumberOrTag::Number(1), None),
+            ))]),
+            vec![TransactionField::Hash, TransactionField::L1Fee],
+        );
+        assert_eq!(
+            portal_internal_fields(&transaction, &Chain::Ethereum),
+            [TransactionField::Hash]
+        );
+        assert_eq!(
+            portal_internal_fields(&transaction, &Chain::Optimism),
+            [TransactionField::Hash, TransactionField::L1Fee]
+        );
+    }
+
+    #[test]
+    fn test_deposit_fields_route_to_rpc_on_op_stack_chains_only() {
+        let transaction = Transaction::new(
+            None,
+            Some(vec![TransactionFilter::BlockId(BlockId::Range(
+                BlockRange::new(
+                    BlockNumberOrTag::Number(1),
+                    Some(BlockNumberOrTag::Number(2)),
+                ),
+            ))]),
+            vec![TransactionField::SourceHash, TransactionField::Mint],
+        );
+        assert!(!should_use_portal(
+            &ChainOrRpc::Chain(Chain::Base),
+            &transaction
+        ));
+        // Always NULL on Ethereum, so there's nothing for RPC to add.
+        assert!(should_use_portal(
+            &ChainOrRpc::Chain(Chain::Ethereum),
+            &transaction
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rpc_decodes_deposit_and_l1_fee_fields() {
+        let deposit: AnyRpcTransaction = serde_json::from_value(json!({
+            "type": "0x7e",
+            "hash": "0x00000000000000000000000000000000000000000000000000000000000000aa",
+            "blockHash": "0x00000000000000000000000000000000000000000000000000000000000000bb",
+            "blockNumber": "0x10",
+            "transactionIndex": "0x0",
+            "from": "0xdeaddeaddeaddeaddeaddeaddeaddeaddead0001",
+            "to": "0x4200000000000000000000000000000000000015",
+            "sourceHash": "0x00000000000000000000000000000000000000000000000000000000000000cc",
+            "mint": "0x0",
+            "value": "0x0",
+            "gas": "0xf4240",
+            "isSystemTx": false,
+            "input": "0x",
+            "nonce": "0x1"
+        }))
+        .unwrap();
+        let receipt: AnyTransactionReceipt = serde_json::from_value(json!({
+            "type": "0x7e",
+            "status": "0x1",
+            "cumulativeGasUsed": "0xb3cb",
+            "logs": [],
+            "logsBloom": format!("0x{}", "0".repeat(512)),
+            "transactionHash": "0x00000000000000000000000000000000000000000000000000000000000000aa",
+            "transactionIndex": "0x0",
+            "blockHash": "0x00000000000000000000000000000000000000000000000000000000000000bb",
+            "blockNumber": "0x10",
+            "gasUsed": "0xb3cb",
+            "effectiveGasPrice": "0x0",
+            "from": "0xdeaddeaddeaddeaddeaddeaddeaddeaddead0001",
+            "to": "0x4200000000000000000000000000000000000015",
+            "contractAddress": null,
+            "l1Fee": "0x64",
+            "l1GasUsed": "0x640",
+            "l1GasPrice": "0x3b9aca00",
+            "l1FeeScalar": "0.684"
+        }))
+        .unwrap();
+        assert_eq!(rpc_tx_hash(&deposit), receipt.transaction_hash);
+
+        let fields = [
+            TransactionField::Type,
+            TransactionField::V,
+            TransactionField::SourceHash,
+            TransactionField::Mint,
+            TransactionField::IsSystemTx,
+            TransactionField::L1Fee,
+            TransactionField::L1FeeScalar,
+            TransactionField::GasUsedForL1,
+        ];
+        let row = pick_transaction_fields(
+            &deposit,
+            &fields,
+            Some(&receipt),
+            &ChainOrRpc::Chain(Chain::Base),
+        )
+        .await
+        .unwrap();
+        assert_eq!(row.r#type, Some(DEPOSIT_TX_TYPE));
+        assert_eq!(row.v, None, "deposits aren't signed");
+        assert_eq!(
+            row.source_hash,
+            Some(b256!(
+                "00000000000000000000000000000000000000000000000000000000000000cc"
+            ))
+        );
+        assert_eq!(row.mint, Some(U256::ZERO));
+        assert_eq!(row.is_system_tx, Some(false));
+        assert_eq!(row.l1_fee, Some(U256::from(100)));
+        assert_eq!(row.l1_fee_scalar.as_deref(), Some("0.684"));
+        assert_eq!(row.gas_used_for_l1, None);
+
+        // The same transaction read as an Ethereum one leaves the L2 columns NULL.
+        let row = pick_transaction_fields(
+            &deposit,
+            &fields,
+            Some(&receipt),
+            &ChainOrRpc::Chain(Chain::Ethereum),
+        )
+        .await
+        .unwrap();
+        assert_eq!(row.source_hash, None);
+        assert_eq!(row.l1_fee, None);
+    }
+
+    #[tokio::test]
+    async fn test_ens_names_are_looked_up_for_the_projected_addresses() {
+        use crate::interpreter::backend::ens_cache::{ens_names, unix_now};
+        let sender = address!("e500000000000000000000000000000000000001");
+        let recipient = address!("e500000000000000000000000000000000000002");
+        {
+            // Cached, so nothing is read from the network.
+            let mut cache = ens_names().lock().unwrap();
+            cache.insert(sender, Some("sender.eth".to_string()), unix_now());
+            cache.insert(recipient, None, unix_now());
+        }
+        let transaction = Transaction::new(
+            None,
+            None,
+            vec![
+                TransactionField::FromEns,
+                TransactionField::To,
+                TransactionField::ToEns,
+            ],
+        );
+        assert_eq!(
+            transaction_internal_fields(&transaction),
+            vec![
+                TransactionField::FromEns,
+                TransactionField::To,
+                TransactionField::ToEns,
+                TransactionField::From,
+            ]
+        );
+
+        let internal_row = TransactionQueryRes {
+            from_address: Some(sender),
+            to_address: Some(recipient),
+            ..TransactionQueryRes::default()
+        };
+        let mut rows = vec![project_transaction_row(&internal_row, transaction.fields())];
+        fill_ens_names(transaction.fields(), &mut rows)
+            .await
+            .unwrap();
+
+        assert_eq!(rows[0].from_ens.as_deref(), Some("sender.eth"));
+        assert_eq!(rows[0].from_address, None, "from_address isn't selected");
+        assert_eq!(rows[0].to_ens, None, "the recipient has no primary name");
+        assert_eq!(rows[0].to_address, Some(recipient));
+    }
 }
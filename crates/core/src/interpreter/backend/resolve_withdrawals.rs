@@ -0,0 +1,200 @@
+use super::{
+    resolve_account::{planned_blocks, state_blocks},
+    resolve_block::batch_get_blocks,
+    resolve_explain::{block_id_text, chain_id_lookups, ChainPlan},
+};
+use crate::common::{
+    chain::{Chain, ChainOrRpc},
+    ens::NameOrAddress,
+    query_result::WithdrawalQueryRes,
+    withdrawal::{WithdrawalField, Withdrawals},
+};
+use alloy::{eips::eip4895::Withdrawal, primitives::Address, providers::ProviderBuilder};
+use anyhow::Result;
+use futures::future::try_join_all;
+use std::sync::Arc;
+
+/// Resolve the query to list beacon-chain withdrawals. They are read from
+/// the `withdrawals` of each block body over RPC: Portal doesn't serve them.
+/// Blocks before Shanghai (or on chains without withdrawals) have none.
+pub async fn resolve_withdrawals_query(
+    withdrawals: &Withdrawals,
+    chains: &[ChainOrRpc],
+) -> Result<Vec<WithdrawalQueryRes>> {
+    let fields = withdrawals.fields();
+    let addresses = withdrawals
+        .addresses()
+        .iter()
+        .map(NameOrAddress::resolved)
+        .collect::<Vec<_>>();
+    let mut all_withdrawal_futures = Vec::new();
+
+    for chain in chains {
+        let fields = &fields;
+        let addresses = &addresses;
+
+        let withdrawal_future = async move {
+            let provider = Arc::new(ProviderBuilder::new().on_http(chain.rpc_url()?));
+            let chain_enum = chain.to_chain().await?;
+            let numbers = state_blocks(&withdrawals.block_ids(), false, &provider)
+                .await?
+                .into_iter()
+                .flatten()
+                .collect();
+            let blocks = batch_get_blocks(numbers, &provider, false).await?;
+
+            let mut rows = Vec::new();
+            for block in blocks {
+                for withdrawal in block.withdrawals.iter().flatten() {
+                    if is_selected(withdrawal, addresses, withdrawals.validator_indices()) {
+                        rows.push(withdrawal_row(
+                            fields,
+                            &chain_enum,
+                            block.header.number,
+                            withdrawal,
+                        ));
+                    }
+                }
+            }
+            Ok::<_, anyhow::Error>(rows)
+        };
+
+        all_withdrawal_futures.push(withdrawal_future);
+    }
+
+    let withdrawal_res = try_join_all(all_withdrawal_futures).await?;
+    Ok(withdrawal_res.concat())
+}
+
+/// The `EXPLAIN` plan for `withdrawals` on `chain`: one
+/// `eth_getBlockByNumber` per block. Address and validator filters are applied to the
+/// fetched withdrawals.
+pub(crate) fn plan_withdrawals_query(withdrawals: &Withdrawals, chain: &ChainOrRpc) -> ChainPlan {
+    let block_ids = withdrawals.block_ids();
+    let block_range = Some(
+        block_ids
+            .iter()
+            .map(|id| block_id_text(id))
+            .collect::<Vec<_>>()
+            .join(", "),
+    );
+    let mut client_filters = Vec::new();
+    if !withdrawals.addresses().is_empty() {
+        client_filters.push(WithdrawalField::Address.to_string());
+    }
+    if !withdrawals.validator_indices().is_empty() {
+        client_filters.push(WithdrawalField::ValidatorIndex.to_string());
+    }
+    let calls = planned_blocks(&block_ids, false)
+        .map(|(blocks, lookups)| blocks + lookups + chain_id_lookups(chain));
+    ChainPlan::rpc(block_range, client_filters, calls, 0)
+}
+
+fn is_selected(withdrawal: &Withdrawal, addresses: &[Address], validator_indices: &[u64]) -> bool {
+    (addresses.is_empty() || addresses.contains(&withdrawal.address))
+        && (validator_indices.is_empty() || validator_indices.contains(&withdrawal.validator_index))
+}
+
+fn withdrawal_row(
+    fields: &[WithdrawalField],
+    chain: &Chain,
+    block_number: u64,
+    withdrawal: &Withdrawal,
+) -> WithdrawalQueryRes {
+    let mut row = WithdrawalQueryRes::default();
+    for field in fields {
+        match field {
+            WithdrawalField::BlockNumber => row.block_number = Some(block_number),
+            WithdrawalField::Index => row.index = Some(withdrawal.index),
+            WithdrawalField::ValidatorIndex => {
+                row.validator_index = Some(withdrawal.validator_index)
+            }
+            WithdrawalField::Address => row.address = Some(withdrawal.address),
+            WithdrawalField::AmountGwei => row.amount_gwei = Some(withdrawal.amount),
+            WithdrawalField::Amount => row.amount = Some(withdrawal.amount_wei()),
+            WithdrawalField::Chain => row.chain = Some(chain.clone()),
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::block::{BlockId, BlockRange};
+    use alloy::{eips::BlockNumberOrTag, primitives::U256};
+
+    fn withdrawal(validator_index: u64, address: Address) -> Withdrawal {
+        Withdrawal {
+            index: 7,
+            validator_index,
+            address,
+            amount: 16_000_000,
+        }
+    }
+
+    #[test]
+    fn plans_one_block_fetch_per_block() {
+        let chain = ChainOrRpc::Chain(Chain::Ethereum);
+        let withdrawals = Withdrawals::new(
+            vec![BlockId::Range(BlockRange::new(
+                BlockNumberOrTag::Number(10),
+                Some(BlockNumberOrTag::Number(12)),
+            ))],
+            vec![NameOrAddress::Address(Address::ZERO)],
+            vec![],
+            WithdrawalField::all_variants().to_vec(),
+        );
+        let plan = plan_withdrawals_query(&withdrawals, &chain);
+        assert_eq!(plan.rpc_calls, Some(3));
+        assert_eq!(plan.block_range.as_deref(), Some("10..12"));
+        assert_eq!(plan.client_filters, ["address"]);
+
+        let withdrawals = Withdrawals::new(
+            vec![BlockId::Number(BlockNumberOrTag::Latest)],
+            vec![],
+            vec![1],
+            vec![WithdrawalField::Amount],
+        );
+        let plan = plan_withdrawals_query(&withdrawals, &chain);
+        assert_eq!(plan.rpc_calls, Some(2));
+        assert_eq!(plan.client_filters, ["validator_index"]);
+    }
+
+    #[test]
+    fn filters_by_address_and_validator() {
+        let recipient = Address::with_last_byte(1);
+        let other = Address::with_last_byte(2);
+        assert!(is_selected(&withdrawal(5, other), &[], &[]));
+        assert!(is_selected(&withdrawal(5, recipient), &[recipient], &[]));
+        assert!(!is_selected(&withdrawal(5, other), &[recipient], &[]));
+        assert!(is_selected(
+            &withdrawal(5, recipient),
+            &[recipient],
+            &[4, 5]
+        ));
+        assert!(!is_selected(
+            &withdrawal(6, recipient),
+            &[recipient],
+            &[4, 5]
+        ));
+    }
+
+    #[test]
+    fn rows_carry_both_amounts() {
+        let row = withdrawal_row(
+            &[WithdrawalField::AmountGwei, WithdrawalField::Amount],
+            &Chain::Ethereum,
+            100,
+            &withdrawal(5, Address::ZERO),
+        );
+        assert_eq!(
+            row,
+            WithdrawalQueryRes {
+                amount_gwei: Some(16_000_000),
+                amount: Some(U256::from(16_000_000_000_000_000u64)),
+                ..Default::default()
+            }
+        );
+    }
+}
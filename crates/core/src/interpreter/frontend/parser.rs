@@ -326,9 +326,9 @@ mod tests {
                             BlockNumberOrTag::Number(4638757),
                             None,
                         )),
-                        LogFilter::EmitterAddress(address!(
-                            "dac17f958d2ee523a2206206994597c13d831ec7"
-                        )),
+                        LogFilter::EmitterAddress(
+                            address!("dac17f958d2ee523a2206206994597c13d831ec7").into(),
+                        ),
                         LogFilter::Topic0(b256!(
                             "cb8241adb0c3fdb35b70c24ce35c5eb0c17af7431c99f827d44a445ca624176a"
                         )),
@@ -452,13 +452,17 @@ mod tests {
                     TransactionFilter::Status(EqualityFilter::Eq(true)),
                     TransactionFilter::YParity(EqualityFilter::Eq(false)),
                     TransactionFilter::From(EqualityFilter::Eq(
-                        Address::from_str("0x1234567890123456789012345678901234567890").unwrap(),
+                        Address::from_str("0x1234567890123456789012345678901234567890")
+                            .unwrap()
+                            .into(),
                     )),
                     TransactionFilter::To(EqualityFilter::Eq(
-                        Address::from_str("0x1234567890123456789012345678901234567890").unwrap(),
+                        Address::from_str("0x1234567890123456789012345678901234567890")
+                            .unwrap()
+                            .into(),
                     )),
                 ]),
-                TransactionField::all_variants().to_vec(),
+                TransactionField::wildcard(),
             )),
             chains: vec![ChainOrRpc::Chain(Chain::Ethereum)],
             dump: None,
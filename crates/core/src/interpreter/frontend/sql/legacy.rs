@@ -56,6 +56,14 @@ pub fn legacy_error(source: &str) -> Option<EqlSqlError> {
                 Expression::Set(set) => {
                     format!("-- unexpected SET expression from the legacy parser: {set:?}")
                 }
+                // Likewise only built by `sql::translate` (SHOW / DESCRIBE /
+                // information_schema).
+                Expression::Catalog(catalog) => {
+                    format!("-- unexpected catalog expression from the legacy parser: {catalog:?}")
+                }
+                Expression::Explain(explain) => {
+                    format!("-- unexpected EXPLAIN expression from the legacy parser: {explain:?}")
+                }
             })
             .collect::<Vec<_>>()
             .join(";\n"),
@@ -203,7 +211,7 @@ enum Rendered {
 }
 
 fn render_account(account: &Account) -> Rendered {
-    let field_list_str = field_list(&account.fields(), AccountField::all_variants());
+    let field_list_str = field_list(&account.fields(), &AccountField::wildcard());
     let mut conditions = Vec::new();
     // `Account.id` is `Some(non-empty)` for every legacy query that
     // actually parses: `account_get`'s only reachable production is
@@ -235,7 +243,7 @@ fn render_block(block: &Block) -> Rendered {
     // The legacy grammar's other production for a block entity —
     // `block_filter_list` (`WHERE block = ...`) — populates `filter`
     // instead of `ids` (leaving `ids` as `Some(vec![])`). Matched
-    // exhaustively (one variant, `BlockFilter::Range`) so this can't
+    // exhaustively (the grammar only builds `BlockFilter::Range`) so this can't
     // silently vanish from the suggestion the way it used to: `ids()` and
     // `filters()` are independent `Option`s on `Block`, and only reading
     // the first one meant a `WHERE block = ...` legacy query rendered a
@@ -244,6 +252,8 @@ fn render_block(block: &Block) -> Rendered {
         for filter in filters {
             match filter {
                 BlockFilter::Range(range) => conditions.push(range_condition("number", range)),
+                BlockFilter::Miner(f) => conditions.push(eq_condition("miner", f)),
+                BlockFilter::Timestamp(f) => conditions.push(cmp_condition("timestamp", f)),
             }
         }
     }
@@ -255,7 +265,7 @@ fn render_block(block: &Block) -> Rendered {
 }
 
 fn render_transaction(tx: &Transaction) -> Rendered {
-    let field_list_str = field_list(tx.fields(), TransactionField::all_variants());
+    let field_list_str = field_list(tx.fields(), &TransactionField::wildcard());
     let mut conditions = Vec::new();
     let has_hash = match tx.ids() {
         Some(ids) if !ids.is_empty() => {
@@ -306,6 +316,23 @@ fn render_transaction(tx: &Transaction) -> Rendered {
                 }
                 TransactionFilter::Status(f) => conditions.push(eq_condition("status", f)),
                 TransactionFilter::YParity(f) => conditions.push(eq_condition("y_parity", f)),
+                // Receipt and blob columns: only the SQL frontend filters on these.
+                TransactionFilter::GasUsed(f) => conditions.push(cmp_condition("gas_used", f)),
+                TransactionFilter::CumulativeGasUsed(f) => {
+                    conditions.push(cmp_condition("cumulative_gas_used", f))
+                }
+                TransactionFilter::ContractAddress(f) => {
+                    conditions.push(eq_condition("contract_address", f))
+                }
+                TransactionFilter::LogsCount(f) => conditions.push(cmp_condition("logs_count", f)),
+                TransactionFilter::Fee(f) => conditions.push(cmp_condition("fee", f)),
+                TransactionFilter::BlobCount(f) => conditions.push(cmp_condition("blob_count", f)),
+                TransactionFilter::BlobGasUsed(f) => {
+                    conditions.push(cmp_condition("blob_gas_used", f))
+                }
+                TransactionFilter::BlobGasPrice(f) => {
+                    conditions.push(cmp_condition("blob_gas_price", f))
+                }
                 // Unreachable through the legacy grammar today: none of
                 // `tx_filter`'s alternatives in `productions.pest`
                 // construct `Hash`, `ChainId`, `V`, `R` or `S` (there is no
@@ -349,7 +376,7 @@ fn render_transaction(tx: &Transaction) -> Rendered {
 }
 
 fn render_logs(logs: &Logs) -> Rendered {
-    let field_list_str = field_list(logs.fields(), LogField::all_variants());
+    let field_list_str = field_list(logs.fields(), &LogField::wildcard());
     let mut conditions = Vec::new();
     let mut has_block = false;
     for filter in logs.filter() {
@@ -398,6 +425,18 @@ fn render(get: &GetExpression) -> String {
         Entity::Block(block) => render_block(block),
         Entity::Transaction(tx) => render_transaction(tx),
         Entity::Logs(logs) => render_logs(logs),
+        Entity::Storage(_)
+        | Entity::TokenBalances(_)
+        | Entity::Call(_)
+        | Entity::Withdrawals(_)
+        | Entity::Authorizations(_)
+        | Entity::Contracts(_)
+        | Entity::StateDiffs(_)
+        | Entity::Ens(_) => {
+            unreachable!(
+                "the legacy grammar has no storage, token_balances, call, withdrawals, authorizations, contracts, state_diffs or ens entity"
+            )
+        }
     };
     let (table, field_list_str, mut conditions) = match rendered {
         Rendered::Query {
@@ -547,7 +586,7 @@ mod tests {
     fn account_wildcard_fields_round_trip() {
         // `address` is never a nameable legacy `account_field` (it's
         // implicit from `FROM account <id>` in the old grammar), so a
-        // hand-typed field list can never reach all 5 `AccountField`
+        // hand-typed field list can never reach all 6 `AccountField`
         // variants — only the real `*` token can. Naming the other 4 by
         // hand must stay spelled out, never collapse.
         let err = suggestion("GET nonce, balance, code, chain FROM account vitalik.eth ON eth");
@@ -568,8 +607,9 @@ mod tests {
         // set — a real test of the wildcard-collapse equality check, not
         // just of the literal `*` token.
         let err = suggestion(
-            "GET number, timestamp, size, hash, parent_hash, state_root, transactions_root, \
-             receipts_root, logs_bloom, extra_data, mix_hash, total_difficulty, \
+            "GET number, timestamp, size, hash, parent_hash, miner, gas_used, gas_limit, nonce, \
+             difficulty, transaction_count, state_root, transactions_root, receipts_root, \
+             logs_bloom, extra_data, mix_hash, total_difficulty, \
              base_fee_per_gas, withdrawals_root, blob_gas_used, excess_blob_gas, \
              parent_beacon_block_root, chain FROM block 1 ON eth",
         );
@@ -587,14 +627,16 @@ mod tests {
         let err = suggestion(
             "GET chain, parent_beacon_block_root, excess_blob_gas, blob_gas_used, \
              withdrawals_root, base_fee_per_gas, total_difficulty, mix_hash, extra_data, \
-             logs_bloom, receipts_root, transactions_root, state_root, parent_hash, hash, \
+             logs_bloom, receipts_root, transactions_root, state_root, transaction_count, \
+             difficulty, nonce, gas_limit, gas_used, miner, parent_hash, hash, \
              size, timestamp, number FROM block 1 ON eth",
         );
         assert!(
             err.contains(
                 "SELECT chain, parent_beacon_block_root, excess_blob_gas, blob_gas_used, \
                  withdrawals_root, base_fee_per_gas, total_difficulty, mix_hash, extra_data, \
-                 logs_bloom, receipts_root, transactions_root, state_root, parent_hash, hash, \
+                 logs_bloom, receipts_root, transactions_root, state_root, transaction_count, \
+                 difficulty, nonce, gas_limit, gas_used, miner, parent_hash, hash, \
                  size, timestamp, number FROM blocks"
             ),
             "{err}"
@@ -1,4 +1,5 @@
 pub mod legacy;
+pub mod params;
 pub mod prelex;
 pub mod schema;
 pub mod translate;
@@ -6,7 +7,13 @@ pub mod values;
 pub mod where_clause;
 
 use crate::common::types::Expression;
-use sqlparser::{dialect::DuckDbDialect, parser::Parser as SqlParser};
+use params::{Params, Placeholder};
+use sqlparser::{
+    ast::Statement,
+    dialect::DuckDbDialect,
+    parser::{Parser as SqlParser, ParserError},
+    tokenizer::{TokenWithLocation, Tokenizer},
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum EqlSqlError {
@@ -28,21 +35,90 @@ pub enum EqlSqlError {
 /// only ever produce a raw, unhelpful parse error. Catching it here first
 /// means a user who hasn't migrated gets the EQL 2 equivalent instead.
 pub fn parse_program(source: &str) -> Result<Vec<Expression>, EqlSqlError> {
+    parse_program_with_params(source, &Params::new())
+}
+
+/// `parse_program` for a query with `$1` / `:name` placeholders, each
+/// replaced by its value in `params` (see `params::bind`).
+pub fn parse_program_with_params(
+    source: &str,
+    params: &Params,
+) -> Result<Vec<Expression>, EqlSqlError> {
+    parse_statements(source, Some(params))?
+        .iter()
+        .map(translate::statement_to_expression)
+        .collect()
+}
+
+/// A program lexed once, placeholders and all, so it can be bound and run
+/// any number of times without going back to its source text.
+#[derive(Debug, Clone)]
+pub struct LexedProgram {
+    tokens: Vec<TokenWithLocation>,
+    placeholders: Vec<Placeholder>,
+}
+
+impl LexedProgram {
+    /// Lexes `source` and checks that it parses with its placeholders
+    /// unbound, so syntax errors surface before any value is bound.
+    pub fn new(source: &str) -> Result<Self, EqlSqlError> {
+        let tokens = lex(source)?;
+        parse_tokens(tokens.clone())?;
+        let placeholders = params::placeholders(&tokens)?;
+        Ok(LexedProgram {
+            tokens,
+            placeholders,
+        })
+    }
+
+    /// The program's distinct placeholders, in order of first use.
+    pub fn placeholders(&self) -> &[Placeholder] {
+        &self.placeholders
+    }
+
+    /// Binds `params` into the lexed tokens and parses the result.
+    pub fn bind(&self, params: &Params) -> Result<Vec<Expression>, EqlSqlError> {
+        let tokens = params::bind(self.tokens.clone(), params)?;
+        parse_tokens(tokens)?
+            .iter()
+            .map(translate::statement_to_expression)
+            .collect()
+    }
+}
+
+fn parse_statements(source: &str, params: Option<&Params>) -> Result<Vec<Statement>, EqlSqlError> {
+    let mut tokens = lex(source)?;
+    if let Some(params) = params {
+        tokens = params::bind(tokens, params)?;
+    }
+    parse_tokens(tokens)
+}
+
+fn lex(source: &str) -> Result<Vec<TokenWithLocation>, EqlSqlError> {
     if let Some(err) = legacy::legacy_error(source) {
         return Err(err);
     }
     let prelexed = prelex::prelex(source)?;
-    let statements = SqlParser::parse_sql(&DuckDbDialect {}, &prelexed)
-        .map_err(|e| EqlSqlError::Parse(e.to_string()))?;
-    statements
-        .iter()
-        .map(translate::statement_to_expression)
-        .collect()
+    Tokenizer::new(&DuckDbDialect {}, &prelexed)
+        .tokenize_with_location()
+        .map_err(|e| parse_error(e.into()))
+}
+
+fn parse_tokens(tokens: Vec<TokenWithLocation>) -> Result<Vec<Statement>, EqlSqlError> {
+    SqlParser::new(&DuckDbDialect {})
+        .with_tokens_with_locations(tokens)
+        .parse_statements()
+        .map_err(parse_error)
+}
+
+fn parse_error(e: ParserError) -> EqlSqlError {
+    EqlSqlError::Parse(e.to_string())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::parse_program;
+    use super::{parse_program, LexedProgram};
+    use crate::interpreter::frontend::sql::params::{Param, Params, Placeholder};
 
     #[test]
     fn parses_a_multi_statement_program() {
@@ -61,6 +137,28 @@ mod tests {
         assert_eq!(expressions.len(), 3);
     }
 
+    #[test]
+    fn a_lexed_program_binds_many_times() {
+        let program =
+            LexedProgram::new("SELECT * FROM blocks WHERE number = $1 AND chain = :chain").unwrap();
+        assert_eq!(
+            program.placeholders(),
+            [Placeholder::Position(1), Placeholder::Name("chain".into())]
+        );
+        for number in [1u64, 2] {
+            let mut params = Params::new();
+            params.set(1, number);
+            params.set("chain", Param::from("eth"));
+            assert_eq!(
+                program.bind(&params).unwrap(),
+                parse_program(&format!(
+                    "SELECT * FROM blocks WHERE number = {number} AND chain = eth"
+                ))
+                .unwrap()
+            );
+        }
+    }
+
     #[test]
     fn legacy_get_reports_equivalent() {
         let err = parse_program("GET balance FROM account vitalik.eth ON eth")
@@ -0,0 +1,531 @@
+//! Query parameters: positional (`$1`) and named (`:addr`) placeholders.
+//!
+//! Binding happens on sqlparser's token stream, after `prelex` and before
+//! parsing. Each placeholder token is swapped for the literal token of its
+//! bound value, so the value reaches `values::parse_*` as the plain string,
+//! number, boolean or identifier it would have been if typed by hand. It
+//! never passes through prelex's sugar handling or SQL quoting.
+
+use super::EqlSqlError;
+use alloy::eips::BlockNumberOrTag;
+use alloy::primitives::{Address, B256, U256};
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use sqlparser::tokenizer::{Token, TokenWithLocation};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::iter::Peekable;
+use std::str::FromStr;
+
+/// A placeholder in the query text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Placeholder {
+    /// `$1`, `$2`, ... (1-based).
+    Position(usize),
+    /// `:name`.
+    Name(String),
+}
+
+impl fmt::Display for Placeholder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Placeholder::Position(n) => write!(f, "${n}"),
+            Placeholder::Name(name) => write!(f, ":{name}"),
+        }
+    }
+}
+
+impl From<usize> for Placeholder {
+    fn from(position: usize) -> Self {
+        Placeholder::Position(position)
+    }
+}
+
+/// `"1"` and `"$1"` are positions; `"addr"` and `":addr"` are names.
+impl From<&str> for Placeholder {
+    fn from(key: &str) -> Self {
+        let key = key
+            .strip_prefix('$')
+            .or_else(|| key.strip_prefix(':'))
+            .unwrap_or(key);
+        match key.parse() {
+            Ok(position) => Placeholder::Position(position),
+            Err(_) => Placeholder::Name(key.to_string()),
+        }
+    }
+}
+
+impl From<String> for Placeholder {
+    fn from(key: String) -> Self {
+        Placeholder::from(key.as_str())
+    }
+}
+
+/// A bound value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Param {
+    Address(Address),
+    Hash(B256),
+    Number(U256),
+    Bool(bool),
+    Block(BlockNumberOrTag),
+    /// A string: an ENS name, a chain name, an RPC url, ...
+    Text(String),
+}
+
+impl Param {
+    /// Types an untyped value (a CLI flag, a JS string): `0x` + 40 hex digits
+    /// is an address, `0x` + 64 a hash, digits a number, `true`/`false` a
+    /// boolean and a block tag a block. Anything else is text.
+    pub fn from_text(text: &str) -> Param {
+        let hex_digits = text
+            .strip_prefix("0x")
+            .filter(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()))
+            .map(str::len);
+        match hex_digits {
+            Some(40) => {
+                if let Ok(address) = Address::from_str(text) {
+                    return Param::Address(address);
+                }
+            }
+            Some(64) => {
+                if let Ok(hash) = B256::from_str(text) {
+                    return Param::Hash(hash);
+                }
+            }
+            _ => {}
+        }
+        if !text.is_empty() && text.chars().all(|c| c.is_ascii_digit()) {
+            if let Ok(number) = U256::from_str(text) {
+                return Param::Number(number);
+            }
+        }
+        match text.to_ascii_lowercase().as_str() {
+            "true" => Param::Bool(true),
+            "false" => Param::Bool(false),
+            "latest" => Param::Block(BlockNumberOrTag::Latest),
+            "earliest" => Param::Block(BlockNumberOrTag::Earliest),
+            "pending" => Param::Block(BlockNumberOrTag::Pending),
+            "finalized" => Param::Block(BlockNumberOrTag::Finalized),
+            "safe" => Param::Block(BlockNumberOrTag::Safe),
+            _ => Param::Text(text.to_string()),
+        }
+    }
+
+    /// The literal token `values::parse_*` expects for this value.
+    fn token(&self) -> Token {
+        match self {
+            Param::Address(address) => Token::SingleQuotedString(address.to_string()),
+            Param::Hash(hash) => Token::SingleQuotedString(hash.to_string()),
+            Param::Number(number) => Token::Number(number.to_string(), false),
+            Param::Bool(true) => Token::make_keyword("TRUE"),
+            Param::Bool(false) => Token::make_keyword("FALSE"),
+            Param::Block(BlockNumberOrTag::Number(number)) => {
+                Token::Number(number.to_string(), false)
+            }
+            // Block tags are bare identifiers, as when written in the query.
+            Param::Block(tag) => Token::make_word(&tag.to_string(), None),
+            Param::Text(text) => Token::SingleQuotedString(text.clone()),
+        }
+    }
+}
+
+impl From<Address> for Param {
+    fn from(address: Address) -> Self {
+        Param::Address(address)
+    }
+}
+
+impl From<B256> for Param {
+    fn from(hash: B256) -> Self {
+        Param::Hash(hash)
+    }
+}
+
+impl From<U256> for Param {
+    fn from(number: U256) -> Self {
+        Param::Number(number)
+    }
+}
+
+impl From<u64> for Param {
+    fn from(number: u64) -> Self {
+        Param::Number(U256::from(number))
+    }
+}
+
+impl From<bool> for Param {
+    fn from(value: bool) -> Self {
+        Param::Bool(value)
+    }
+}
+
+impl From<BlockNumberOrTag> for Param {
+    fn from(block: BlockNumberOrTag) -> Self {
+        Param::Block(block)
+    }
+}
+
+impl From<&str> for Param {
+    fn from(text: &str) -> Self {
+        Param::Text(text.to_string())
+    }
+}
+
+impl From<String> for Param {
+    fn from(text: String) -> Self {
+        Param::Text(text)
+    }
+}
+
+/// Accepts JSON-shaped values: booleans, non-negative integers, and strings,
+/// which are typed with [`Param::from_text`].
+impl<'de> Deserialize<'de> for Param {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ParamVisitor;
+
+        impl Visitor<'_> for ParamVisitor {
+            type Value = Param;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a string, a non-negative integer or a boolean")
+            }
+
+            fn visit_bool<E: de::Error>(self, value: bool) -> Result<Param, E> {
+                Ok(Param::Bool(value))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Param, E> {
+                Ok(Param::from(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Param, E> {
+                u64::try_from(value)
+                    .map(Param::from)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+            }
+
+            // JavaScript numbers arrive as floats.
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Param, E> {
+                if value >= 0.0 && value.fract() == 0.0 && value <= u64::MAX as f64 {
+                    Ok(Param::from(value as u64))
+                } else {
+                    Err(E::invalid_value(de::Unexpected::Float(value), &self))
+                }
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Param, E> {
+                Ok(Param::from_text(value))
+            }
+        }
+
+        deserializer.deserialize_any(ParamVisitor)
+    }
+}
+
+/// The values bound to a query's placeholders.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Params {
+    values: HashMap<Placeholder, Param>,
+}
+
+impl Params {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds `value` to `key`, replacing any earlier value.
+    pub fn set(&mut self, key: impl Into<Placeholder>, value: impl Into<Param>) {
+        self.values.insert(key.into(), value.into());
+    }
+
+    pub fn get(&self, key: &Placeholder) -> Option<&Param> {
+        self.values.get(key)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// A list binds `$1`, `$2`, ... in order; a map binds by key (see
+/// `Placeholder`'s `From<&str>`).
+impl<'de> Deserialize<'de> for Params {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ParamsVisitor;
+
+        impl<'de> Visitor<'de> for ParamsVisitor {
+            type Value = Params;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a list of positional parameters or a map of named ones")
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Params, E> {
+                Ok(Params::new())
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Params, A::Error> {
+                let mut params = Params::new();
+                while let Some(value) = seq.next_element::<Param>()? {
+                    params.set(params.values.len() + 1, value);
+                }
+                Ok(params)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Params, A::Error> {
+                let mut params = Params::new();
+                while let Some((key, value)) = map.next_entry::<String, Param>()? {
+                    params.set(key, value);
+                }
+                Ok(params)
+            }
+        }
+
+        deserializer.deserialize_any(ParamsVisitor)
+    }
+}
+
+/// Replaces every placeholder in `tokens` with its bound value.
+///
+/// A placeholder without a value and a value without a placeholder are both
+/// errors: either one means the query and its bindings have drifted apart.
+pub fn bind(
+    tokens: Vec<TokenWithLocation>,
+    params: &Params,
+) -> Result<Vec<TokenWithLocation>, EqlSqlError> {
+    let mut used = HashSet::new();
+    let mut out = Vec::with_capacity(tokens.len());
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let Some(placeholder) = placeholder(&token.token, &mut tokens)? else {
+            out.push(token);
+            continue;
+        };
+        let value = params.get(&placeholder).ok_or_else(|| {
+            EqlSqlError::Validation(format!("no value is bound to parameter {placeholder}"))
+        })?;
+        out.push(TokenWithLocation {
+            token: value.token(),
+            location: token.location,
+        });
+        used.insert(placeholder);
+    }
+
+    let mut unused: Vec<String> = params
+        .values
+        .keys()
+        .filter(|key| !used.contains(*key))
+        .map(ToString::to_string)
+        .collect();
+    if !unused.is_empty() {
+        unused.sort();
+        return Err(EqlSqlError::Validation(format!(
+            "parameter {} is bound but never used in the query",
+            unused.join(", ")
+        )));
+    }
+    Ok(out)
+}
+
+/// The distinct placeholders in `tokens`, in order of first use.
+pub fn placeholders(tokens: &[TokenWithLocation]) -> Result<Vec<Placeholder>, EqlSqlError> {
+    let mut found = Vec::new();
+    let mut tokens = tokens.iter().cloned().peekable();
+    while let Some(token) = tokens.next() {
+        if let Some(placeholder) = placeholder(&token.token, &mut tokens)? {
+            if !found.contains(&placeholder) {
+                found.push(placeholder);
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// The placeholder `token` starts, if any. `:name` tokenizes as a colon
+/// directly followed by a word, so the word is taken from `rest`.
+fn placeholder(
+    token: &Token,
+    rest: &mut Peekable<impl Iterator<Item = TokenWithLocation>>,
+) -> Result<Option<Placeholder>, EqlSqlError> {
+    match token {
+        Token::Placeholder(text) => positional(text).map(Some),
+        Token::Colon => match rest.peek().map(|t| &t.token) {
+            Some(Token::Word(word)) if word.quote_style.is_none() => {
+                let name = word.value.clone();
+                rest.next();
+                Ok(Some(Placeholder::Name(name)))
+            }
+            _ => Ok(None),
+        },
+        _ => Ok(None),
+    }
+}
+
+/// `$1`, `$2`, ... — the only placeholder tokens EQL accepts besides `:name`.
+fn positional(text: &str) -> Result<Placeholder, EqlSqlError> {
+    match text.strip_prefix('$').map(str::parse::<usize>) {
+        Some(Ok(position)) if position > 0 => Ok(Placeholder::Position(position)),
+        _ => Err(EqlSqlError::NotSupported(format!(
+            "placeholder {text} (use $1, $2, ... or :name)"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::frontend::sql::parse_program_with_params;
+
+    fn params(values: &[(&str, Param)]) -> Params {
+        let mut params = Params::new();
+        for (key, value) in values {
+            params.set(*key, value.clone());
+        }
+        params
+    }
+
+    #[test]
+    fn keys_name_positions_and_names() {
+        assert_eq!(Placeholder::from("$2"), Placeholder::Position(2));
+        assert_eq!(Placeholder::from("2"), Placeholder::Position(2));
+        assert_eq!(Placeholder::from(":addr"), Placeholder::Name("addr".into()));
+        assert_eq!(Placeholder::from("addr"), Placeholder::Name("addr".into()));
+    }
+
+    #[test]
+    fn infers_types_from_text() {
+        let address = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045";
+        assert_eq!(
+            Param::from_text(address),
+            Param::Address(Address::from_str(address).unwrap())
+        );
+        assert!(matches!(
+            Param::from_text(&format!("0x{}", "ab".repeat(32))),
+            Param::Hash(_)
+        ));
+        assert_eq!(Param::from_text("18000000"), Param::from(18_000_000u64));
+        assert_eq!(Param::from_text("TRUE"), Param::Bool(true));
+        assert_eq!(
+            Param::from_text("latest"),
+            Param::Block(BlockNumberOrTag::Latest)
+        );
+        assert_eq!(
+            Param::from_text("vitalik.eth"),
+            Param::Text("vitalik.eth".into())
+        );
+        assert_eq!(Param::from_text("0xabc"), Param::Text("0xabc".into()));
+    }
+
+    #[test]
+    fn binds_positional_and_named_parameters() {
+        let sql = "SELECT * FROM blocks WHERE number BETWEEN $1 AND :to AND chain = $2";
+        let bound = parse_program_with_params(
+            sql,
+            &params(&[
+                ("1", Param::from(1u64)),
+                ("to", Param::from(10u64)),
+                ("2", Param::from("eth")),
+            ]),
+        )
+        .unwrap();
+        let typed = crate::interpreter::frontend::sql::parse_program(
+            "SELECT * FROM blocks WHERE number BETWEEN 1 AND 10 AND chain = eth",
+        )
+        .unwrap();
+        assert_eq!(bound, typed);
+    }
+
+    #[test]
+    fn bound_text_skips_prelex_sugar() {
+        // Typed into the query, `1 ether` would fold into wei and an
+        // unquoted name would be quoted; bound, both stay exactly as given.
+        let err = parse_program_with_params(
+            "SELECT * FROM blocks WHERE number = $1 AND chain = eth",
+            &params(&[("1", Param::from("1 ether"))]),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("'1 ether'"), "{err}");
+
+        let bound = parse_program_with_params(
+            "SELECT balance FROM accounts WHERE address = :who AND chain = eth",
+            &params(&[("who", Param::from("it's.eth"))]),
+        )
+        .unwrap();
+        let typed = crate::interpreter::frontend::sql::parse_program(
+            "SELECT balance FROM accounts WHERE address = 'it''s.eth' AND chain = eth",
+        )
+        .unwrap();
+        assert_eq!(bound, typed);
+    }
+
+    #[test]
+    fn bound_block_tags_stay_identifiers() {
+        let bound = parse_program_with_params(
+            "SELECT number FROM blocks WHERE number = $1 AND chain = eth",
+            &params(&[("1", Param::Block(BlockNumberOrTag::Latest))]),
+        )
+        .unwrap();
+        let typed = crate::interpreter::frontend::sql::parse_program(
+            "SELECT number FROM blocks WHERE number = latest AND chain = eth",
+        )
+        .unwrap();
+        assert_eq!(bound, typed);
+    }
+
+    #[test]
+    fn unbound_and_unused_parameters_are_errors() {
+        let sql = "SELECT number FROM blocks WHERE number = $1 AND chain = eth";
+        let err = parse_program_with_params(sql, &Params::new())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("$1"), "{err}");
+
+        let err = parse_program_with_params(
+            sql,
+            &params(&[("1", Param::from(1u64)), ("extra", Param::from(2u64))]),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains(":extra"), "{err}");
+    }
+
+    #[test]
+    fn rejects_other_placeholder_styles() {
+        let err = parse_program_with_params(
+            "SELECT number FROM blocks WHERE number = ? AND chain = eth",
+            &Params::new(),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("placeholder ?"), "{err}");
+    }
+
+    #[test]
+    fn deserializes_lists_and_maps() {
+        let positional: Params =
+            serde_json::from_str(r#"["vitalik.eth", 18000000, true]"#).unwrap();
+        assert_eq!(
+            positional.get(&Placeholder::Position(1)),
+            Some(&Param::Text("vitalik.eth".into()))
+        );
+        assert_eq!(
+            positional.get(&Placeholder::Position(2)),
+            Some(&Param::from(18_000_000u64))
+        );
+        assert_eq!(
+            positional.get(&Placeholder::Position(3)),
+            Some(&Param::Bool(true))
+        );
+
+        let named: Params = serde_json::from_str(r#"{":addr": "vitalik.eth", "2": 7}"#).unwrap();
+        assert_eq!(
+            named.get(&Placeholder::Name("addr".into())),
+            Some(&Param::Text("vitalik.eth".into()))
+        );
+        assert_eq!(
+            named.get(&Placeholder::Position(2)),
+            Some(&Param::from(7u64))
+        );
+    }
+}
@@ -67,7 +67,11 @@ fn is_hex(w: &str) -> bool {
 
 fn is_ens(w: &str) -> bool {
     let mut segs = w.split('.').collect::<Vec<_>>();
-    if segs.len() < 2 || segs.pop() != Some("eth") {
+    if segs.len() < 2
+        || !segs
+            .pop()
+            .is_some_and(|tld| tld.eq_ignore_ascii_case("eth"))
+    {
         return false;
     }
     segs.iter()
@@ -111,6 +115,10 @@ fn fold_unit(number: &str, pow: u32) -> Result<String, EqlSqlError> {
     })
 }
 
+fn is_placeholder_sigil(tok: &Tok) -> bool {
+    matches!(tok, Tok::Other(o) if o == "$" || o == ":")
+}
+
 pub fn prelex(input: &str) -> Result<String, EqlSqlError> {
     let toks = tokenize(input);
     let mut out = String::new();
@@ -118,6 +126,8 @@ pub fn prelex(input: &str) -> Result<String, EqlSqlError> {
     while i < toks.len() {
         match &toks[i] {
             Tok::Quoted(s) | Tok::Other(s) => out.push_str(s),
+            // A placeholder name (`$1`, `:addr`) is never a value to rewrite.
+            Tok::Word(w) if i > 0 && is_placeholder_sigil(&toks[i - 1]) => out.push_str(w),
             Tok::Word(w) => {
                 // number followed by a unit word (skipping whitespace-only Others)?
                 if is_number(w) {
@@ -176,6 +186,10 @@ mod tests {
             prelex("address = sub.vitalik.eth").unwrap(),
             "address = 'sub.vitalik.eth'"
         );
+        assert_eq!(
+            prelex("address = Vitalik.ETH").unwrap(),
+            "address = 'Vitalik.ETH'"
+        );
     }
 
     #[test]
@@ -220,6 +234,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn leaves_placeholders_alone() {
+        assert_eq!(
+            prelex("value > $1 ether AND address = :vitalik").unwrap(),
+            "value > $1 ether AND address = :vitalik"
+        );
+    }
+
     #[test]
     fn hex_inside_in_list() {
         assert_eq!(
@@ -1,7 +1,23 @@
 use super::EqlSqlError;
 use crate::common::{
-    account::AccountField, block::BlockField, logs::LogField, transaction::TransactionField,
+    account::AccountField,
+    authorization::AuthorizationField,
+    block::BlockField,
+    call::{argument_columns, output_columns, CallField},
+    chain::Chain,
+    contract::ContractField,
+    ens::EnsField,
+    logs::LogField,
+    query_result::ExpressionResult,
+    serializer::to_record_batch,
+    state_diff::StateDiffField,
+    storage::StorageField,
+    token_balance::TokenBalanceField,
+    transaction::TransactionField,
+    withdrawal::WithdrawalField,
 };
+use alloy::json_abi::Function;
+use arrow::datatypes::SchemaRef;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum EntityKind {
@@ -9,6 +25,124 @@ pub enum EntityKind {
     Blocks,
     Transactions,
     Logs,
+    Storage,
+    TokenBalances,
+    Call,
+    Withdrawals,
+    Authorizations,
+    Contracts,
+    StateDiffs,
+    Ens,
+}
+
+impl EntityKind {
+    pub const ALL: [EntityKind; 12] = [
+        EntityKind::Accounts,
+        EntityKind::Blocks,
+        EntityKind::Transactions,
+        EntityKind::Logs,
+        EntityKind::Storage,
+        EntityKind::TokenBalances,
+        EntityKind::Call,
+        EntityKind::Withdrawals,
+        EntityKind::Authorizations,
+        EntityKind::Contracts,
+        EntityKind::StateDiffs,
+        EntityKind::Ens,
+    ];
+
+    /// The canonical (plural) table name, as written after `FROM`.
+    pub fn table_name(&self) -> &'static str {
+        match self {
+            EntityKind::Accounts => "accounts",
+            EntityKind::Blocks => "blocks",
+            EntityKind::Transactions => "transactions",
+            EntityKind::Logs => "logs",
+            EntityKind::Storage => "storage",
+            EntityKind::TokenBalances => "token_balances",
+            EntityKind::Call => "call",
+            EntityKind::Withdrawals => "withdrawals",
+            EntityKind::Authorizations => "authorizations",
+            EntityKind::Contracts => "contracts",
+            EntityKind::StateDiffs => "state_diffs",
+            EntityKind::Ens => "ens",
+        }
+    }
+
+    /// The parameters a table function takes by position, in order, or
+    /// `None` for a plain table. `FROM f(a, b => c)` reads the same as
+    /// `FROM f WHERE <first parameter> = a AND b = c`.
+    pub fn parameters(&self) -> Option<&'static [&'static str]> {
+        match self {
+            EntityKind::Accounts
+            | EntityKind::Blocks
+            | EntityKind::Transactions
+            | EntityKind::Logs
+            | EntityKind::Storage
+            | EntityKind::Withdrawals
+            | EntityKind::Authorizations
+            | EntityKind::Contracts
+            | EntityKind::StateDiffs
+            | EntityKind::Ens => None,
+            EntityKind::TokenBalances => Some(&["holder", "token"]),
+            // Inputs past the eighth can still be passed by name.
+            EntityKind::Call => Some(&[
+                "address",
+                "signature",
+                "arg0",
+                "arg1",
+                "arg2",
+                "arg3",
+                "arg4",
+                "arg5",
+                "arg6",
+                "arg7",
+            ]),
+        }
+    }
+
+    /// The chain the entity's data lives on whatever chain is asked for,
+    /// assumed when a query names none. ENS lives on Ethereum; its
+    /// Sepolia deployment can still be asked for with `chain = sepolia`.
+    pub fn home_chain(&self) -> Option<Chain> {
+        match self {
+            EntityKind::Ens => Some(Chain::Ethereum),
+            EntityKind::Accounts
+            | EntityKind::Blocks
+            | EntityKind::Transactions
+            | EntityKind::Logs
+            | EntityKind::Storage
+            | EntityKind::TokenBalances
+            | EntityKind::Call
+            | EntityKind::Withdrawals
+            | EntityKind::Authorizations
+            | EntityKind::Contracts
+            | EntityKind::StateDiffs => None,
+        }
+    }
+
+    /// The full typed Arrow schema of the entity's table — every column a
+    /// `SELECT *` can return, typed as the Parquet/Arrow serializer types it.
+    pub fn arrow_schema(&self) -> SchemaRef {
+        let empty = match self {
+            EntityKind::Accounts => ExpressionResult::Account(vec![]),
+            EntityKind::Blocks => ExpressionResult::Block(vec![]),
+            EntityKind::Transactions => ExpressionResult::Transaction(vec![]),
+            EntityKind::Logs => ExpressionResult::Log(vec![]),
+            EntityKind::Storage => ExpressionResult::Storage(vec![]),
+            EntityKind::TokenBalances => ExpressionResult::TokenBalance(vec![]),
+            EntityKind::Call => ExpressionResult::Call(vec![]),
+            EntityKind::Withdrawals => ExpressionResult::Withdrawal(vec![]),
+            EntityKind::Authorizations => ExpressionResult::Authorization(vec![]),
+            EntityKind::Contracts => ExpressionResult::Contract(vec![]),
+            EntityKind::StateDiffs => ExpressionResult::StateDiff(vec![]),
+            EntityKind::Ens => ExpressionResult::Ens(vec![]),
+        };
+        // Zero rows always fit every column type, so this can't fail.
+        to_record_batch(&empty)
+            .expect("an empty result always yields a schema")
+            .schema()
+    }
 }
 
 pub fn resolve_entity(name: &str) -> Result<EntityKind, EqlSqlError> {
@@ -17,12 +151,25 @@ pub fn resolve_entity(name: &str) -> Result<EntityKind, EqlSqlError> {
         "blocks" => Ok(EntityKind::Blocks),
         "transactions" | "tx" => Ok(EntityKind::Transactions),
         "logs" => Ok(EntityKind::Logs),
+        "storage" => Ok(EntityKind::Storage),
+        "token_balances" => Ok(EntityKind::TokenBalances),
+        "call" => Ok(EntityKind::Call),
+        "withdrawals" => Ok(EntityKind::Withdrawals),
+        "authorizations" => Ok(EntityKind::Authorizations),
+        "contracts" => Ok(EntityKind::Contracts),
+        "state_diffs" => Ok(EntityKind::StateDiffs),
+        "ens" => Ok(EntityKind::Ens),
         "account" => Err(unknown_entity(name, "accounts")),
         "block" => Err(unknown_entity(name, "blocks")),
         "transaction" | "txs" => Err(unknown_entity(name, "transactions")),
         "log" => Err(unknown_entity(name, "logs")),
+        "withdrawal" => Err(unknown_entity(name, "withdrawals")),
+        "authorization" => Err(unknown_entity(name, "authorizations")),
+        "contract" | "deployments" => Err(unknown_entity(name, "contracts")),
+        "state_diff" | "statediffs" | "diffs" => Err(unknown_entity(name, "state_diffs")),
+        "ens_names" | "ens_records" | "names" => Err(unknown_entity(name, "ens")),
         _ => Err(EqlSqlError::Validation(format!(
-            "unknown entity '{name}'; expected accounts, blocks, transactions (tx) or logs"
+            "unknown entity '{name}'; expected accounts, blocks, transactions (tx), logs, storage, token_balances, call, withdrawals, authorizations, contracts, state_diffs or ens"
         ))),
     }
 }
@@ -57,6 +204,56 @@ pub fn resolve_log_field(name: &str) -> Result<LogField, EqlSqlError> {
     LogField::try_from(name.to_ascii_lowercase().as_str()).map_err(|_| unknown_field("logs", name))
 }
 
+pub fn resolve_storage_field(name: &str) -> Result<StorageField, EqlSqlError> {
+    StorageField::try_from(name.to_ascii_lowercase().as_str())
+        .map_err(|_| unknown_field("storage", name))
+}
+
+pub fn resolve_token_balance_field(name: &str) -> Result<TokenBalanceField, EqlSqlError> {
+    TokenBalanceField::try_from(name.to_ascii_lowercase().as_str())
+        .map_err(|_| unknown_field("token_balances", name))
+}
+
+pub fn resolve_withdrawal_field(name: &str) -> Result<WithdrawalField, EqlSqlError> {
+    WithdrawalField::try_from(name.to_ascii_lowercase().as_str())
+        .map_err(|_| unknown_field("withdrawals", name))
+}
+
+pub fn resolve_authorization_field(name: &str) -> Result<AuthorizationField, EqlSqlError> {
+    AuthorizationField::try_from(name.to_ascii_lowercase().as_str())
+        .map_err(|_| unknown_field("authorizations", name))
+}
+
+pub fn resolve_contract_field(name: &str) -> Result<ContractField, EqlSqlError> {
+    ContractField::try_from(name.to_ascii_lowercase().as_str())
+        .map_err(|_| unknown_field("contracts", name))
+}
+
+pub fn resolve_state_diff_field(name: &str) -> Result<StateDiffField, EqlSqlError> {
+    StateDiffField::try_from(name.to_ascii_lowercase().as_str())
+        .map_err(|_| unknown_field("state_diffs", name))
+}
+
+pub fn resolve_ens_field(name: &str) -> Result<EnsField, EqlSqlError> {
+    EnsField::try_from(name.to_ascii_lowercase().as_str()).map_err(|_| unknown_field("ens", name))
+}
+
+/// A column of a call to `function`: a fixed one, or an argument or output
+/// by the name `argument_columns`/`output_columns` give it.
+pub fn resolve_call_field(function: &Function, name: &str) -> Result<CallField, EqlSqlError> {
+    let name = name.to_ascii_lowercase();
+    let position = |columns: Vec<String>| columns.iter().position(|c| *c == name);
+    match name.as_str() {
+        "address" => Ok(CallField::Address),
+        "block_number" => Ok(CallField::BlockNumber),
+        "chain" => Ok(CallField::Chain),
+        _ => position(argument_columns(function))
+            .map(CallField::Argument)
+            .or_else(|| position(output_columns(function)).map(CallField::Output))
+            .ok_or_else(|| unknown_field(&format!("call to {}", function.signature()), &name)),
+    }
+}
+
 fn unknown_field(entity: &str, field: &str) -> EqlSqlError {
     EqlSqlError::Validation(format!("unknown field '{field}' on {entity}"))
 }
@@ -77,6 +274,22 @@ mod tests {
         assert_eq!(resolve_entity("blocks").unwrap(), EntityKind::Blocks);
     }
 
+    #[test]
+    fn every_entity_resolves_by_its_table_name() {
+        for kind in EntityKind::ALL {
+            assert_eq!(resolve_entity(kind.table_name()).unwrap(), kind);
+        }
+    }
+
+    #[test]
+    fn arrow_schema_covers_every_column() {
+        let schema = EntityKind::Transactions.arrow_schema();
+        assert!(schema.field_with_name("from_address").is_ok());
+        assert!(schema.field_with_name("chain").is_ok());
+        let schema = EntityKind::Logs.arrow_schema();
+        assert!(schema.field_with_name("topic0").is_ok());
+    }
+
     #[test]
     fn singular_names_get_a_hint() {
         let err = resolve_entity("account").unwrap_err().to_string();
@@ -131,4 +344,92 @@ mod tests {
             assert_eq!(&resolve_log_field(&field.to_string()).unwrap(), field);
         }
     }
+
+    #[test]
+    fn resolves_every_storage_field_by_its_display_name() {
+        for field in StorageField::all_variants() {
+            assert_eq!(&resolve_storage_field(&field.to_string()).unwrap(), field);
+        }
+    }
+
+    #[test]
+    fn resolves_every_token_balance_field_by_its_display_name() {
+        for field in TokenBalanceField::all_variants() {
+            assert_eq!(
+                &resolve_token_balance_field(&field.to_string()).unwrap(),
+                field
+            );
+        }
+    }
+
+    #[test]
+    fn resolves_every_withdrawal_field_by_its_display_name() {
+        for field in WithdrawalField::all_variants() {
+            assert_eq!(
+                &resolve_withdrawal_field(&field.to_string()).unwrap(),
+                field
+            );
+        }
+    }
+
+    #[test]
+    fn resolves_every_authorization_field_by_its_display_name() {
+        for field in AuthorizationField::all_variants() {
+            assert_eq!(
+                &resolve_authorization_field(&field.to_string()).unwrap(),
+                field
+            );
+        }
+    }
+
+    #[test]
+    fn resolves_every_contract_field_by_its_display_name() {
+        for field in ContractField::all_variants() {
+            assert_eq!(&resolve_contract_field(&field.to_string()).unwrap(), field);
+        }
+    }
+
+    #[test]
+    fn resolves_every_ens_field_by_its_display_name() {
+        for field in EnsField::all_variants() {
+            assert_eq!(&resolve_ens_field(&field.to_string()).unwrap(), field);
+        }
+    }
+
+    #[test]
+    fn resolves_every_state_diff_field_by_its_display_name() {
+        for field in StateDiffField::all_variants() {
+            assert_eq!(
+                &resolve_state_diff_field(&field.to_string()).unwrap(),
+                field
+            );
+        }
+    }
+
+    #[test]
+    fn resolves_every_call_field_by_its_column_name() {
+        use crate::common::call::{call_fields, CALL_COLUMNS};
+        let function = Function::parse("quote(uint256 amount, address)(uint256 out)").unwrap();
+        let columns = argument_columns(&function)
+            .into_iter()
+            .chain(output_columns(&function));
+        let names: Vec<_> = CALL_COLUMNS
+            .iter()
+            .map(|c| c.to_string())
+            .chain(columns)
+            .collect();
+        assert_eq!(names.len(), call_fields(&function).len());
+        for name in &names {
+            assert!(resolve_call_field(&function, name).is_ok(), "{name}");
+        }
+        assert_eq!(
+            resolve_call_field(&function, "ARG1").unwrap(),
+            CallField::Argument(1)
+        );
+        assert_eq!(
+            resolve_call_field(&function, "out").unwrap(),
+            CallField::Output(0)
+        );
+        assert!(resolve_call_field(&function, "output0").is_err());
+    }
 }
@@ -1,9 +1,11 @@
 //! Translates a parsed SQL `Statement` into the existing `Expression` /
 //! `GetExpression` / `Entity` structs the backend already executes.
 //!
-//! Covers all four entities (`accounts`, `blocks`, `transactions`/`tx`,
-//! `logs`). This module also owns every statement-level rejection that
-//! `where_clause` can't see.
+//! Covers every table in `EntityKind::ALL`, plus the catalog statements
+//! that describe them (`SHOW TABLES`, `DESCRIBE <entity>`, `SHOW COLUMNS
+//! FROM <entity>` and the `information_schema.tables`/`columns` views) and
+//! `EXPLAIN [ANALYZE]` of a query. This module also owns every
+//! statement-level rejection that `where_clause` can't see.
 //!
 //! `query_to_get` and `validate_select_shape` destructure `sqlparser`'s
 //! `Query` and `Select` structs field-by-field, with no `..` catch-all. Each
@@ -27,20 +29,33 @@ use super::{
     EqlSqlError,
 };
 use crate::common::{
-    account::{Account, AccountField},
-    block::{Block, BlockField, BlockId, BlockRange},
-    chain::Chain,
+    account::{Account, AccountField, AccountFilter},
+    authorization::{AuthorizationField, Authorizations},
+    block::{Block, BlockField, BlockFilter, BlockId, BlockRange},
+    call::{argument_columns, call_fields, Call},
+    chain::{Chain, ChainOrRpc},
+    contract::{ContractField, Contracts},
     dump::{Dump, DumpFormat},
-    ens::NameOrAddress,
+    ens::{Ens, EnsField},
     entity::Entity,
     filters::{ComparisonFilter, EqualityFilter, FilterType},
     logs::{LogField, LogFilter, Logs},
+    state_diff::{StateDiffField, StateDiffKind, StateDiffs},
+    storage::{Storage, StorageField},
+    token_balance::{TokenBalanceField, TokenBalances},
     transaction::{Transaction, TransactionField, TransactionFilter},
-    types::{Expression, GetExpression, SetRpcExpression},
+    types::{
+        CatalogExpression, CatalogTarget, ExplainExpression, Expression, GetExpression,
+        SetRpcExpression,
+    },
+    withdrawal::{WithdrawalField, Withdrawals},
 };
+use alloy::dyn_abi::Specifier;
 use alloy::transports::http::reqwest::Url;
 use sqlparser::ast::{
-    CopySource, CopyTarget, Expr, Select, SelectItem, SetExpr, Statement, TableFactor,
+    AnalyzeFormat, CopySource, CopyTarget, DescribeAlias, Expr, HiveDescribeFormat, Ident,
+    ObjectName, Select, SelectItem, SetExpr, ShowClause, ShowStatementFilter, Statement,
+    TableFactor, TableFunctionArgs, UtilityOption,
 };
 use std::collections::HashMap;
 use std::fmt::Display;
@@ -78,6 +93,46 @@ pub fn statement_to_expression(stmt: &Statement) -> Result<Expression, EqlSqlErr
             variables,
             value,
         } => set_variable_to_expression(*local, *hivevar, variables, value),
+        // Exhaustive over every field of the three catalog statements — see
+        // `show_tables_to_expression` and friends.
+        Statement::ShowTables {
+            extended,
+            full,
+            clause,
+            db_name,
+            filter,
+        } => show_tables_to_expression(*extended, *full, clause, db_name, filter),
+        Statement::ShowColumns {
+            extended,
+            full,
+            table_name,
+            filter,
+        } => show_columns_to_expression(*extended, *full, table_name, filter),
+        Statement::ExplainTable {
+            describe_alias,
+            hive_format,
+            has_table_keyword,
+            table_name,
+        } => describe_to_expression(describe_alias, hive_format, *has_table_keyword, table_name),
+        // Exhaustive over every `Statement::Explain` field — see
+        // `explain_to_expression`.
+        Statement::Explain {
+            describe_alias,
+            analyze,
+            verbose,
+            query_plan,
+            statement,
+            format,
+            options,
+        } => explain_to_expression(
+            describe_alias,
+            *analyze,
+            *verbose,
+            *query_plan,
+            statement,
+            format,
+            options,
+        ),
         other => Err(EqlSqlError::NotSupported(format!("statement {other}"))),
     }
 }
@@ -207,6 +262,206 @@ fn variables_single_name(
     }
 }
 
+/// `SHOW TABLES`: one row per entity. The MySQL/Snowflake modifiers
+/// (`EXTENDED`, `FULL`, `FROM <db>`, `LIKE '<pattern>'`) are rejected by
+/// name — there is one flat namespace of tables (`EntityKind::ALL`) to list.
+fn show_tables_to_expression(
+    extended: bool,
+    full: bool,
+    clause: &Option<ShowClause>,
+    db_name: &Option<Ident>,
+    filter: &Option<ShowStatementFilter>,
+) -> Result<Expression, EqlSqlError> {
+    if extended {
+        return Err(EqlSqlError::NotSupported("SHOW EXTENDED TABLES".into()));
+    }
+    if full {
+        return Err(EqlSqlError::NotSupported("SHOW FULL TABLES".into()));
+    }
+    if let Some(db_name) = db_name {
+        let clause = clause
+            .as_ref()
+            .map_or("FROM".to_string(), ToString::to_string);
+        return Err(EqlSqlError::NotSupported(format!(
+            "SHOW TABLES {clause} {db_name}"
+        )));
+    }
+    if let Some(filter) = filter {
+        return Err(EqlSqlError::NotSupported(format!("SHOW TABLES {filter}")));
+    }
+    Ok(Expression::Catalog(CatalogExpression {
+        target: CatalogTarget::Tables,
+        limit: None,
+    }))
+}
+
+/// `SHOW COLUMNS FROM <entity>` — the MySQL spelling of `DESCRIBE`.
+fn show_columns_to_expression(
+    extended: bool,
+    full: bool,
+    table_name: &ObjectName,
+    filter: &Option<ShowStatementFilter>,
+) -> Result<Expression, EqlSqlError> {
+    if extended {
+        return Err(EqlSqlError::NotSupported("SHOW EXTENDED COLUMNS".into()));
+    }
+    if full {
+        return Err(EqlSqlError::NotSupported("SHOW FULL COLUMNS".into()));
+    }
+    if let Some(filter) = filter {
+        return Err(EqlSqlError::NotSupported(format!(
+            "SHOW COLUMNS FROM {table_name} {filter}"
+        )));
+    }
+    describe_entity(table_name)
+}
+
+/// `DESCRIBE <entity>` / `DESC <entity>`. `EXPLAIN <entity>` parses to the
+/// same statement but means something else everywhere it's accepted, so it
+/// is rejected rather than treated as a synonym; `DESCRIBE TABLE <entity>`
+/// reads the same as `DESCRIBE <entity>` and is accepted.
+fn describe_to_expression(
+    describe_alias: &DescribeAlias,
+    hive_format: &Option<HiveDescribeFormat>,
+    _has_table_keyword: bool,
+    table_name: &ObjectName,
+) -> Result<Expression, EqlSqlError> {
+    if let DescribeAlias::Explain = describe_alias {
+        return Err(EqlSqlError::NotSupported(format!(
+            "EXPLAIN {table_name} (use DESCRIBE {table_name})"
+        )));
+    }
+    if let Some(hive_format) = hive_format {
+        return Err(EqlSqlError::NotSupported(format!(
+            "{describe_alias} {hive_format}"
+        )));
+    }
+    describe_entity(table_name)
+}
+
+fn describe_entity(table_name: &ObjectName) -> Result<Expression, EqlSqlError> {
+    let kind = schema::resolve_entity(&object_name(table_name))?;
+    Ok(Expression::Catalog(CatalogExpression {
+        target: CatalogTarget::Columns(vec![kind]),
+        limit: None,
+    }))
+}
+
+/// `EXPLAIN [ANALYZE] SELECT …`. Only the two forms the backend can report
+/// on are accepted; the dialect-specific modifiers (`VERBOSE`,
+/// `QUERY PLAN`, `FORMAT …`, Postgres-style `(option, …)` lists) and
+/// `DESCRIBE SELECT …` (which means "describe the result columns" in
+/// DuckDB) are rejected by name.
+fn explain_to_expression(
+    describe_alias: &DescribeAlias,
+    analyze: bool,
+    verbose: bool,
+    query_plan: bool,
+    statement: &Statement,
+    format: &Option<AnalyzeFormat>,
+    options: &Option<Vec<UtilityOption>>,
+) -> Result<Expression, EqlSqlError> {
+    if !matches!(describe_alias, DescribeAlias::Explain) {
+        return Err(EqlSqlError::NotSupported(format!(
+            "{describe_alias} of a query (use EXPLAIN)"
+        )));
+    }
+    if verbose {
+        return Err(EqlSqlError::NotSupported("EXPLAIN VERBOSE".into()));
+    }
+    if query_plan {
+        return Err(EqlSqlError::NotSupported("EXPLAIN QUERY PLAN".into()));
+    }
+    if let Some(format) = format {
+        return Err(EqlSqlError::NotSupported(format!(
+            "EXPLAIN FORMAT {format}"
+        )));
+    }
+    if let Some(options) = options {
+        return Err(EqlSqlError::NotSupported(format!(
+            "EXPLAIN ({})",
+            joined(options)
+        )));
+    }
+    let query = match statement {
+        Statement::Query(query) => query,
+        other => return Err(EqlSqlError::NotSupported(format!("EXPLAIN {other}"))),
+    };
+    match query_to_get(query, None)? {
+        Expression::Get(get) => Ok(Expression::Explain(ExplainExpression { get, analyze })),
+        // `information_schema` views are answered locally; there is no
+        // fetch to explain.
+        _ => Err(EqlSqlError::NotSupported(format!(
+            "EXPLAIN of a catalog query ({query})"
+        ))),
+    }
+}
+
+/// `SELECT * FROM information_schema.tables` / `information_schema.columns`.
+/// `columns` can be narrowed with `table_name = '<entity>'` or
+/// `table_name IN (...)` (entity aliases such as `tx` resolve as they do after
+/// `FROM`); any other projection or predicate is rejected.
+fn information_schema_query(
+    view: &str,
+    select: &Select,
+    limit: Option<usize>,
+    dump: Option<Dump>,
+) -> Result<Expression, EqlSqlError> {
+    if dump.is_some() {
+        return Err(EqlSqlError::NotSupported(format!(
+            "COPY from information_schema.{view}"
+        )));
+    }
+    let (field_names, aliases) = projection(select)?;
+    if field_names != ["*"] || !aliases.is_empty() {
+        return Err(EqlSqlError::NotSupported(format!(
+            "information_schema.{view} only supports SELECT *"
+        )));
+    }
+    let conds = where_clause::split_conditions(select.selection.as_ref())?;
+    let target = match view {
+        "tables" => {
+            if let Some(cond) = conds.first() {
+                return Err(EqlSqlError::NotSupported(format!(
+                    "WHERE {} on information_schema.tables",
+                    cond.column
+                )));
+            }
+            CatalogTarget::Tables
+        }
+        "columns" => {
+            let mut kinds = Vec::new();
+            for cond in conds {
+                if cond.column != "table_name" {
+                    return Err(EqlSqlError::NotSupported(format!(
+                        "WHERE {} on information_schema.columns (only table_name)",
+                        cond.column
+                    )));
+                }
+                if !matches!(cond.op, CondOp::Eq | CondOp::In) {
+                    return Err(EqlSqlError::NotSupported(format!(
+                        "table_name {} (only = and IN)",
+                        op_text(cond.op)
+                    )));
+                }
+                for value in &cond.values {
+                    let kind = schema::resolve_entity(&values::expr_as_string(value)?)?;
+                    if !kinds.contains(&kind) {
+                        kinds.push(kind);
+                    }
+                }
+            }
+            CatalogTarget::Columns(kinds)
+        }
+        other => {
+            return Err(EqlSqlError::NotSupported(format!(
+                "information_schema.{other} (only tables and columns)"
+            )))
+        }
+    };
+    Ok(Expression::Catalog(CatalogExpression { target, limit }))
+}
+
 pub(super) fn query_to_get(
     query: &sqlparser::ast::Query,
     dump: Option<crate::common::dump::Dump>,
@@ -288,17 +543,36 @@ pub(super) fn query_to_get(
     validate_select_shape(select)?;
 
     let entity_name = table_name(select)?;
+    if let Some(view) = entity_name.strip_prefix("information_schema.") {
+        return information_schema_query(view, select, limit, dump);
+    }
     let kind = schema::resolve_entity(&entity_name)?;
     let (field_names, aliases) = projection(select)?;
 
     let mut conds = where_clause::split_conditions(select.selection.as_ref())?;
-    let chains = where_clause::extract_chains(&mut conds)?;
+    if let Some(args) = table_function_args(select) {
+        conds.splice(0..0, function_conditions(kind, args)?);
+    }
+    let chains = match kind.home_chain() {
+        Some(chain) if !conds.iter().any(|c| c.column == "chain") => {
+            vec![ChainOrRpc::Chain(chain)]
+        }
+        _ => where_clause::extract_chains(&mut conds)?,
+    };
 
     let entity = match kind {
         EntityKind::Accounts => build_account(&field_names, conds)?,
         EntityKind::Blocks => build_block(&field_names, conds)?,
         EntityKind::Transactions => build_transaction(&field_names, conds)?,
         EntityKind::Logs => build_logs(&field_names, conds)?,
+        EntityKind::Storage => build_storage(&field_names, conds)?,
+        EntityKind::TokenBalances => build_token_balances(&field_names, conds)?,
+        EntityKind::Call => build_call(&field_names, conds)?,
+        EntityKind::Withdrawals => build_withdrawals(&field_names, conds)?,
+        EntityKind::Authorizations => build_authorizations(&field_names, conds)?,
+        EntityKind::Contracts => build_contracts(&field_names, conds)?,
+        EntityKind::StateDiffs => build_state_diffs(&field_names, conds)?,
+        EntityKind::Ens => build_ens(&field_names, conds)?,
     };
 
     Ok(Expression::Get(GetExpression {
@@ -421,17 +695,52 @@ fn validate_select_shape(select: &Select) -> Result<(), EqlSqlError> {
 
 fn table_name(select: &Select) -> Result<String, EqlSqlError> {
     match &select.from[0].relation {
-        TableFactor::Table { name, .. } => Ok(name
-            .0
-            .iter()
-            .map(|ident| ident.to_string())
-            .collect::<Vec<_>>()
-            .join(".")
-            .to_ascii_lowercase()),
+        TableFactor::Table { name, .. } => Ok(object_name(name)),
         other => Err(EqlSqlError::NotSupported(format!("FROM {other}"))),
     }
 }
 
+/// The arguments of `FROM f(...)`, if the table is called like a function.
+fn table_function_args(select: &Select) -> Option<&TableFunctionArgs> {
+    match &select.from[0].relation {
+        TableFactor::Table { args, .. } => args.as_ref(),
+        _ => None,
+    }
+}
+
+/// The conditions a table function's arguments stand for (see
+/// `where_clause::argument_conditions`). Plain tables take no arguments.
+fn function_conditions(
+    kind: EntityKind,
+    args: &TableFunctionArgs,
+) -> Result<Vec<Condition>, EqlSqlError> {
+    let name = kind.table_name();
+    let Some(params) = kind.parameters() else {
+        return Err(EqlSqlError::NotSupported(format!(
+            "arguments to {name} ({name} is a table, not a table function)"
+        )));
+    };
+    let TableFunctionArgs { args, settings } = args;
+    // ClickHouse-only syntax; unreachable under `DuckDbDialect` today.
+    if let Some(settings) = settings {
+        return Err(EqlSqlError::NotSupported(format!(
+            "SETTINGS {}",
+            joined(settings)
+        )));
+    }
+    where_clause::argument_conditions(name, params, args)
+}
+
+/// A possibly-qualified table name, lower-cased and dot-joined.
+fn object_name(name: &ObjectName) -> String {
+    name.0
+        .iter()
+        .map(|ident| ident.to_string())
+        .collect::<Vec<_>>()
+        .join(".")
+        .to_ascii_lowercase()
+}
+
 /// Returns (field names in canonical spelling or ["*"], alias map keyed by canonical field name).
 fn projection(select: &Select) -> Result<(Vec<String>, HashMap<String, String>), EqlSqlError> {
     let mut names = Vec::new();
@@ -462,7 +771,7 @@ fn projection(select: &Select) -> Result<(Vec<String>, HashMap<String, String>),
 
 fn build_account(fields: &[String], conds: Vec<Condition>) -> Result<Entity, EqlSqlError> {
     let fields = if fields == ["*"] {
-        AccountField::all_variants().to_vec()
+        AccountField::wildcard()
     } else {
         fields
             .iter()
@@ -477,6 +786,10 @@ fn build_account(fields: &[String], conds: Vec<Condition>) -> Result<Entity, Eql
     // rather than silently unioned. `IN (...)` is the sanctioned way to
     // match several addresses.
     let mut address_seen = false;
+    // `block_number` selects the blocks to read state at, so like
+    // `blocks.number` its conditions union: `IN` and `BETWEEN` give a time
+    // series, one row per address per block.
+    let mut blocks = Vec::new();
     for cond in conds {
         match (cond.column.as_str(), cond.op) {
             ("address", CondOp::Eq) | ("address", CondOp::In) => {
@@ -490,9 +803,22 @@ fn build_account(fields: &[String], conds: Vec<Condition>) -> Result<Entity, Eql
                     ids.push(values::parse_name_or_address(value)?);
                 }
             }
+            ("block_number", CondOp::Eq) | ("block_number", CondOp::In) => {
+                for value in &cond.values {
+                    blocks.push(AccountFilter::BlockNumber(BlockId::Number(
+                        values::parse_block_number_or_tag(value)?,
+                    )));
+                }
+            }
+            ("block_number", CondOp::Between) => {
+                blocks.push(AccountFilter::BlockNumber(BlockId::Range(BlockRange::new(
+                    values::parse_block_number_or_tag(&cond.values[0])?,
+                    Some(values::parse_block_number_or_tag(&cond.values[1])?),
+                ))));
+            }
             (col, _) => {
                 return Err(EqlSqlError::NotSupported(format!(
-                    "filter on accounts.{col} (only address = / IN)"
+                    "filter on accounts.{col} (only address = / IN and block_number =, IN, BETWEEN)"
                 )))
             }
         }
@@ -502,7 +828,8 @@ fn build_account(fields: &[String], conds: Vec<Condition>) -> Result<Entity, Eql
             "accounts queries need an address predicate (= or IN)".into(),
         ));
     }
-    Ok(Entity::Account(Account::new(Some(ids), None, fields)))
+    let filter = (!blocks.is_empty()).then_some(blocks);
+    Ok(Entity::Account(Account::new(Some(ids), filter, fields)))
 }
 
 // Unlike `accounts.address`/`transactions.hash`/`chain` (Fix 1: see
@@ -524,6 +851,7 @@ fn build_block(fields: &[String], conds: Vec<Condition>) -> Result<Entity, EqlSq
             .collect::<Result<Vec<_>, _>>()?
     };
     let mut ids = Vec::new();
+    let mut filters = Vec::new();
     for cond in conds {
         match (cond.column.as_str(), cond.op) {
             ("number", CondOp::Eq) | ("number", CondOp::In) => {
@@ -537,9 +865,30 @@ fn build_block(fields: &[String], conds: Vec<Condition>) -> Result<Entity, EqlSq
                     Some(values::parse_block_number_or_tag(&cond.values[1])?),
                 )));
             }
+            ("miner", op) => filters.push(BlockFilter::Miner(eq_only(
+                op,
+                values::parse_name_or_address(&cond.values[0])?,
+                "miner",
+            )?)),
+            // Blocks are still fetched by number; a timestamp only narrows
+            // them, so `BETWEEN` is just its two bounds.
+            ("timestamp", CondOp::Between) => {
+                filters.push(BlockFilter::Timestamp(FilterType::Comparison(
+                    ComparisonFilter::Gte(values::parse_u64(&cond.values[0])?),
+                )));
+                filters.push(BlockFilter::Timestamp(FilterType::Comparison(
+                    ComparisonFilter::Lte(values::parse_u64(&cond.values[1])?),
+                )));
+            }
+            ("timestamp", op) => filters.push(BlockFilter::Timestamp(cmp_filter(
+                op,
+                values::parse_u64(&cond.values[0])?,
+                "timestamp",
+            )?)),
             (col, _) => {
                 return Err(EqlSqlError::NotSupported(format!(
-                    "filter on blocks.{col} (only number =, IN, BETWEEN)"
+                    "filter on blocks.{col} (only number =, IN, BETWEEN, miner = \
+                     and timestamp comparisons)"
                 )))
             }
         }
@@ -549,7 +898,15 @@ fn build_block(fields: &[String], conds: Vec<Condition>) -> Result<Entity, EqlSq
             "blocks queries need a number predicate (=, IN or BETWEEN)".into(),
         ));
     }
-    Ok(Entity::Block(Block::new(Some(ids), None, fields)))
+    Ok(Entity::Block(Block::new(
+        Some(ids),
+        if filters.is_empty() {
+            None
+        } else {
+            Some(filters)
+        },
+        fields,
+    )))
 }
 
 /// Renders a `CondOp` the way the user wrote it, for error messages that
@@ -605,15 +962,6 @@ fn cmp_filter<T>(op: CondOp, value: T, what: &str) -> Result<FilterType<T>, EqlS
     })
 }
 
-fn tx_address(cond: &Condition) -> Result<alloy::primitives::Address, EqlSqlError> {
-    match values::parse_name_or_address(&cond.values[0])? {
-        NameOrAddress::Address(address) => Ok(address),
-        NameOrAddress::Name(_) => Err(EqlSqlError::NotSupported(
-            "ENS names outside accounts.address".into(),
-        )),
-    }
-}
-
 /// `data` has no dedicated parser in `values` (it's the only transaction
 /// filter column typed as raw bytes rather than a fixed-width value), so
 /// it's parsed locally rather than growing `values`'s public surface for a
@@ -648,7 +996,7 @@ fn push_block_id_filter(
 
 fn build_transaction(fields: &[String], conds: Vec<Condition>) -> Result<Entity, EqlSqlError> {
     let fields = if fields == ["*"] {
-        TransactionField::all_variants().to_vec()
+        TransactionField::wildcard()
     } else {
         fields
             .iter()
@@ -692,12 +1040,12 @@ fn build_transaction(fields: &[String], conds: Vec<Condition>) -> Result<Entity,
             )?,
             ("from_address", _) => filters.push(TransactionFilter::From(eq_only(
                 cond.op,
-                tx_address(cond)?,
+                values::parse_name_or_address(&cond.values[0])?,
                 "from_address",
             )?)),
             ("to_address", _) => filters.push(TransactionFilter::To(eq_only(
                 cond.op,
-                tx_address(cond)?,
+                values::parse_name_or_address(&cond.values[0])?,
                 "to_address",
             )?)),
             ("value", _) => filters.push(TransactionFilter::Value(cmp_filter(
@@ -734,6 +1082,21 @@ fn build_transaction(fields: &[String], conds: Vec<Condition>) -> Result<Entity,
                     "max_fee_per_blob_gas",
                 )?))
             }
+            ("blob_count", _) => filters.push(TransactionFilter::BlobCount(cmp_filter(
+                cond.op,
+                values::parse_u64(&cond.values[0])?,
+                "blob_count",
+            )?)),
+            ("blob_gas_used", _) => filters.push(TransactionFilter::BlobGasUsed(cmp_filter(
+                cond.op,
+                values::parse_u64(&cond.values[0])?,
+                "blob_gas_used",
+            )?)),
+            ("blob_gas_price", _) => filters.push(TransactionFilter::BlobGasPrice(cmp_filter(
+                cond.op,
+                values::parse_u128(&cond.values[0])?,
+                "blob_gas_price",
+            )?)),
             ("max_priority_fee_per_gas", _) => {
                 filters.push(TransactionFilter::MaxPriorityFeePerGas(cmp_filter(
                     cond.op,
@@ -741,6 +1104,33 @@ fn build_transaction(fields: &[String], conds: Vec<Condition>) -> Result<Entity,
                     "max_priority_fee_per_gas",
                 )?))
             }
+            ("gas_used", _) => filters.push(TransactionFilter::GasUsed(cmp_filter(
+                cond.op,
+                values::parse_u64(&cond.values[0])?,
+                "gas_used",
+            )?)),
+            ("cumulative_gas_used", _) => {
+                filters.push(TransactionFilter::CumulativeGasUsed(cmp_filter(
+                    cond.op,
+                    values::parse_u64(&cond.values[0])?,
+                    "cumulative_gas_used",
+                )?))
+            }
+            ("contract_address", _) => filters.push(TransactionFilter::ContractAddress(eq_only(
+                cond.op,
+                values::parse_name_or_address(&cond.values[0])?,
+                "contract_address",
+            )?)),
+            ("logs_count", _) => filters.push(TransactionFilter::LogsCount(cmp_filter(
+                cond.op,
+                values::parse_u64(&cond.values[0])?,
+                "logs_count",
+            )?)),
+            ("fee", _) => filters.push(TransactionFilter::Fee(cmp_filter(
+                cond.op,
+                values::parse_u256(&cond.values[0])?,
+                "fee",
+            )?)),
             ("type", _) => filters.push(TransactionFilter::Type(eq_only(
                 cond.op,
                 values::parse_u8(&cond.values[0])?,
@@ -824,7 +1214,7 @@ fn reject_duplicate_log_filter(
 
 fn build_logs(fields: &[String], conds: Vec<Condition>) -> Result<Entity, EqlSqlError> {
     let fields = if fields == ["*"] {
-        LogField::all_variants().to_vec()
+        LogField::wildcard()
     } else {
         fields
             .iter()
@@ -839,9 +1229,9 @@ fn build_logs(fields: &[String], conds: Vec<Condition>) -> Result<Entity, EqlSql
                 reject_duplicate_log_filter(&filters, "address", |f| {
                     matches!(f, LogFilter::EmitterAddress(_))
                 })?;
-                filters.push(LogFilter::EmitterAddress(values::parse_address(log_eq(
-                    cond, "address",
-                )?)?));
+                filters.push(LogFilter::EmitterAddress(values::parse_name_or_address(
+                    log_eq(cond, "address")?,
+                )?));
             }
             "topic0" => {
                 reject_duplicate_log_filter(&filters, "topic0", |f| {
@@ -939,77 +1329,1108 @@ fn build_logs(fields: &[String], conds: Vec<Condition>) -> Result<Entity, EqlSql
     Ok(Entity::Logs(Logs::new(filters, fields)))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::common::{
-        account::{Account, AccountField},
-        block::{BlockId, BlockRange},
-        chain::{Chain, ChainOrRpc},
-        ens::NameOrAddress,
-        types::Expression,
+fn build_storage(fields: &[String], conds: Vec<Condition>) -> Result<Entity, EqlSqlError> {
+    let fields = if fields == ["*"] {
+        StorageField::all_variants().to_vec()
+    } else {
+        fields
+            .iter()
+            .map(|f| schema::resolve_storage_field(f))
+            .collect::<Result<Vec<_>, _>>()?
     };
-    use alloy::eips::BlockNumberOrTag;
+    let mut addresses = Vec::new();
+    let mut slots = Vec::new();
+    // `address` and `slot` are identity columns, rejected when repeated for
+    // the same reason as `accounts.address` (see `build_account`);
+    // `block_number` unions like it does there.
+    let mut address_seen = false;
+    let mut slot_seen = false;
+    let mut blocks = Vec::new();
+    for cond in conds {
+        match (cond.column.as_str(), cond.op) {
+            ("address", CondOp::Eq) | ("address", CondOp::In) => {
+                if address_seen {
+                    return Err(EqlSqlError::Validation(
+                        "storage.address is given more than once; use IN (...) to match several addresses".into(),
+                    ));
+                }
+                address_seen = true;
+                for value in &cond.values {
+                    addresses.push(values::parse_name_or_address(value)?);
+                }
+            }
+            ("slot", CondOp::Eq) | ("slot", CondOp::In) => {
+                if slot_seen {
+                    return Err(EqlSqlError::Validation(
+                        "storage.slot is given more than once; use IN (...) to read several slots"
+                            .into(),
+                    ));
+                }
+                slot_seen = true;
+                for value in &cond.values {
+                    slots.push(values::parse_storage_slot(value)?);
+                }
+            }
+            ("block_number", CondOp::Eq) | ("block_number", CondOp::In) => {
+                for value in &cond.values {
+                    blocks.push(BlockId::Number(values::parse_block_number_or_tag(value)?));
+                }
+            }
+            ("block_number", CondOp::Between) => {
+                blocks.push(BlockId::Range(BlockRange::new(
+                    values::parse_block_number_or_tag(&cond.values[0])?,
+                    Some(values::parse_block_number_or_tag(&cond.values[1])?),
+                )));
+            }
+            (col, _) => {
+                return Err(EqlSqlError::NotSupported(format!(
+                    "filter on storage.{col} (only address = / IN, slot = / IN and block_number =, IN, BETWEEN)"
+                )))
+            }
+        }
+    }
+    if addresses.is_empty() || slots.is_empty() {
+        return Err(EqlSqlError::Validation(
+            "storage queries need an address and a slot predicate (= or IN)".into(),
+        ));
+    }
+    Ok(Entity::Storage(Storage::new(
+        addresses, slots, blocks, fields,
+    )))
+}
 
-    fn translate_one(sql: &str) -> Result<Expression, EqlSqlError> {
-        let prelexed = crate::interpreter::frontend::sql::prelex::prelex(sql)?;
-        let stmts =
-            sqlparser::parser::Parser::parse_sql(&sqlparser::dialect::DuckDbDialect {}, &prelexed)
-                .map_err(|e| EqlSqlError::Parse(e.to_string()))?;
-        statement_to_expression(&stmts[0])
+/// Rejects a second `=`/`IN` condition on an identity column of a table
+/// function, for the same reason `build_account` rejects a second
+/// `address`.
+fn reject_repeated(seen: &mut Vec<String>, table: &str, column: &str) -> Result<(), EqlSqlError> {
+    if seen.iter().any(|c| c == column) {
+        return Err(EqlSqlError::Validation(format!(
+            "{table}.{column} is given more than once; use IN (...) or [...] to pass several values"
+        )));
+    }
+    seen.push(column.to_string());
+    Ok(())
+}
+
+fn build_token_balances(fields: &[String], conds: Vec<Condition>) -> Result<Entity, EqlSqlError> {
+    let fields = if fields == ["*"] {
+        TokenBalanceField::all_variants().to_vec()
+    } else {
+        fields
+            .iter()
+            .map(|f| schema::resolve_token_balance_field(f))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    let mut holders = Vec::new();
+    let mut tokens = Vec::new();
+    let mut spenders = Vec::new();
+    let mut token_ids = Vec::new();
+    let mut blocks = Vec::new();
+    let mut seen = Vec::new();
+    for cond in conds {
+        match (cond.column.as_str(), cond.op) {
+            ("holder", CondOp::Eq) | ("holder", CondOp::In) => {
+                reject_repeated(&mut seen, "token_balances", "holder")?;
+                for value in &cond.values {
+                    holders.push(values::parse_name_or_address(value)?);
+                }
+            }
+            ("token", CondOp::Eq) | ("token", CondOp::In) => {
+                reject_repeated(&mut seen, "token_balances", "token")?;
+                for value in &cond.values {
+                    tokens.push(values::parse_name_or_address(value)?);
+                }
+            }
+            ("spender", CondOp::Eq) | ("spender", CondOp::In) => {
+                reject_repeated(&mut seen, "token_balances", "spender")?;
+                for value in &cond.values {
+                    spenders.push(values::parse_name_or_address(value)?);
+                }
+            }
+            ("token_id", CondOp::Eq) | ("token_id", CondOp::In) => {
+                reject_repeated(&mut seen, "token_balances", "token_id")?;
+                for value in &cond.values {
+                    token_ids.push(values::parse_u256(value)?);
+                }
+            }
+            ("block_number", CondOp::Eq) | ("block_number", CondOp::In) => {
+                for value in &cond.values {
+                    blocks.push(BlockId::Number(values::parse_block_number_or_tag(value)?));
+                }
+            }
+            ("block_number", CondOp::Between) => {
+                blocks.push(BlockId::Range(BlockRange::new(
+                    values::parse_block_number_or_tag(&cond.values[0])?,
+                    Some(values::parse_block_number_or_tag(&cond.values[1])?),
+                )));
+            }
+            (col, _) => {
+                return Err(EqlSqlError::NotSupported(format!(
+                    "filter on token_balances.{col} (only holder, token, spender, token_id = / IN and block_number =, IN, BETWEEN)"
+                )))
+            }
+        }
+    }
+    if tokens.is_empty() || (holders.is_empty() && token_ids.is_empty()) {
+        return Err(EqlSqlError::Validation(
+            "token_balances needs a token and a holder or token_id, e.g. token_balances(vitalik.eth, 0x...)".into(),
+        ));
+    }
+    Ok(Entity::TokenBalances(TokenBalances::new(
+        holders, tokens, spenders, token_ids, blocks, fields,
+    )))
+}
+
+fn build_call(fields: &[String], mut conds: Vec<Condition>) -> Result<Entity, EqlSqlError> {
+    // The signature types every other column, so it is read first.
+    let mut signatures = conds.iter().filter(|c| c.column == "signature");
+    let function = match (signatures.next(), signatures.next()) {
+        (Some(cond), None) if cond.op == CondOp::Eq => {
+            values::parse_function_signature(&cond.values[0])?
+        }
+        (None, _) => {
+            return Err(EqlSqlError::Validation(
+                "call needs a signature, e.g. call(0x..., 'totalSupply()(uint256)')".into(),
+            ))
+        }
+        _ => {
+            return Err(EqlSqlError::Validation(
+                "call takes a single signature (signature = '...'); run one query per function"
+                    .into(),
+            ))
+        }
+    };
+    conds.retain(|c| c.column != "signature");
+
+    let fields = if fields == ["*"] {
+        call_fields(&function)
+    } else {
+        fields
+            .iter()
+            .map(|f| schema::resolve_call_field(&function, f))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    let columns = argument_columns(&function);
+    let types = function
+        .inputs
+        .iter()
+        .map(|param| {
+            param.resolve().map_err(|e| {
+                EqlSqlError::Validation(format!("invalid parameter type {}: {e}", param.ty))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut addresses = Vec::new();
+    let mut arguments = vec![Vec::new(); columns.len()];
+    let mut blocks = Vec::new();
+    let mut seen = Vec::new();
+    for cond in conds {
+        // An input answers to its column name and to `arg<index>`.
+        let input = columns
+            .iter()
+            .enumerate()
+            .position(|(i, column)| cond.column == *column || cond.column == format!("arg{i}"));
+        match (cond.column.as_str(), cond.op, input) {
+            ("address", CondOp::Eq, _) | ("address", CondOp::In, _) => {
+                reject_repeated(&mut seen, "call", "address")?;
+                for value in &cond.values {
+                    addresses.push(values::parse_name_or_address(value)?);
+                }
+            }
+            ("block_number", CondOp::Eq, _) | ("block_number", CondOp::In, _) => {
+                for value in &cond.values {
+                    blocks.push(BlockId::Number(values::parse_block_number_or_tag(value)?));
+                }
+            }
+            ("block_number", CondOp::Between, _) => {
+                blocks.push(BlockId::Range(BlockRange::new(
+                    values::parse_block_number_or_tag(&cond.values[0])?,
+                    Some(values::parse_block_number_or_tag(&cond.values[1])?),
+                )));
+            }
+            (_, CondOp::Eq, Some(i)) | (_, CondOp::In, Some(i)) => {
+                reject_repeated(&mut seen, "call", &columns[i])?;
+                for value in &cond.values {
+                    arguments[i].push(values::parse_sol_value(value, &types[i])?);
+                }
+            }
+            (col, _, _) => {
+                return Err(EqlSqlError::NotSupported(format!(
+                    "filter on call.{col} (only address, signature and the function's arguments = / IN, and block_number =, IN, BETWEEN)"
+                )))
+            }
+        }
+    }
+    if addresses.is_empty() {
+        return Err(EqlSqlError::Validation(
+            "call needs an address, e.g. call(0x..., 'totalSupply()(uint256)')".into(),
+        ));
+    }
+    if let Some(i) = arguments.iter().position(Vec::is_empty) {
+        return Err(EqlSqlError::Validation(format!(
+            "{} needs a value for {} ({}), by position or as {} => ...",
+            function.signature(),
+            columns[i],
+            function.inputs[i].selector_type(),
+            columns[i]
+        )));
+    }
+    Ok(Entity::Call(Call::new(
+        addresses, function, arguments, blocks, fields,
+    )))
+}
+
+fn build_withdrawals(fields: &[String], conds: Vec<Condition>) -> Result<Entity, EqlSqlError> {
+    let fields = if fields == ["*"] {
+        WithdrawalField::all_variants().to_vec()
+    } else {
+        fields
+            .iter()
+            .map(|f| schema::resolve_withdrawal_field(f))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    let mut blocks = Vec::new();
+    let mut addresses = Vec::new();
+    let mut validator_indices = Vec::new();
+    let mut seen = Vec::new();
+    for cond in conds {
+        match (cond.column.as_str(), cond.op) {
+            ("block_number", CondOp::Eq) | ("block_number", CondOp::In) => {
+                for value in &cond.values {
+                    blocks.push(BlockId::Number(values::parse_block_number_or_tag(value)?));
+                }
+            }
+            ("block_number", CondOp::Between) => {
+                blocks.push(BlockId::Range(BlockRange::new(
+                    values::parse_block_number_or_tag(&cond.values[0])?,
+                    Some(values::parse_block_number_or_tag(&cond.values[1])?),
+                )));
+            }
+            ("address", CondOp::Eq) | ("address", CondOp::In) => {
+                reject_repeated(&mut seen, "withdrawals", "address")?;
+                for value in &cond.values {
+                    addresses.push(values::parse_name_or_address(value)?);
+                }
+            }
+            ("validator_index", CondOp::Eq) | ("validator_index", CondOp::In) => {
+                reject_repeated(&mut seen, "withdrawals", "validator_index")?;
+                for value in &cond.values {
+                    validator_indices.push(values::parse_u64(value)?);
+                }
+            }
+            (col, _) => {
+                return Err(EqlSqlError::NotSupported(format!(
+                    "filter on withdrawals.{col} (only block_number =, IN, BETWEEN and address, validator_index = / IN)"
+                )))
+            }
+        }
+    }
+    if blocks.is_empty() {
+        return Err(EqlSqlError::Validation(
+            "withdrawals queries need block_number (=, IN or BETWEEN)".into(),
+        ));
+    }
+    Ok(Entity::Withdrawals(Withdrawals::new(
+        blocks,
+        addresses,
+        validator_indices,
+        fields,
+    )))
+}
+
+fn build_authorizations(fields: &[String], conds: Vec<Condition>) -> Result<Entity, EqlSqlError> {
+    let fields = if fields == ["*"] {
+        AuthorizationField::all_variants().to_vec()
+    } else {
+        fields
+            .iter()
+            .map(|f| schema::resolve_authorization_field(f))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    let mut blocks = Vec::new();
+    let mut authorities = Vec::new();
+    let mut delegates = Vec::new();
+    let mut seen = Vec::new();
+    for cond in conds {
+        match (cond.column.as_str(), cond.op) {
+            ("block_number", CondOp::Eq) | ("block_number", CondOp::In) => {
+                for value in &cond.values {
+                    blocks.push(BlockId::Number(values::parse_block_number_or_tag(value)?));
+                }
+            }
+            ("block_number", CondOp::Between) => {
+                blocks.push(BlockId::Range(BlockRange::new(
+                    values::parse_block_number_or_tag(&cond.values[0])?,
+                    Some(values::parse_block_number_or_tag(&cond.values[1])?),
+                )));
+            }
+            ("authority", CondOp::Eq) | ("authority", CondOp::In) => {
+                reject_repeated(&mut seen, "authorizations", "authority")?;
+                for value in &cond.values {
+                    authorities.push(values::parse_name_or_address(value)?);
+                }
+            }
+            ("delegate", CondOp::Eq) | ("delegate", CondOp::In) => {
+                reject_repeated(&mut seen, "authorizations", "delegate")?;
+                for value in &cond.values {
+                    delegates.push(values::parse_name_or_address(value)?);
+                }
+            }
+            (col, _) => {
+                return Err(EqlSqlError::NotSupported(format!(
+                    "filter on authorizations.{col} (only block_number =, IN, BETWEEN and authority, delegate = / IN)"
+                )))
+            }
+        }
+    }
+    if blocks.is_empty() {
+        return Err(EqlSqlError::Validation(
+            "authorizations queries need block_number (=, IN or BETWEEN)".into(),
+        ));
+    }
+    Ok(Entity::Authorizations(Authorizations::new(
+        blocks,
+        authorities,
+        delegates,
+        fields,
+    )))
+}
+
+fn build_contracts(fields: &[String], conds: Vec<Condition>) -> Result<Entity, EqlSqlError> {
+    let fields = if fields == ["*"] {
+        ContractField::all_variants().to_vec()
+    } else {
+        fields
+            .iter()
+            .map(|f| schema::resolve_contract_field(f))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    let mut addresses = Vec::new();
+    let mut deployers = Vec::new();
+    let mut blocks = Vec::new();
+    let mut seen = Vec::new();
+    for cond in conds {
+        match (cond.column.as_str(), cond.op) {
+            ("address", CondOp::Eq) | ("address", CondOp::In) => {
+                reject_repeated(&mut seen, "contracts", "address")?;
+                for value in &cond.values {
+                    addresses.push(values::parse_name_or_address(value)?);
+                }
+            }
+            ("deployer", CondOp::Eq) | ("deployer", CondOp::In) => {
+                reject_repeated(&mut seen, "contracts", "deployer")?;
+                for value in &cond.values {
+                    deployers.push(values::parse_name_or_address(value)?);
+                }
+            }
+            ("block_number", CondOp::Eq) | ("block_number", CondOp::In) => {
+                for value in &cond.values {
+                    blocks.push(BlockId::Number(values::parse_block_number_or_tag(value)?));
+                }
+            }
+            ("block_number", CondOp::Between) => {
+                blocks.push(BlockId::Range(BlockRange::new(
+                    values::parse_block_number_or_tag(&cond.values[0])?,
+                    Some(values::parse_block_number_or_tag(&cond.values[1])?),
+                )));
+            }
+            (col, _) => {
+                return Err(EqlSqlError::NotSupported(format!(
+                    "filter on contracts.{col} (only address, deployer = / IN and block_number =, IN, BETWEEN)"
+                )))
+            }
+        }
+    }
+    // Without blocks, each address's creation block is searched for; a
+    // deployer alone gives nothing to search by.
+    if blocks.is_empty() && addresses.is_empty() {
+        return Err(EqlSqlError::Validation(
+            "contracts queries need address (= or IN) or block_number (=, IN or BETWEEN)".into(),
+        ));
+    }
+    Ok(Entity::Contracts(Contracts::new(
+        addresses, deployers, blocks, fields,
+    )))
+}
+
+fn build_state_diffs(fields: &[String], conds: Vec<Condition>) -> Result<Entity, EqlSqlError> {
+    let fields = if fields == ["*"] {
+        StateDiffField::all_variants().to_vec()
+    } else {
+        fields
+            .iter()
+            .map(|f| schema::resolve_state_diff_field(f))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    let mut blocks = Vec::new();
+    let mut addresses = Vec::new();
+    let mut kinds = Vec::new();
+    let mut keys = Vec::new();
+    let mut seen = Vec::new();
+    for cond in conds {
+        match (cond.column.as_str(), cond.op) {
+            ("block_number", CondOp::Eq) | ("block_number", CondOp::In) => {
+                for value in &cond.values {
+                    blocks.push(BlockId::Number(values::parse_block_number_or_tag(value)?));
+                }
+            }
+            ("block_number", CondOp::Between) => {
+                blocks.push(BlockId::Range(BlockRange::new(
+                    values::parse_block_number_or_tag(&cond.values[0])?,
+                    Some(values::parse_block_number_or_tag(&cond.values[1])?),
+                )));
+            }
+            ("address", CondOp::Eq) | ("address", CondOp::In) => {
+                reject_repeated(&mut seen, "state_diffs", "address")?;
+                for value in &cond.values {
+                    addresses.push(values::parse_name_or_address(value)?);
+                }
+            }
+            ("kind", CondOp::Eq) | ("kind", CondOp::In) => {
+                reject_repeated(&mut seen, "state_diffs", "kind")?;
+                for value in &cond.values {
+                    let kind = values::expr_as_string(value)?.to_ascii_lowercase();
+                    kinds.push(
+                        StateDiffKind::try_from(kind.as_str())
+                            .map_err(|e| EqlSqlError::Validation(e.to_string()))?,
+                    );
+                }
+            }
+            ("key", CondOp::Eq) | ("key", CondOp::In) | ("slot", CondOp::Eq)
+            | ("slot", CondOp::In) => {
+                reject_repeated(&mut seen, "state_diffs", "key")?;
+                for value in &cond.values {
+                    keys.push(values::parse_storage_slot(value)?);
+                }
+            }
+            (col, _) => {
+                return Err(EqlSqlError::NotSupported(format!(
+                    "filter on state_diffs.{col} (only address, kind, key = / IN and block_number =, IN, BETWEEN)"
+                )))
+            }
+        }
+    }
+    if blocks.is_empty() {
+        return Err(EqlSqlError::Validation(
+            "state_diffs queries need block_number (=, IN or BETWEEN)".into(),
+        ));
+    }
+    Ok(Entity::StateDiffs(StateDiffs::new(
+        blocks, addresses, kinds, keys, fields,
+    )))
+}
+
+fn build_ens(fields: &[String], conds: Vec<Condition>) -> Result<Entity, EqlSqlError> {
+    let fields = if fields == ["*"] {
+        EnsField::all_variants().to_vec()
+    } else {
+        fields
+            .iter()
+            .map(|f| schema::resolve_ens_field(f))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    let mut names = Vec::new();
+    let mut addresses = Vec::new();
+    let mut blocks = Vec::new();
+    let mut seen = Vec::new();
+    for cond in conds {
+        match (cond.column.as_str(), cond.op) {
+            ("name", CondOp::Eq) | ("name", CondOp::In) => {
+                reject_repeated(&mut seen, "ens", "name")?;
+                for value in &cond.values {
+                    names.push(values::parse_ens_name(value)?);
+                }
+            }
+            ("address", CondOp::Eq) | ("address", CondOp::In) => {
+                reject_repeated(&mut seen, "ens", "address")?;
+                for value in &cond.values {
+                    addresses.push(values::parse_address(value)?);
+                }
+            }
+            ("block_number", CondOp::Eq) | ("block_number", CondOp::In) => {
+                for value in &cond.values {
+                    blocks.push(BlockId::Number(values::parse_block_number_or_tag(value)?));
+                }
+            }
+            ("block_number", CondOp::Between) => {
+                blocks.push(BlockId::Range(BlockRange::new(
+                    values::parse_block_number_or_tag(&cond.values[0])?,
+                    Some(values::parse_block_number_or_tag(&cond.values[1])?),
+                )));
+            }
+            (col, _) => {
+                return Err(EqlSqlError::NotSupported(format!(
+                "filter on ens.{col} (only name, address = / IN and block_number =, IN, BETWEEN)"
+            )))
+            }
+        }
+    }
+    if names.is_empty() && addresses.is_empty() {
+        return Err(EqlSqlError::Validation(
+            "ens queries need name or address (= or IN)".into(),
+        ));
+    }
+    Ok(Entity::Ens(Ens::new(names, addresses, blocks, fields)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{
+        account::{Account, AccountField},
+        block::{BlockId, BlockRange},
+        chain::{Chain, ChainOrRpc},
+        ens::NameOrAddress,
+        types::Expression,
+    };
+    use alloy::eips::BlockNumberOrTag;
+
+    fn translate_one(sql: &str) -> Result<Expression, EqlSqlError> {
+        let prelexed = crate::interpreter::frontend::sql::prelex::prelex(sql)?;
+        let stmts =
+            sqlparser::parser::Parser::parse_sql(&sqlparser::dialect::DuckDbDialect {}, &prelexed)
+                .map_err(|e| EqlSqlError::Parse(e.to_string()))?;
+        statement_to_expression(&stmts[0])
+    }
+
+    #[test]
+    fn account_query_translates() {
+        let expr = translate_one(
+            "SELECT nonce, balance FROM accounts WHERE address = vitalik.eth AND chain = eth",
+        )
+        .unwrap();
+        let Expression::Get(get) = expr else {
+            panic!("not a Get")
+        };
+        assert_eq!(get.chains, vec![ChainOrRpc::Chain(Chain::Ethereum)]);
+        assert_eq!(
+            get.entity,
+            crate::common::entity::Entity::Account(Account::new(
+                Some(vec![NameOrAddress::Name("vitalik.eth".into())]),
+                None,
+                vec![AccountField::Nonce, AccountField::Balance],
+            ))
+        );
+    }
+
+    #[test]
+    fn account_in_list_and_wildcard_fields() {
+        let expr = translate_one(
+            "SELECT * FROM accounts WHERE address IN (0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045, ian.eth) AND chain = eth",
+        )
+        .unwrap();
+        let Expression::Get(get) = expr else {
+            panic!("not a Get")
+        };
+        let crate::common::entity::Entity::Account(account) = get.entity else {
+            panic!()
+        };
+        assert_eq!(account.ids().unwrap().len(), 2);
+        assert_eq!(account.fields(), AccountField::wildcard());
+    }
+
+    // Fix 1: a repeated key predicate on a key column (`address`, `hash`,
+    // `chain`) is a contradiction in SQL terms (`x = a AND x = b` can never
+    // match), not a union — `IN (...)` is the sanctioned way to match
+    // several values. `blocks.number` is deliberately exempt (see
+    // `build_block`'s doc comment and `block_repeated_number_still_unions`
+    // below): it's the only way to express a mixed number+range selection.
+
+    #[test]
+    fn account_duplicate_address_is_rejected_clearly() {
+        let err = translate_one(
+            "SELECT nonce FROM accounts WHERE address = ian.eth AND address = vitalik.eth AND chain = eth",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("address") && err.contains("IN"), "{err}");
+    }
+
+    #[test]
+    fn account_block_number_reads_historical_state() {
+        let expr = translate_one(
+            "SELECT balance, block_number FROM accounts WHERE address = vitalik.eth \
+             AND block_number IN (100, 200) AND block_number BETWEEN 300 AND finalized AND chain = eth",
+        )
+        .unwrap();
+        let Expression::Get(get) = expr else {
+            panic!("not a Get")
+        };
+        let crate::common::entity::Entity::Account(account) = get.entity else {
+            panic!()
+        };
+        assert_eq!(
+            account.block_ids(),
+            vec![
+                &BlockId::Number(BlockNumberOrTag::Number(100)),
+                &BlockId::Number(BlockNumberOrTag::Number(200)),
+                &BlockId::Range(BlockRange::new(
+                    BlockNumberOrTag::Number(300),
+                    Some(BlockNumberOrTag::Finalized),
+                )),
+            ]
+        );
+
+        let err = translate_one(
+            "SELECT balance FROM accounts WHERE address = vitalik.eth AND block_number > 5 AND chain = eth",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("block_number"), "{err}");
+    }
+
+    #[test]
+    fn storage_slots_translate() {
+        use crate::common::storage::{mapping_slot, EIP1967_IMPLEMENTATION_SLOT};
+        let expr = translate_one(
+            "SELECT * FROM storage WHERE address = 0xdAC17F958D2ee523a2206206994597C13D831ec7 \
+             AND slot IN (0x0, eip1967_implementation, mapping_slot(2, 0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045)) \
+             AND block_number = 100 AND chain = eth",
+        )
+        .unwrap();
+        let Expression::Get(get) = expr else {
+            panic!("not a Get")
+        };
+        let crate::common::entity::Entity::Storage(storage) = get.entity else {
+            panic!()
+        };
+        let holder = alloy::primitives::B256::left_padding_from(
+            &alloy::hex::decode("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045").unwrap(),
+        );
+        assert_eq!(
+            storage.slots(),
+            [
+                alloy::primitives::B256::ZERO,
+                EIP1967_IMPLEMENTATION_SLOT,
+                mapping_slot(alloy::primitives::B256::with_last_byte(2), holder),
+            ]
+        );
+        assert_eq!(
+            storage.block_ids(),
+            vec![&BlockId::Number(BlockNumberOrTag::Number(100))]
+        );
+        assert_eq!(storage.fields(), StorageField::all_variants().to_vec());
+    }
+
+    #[test]
+    fn storage_rejects_bad_slots_and_missing_predicates() {
+        for (sql, needle) in [
+            (
+                "SELECT value FROM storage WHERE address = vitalik.eth AND chain = eth",
+                "slot",
+            ),
+            (
+                "SELECT value FROM storage WHERE address = vitalik.eth AND slot = keccak(1) AND chain = eth",
+                "mapping_slot",
+            ),
+            (
+                "SELECT value FROM storage WHERE address = vitalik.eth AND slot = mapping_slot(1) AND chain = eth",
+                "mapping_slot",
+            ),
+            (
+                "SELECT value FROM storage WHERE address = vitalik.eth AND slot = 1 AND slot = 2 AND chain = eth",
+                "IN",
+            ),
+            (
+                "SELECT value FROM storage WHERE address = vitalik.eth AND slot > 1 AND chain = eth",
+                "storage.slot",
+            ),
+        ] {
+            let err = translate_one(sql).unwrap_err().to_string();
+            assert!(err.contains(needle), "{sql}: {err}");
+        }
+    }
+
+    #[test]
+    fn token_balances_arguments_read_like_predicates() {
+        let usdc = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+        let from_args = translate_one(&format!(
+            "SELECT balance FROM token_balances(vitalik.eth, [{usdc}, 0xdAC17F958D2ee523a2206206994597C13D831ec7], \
+             block_number => 100, chain => eth)"
+        ))
+        .unwrap();
+        let from_where = translate_one(&format!(
+            "SELECT balance FROM token_balances WHERE holder = vitalik.eth \
+             AND token IN ({usdc}, 0xdAC17F958D2ee523a2206206994597C13D831ec7) \
+             AND block_number = 100 AND chain = eth"
+        ))
+        .unwrap();
+        assert_eq!(from_args, from_where);
+
+        let Expression::Get(get) = from_args else {
+            panic!("not a Get")
+        };
+        let crate::common::entity::Entity::TokenBalances(balances) = get.entity else {
+            panic!()
+        };
+        assert_eq!(
+            balances.holders(),
+            [NameOrAddress::Name("vitalik.eth".into())]
+        );
+        assert_eq!(balances.tokens().len(), 2);
+        assert_eq!(get.chains, vec![ChainOrRpc::Chain(Chain::Ethereum)]);
+    }
+
+    #[test]
+    fn token_balances_rejects_bad_arguments() {
+        for (sql, needle) in [
+            (
+                "SELECT * FROM token_balances(vitalik.eth) WHERE chain = eth",
+                "token",
+            ),
+            (
+                "SELECT * FROM token_balances(vitalik.eth, 0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48, 5) WHERE chain = eth",
+                "positional",
+            ),
+            (
+                "SELECT * FROM token_balances(holder => vitalik.eth, 0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48) WHERE chain = eth",
+                "follows a named one",
+            ),
+            (
+                "SELECT * FROM token_balances(vitalik.eth, 0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48) \
+                 WHERE holder = ian.eth AND chain = eth",
+                "more than once",
+            ),
+            (
+                "SELECT * FROM accounts(vitalik.eth) WHERE chain = eth",
+                "not a table function",
+            ),
+        ] {
+            let err = translate_one(sql).unwrap_err().to_string();
+            assert!(err.contains(needle), "{sql}: {err}");
+        }
+    }
+
+    #[test]
+    fn call_arguments_are_typed_by_the_signature() {
+        let pair = "0x0d4a11d5EEaaC28EC3F61d100daF4d40471f1852";
+        let from_args = translate_one(&format!(
+            "SELECT reserve0, output2 FROM call({pair}, 'getReserves()(uint112 reserve0, uint112, uint32)', \
+             block_number => 19000000, chain => eth)"
+        ))
+        .unwrap();
+        let from_where = translate_one(&format!(
+            "SELECT reserve0, output2 FROM call WHERE address = {pair} \
+             AND signature = 'getReserves()(uint112 reserve0, uint112, uint32)' \
+             AND block_number = 19000000 AND chain = eth"
+        ))
+        .unwrap();
+        assert_eq!(from_args, from_where);
+
+        // Arguments are coerced to the input's type, so a name is not an
+        // address here.
+        let err = translate_one(
+            "SELECT * FROM call(0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48, \
+             'balanceOf(address owner)(uint256)', owner => vitalik.eth) WHERE chain = eth",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("invalid address 'vitalik.eth'"), "{err}");
+
+        let Expression::Get(get) = translate_one(
+            "SELECT * FROM call(0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48, \
+             'balanceOf(address owner)(uint256)', \
+             [0x0000000000000000000000000000000000000001, 0x0000000000000000000000000000000000000002]) \
+             WHERE chain = eth",
+        )
+        .unwrap() else {
+            panic!("not a Get")
+        };
+        let crate::common::entity::Entity::Call(call) = get.entity else {
+            panic!()
+        };
+        assert_eq!(call.argument_sets().len(), 2);
+        assert_eq!(
+            call.fields(),
+            call_fields(call.function()),
+            "* selects every column"
+        );
+    }
+
+    #[test]
+    fn call_rejects_bad_arguments() {
+        let token = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+        for (sql, needle) in [
+            (format!("SELECT * FROM call({token}) WHERE chain = eth"), "signature"),
+            (
+                format!("SELECT * FROM call({token}, 'totalSupply()') WHERE chain = eth"),
+                "no outputs",
+            ),
+            (
+                format!("SELECT * FROM call({token}, 'balanceOf(address)(uint256)') WHERE chain = eth"),
+                "needs a value for arg0",
+            ),
+            (
+                format!("SELECT * FROM call({token}, 'decimals()(uint8)', 5) WHERE chain = eth"),
+                "filter on call.arg0",
+            ),
+            (
+                format!("SELECT * FROM call({token}, 'balanceOf(address)(uint256)', 12) WHERE chain = eth"),
+                "invalid address",
+            ),
+            (
+                format!("SELECT balance FROM call({token}, 'decimals()(uint8)') WHERE chain = eth"),
+                "unknown field 'balance'",
+            ),
+            (
+                format!(
+                    "SELECT * FROM call({token}, 'decimals()(uint8)') \
+                     WHERE signature = 'name()(string)' AND chain = eth"
+                ),
+                "single signature",
+            ),
+        ] {
+            let err = translate_one(&sql).unwrap_err().to_string();
+            assert!(err.contains(needle), "{sql}: {err}");
+        }
+    }
+
+    #[test]
+    fn withdrawals_filter_by_block_address_and_validator() {
+        let expr = translate_one(
+            "SELECT * FROM withdrawals WHERE block_number BETWEEN 17034870 AND 17034871 \
+             AND address IN (vitalik.eth, 0xb9D7934878B5FB9610B3fE8A5e441e8fad7E293f) \
+             AND validator_index = 42 AND chain = eth",
+        )
+        .unwrap();
+        let Expression::Get(get) = expr else {
+            panic!("not a Get")
+        };
+        let crate::common::entity::Entity::Withdrawals(withdrawals) = get.entity else {
+            panic!()
+        };
+        assert_eq!(
+            withdrawals.block_ids(),
+            vec![&BlockId::Range(BlockRange::new(
+                BlockNumberOrTag::Number(17034870),
+                Some(BlockNumberOrTag::Number(17034871)),
+            ))]
+        );
+        assert_eq!(withdrawals.addresses().len(), 2);
+        assert_eq!(
+            withdrawals.addresses()[0],
+            NameOrAddress::Name("vitalik.eth".into())
+        );
+        assert_eq!(withdrawals.validator_indices(), [42]);
+        assert_eq!(
+            withdrawals.fields(),
+            WithdrawalField::all_variants().to_vec()
+        );
+
+        for (sql, needle) in [
+            (
+                "SELECT * FROM withdrawals WHERE address = vitalik.eth AND chain = eth",
+                "need block_number",
+            ),
+            (
+                "SELECT * FROM withdrawals WHERE block_number = 1 AND amount > 5 AND chain = eth",
+                "withdrawals.amount",
+            ),
+            (
+                "SELECT * FROM withdrawals WHERE block_number = 1 AND validator_index = 1 \
+                 AND validator_index = 2 AND chain = eth",
+                "more than once",
+            ),
+        ] {
+            let err = translate_one(sql).unwrap_err().to_string();
+            assert!(err.contains(needle), "{sql}: {err}");
+        }
+    }
+
+    #[test]
+    fn authorizations_filter_by_block_authority_and_delegate() {
+        let expr = translate_one(
+            "SELECT authority, delegate FROM authorizations WHERE block_number IN (22431084, latest) \
+             AND delegate = 0x63c0c19a282a1B52b07dD5a65b58948A07DAE32B \
+             AND authority IN (vitalik.eth) AND chain = eth",
+        )
+        .unwrap();
+        let Expression::Get(get) = expr else {
+            panic!("not a Get")
+        };
+        let crate::common::entity::Entity::Authorizations(authorizations) = get.entity else {
+            panic!()
+        };
+        assert_eq!(
+            authorizations.block_ids(),
+            vec![
+                &BlockId::Number(BlockNumberOrTag::Number(22431084)),
+                &BlockId::Number(BlockNumberOrTag::Latest),
+            ]
+        );
+        assert_eq!(
+            authorizations.authorities(),
+            [NameOrAddress::Name("vitalik.eth".into())]
+        );
+        assert_eq!(authorizations.delegates().len(), 1);
+        assert_eq!(
+            authorizations.fields(),
+            [AuthorizationField::Authority, AuthorizationField::Delegate]
+        );
+
+        for (sql, needle) in [
+            (
+                "SELECT * FROM authorizations WHERE delegate = vitalik.eth AND chain = eth",
+                "need block_number",
+            ),
+            (
+                "SELECT * FROM authorizations WHERE block_number = 1 AND nonce > 5 AND chain = eth",
+                "authorizations.nonce",
+            ),
+        ] {
+            let err = translate_one(sql).unwrap_err().to_string();
+            assert!(err.contains(needle), "{sql}: {err}");
+        }
     }
 
     #[test]
-    fn account_query_translates() {
+    fn contracts_filter_by_address_deployer_and_block() {
         let expr = translate_one(
-            "SELECT nonce, balance FROM accounts WHERE address = vitalik.eth AND chain = eth",
+            "SELECT address, block_number FROM contracts \
+             WHERE address = 0x1f9840a85d5aF5bf1D1762F925BDADdC4201F984 AND chain = eth",
         )
         .unwrap();
         let Expression::Get(get) = expr else {
             panic!("not a Get")
         };
-        assert_eq!(get.chains, vec![ChainOrRpc::Chain(Chain::Ethereum)]);
+        let crate::common::entity::Entity::Contracts(contracts) = get.entity else {
+            panic!()
+        };
+        assert_eq!(contracts.addresses().len(), 1);
+        assert!(contracts.block_ids().is_empty());
         assert_eq!(
-            get.entity,
-            crate::common::entity::Entity::Account(Account::new(
-                Some(vec![NameOrAddress::Name("vitalik.eth".into())]),
-                None,
-                vec![AccountField::Nonce, AccountField::Balance],
-            ))
+            contracts.fields(),
+            [ContractField::Address, ContractField::BlockNumber]
+        );
+
+        let expr = translate_one(
+            "SELECT * FROM contracts WHERE block_number BETWEEN 10 AND 20 \
+             AND deployer IN (vitalik.eth) AND chain = eth",
+        )
+        .unwrap();
+        let Expression::Get(get) = expr else {
+            panic!("not a Get")
+        };
+        let crate::common::entity::Entity::Contracts(contracts) = get.entity else {
+            panic!()
+        };
+        assert_eq!(
+            contracts.block_ids(),
+            vec![&BlockId::Range(BlockRange::new(
+                BlockNumberOrTag::Number(10),
+                Some(BlockNumberOrTag::Number(20)),
+            ))]
+        );
+        assert_eq!(
+            contracts.deployers(),
+            [NameOrAddress::Name("vitalik.eth".into())]
         );
+        assert_eq!(contracts.fields(), ContractField::all_variants());
+
+        for (sql, needle) in [
+            (
+                "SELECT * FROM contracts WHERE deployer = vitalik.eth AND chain = eth",
+                "need address",
+            ),
+            (
+                "SELECT * FROM contracts WHERE block_number = 1 AND init_code_hash = 0x00 AND chain = eth",
+                "contracts.init_code_hash",
+            ),
+        ] {
+            let err = translate_one(sql).unwrap_err().to_string();
+            assert!(err.contains(needle), "{sql}: {err}");
+        }
     }
 
     #[test]
-    fn account_in_list_and_wildcard_fields() {
+    fn state_diffs_filter_by_block_address_kind_and_slot() {
         let expr = translate_one(
-            "SELECT * FROM accounts WHERE address IN (0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045, ian.eth) AND chain = eth",
+            "SELECT address, kind, prev, next FROM state_diffs \
+             WHERE block_number = 20000000 AND address = vitalik.eth \
+             AND kind IN ('balance', storage) AND slot = 0 AND chain = eth",
         )
         .unwrap();
         let Expression::Get(get) = expr else {
             panic!("not a Get")
         };
-        let crate::common::entity::Entity::Account(account) = get.entity else {
+        let crate::common::entity::Entity::StateDiffs(diffs) = get.entity else {
             panic!()
         };
-        assert_eq!(account.ids().unwrap().len(), 2);
-        assert_eq!(account.fields(), AccountField::all_variants().to_vec());
-    }
+        assert_eq!(
+            diffs.block_ids(),
+            vec![&BlockId::Number(BlockNumberOrTag::Number(20_000_000))]
+        );
+        assert_eq!(
+            diffs.addresses(),
+            [NameOrAddress::Name("vitalik.eth".into())]
+        );
+        assert_eq!(
+            diffs.kinds(),
+            [StateDiffKind::Balance, StateDiffKind::Storage]
+        );
+        assert_eq!(diffs.keys(), [alloy::primitives::B256::ZERO]);
+        assert_eq!(
+            diffs.fields(),
+            [
+                StateDiffField::Address,
+                StateDiffField::Kind,
+                StateDiffField::Prev,
+                StateDiffField::Next
+            ]
+        );
 
-    // Fix 1: a repeated key predicate on a key column (`address`, `hash`,
-    // `chain`) is a contradiction in SQL terms (`x = a AND x = b` can never
-    // match), not a union — `IN (...)` is the sanctioned way to match
-    // several values. `blocks.number` is deliberately exempt (see
-    // `build_block`'s doc comment and `block_repeated_number_still_unions`
-    // below): it's the only way to express a mixed number+range selection.
+        for (sql, needle) in [
+            (
+                "SELECT * FROM state_diffs WHERE address = vitalik.eth AND chain = eth",
+                "need block_number",
+            ),
+            (
+                "SELECT * FROM state_diffs WHERE block_number = 1 AND kind = 'gas' AND chain = eth",
+                "Invalid state diff kind gas",
+            ),
+            (
+                "SELECT * FROM state_diffs WHERE block_number = 1 AND prev = 0 AND chain = eth",
+                "state_diffs.prev",
+            ),
+        ] {
+            let err = translate_one(sql).unwrap_err().to_string();
+            assert!(err.contains(needle), "{sql}: {err}");
+        }
+    }
 
     #[test]
-    fn account_duplicate_address_is_rejected_clearly() {
-        let err = translate_one(
-            "SELECT nonce FROM accounts WHERE address = ian.eth AND address = vitalik.eth AND chain = eth",
+    fn ens_looks_up_names_and_addresses_on_ethereum_by_default() {
+        let expr =
+            translate_one("SELECT * FROM ens WHERE name IN (vitalik.eth, nick.eth)").unwrap();
+        let Expression::Get(get) = expr else {
+            panic!("not a Get")
+        };
+        assert_eq!(get.chains, vec![ChainOrRpc::Chain(Chain::Ethereum)]);
+        let crate::common::entity::Entity::Ens(ens) = get.entity else {
+            panic!()
+        };
+        assert_eq!(ens.names(), ["vitalik.eth", "nick.eth"]);
+        assert_eq!(ens.fields(), EnsField::all_variants());
+
+        let expr = translate_one(
+            "SELECT name FROM ens \
+             WHERE address = 0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045 AND chain = sepolia",
         )
-        .unwrap_err()
-        .to_string();
-        assert!(err.contains("address") && err.contains("IN"), "{err}");
+        .unwrap();
+        let Expression::Get(get) = expr else {
+            panic!("not a Get")
+        };
+        assert_eq!(get.chains, vec![ChainOrRpc::Chain(Chain::Sepolia)]);
+        let crate::common::entity::Entity::Ens(ens) = get.entity else {
+            panic!()
+        };
+        assert_eq!(ens.addresses().len(), 1);
+        assert_eq!(ens.fields(), [EnsField::Name]);
+
+        let err = translate_one("SELECT * FROM ens WHERE block_number = 1")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("need name or address"), "{err}");
     }
 
     #[test]
@@ -1465,6 +2886,39 @@ mod tests {
 
     // Task 7: transactions and logs translation.
 
+    #[test]
+    fn blocks_filter_by_miner_and_timestamp() {
+        use crate::common::filters::{ComparisonFilter, EqualityFilter, FilterType};
+        let expr = translate_one(
+            "SELECT number FROM blocks WHERE number BETWEEN 1 AND 100 \
+             AND miner = 0x95222290dd7278aa3ddd389cc1e1d165cc4bafe5 \
+             AND timestamp BETWEEN 1700000000 AND 1700000600 AND chain = eth",
+        )
+        .unwrap();
+        let Expression::Get(get) = expr else {
+            panic!("not a Get")
+        };
+        let crate::common::entity::Entity::Block(block) = get.entity else {
+            panic!()
+        };
+        assert_eq!(
+            block.filters().unwrap(),
+            &vec![
+                BlockFilter::Miner(EqualityFilter::Eq(
+                    alloy::primitives::address!("95222290dd7278aa3ddd389cc1e1d165cc4bafe5").into()
+                )),
+                BlockFilter::Timestamp(FilterType::Comparison(ComparisonFilter::Gte(1700000000))),
+                BlockFilter::Timestamp(FilterType::Comparison(ComparisonFilter::Lte(1700000600))),
+            ]
+        );
+
+        // A timestamp narrows the blocks fetched by number; it can't replace it.
+        let err = translate_one("SELECT number FROM blocks WHERE timestamp > 1 AND chain = eth")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("number predicate"), "{err}");
+    }
+
     #[test]
     fn tx_by_hash() {
         let expr = translate_one(
@@ -1547,13 +3001,58 @@ mod tests {
     }
 
     #[test]
-    fn tx_ens_in_address_filter_not_supported_yet() {
-        let err = translate_one(
-            "SELECT value FROM tx WHERE block_number = latest AND from_address = vitalik.eth AND chain = eth",
+    fn ens_names_in_address_filters_are_kept_for_resolution() {
+        use crate::common::{logs::LogFilter, transaction::TransactionFilter};
+        let vitalik = || NameOrAddress::Name("vitalik.eth".into());
+
+        let expr = translate_one(
+            "SELECT value FROM tx WHERE block_number = latest AND from_address = vitalik.eth \
+             AND to_address != 0x0000000000000000000000000000000000000000 AND chain = eth",
         )
-        .unwrap_err()
-        .to_string();
-        assert!(err.contains("ENS"));
+        .unwrap();
+        let Expression::Get(get) = expr else {
+            panic!("not a Get")
+        };
+        let crate::common::entity::Entity::Transaction(tx) = get.entity else {
+            panic!()
+        };
+        let filters = tx.filters().unwrap();
+        assert!(filters.contains(&TransactionFilter::From(EqualityFilter::Eq(vitalik()))));
+        assert!(filters.contains(&TransactionFilter::To(EqualityFilter::Neq(
+            alloy::primitives::Address::ZERO.into()
+        ))));
+
+        let expr = translate_one(
+            "SELECT * FROM logs WHERE address = uniswap.eth \
+             AND block_number BETWEEN 1 AND 2 AND chain = eth",
+        )
+        .unwrap();
+        let Expression::Get(get) = expr else {
+            panic!("not a Get")
+        };
+        let crate::common::entity::Entity::Logs(logs) = get.entity else {
+            panic!()
+        };
+        assert!(logs
+            .filter()
+            .contains(&LogFilter::EmitterAddress(NameOrAddress::Name(
+                "uniswap.eth".into()
+            ))));
+
+        let expr = translate_one(
+            "SELECT balance FROM token_balances \
+             WHERE token IN (weth.eth, 0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48) \
+             AND holder = vitalik.eth AND chain = eth",
+        )
+        .unwrap();
+        let Expression::Get(get) = expr else {
+            panic!("not a Get")
+        };
+        let crate::common::entity::Entity::TokenBalances(balances) = get.entity else {
+            panic!()
+        };
+        assert_eq!(balances.tokens()[0], NameOrAddress::Name("weth.eth".into()));
+        assert_eq!(balances.holders(), [vitalik()]);
     }
 
     #[test]
@@ -1647,6 +3146,85 @@ mod tests {
             .any(|f| matches!(f, TransactionFilter::Data(_))));
     }
 
+    #[test]
+    fn tx_blob_filters_translate() {
+        use crate::common::{
+            filters::{ComparisonFilter, FilterType},
+            transaction::TransactionFilter,
+        };
+        let expr = translate_one(
+            "SELECT hash, blob_versioned_hashes FROM tx WHERE block_number = latest \
+             AND blob_count >= 2 AND blob_gas_used > 131072 AND blob_gas_price < 1 gwei \
+             AND chain = eth",
+        )
+        .unwrap();
+        let Expression::Get(get) = expr else {
+            panic!("not a Get")
+        };
+        let crate::common::entity::Entity::Transaction(tx) = get.entity else {
+            panic!()
+        };
+        let filters = tx.filters().unwrap();
+        assert!(
+            filters.contains(&TransactionFilter::BlobCount(FilterType::Comparison(
+                ComparisonFilter::Gte(2)
+            )))
+        );
+        assert!(
+            filters.contains(&TransactionFilter::BlobGasUsed(FilterType::Comparison(
+                ComparisonFilter::Gt(131072)
+            )))
+        );
+        assert!(
+            filters.contains(&TransactionFilter::BlobGasPrice(FilterType::Comparison(
+                ComparisonFilter::Lt(1_000_000_000)
+            )))
+        );
+    }
+
+    #[test]
+    fn tx_receipt_filters_translate() {
+        use crate::common::{
+            filters::{ComparisonFilter, EqualityFilter, FilterType},
+            transaction::TransactionFilter,
+        };
+        let expr = translate_one(
+            "SELECT hash, fee FROM tx WHERE block_number = latest AND gas_used > 21000 \
+             AND contract_address != 0x0000000000000000000000000000000000000000 \
+             AND logs_count >= 1 AND fee < 1 ether AND chain = eth",
+        )
+        .unwrap();
+        let Expression::Get(get) = expr else {
+            panic!("not a Get")
+        };
+        let crate::common::entity::Entity::Transaction(tx) = get.entity else {
+            panic!()
+        };
+        let filters = tx.filters().unwrap();
+        assert!(
+            filters.contains(&TransactionFilter::GasUsed(FilterType::Comparison(
+                ComparisonFilter::Gt(21000)
+            )))
+        );
+        assert!(
+            filters.contains(&TransactionFilter::ContractAddress(EqualityFilter::Neq(
+                alloy::primitives::Address::ZERO.into()
+            )))
+        );
+        assert!(
+            filters.contains(&TransactionFilter::LogsCount(FilterType::Comparison(
+                ComparisonFilter::Gte(1)
+            )))
+        );
+        assert!(filters
+            .iter()
+            .any(|f| matches!(f, TransactionFilter::Fee(FilterType::Comparison(_)))));
+        assert_eq!(
+            tx.fields(),
+            &vec![TransactionField::Hash, TransactionField::Fee]
+        );
+    }
+
     // Shapes the brief's tests don't cover. Each is either rejected clearly
     // (naming the real column and operator) or translated sensibly — never
     // mis-translated silently and never a panic.
@@ -2012,4 +3590,121 @@ mod tests {
         let err = statement_to_expression(&stmt).unwrap_err().to_string();
         assert!(err.contains("rpc_eth") && err.contains("rpc_op"), "{err}");
     }
+
+    #[test]
+    fn show_tables_and_describe_translate_to_catalog() {
+        assert_eq!(
+            translate_one("SHOW TABLES").unwrap(),
+            Expression::Catalog(CatalogExpression {
+                target: CatalogTarget::Tables,
+                limit: None,
+            })
+        );
+        for sql in [
+            "DESCRIBE tx",
+            "DESC transactions",
+            "SHOW COLUMNS FROM transactions",
+        ] {
+            assert_eq!(
+                translate_one(sql).unwrap(),
+                Expression::Catalog(CatalogExpression {
+                    target: CatalogTarget::Columns(vec![EntityKind::Transactions]),
+                    limit: None,
+                }),
+                "{sql}"
+            );
+        }
+    }
+
+    #[test]
+    fn describe_unknown_entity_suggests_the_table() {
+        let err = translate_one("DESCRIBE log").unwrap_err().to_string();
+        assert!(err.contains("logs"), "{err}");
+    }
+
+    #[test]
+    fn catalog_modifiers_are_rejected_by_name() {
+        let err = translate_one("SHOW TABLES LIKE 'l%'")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("LIKE"), "{err}");
+        let err = translate_one("SHOW FULL TABLES").unwrap_err().to_string();
+        assert!(err.contains("FULL"), "{err}");
+    }
+
+    #[test]
+    fn information_schema_columns_filters_by_table_name() {
+        let expr = translate_one(
+            "SELECT * FROM information_schema.columns WHERE table_name IN ('logs', 'tx') LIMIT 4",
+        )
+        .unwrap();
+        assert_eq!(
+            expr,
+            Expression::Catalog(CatalogExpression {
+                target: CatalogTarget::Columns(vec![EntityKind::Logs, EntityKind::Transactions]),
+                limit: Some(4),
+            })
+        );
+        let expr = translate_one("SELECT * FROM information_schema.tables").unwrap();
+        assert_eq!(
+            expr,
+            Expression::Catalog(CatalogExpression {
+                target: CatalogTarget::Tables,
+                limit: None,
+            })
+        );
+    }
+
+    #[test]
+    fn information_schema_rejects_other_shapes() {
+        let err = translate_one("SELECT column_name FROM information_schema.columns")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("SELECT *"), "{err}");
+        let err = translate_one("SELECT * FROM information_schema.columns WHERE data_type = 'x'")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("data_type"), "{err}");
+        let err = translate_one("SELECT * FROM information_schema.schemata")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("schemata"), "{err}");
+    }
+
+    #[test]
+    fn explain_wraps_the_translated_query() {
+        let expr = translate_one(
+            "EXPLAIN ANALYZE SELECT hash FROM blocks WHERE number = 1 AND chain = eth",
+        )
+        .unwrap();
+        let Expression::Explain(explain) = expr else {
+            panic!("not an Explain")
+        };
+        assert!(explain.analyze);
+        assert_eq!(explain.get.chains, vec![ChainOrRpc::Chain(Chain::Ethereum)]);
+
+        let Expression::Explain(explain) =
+            translate_one("EXPLAIN SELECT hash FROM blocks WHERE number = 1 AND chain = eth")
+                .unwrap()
+        else {
+            panic!("not an Explain")
+        };
+        assert!(!explain.analyze);
+    }
+
+    #[test]
+    fn explain_rejects_other_forms_by_name() {
+        let err = translate_one("EXPLAIN VERBOSE SELECT hash FROM blocks WHERE number = 1")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("VERBOSE"), "{err}");
+        let err = translate_one("EXPLAIN SELECT * FROM information_schema.tables")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("catalog"), "{err}");
+        let err = translate_one("EXPLAIN SET rpc_eth = 'http://localhost:8545'")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("EXPLAIN SET"), "{err}");
+    }
 }
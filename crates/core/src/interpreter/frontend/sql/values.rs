@@ -8,10 +8,18 @@
 //! relevant.
 
 use super::EqlSqlError;
-use crate::common::ens::NameOrAddress;
+use crate::common::{
+    ens::{normalize, NameOrAddress},
+    storage::{mapping_slot, well_known_slot, WELL_KNOWN_SLOTS},
+};
+use alloy::dyn_abi::{DynSolType, DynSolValue};
 use alloy::eips::BlockNumberOrTag;
+use alloy::json_abi;
 use alloy::primitives::{Address, B256, U256};
-use sqlparser::ast::{Expr, Value};
+use sqlparser::ast::{
+    Expr, Function, FunctionArg, FunctionArgExpr, FunctionArgumentList, FunctionArguments,
+    UnaryOperator, Value,
+};
 use std::str::FromStr;
 
 /// Extracts the text of a string literal or a bare identifier.
@@ -31,17 +39,24 @@ pub fn parse_address(expr: &Expr) -> Result<Address, EqlSqlError> {
         .map_err(|e| EqlSqlError::Validation(format!("invalid address '{s}': {e}")))
 }
 
+/// An address, or an ENS name on any TLD, normalized (see `ens::normalize`).
 pub fn parse_name_or_address(expr: &Expr) -> Result<NameOrAddress, EqlSqlError> {
     let s = expr_as_string(expr)?;
-    if s.ends_with(".eth") {
-        Ok(NameOrAddress::Name(s))
-    } else {
+    if s.starts_with("0x") || !s.contains('.') {
         Ok(NameOrAddress::Address(Address::from_str(&s).map_err(
             |e| EqlSqlError::Validation(format!("invalid address '{s}': {e}")),
         )?))
+    } else {
+        Ok(NameOrAddress::Name(parse_ens_name(expr)?))
     }
 }
 
+/// An ENS name, normalized (see `ens::normalize`).
+pub fn parse_ens_name(expr: &Expr) -> Result<String, EqlSqlError> {
+    let s = expr_as_string(expr)?;
+    normalize(&s).map_err(|e| EqlSqlError::Validation(e.to_string()))
+}
+
 pub fn parse_b256(expr: &Expr) -> Result<B256, EqlSqlError> {
     let s = expr_as_string(expr)?;
     B256::from_str(&s).map_err(|e| EqlSqlError::Validation(format!("invalid hash '{s}': {e}")))
@@ -107,6 +122,126 @@ pub fn parse_block_number_or_tag(expr: &Expr) -> Result<BlockNumberOrTag, EqlSql
     }
 }
 
+/// A storage slot: a number or hex word, the name of a well-known slot
+/// (`eip1967_implementation`, ...), or `mapping_slot(base, key)` for an
+/// entry of a Solidity mapping. `base` is itself a slot, so nested mappings
+/// are written by nesting the call.
+pub fn parse_storage_slot(expr: &Expr) -> Result<B256, EqlSqlError> {
+    match expr {
+        Expr::Identifier(ident) => well_known_slot(&ident.value).ok_or_else(|| {
+            let known: Vec<_> = WELL_KNOWN_SLOTS.iter().map(|(name, _)| *name).collect();
+            EqlSqlError::Validation(format!(
+                "unknown slot '{}'; expected a number, a hex word, mapping_slot(base, key) or one of {}",
+                ident.value,
+                known.join(", ")
+            ))
+        }),
+        Expr::Function(function) => {
+            let (base, key) = mapping_slot_args(function)?;
+            Ok(mapping_slot(parse_storage_slot(base)?, parse_storage_word(key)?))
+        }
+        other => parse_storage_word(other),
+    }
+}
+
+/// A 32-byte word written as a decimal number or a hex string, left-padded
+/// the way the EVM pads it (so an address key reads as `abi.encode` would
+/// encode it).
+fn parse_storage_word(expr: &Expr) -> Result<B256, EqlSqlError> {
+    let s = match expr {
+        Expr::Value(Value::Number(n, _)) => n.clone(),
+        Expr::Value(Value::SingleQuotedString(s)) => s.clone(),
+        other => {
+            return Err(EqlSqlError::Validation(format!(
+                "expected a number or hex word, got {other}"
+            )))
+        }
+    };
+    U256::from_str(&s)
+        .map(B256::from)
+        .map_err(|e| EqlSqlError::Validation(format!("invalid storage word '{s}': {e}")))
+}
+
+/// The `(base, key)` arguments of a `mapping_slot(...)` call, rejecting any
+/// other function and any argument shape beyond two plain expressions.
+fn mapping_slot_args(function: &Function) -> Result<(&Expr, &Expr), EqlSqlError> {
+    let not_supported = || {
+        EqlSqlError::NotSupported(format!(
+            "slot expression {function} (only mapping_slot(base, key))"
+        ))
+    };
+    let Function {
+        name,
+        parameters,
+        args,
+        filter,
+        null_treatment,
+        over,
+        within_group,
+    } = function;
+    if !name.to_string().eq_ignore_ascii_case("mapping_slot")
+        || !matches!(parameters, FunctionArguments::None)
+        || filter.is_some()
+        || null_treatment.is_some()
+        || over.is_some()
+        || !within_group.is_empty()
+    {
+        return Err(not_supported());
+    }
+    let FunctionArguments::List(FunctionArgumentList {
+        duplicate_treatment: None,
+        args,
+        clauses,
+    }) = args
+    else {
+        return Err(not_supported());
+    };
+    match (args.as_slice(), clauses.is_empty()) {
+        (
+            [FunctionArg::Unnamed(FunctionArgExpr::Expr(base)), FunctionArg::Unnamed(FunctionArgExpr::Expr(key))],
+            true,
+        ) => Ok((base, key)),
+        _ => Err(not_supported()),
+    }
+}
+
+/// A function signature with its outputs, as `cast` writes them:
+/// `getReserves()(uint112,uint112,uint32)`. Parameters may be named, e.g.
+/// `balanceOf(address owner)(uint256 balance)`, and the human-readable
+/// `function f(...) returns (...)` form works too.
+pub fn parse_function_signature(expr: &Expr) -> Result<json_abi::Function, EqlSqlError> {
+    let s = expr_as_string(expr)?;
+    let function = json_abi::Function::parse(&s).map_err(|e| {
+        EqlSqlError::Validation(format!(
+            "invalid function signature '{s}': {e}; expected e.g. 'balanceOf(address)(uint256)'"
+        ))
+    })?;
+    if function.outputs.is_empty() {
+        return Err(EqlSqlError::Validation(format!(
+            "signature '{s}' has no outputs to decode; list them after the inputs, e.g. '{}(uint256)'",
+            function.signature()
+        )));
+    }
+    Ok(function)
+}
+
+/// A literal coerced to the Solidity type `ty`: numbers, `true`/`false`,
+/// and strings, which carry addresses, bytes and anything `ty` can parse
+/// from text (e.g. `'[1, 2]'` for a `uint256[]`).
+pub fn parse_sol_value(expr: &Expr, ty: &DynSolType) -> Result<DynSolValue, EqlSqlError> {
+    let s = match expr {
+        Expr::Value(Value::Number(n, _)) => n.clone(),
+        Expr::Value(Value::Boolean(b)) => b.to_string(),
+        Expr::UnaryOp {
+            op: UnaryOperator::Minus,
+            expr: inner,
+        } => format!("-{}", number_text(inner)?),
+        other => expr_as_string(other)?,
+    };
+    ty.coerce_str(&s)
+        .map_err(|e| EqlSqlError::Validation(format!("invalid {ty} '{s}': {e}")))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -143,6 +278,23 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn normalizes_ens_names_on_any_tld() {
+        use crate::common::ens::NameOrAddress;
+        assert_eq!(
+            parse_name_or_address(&s("Vitalik.ETH")).unwrap(),
+            NameOrAddress::Name("vitalik.eth".into())
+        );
+        assert_eq!(
+            parse_name_or_address(&s("alice.cb.id")).unwrap(),
+            NameOrAddress::Name("alice.cb.id".into())
+        );
+        let err = parse_name_or_address(&s("a_b.box")).unwrap_err();
+        assert!(err.to_string().contains("Invalid ENS name"), "{err}");
+        let err = parse_name_or_address(&s("0x1234")).unwrap_err();
+        assert!(err.to_string().contains("invalid address"), "{err}");
+    }
+
     #[test]
     fn parses_block_tags_and_numbers() {
         use alloy::eips::BlockNumberOrTag;
@@ -176,6 +328,45 @@ mod tests {
         assert!(parse_block_number_or_tag(&ident("newest")).is_err());
     }
 
+    #[test]
+    fn coerces_literals_to_solidity_types() {
+        let ty = |t: &str| DynSolType::parse(t).unwrap();
+        assert_eq!(
+            parse_sol_value(&n("5"), &ty("uint8")).unwrap(),
+            DynSolValue::Uint(U256::from(5), 8)
+        );
+        assert!(matches!(
+            parse_sol_value(&neg("5"), &ty("int24")).unwrap(),
+            DynSolValue::Int(v, 24) if v.is_negative()
+        ));
+        assert_eq!(
+            parse_sol_value(&Expr::Value(Value::Boolean(true)), &ty("bool")).unwrap(),
+            DynSolValue::Bool(true)
+        );
+        assert_eq!(
+            parse_sol_value(&s("[1, 2]"), &ty("uint256[]")).unwrap(),
+            DynSolValue::Array(vec![
+                DynSolValue::Uint(U256::from(1), 256),
+                DynSolValue::Uint(U256::from(2), 256)
+            ])
+        );
+        assert!(parse_sol_value(&n("256"), &ty("uint8")).is_err());
+        assert!(parse_sol_value(&s("0x12"), &ty("address")).is_err());
+    }
+
+    #[test]
+    fn parses_signatures_with_outputs() {
+        let function =
+            parse_function_signature(&s("getReserves()(uint112,uint112,uint32)")).unwrap();
+        assert_eq!(function.name, "getReserves");
+        assert_eq!(function.outputs.len(), 3);
+        let err = parse_function_signature(&s("totalSupply()"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("totalSupply()(uint256)"), "{err}");
+        assert!(parse_function_signature(&s("not a signature")).is_err());
+    }
+
     #[test]
     fn parses_hashes() {
         let hash = "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
@@ -236,6 +427,22 @@ mod tests {
         assert!(parse_u256(&s("0x2a")).is_err());
     }
 
+    #[test]
+    fn parses_storage_slots() {
+        use crate::common::storage::EIP1967_IMPLEMENTATION_SLOT;
+        assert_eq!(parse_storage_slot(&n("0")).unwrap(), B256::ZERO);
+        assert_eq!(
+            parse_storage_slot(&s("0x2a")).unwrap(),
+            B256::with_last_byte(0x2a)
+        );
+        assert_eq!(
+            parse_storage_slot(&ident("eip1967_implementation")).unwrap(),
+            EIP1967_IMPLEMENTATION_SLOT
+        );
+        assert!(parse_storage_slot(&ident("eip1967_owner")).is_err());
+        assert!(parse_storage_slot(&s("not-hex")).is_err());
+    }
+
     #[test]
     fn rejects_decimal_point() {
         assert!(parse_u8(&n("1.5")).is_err());
@@ -5,7 +5,7 @@
 use super::{values::expr_as_string, EqlSqlError};
 use crate::common::chain::{Chain, ChainOrRpc};
 use alloy::transports::http::reqwest::Url;
-use sqlparser::ast::{BinaryOperator, Expr, UnaryOperator};
+use sqlparser::ast::{Array, BinaryOperator, Expr, FunctionArg, FunctionArgExpr, UnaryOperator};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum CondOp {
@@ -129,6 +129,73 @@ fn collect(expr: &Expr, out: &mut Vec<Condition>) -> Result<(), EqlSqlError> {
     }
 }
 
+/// Turns the arguments of a table function call into the conditions they
+/// stand for: `name => value` is `name = value`, `name => [a, b]` is
+/// `name IN (a, b)`, and an unnamed argument takes the name of the parameter
+/// in its position (`params`). The result is checked exactly like a `WHERE`
+/// clause, so an argument and a predicate on the same column conflict the
+/// same way two predicates would.
+pub fn argument_conditions(
+    function: &str,
+    params: &[&str],
+    args: &[FunctionArg],
+) -> Result<Vec<Condition>, EqlSqlError> {
+    let mut out = Vec::new();
+    let mut named_seen = false;
+    for (position, arg) in args.iter().enumerate() {
+        let (column, value) = match arg {
+            FunctionArg::Unnamed(FunctionArgExpr::Expr(value)) => {
+                if named_seen {
+                    return Err(EqlSqlError::Validation(format!(
+                        "positional argument {value} of {function}(...) follows a named one"
+                    )));
+                }
+                let column = params.get(position).ok_or_else(|| {
+                    EqlSqlError::Validation(format!(
+                        "{function}(...) takes {} positional argument(s) ({}); pass the rest by name, e.g. block_number => ...",
+                        params.len(),
+                        params.join(", ")
+                    ))
+                })?;
+                (column.to_string(), value)
+            }
+            FunctionArg::Named {
+                name,
+                arg: FunctionArgExpr::Expr(value),
+                operator: _, // `=>`, `=` and `:=` all mean the same here
+            } => {
+                named_seen = true;
+                (name.value.to_ascii_lowercase(), value)
+            }
+            other => {
+                return Err(EqlSqlError::NotSupported(format!(
+                    "argument {other} of {function}(...)"
+                )))
+            }
+        };
+        out.push(match value {
+            Expr::Array(Array { elem, .. }) => {
+                if elem.is_empty() {
+                    return Err(EqlSqlError::NotSupported(format!(
+                        "empty [] list for {function}(... {column} ...)"
+                    )));
+                }
+                Condition {
+                    column,
+                    op: CondOp::In,
+                    values: elem.clone(),
+                }
+            }
+            value => Condition {
+                column,
+                op: CondOp::Eq,
+                values: vec![value.clone()],
+            },
+        });
+    }
+    Ok(out)
+}
+
 /// Renders a `CondOp` the way the user wrote it, for error messages that
 /// must name the actual construct rather than a fixed placeholder.
 fn cond_op_text(op: CondOp) -> &'static str {
@@ -4,6 +4,10 @@ pub mod frontend;
 use crate::common::{query_result::QueryResult, types::Expression};
 use anyhow::Result;
 use backend::execution_engine::ExecutionEngine;
+use frontend::sql::{
+    params::{Param, Params, Placeholder},
+    LexedProgram,
+};
 
 pub struct Interpreter;
 
@@ -21,6 +25,26 @@ impl Interpreter {
         Interpreter::run_backend(exressions).await
     }
 
+    /// Runs a program whose `$1` / `:name` placeholders take their values
+    /// from `params`.
+    pub async fn run_program_with_params(
+        source: &str,
+        params: &Params,
+    ) -> Result<Vec<QueryResult>> {
+        let expressions = frontend::sql::parse_program_with_params(source, params)?;
+        Interpreter::run_backend(expressions).await
+    }
+
+    /// Lexes `source` once and reports syntax errors up front; values are
+    /// bound with `PreparedStatement::bind`, and every run binds them into
+    /// the same lexed program.
+    pub fn prepare(source: &str) -> Result<PreparedStatement> {
+        Ok(PreparedStatement {
+            program: LexedProgram::new(source)?,
+            params: Params::new(),
+        })
+    }
+
     fn run_frontend(source: &str) -> Result<Vec<Expression>> {
         let expressions = frontend::sql::parse_program(source)?;
         Ok(expressions)
@@ -32,6 +56,48 @@ impl Interpreter {
     }
 }
 
+/// A program with placeholders, built by `Interpreter::prepare`:
+///
+/// ```no_run
+/// # async fn example() -> anyhow::Result<()> {
+/// use eql_core::interpreter::Interpreter;
+///
+/// let balances = Interpreter::prepare(
+///     "SELECT balance FROM accounts WHERE address = $1 AND chain = :chain",
+/// )?
+/// .bind(1, "vitalik.eth")
+/// .bind("chain", "eth")
+/// .run()
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PreparedStatement {
+    program: LexedProgram,
+    params: Params,
+}
+
+impl PreparedStatement {
+    /// Binds `value` to a placeholder: `1` or `"$1"` for `$1`, `"name"` or
+    /// `":name"` for `:name`. Binding the same placeholder again replaces
+    /// its value, so a prepared statement can be re-run with new values.
+    pub fn bind(mut self, key: impl Into<Placeholder>, value: impl Into<Param>) -> Self {
+        self.params.set(key, value);
+        self
+    }
+
+    /// The statement's distinct placeholders, in order of first use.
+    pub fn placeholders(&self) -> &[Placeholder] {
+        self.program.placeholders()
+    }
+
+    pub async fn run(&self) -> Result<Vec<QueryResult>> {
+        let expressions = self.program.bind(&self.params)?;
+        Interpreter::run_backend(expressions).await
+    }
+}
+
 pub async fn eql(source: &str) -> Result<QueryResult> {
     let result = Interpreter::run_program(source).await?;
 
@@ -40,3 +106,13 @@ pub async fn eql(source: &str) -> Result<QueryResult> {
         None => Err(InterpreterError::SingleQueryError.into()),
     }
 }
+
+/// `eql` for a single query with placeholders.
+pub async fn eql_with_params(source: &str, params: &Params) -> Result<QueryResult> {
+    let result = Interpreter::run_program_with_params(source, params).await?;
+
+    match result.first() {
+        Some(result) => Ok(result.clone()),
+        None => Err(InterpreterError::SingleQueryError.into()),
+    }
+}
@@ -1,4 +1,6 @@
 use eql_core::interpreter::eql as eql_interpreter;
+use eql_core::interpreter::eql_with_params as eql_with_params_interpreter;
+use eql_core::interpreter::frontend::sql::params::Params;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -15,3 +17,19 @@ pub async fn eql(program: &str) -> Result<JsValue, JsValue> {
         }
     }
 }
+
+/// `eql` with query parameters: an array binds `$1`, `$2`, ... in order, an
+/// object binds by name (`{ addr: "vitalik.eth" }` for `:addr`).
+#[wasm_bindgen(js_name = eqlWithParams)]
+pub async fn eql_with_params(program: &str, params: JsValue) -> Result<JsValue, JsValue> {
+    let params: Params = serde_wasm_bindgen::from_value(params)?;
+    let result = eql_with_params_interpreter(program, &params).await;
+
+    match result {
+        Ok(result) => {
+            let result = serde_wasm_bindgen::to_value(&result)?;
+            Ok(result)
+        }
+        Err(e) => Err(JsValue::from_str(&e.to_string())),
+    }
+}